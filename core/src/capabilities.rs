@@ -0,0 +1,116 @@
+//! Features supported by a driver.
+//!
+//! [Capabilities] are obtained with [Connection::capabilities]. Native drivers
+//! declare them by overriding this method, while the
+//! [driver manager][crate::driver_manager] derives them from the functions
+//! populated by the loaded driver. Either way, they are then refined with
+//! what the connection reports through [Connection::get_info], see
+//! [Capabilities::with_info].
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! # use adbc_core::Connection;
+//! # fn run(connection: &impl Connection) -> adbc_core::error::Result<()> {
+//! let capabilities = connection.capabilities()?;
+//! if capabilities.partitions {
+//!     // Use Statement::execute_partitions...
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashSet;
+
+use arrow::array::{Array, AsArray, UnionArray};
+use arrow::datatypes::{Int64Type, UInt32Type};
+
+use crate::error::{Error, Result, Status};
+use crate::ffi::constants;
+use crate::options::{InfoCode, IngestMode, IsolationLevel};
+use crate::Connection;
+
+/// Features supported by a connection and the statements it creates.
+///
+/// A feature is only reported as supported when the driver is known to
+/// implement it, so `false` and empty lists may also mean "unknown".
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Capabilities {
+    /// Whether queries can be executed as partitions with
+    /// [Statement::execute_partitions][crate::Statement::execute_partitions]
+    /// and read with [Connection::read_partition].
+    pub partitions: bool,
+    /// Whether Substrait plans can be executed with
+    /// [Statement::set_substrait_plan][crate::Statement::set_substrait_plan].
+    pub substrait: bool,
+    /// Whether table statistics can be retrieved with
+    /// [Connection::get_statistics] and [Connection::get_statistic_names].
+    pub statistics: bool,
+    /// Whether in-progress operations can be cancelled.
+    pub cancellation: bool,
+    /// Whether transactions can be committed and rolled back once autocommit
+    /// is disabled.
+    pub transactions: bool,
+    /// Isolation levels which can be set with
+    /// [OptionConnection::IsolationLevel][crate::options::OptionConnection::IsolationLevel].
+    pub isolation_levels: Vec<IsolationLevel>,
+    /// Modes which can be set with
+    /// [OptionStatement::IngestMode][crate::options::OptionStatement::IngestMode].
+    pub ingest_modes: Vec<IngestMode>,
+}
+
+impl Capabilities {
+    /// Refine the capabilities with the metadata reported by `connection`.
+    ///
+    /// Statistics and cancellation are cleared when the driver reports an
+    /// ADBC API revision older than 1.1.0, which introduced them, and
+    /// Substrait support is taken from [InfoCode::VendorSubstrait] when
+    /// reported. Drivers not implementing [Connection::get_info] leave the
+    /// capabilities unchanged.
+    pub fn with_info<C: Connection + ?Sized>(mut self, connection: &C) -> Result<Self> {
+        let codes = HashSet::from([InfoCode::DriverAdbcVersion, InfoCode::VendorSubstrait]);
+        let reader = match connection.get_info(Some(codes)) {
+            Ok(reader) => reader,
+            Err(error) if error.status == Status::NotImplemented => return Ok(self),
+            Err(error) => return Err(error),
+        };
+        for batch in reader {
+            let batch = batch?;
+            let (Some(names), Some(values)) = (
+                batch.column(0).as_primitive_opt::<UInt32Type>(),
+                batch.column(1).as_any().downcast_ref::<UnionArray>(),
+            ) else {
+                return Err(Error::with_message_and_status(
+                    "Unexpected schema of the driver metadata",
+                    Status::InvalidData,
+                ));
+            };
+            for row in 0..batch.num_rows() {
+                let value = values.value(row);
+                let Ok(code) = InfoCode::try_from(names.value(row)) else {
+                    continue;
+                };
+                match code {
+                    InfoCode::DriverAdbcVersion => {
+                        if let Some(version) = value.as_primitive_opt::<Int64Type>() {
+                            if version.is_valid(0)
+                                && version.value(0) < i64::from(constants::ADBC_VERSION_1_1_0)
+                            {
+                                self.statistics = false;
+                                self.cancellation = false;
+                            }
+                        }
+                    }
+                    InfoCode::VendorSubstrait => {
+                        if let Some(substrait) = value.as_boolean_opt() {
+                            self.substrait = substrait.is_valid(0) && substrait.value(0);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok(self)
+    }
+}
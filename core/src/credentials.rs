@@ -0,0 +1,364 @@
+//! Credentials obtained when databases and connections are created.
+//!
+//! Databases authenticating with short-lived tokens cannot be configured with
+//! static options. A [CredentialProvider] returns fresh [Credentials], i.e.
+//! database options such as [OptionDatabase::Username],
+//! [OptionDatabase::Password] or a driver-specific token, along with the
+//! time they expire at.
+//!
+//! The [driver manager][crate::driver_manager] asks the provider given to
+//! `ManagedDriver::with_credentials` for credentials when creating a
+//! database, and again before creating a connection once the credentials of
+//! the database are about to expire, see [REFRESH_MARGIN]. The connection is
+//! then created from a new database initialized with the fresh credentials.
+//!
+//! [Cached] keeps the credentials of a provider until they expire, so that
+//! several databases share them. [FileProvider] and [CommandProvider] read
+//! credentials from a file or from the output of a command, written as lines
+//! of `key=value`:
+//!
+//! ```text
+//! # Comments and blank lines are ignored.
+//! username=analytics
+//! password=eyJhbGciOiJIUzI1NiJ9
+//! # Seconds since the Unix epoch, or from now with expires_in.
+//! expires_at=1767225600
+//! ```
+//!
+//! Keys are database option keys, e.g. `password` or
+//! `adbc.snowflake.sql.client_option.token`. Credentials without
+//! `expires_at` or `expires_in` never expire.
+
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use crate::error::{Error, Result, Status};
+use crate::options::{OptionDatabase, OptionValue};
+use crate::secret::{self, Secret};
+
+/// Credentials are refreshed this long before they expire, so that they
+/// remain valid while connections are being opened.
+pub const REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+/// Database options authenticating to a database, valid until a point in
+/// time.
+///
+/// The values of [secret] options are masked by [Debug][fmt::Debug] and
+/// zeroized on drop.
+#[derive(Clone)]
+pub struct Credentials {
+    options: Vec<(OptionDatabase, OptionValue)>,
+    expires_at: Option<SystemTime>,
+}
+
+impl Credentials {
+    /// Credentials made of `options`, which never expire.
+    pub fn new(options: impl IntoIterator<Item = (OptionDatabase, OptionValue)>) -> Self {
+        Self {
+            options: options.into_iter().collect(),
+            expires_at: None,
+        }
+    }
+
+    /// Set the time the credentials expire at.
+    pub fn expires_at(mut self, expires_at: SystemTime) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+
+    /// Parse credentials written as lines of `key=value`, see the
+    /// [module][self] documentation.
+    ///
+    /// Fails with [Status::InvalidData] if a line is not a `key=value` pair
+    /// or if the expiry is not a number of seconds.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut credentials = Self::new([]);
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line.split_once('=').ok_or_else(|| {
+                Error::with_message_and_status(
+                    "Credentials must be written as key=value lines",
+                    Status::InvalidData,
+                )
+            })?;
+            let (key, value) = (key.trim(), value.trim());
+            let seconds = || {
+                value.parse().map(Duration::from_secs).map_err(|_| {
+                    Error::with_message_and_status(
+                        format!("Invalid {key} in credentials: {value}"),
+                        Status::InvalidData,
+                    )
+                })
+            };
+            match key {
+                "expires_at" => credentials.expires_at = Some(SystemTime::UNIX_EPOCH + seconds()?),
+                "expires_in" => credentials.expires_at = Some(SystemTime::now() + seconds()?),
+                _ => credentials.options.push((key.into(), value.into())),
+            }
+        }
+        Ok(credentials)
+    }
+
+    /// The database options.
+    pub fn options(&self) -> &[(OptionDatabase, OptionValue)] {
+        &self.options
+    }
+
+    /// The time the credentials expire at, if any.
+    pub fn expiry(&self) -> Option<SystemTime> {
+        self.expires_at
+    }
+
+    /// Whether the credentials expire within [REFRESH_MARGIN].
+    pub fn is_expired(&self) -> bool {
+        is_expired(self.expires_at)
+    }
+}
+
+/// Whether credentials expiring at `expires_at` must be refreshed.
+pub(crate) fn is_expired(expires_at: Option<SystemTime>) -> bool {
+    expires_at.is_some_and(|expires_at| SystemTime::now() + REFRESH_MARGIN >= expires_at)
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("options", &secret::redacted_options(&self.options))
+            .field("expires_at", &self.expires_at)
+            .finish()
+    }
+}
+
+impl Drop for Credentials {
+    fn drop(&mut self) {
+        secret::zeroize_secrets(self.options.iter_mut().map(|(key, value)| (&*key, value)));
+    }
+}
+
+/// A source of fresh credentials.
+///
+/// Providers are called from the threads creating databases and
+/// connections, hence must be [Send] and [Sync]. Closures returning
+/// [Credentials] are providers.
+pub trait CredentialProvider: Send + Sync {
+    /// Get credentials, which should not be expired.
+    fn credentials(&self) -> Result<Credentials>;
+}
+
+impl<F> CredentialProvider for F
+where
+    F: Fn() -> Result<Credentials> + Send + Sync,
+{
+    fn credentials(&self) -> Result<Credentials> {
+        self()
+    }
+}
+
+impl<P: CredentialProvider + ?Sized> CredentialProvider for Arc<P> {
+    fn credentials(&self) -> Result<Credentials> {
+        (**self).credentials()
+    }
+}
+
+/// A provider keeping the credentials of another provider until they
+/// expire.
+pub struct Cached<P> {
+    provider: P,
+    cached: Mutex<Option<Credentials>>,
+}
+
+impl<P: CredentialProvider> Cached<P> {
+    pub fn new(provider: P) -> Self {
+        Self {
+            provider,
+            cached: Mutex::new(None),
+        }
+    }
+
+    /// Forget the cached credentials, e.g. after they were revoked.
+    pub fn invalidate(&self) {
+        *self.cached.lock().unwrap() = None;
+    }
+}
+
+impl<P: CredentialProvider> CredentialProvider for Cached<P> {
+    fn credentials(&self) -> Result<Credentials> {
+        let mut cached = self.cached.lock().unwrap();
+        match cached.as_ref() {
+            Some(credentials) if !credentials.is_expired() => Ok(credentials.clone()),
+            _ => {
+                let credentials = self.provider.credentials()?;
+                *cached = Some(credentials.clone());
+                Ok(credentials)
+            }
+        }
+    }
+}
+
+/// A provider reading credentials from a file, which is read again on every
+/// call so that an external process can rotate them.
+#[derive(Debug, Clone)]
+pub struct FileProvider {
+    path: PathBuf,
+}
+
+impl FileProvider {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl CredentialProvider for FileProvider {
+    fn credentials(&self) -> Result<Credentials> {
+        let text = std::fs::read_to_string(&self.path).map_err(|error| {
+            Error::with_message_and_status(
+                format!(
+                    "Cannot read credentials from {}: {error}",
+                    self.path.display()
+                ),
+                Status::IO,
+            )
+        })?;
+        Credentials::parse(Secret::new(text).expose())
+    }
+}
+
+/// A provider running a command and reading credentials from its standard
+/// output.
+///
+/// Fails with [Status::IO] if the command cannot be run or exits with a
+/// failure.
+#[derive(Debug, Clone)]
+pub struct CommandProvider {
+    program: OsString,
+    args: Vec<OsString>,
+}
+
+impl CommandProvider {
+    /// A provider running `program`, looked up in `PATH` if it is not a
+    /// path.
+    pub fn new(program: impl AsRef<OsStr>) -> Self {
+        Self {
+            program: program.as_ref().into(),
+            args: Vec::new(),
+        }
+    }
+
+    /// Add an argument passed to the program.
+    pub fn arg(mut self, arg: impl AsRef<OsStr>) -> Self {
+        self.args.push(arg.as_ref().into());
+        self
+    }
+
+    /// Add arguments passed to the program.
+    pub fn args(mut self, args: impl IntoIterator<Item = impl AsRef<OsStr>>) -> Self {
+        self.args
+            .extend(args.into_iter().map(|arg| arg.as_ref().into()));
+        self
+    }
+}
+
+impl CredentialProvider for CommandProvider {
+    fn credentials(&self) -> Result<Credentials> {
+        let program = self.program.to_string_lossy();
+        let output = Command::new(&self.program)
+            .args(&self.args)
+            .output()
+            .map_err(|error| {
+                Error::with_message_and_status(
+                    format!("Cannot run credential command {program}: {error}"),
+                    Status::IO,
+                )
+            })?;
+        if !output.status.success() {
+            return Err(Error::with_message_and_status(
+                format!(
+                    "Credential command {program} failed with {}: {}",
+                    output.status,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+                Status::IO,
+            ));
+        }
+        let stdout = String::from_utf8(output.stdout).map_err(|_| {
+            Error::with_message_and_status(
+                format!("Credential command {program} wrote invalid UTF-8"),
+                Status::InvalidData,
+            )
+        })?;
+        Credentials::parse(Secret::new(stdout).expose())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let credentials =
+            Credentials::parse("# token\nusername = me\npassword=p=ss\n\nexpires_at=1767225600\n")
+                .unwrap();
+        let options: Vec<_> = credentials
+            .options()
+            .iter()
+            .map(|(key, value)| match value {
+                OptionValue::String(value) => (key.clone(), value.as_str()),
+                value => panic!("Unexpected value {value:?}"),
+            })
+            .collect();
+        assert_eq!(
+            options,
+            [
+                (OptionDatabase::Username, "me"),
+                (OptionDatabase::Password, "p=ss")
+            ]
+        );
+        assert_eq!(
+            credentials.expiry(),
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1767225600))
+        );
+        assert!(credentials.is_expired());
+        assert!(!format!("{credentials:?}").contains("p=ss"));
+
+        let credentials = Credentials::parse("token=abc\nexpires_in=3600").unwrap();
+        assert!(!credentials.is_expired());
+        assert!(!Credentials::new([]).is_expired());
+
+        for text in ["password", "expires_in=soon"] {
+            let error = Credentials::parse(text).unwrap_err();
+            assert_eq!(error.status, Status::InvalidData, "{text}");
+        }
+    }
+
+    #[test]
+    fn test_cached() {
+        let calls = AtomicUsize::new(0);
+        let expires_in = Mutex::new(Duration::from_secs(3600));
+        let cached = Cached::new(|| {
+            calls.fetch_add(1, Ordering::Relaxed);
+            let expires_at = SystemTime::now() + *expires_in.lock().unwrap();
+            Ok(
+                Credentials::new([(OptionDatabase::Password, "token".into())])
+                    .expires_at(expires_at),
+            )
+        });
+        cached.credentials().unwrap();
+        cached.credentials().unwrap();
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+
+        cached.invalidate();
+        *expires_in.lock().unwrap() = REFRESH_MARGIN / 2;
+        cached.credentials().unwrap();
+        cached.credentials().unwrap();
+        assert_eq!(calls.load(Ordering::Relaxed), 3);
+    }
+}
@@ -1,6 +1,7 @@
 use std::collections::{HashMap, HashSet};
 use std::ffi::{CStr, CString};
 use std::hash::Hash;
+use std::marker::PhantomData;
 use std::os::raw::{c_char, c_int, c_void};
 
 use arrow::array::StructArray;
@@ -11,10 +12,14 @@ use arrow::ffi_stream::{ArrowArrayStreamReader, FFI_ArrowArrayStream};
 use crate::error::{Error, Result, Status};
 use crate::ffi::constants::ADBC_STATUS_OK;
 use crate::ffi::{
-    types::ErrorPrivateData, FFI_AdbcConnection, FFI_AdbcDatabase, FFI_AdbcDriver, FFI_AdbcError,
-    FFI_AdbcErrorDetail, FFI_AdbcPartitions, FFI_AdbcStatement, FFI_AdbcStatusCode,
+    methods, types::ErrorPrivateData, FFI_AdbcConnection, FFI_AdbcDatabase, FFI_AdbcDriver,
+    FFI_AdbcError, FFI_AdbcErrorDetail, FFI_AdbcPartitions, FFI_AdbcStatement, FFI_AdbcStatusCode,
 };
-use crate::options::{InfoCode, ObjectDepth, OptionConnection, OptionDatabase, OptionValue};
+use crate::options::{
+    InfoCode, ObjectDepth, OptionConnection, OptionDatabase, OptionSpec, OptionValue,
+    TypedOptionable, TypedValue,
+};
+use crate::trace;
 use crate::{Connection, Database, Driver, Optionable, Statement};
 
 type DatabaseType<DriverType> = <DriverType as Driver>::DatabaseType;
@@ -75,7 +80,70 @@ impl<DriverType: Driver> ExportedConnection<DriverType> {
     }
 }
 
-struct ExportedStatement<DriverType: Driver>(StatementType<DriverType>);
+struct ExportedStatement<DriverType: Driver> {
+    statement: StatementType<DriverType>,
+    /// SQL query last set on the statement, reported in traces.
+    query: Option<String>,
+}
+
+/// An exported function, whose calls are traced by [Traceable::traced].
+trait Exported {
+    type Function;
+    const METHOD: &'static str;
+    fn driver() -> &'static str;
+    fn function() -> Self::Function;
+}
+
+/// Function pointer types of the exported functions.
+trait Traceable: Sized {
+    /// A function calling the function of `M` in [trace::ffi_call].
+    fn traced<M: Exported<Function = Self>>() -> Self;
+}
+
+macro_rules! traceable {
+    ($($arg:ident: $type:ident),*) => {
+        impl<$($type: 'static),*> Traceable for unsafe extern "C" fn($($type),*) -> FFI_AdbcStatusCode {
+            fn traced<M: Exported<Function = Self>>() -> Self {
+                unsafe extern "C" fn traced<M, $($type),*>($($arg: $type),*) -> FFI_AdbcStatusCode
+                where
+                    M: Exported<Function = unsafe extern "C" fn($($type),*) -> FFI_AdbcStatusCode>,
+                {
+                    trace::ffi_call(M::METHOD, M::driver(), || M::function()($($arg),*))
+                }
+                traced::<M, $($type),*>
+            }
+        }
+    };
+}
+
+traceable!(a: A, b: B);
+traceable!(a: A, b: B, c: C);
+traceable!(a: A, b: B, c: C, d: D);
+traceable!(a: A, b: B, c: C, d: D, e: E);
+traceable!(a: A, b: B, c: C, d: D, e: E, f: F);
+traceable!(a: A, b: B, c: C, d: D, e: E, f: F, g: G);
+traceable!(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H);
+traceable!(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I);
+
+/// The entry of `$method` in the function table, calling `$function` in
+/// [trace::ffi_call]. The wrapper has the signature of `$type`, the type of
+/// the method in [methods].
+macro_rules! traced {
+    ($method:ident, $type:ident, $function:ident) => {{
+        struct Method<DriverType>(PhantomData<DriverType>);
+        impl<DriverType: Driver + Default + 'static> Exported for Method<DriverType> {
+            type Function = methods::$type;
+            const METHOD: &'static str = concat!("Adbc", stringify!($method));
+            fn driver() -> &'static str {
+                std::any::type_name::<DriverType>()
+            }
+            fn function() -> Self::Function {
+                $function::<DriverType>
+            }
+        }
+        Some(Traceable::traced::<Method<DriverType>>())
+    }};
+}
 
 pub trait FFIDriver {
     fn ffi_driver() -> FFI_AdbcDriver;
@@ -87,61 +155,221 @@ impl<DriverType: Driver + Default + 'static> FFIDriver for DriverType {
             private_data: std::ptr::null_mut(),
             private_manager: std::ptr::null(),
             release: Some(release_ffi_driver),
-            DatabaseInit: Some(database_init::<DriverType>),
-            DatabaseNew: Some(database_new::<DriverType>),
-            DatabaseSetOption: Some(database_set_option::<DriverType>),
-            DatabaseRelease: Some(database_release::<DriverType>),
-            ConnectionCommit: Some(connection_commit::<DriverType>),
-            ConnectionGetInfo: Some(connection_get_info::<DriverType>),
-            ConnectionGetObjects: Some(connection_get_objects::<DriverType>),
-            ConnectionGetTableSchema: Some(connection_get_table_schema::<DriverType>),
-            ConnectionGetTableTypes: Some(connection_get_table_types::<DriverType>),
-            ConnectionInit: Some(connection_init::<DriverType>),
-            ConnectionNew: Some(connection_new::<DriverType>),
-            ConnectionSetOption: Some(connection_set_option::<DriverType>),
-            ConnectionReadPartition: Some(connection_read_partition::<DriverType>),
-            ConnectionRelease: Some(connection_release::<DriverType>),
-            ConnectionRollback: Some(connection_rollback::<DriverType>),
-            StatementBind: Some(statement_bind::<DriverType>),
-            StatementBindStream: Some(statement_bind_stream::<DriverType>),
-            StatementExecuteQuery: Some(statement_execute_query::<DriverType>),
-            StatementExecutePartitions: Some(statement_execute_partitions::<DriverType>),
-            StatementGetParameterSchema: Some(statement_get_parameter_schema::<DriverType>),
-            StatementNew: Some(statement_new::<DriverType>),
-            StatementPrepare: Some(statement_prepare::<DriverType>),
-            StatementRelease: Some(statement_release::<DriverType>),
-            StatementSetOption: Some(statement_set_option::<DriverType>),
-            StatementSetSqlQuery: Some(statement_set_sql_query::<DriverType>),
-            StatementSetSubstraitPlan: Some(statement_set_substrait_plan::<DriverType>),
+            DatabaseInit: traced!(DatabaseInit, FuncDatabaseInit, database_init),
+            DatabaseNew: traced!(DatabaseNew, FuncDatabaseNew, database_new),
+            DatabaseSetOption: traced!(
+                DatabaseSetOption,
+                FuncDatabaseSetOption,
+                database_set_option
+            ),
+            DatabaseRelease: traced!(DatabaseRelease, FuncDatabaseRelease, database_release),
+            ConnectionCommit: traced!(ConnectionCommit, FuncConnectionCommit, connection_commit),
+            ConnectionGetInfo: traced!(
+                ConnectionGetInfo,
+                FuncConnectionGetInfo,
+                connection_get_info
+            ),
+            ConnectionGetObjects: traced!(
+                ConnectionGetObjects,
+                FuncConnectionGetObjects,
+                connection_get_objects
+            ),
+            ConnectionGetTableSchema: traced!(
+                ConnectionGetTableSchema,
+                FuncConnectionGetTableSchema,
+                connection_get_table_schema
+            ),
+            ConnectionGetTableTypes: traced!(
+                ConnectionGetTableTypes,
+                FuncConnectionGetTableTypes,
+                connection_get_table_types
+            ),
+            ConnectionInit: traced!(ConnectionInit, FuncConnectionInit, connection_init),
+            ConnectionNew: traced!(ConnectionNew, FuncConnectionNew, connection_new),
+            ConnectionSetOption: traced!(
+                ConnectionSetOption,
+                FuncConnectionSetOption,
+                connection_set_option
+            ),
+            ConnectionReadPartition: traced!(
+                ConnectionReadPartition,
+                FuncConnectionReadPartition,
+                connection_read_partition
+            ),
+            ConnectionRelease: traced!(
+                ConnectionRelease,
+                FuncConnectionRelease,
+                connection_release
+            ),
+            ConnectionRollback: traced!(
+                ConnectionRollback,
+                FuncConnectionRollback,
+                connection_rollback
+            ),
+            StatementBind: traced!(StatementBind, FuncStatementBind, statement_bind),
+            StatementBindStream: traced!(
+                StatementBindStream,
+                FuncStatementBindStream,
+                statement_bind_stream
+            ),
+            StatementExecuteQuery: traced!(
+                StatementExecuteQuery,
+                FuncStatementExecuteQuery,
+                statement_execute_query
+            ),
+            StatementExecutePartitions: traced!(
+                StatementExecutePartitions,
+                FuncStatementExecutePartitions,
+                statement_execute_partitions
+            ),
+            StatementGetParameterSchema: traced!(
+                StatementGetParameterSchema,
+                FuncStatementGetParameterSchema,
+                statement_get_parameter_schema
+            ),
+            StatementNew: traced!(StatementNew, FuncStatementNew, statement_new),
+            StatementPrepare: traced!(StatementPrepare, FuncStatementPrepare, statement_prepare),
+            StatementRelease: traced!(StatementRelease, FuncStatementRelease, statement_release),
+            StatementSetOption: traced!(
+                StatementSetOption,
+                FuncStatementSetOption,
+                statement_set_option
+            ),
+            StatementSetSqlQuery: traced!(
+                StatementSetSqlQuery,
+                FuncStatementSetSqlQuery,
+                statement_set_sql_query
+            ),
+            StatementSetSubstraitPlan: traced!(
+                StatementSetSubstraitPlan,
+                FuncStatementSetSubstraitPlan,
+                statement_set_substrait_plan
+            ),
             ErrorGetDetailCount: Some(error_get_detail_count),
             ErrorGetDetail: Some(error_get_detail),
             ErrorFromArrayStream: None, // TODO(alexandreyc): what to do with this?
-            DatabaseGetOption: Some(database_get_option::<DriverType>),
-            DatabaseGetOptionBytes: Some(database_get_option_bytes::<DriverType>),
-            DatabaseGetOptionDouble: Some(database_get_option_double::<DriverType>),
-            DatabaseGetOptionInt: Some(database_get_option_int::<DriverType>),
-            DatabaseSetOptionBytes: Some(database_set_option_bytes::<DriverType>),
-            DatabaseSetOptionDouble: Some(database_set_option_double::<DriverType>),
-            DatabaseSetOptionInt: Some(database_set_option_int::<DriverType>),
-            ConnectionCancel: Some(connection_cancel::<DriverType>),
-            ConnectionGetOption: Some(connection_get_option::<DriverType>),
-            ConnectionGetOptionBytes: Some(connection_get_option_bytes::<DriverType>),
-            ConnectionGetOptionDouble: Some(connection_get_option_double::<DriverType>),
-            ConnectionGetOptionInt: Some(connection_get_option_int::<DriverType>),
-            ConnectionGetStatistics: Some(connection_get_statistics::<DriverType>),
-            ConnectionGetStatisticNames: Some(connection_get_statistic_names::<DriverType>),
-            ConnectionSetOptionBytes: Some(connection_set_option_bytes::<DriverType>),
-            ConnectionSetOptionDouble: Some(connection_set_option_double::<DriverType>),
-            ConnectionSetOptionInt: Some(connection_set_option_int::<DriverType>),
-            StatementCancel: Some(statement_cancel::<DriverType>),
-            StatementExecuteSchema: Some(statement_execute_schema::<DriverType>),
-            StatementGetOption: Some(statement_get_option::<DriverType>),
-            StatementGetOptionBytes: Some(statement_get_option_bytes::<DriverType>),
-            StatementGetOptionDouble: Some(statement_get_option_double::<DriverType>),
-            StatementGetOptionInt: Some(statement_get_option_int::<DriverType>),
-            StatementSetOptionBytes: Some(statement_set_option_bytes::<DriverType>),
-            StatementSetOptionDouble: Some(statement_set_option_double::<DriverType>),
-            StatementSetOptionInt: Some(statement_set_option_int::<DriverType>),
+            DatabaseGetOption: traced!(
+                DatabaseGetOption,
+                FuncDatabaseGetOption,
+                database_get_option
+            ),
+            DatabaseGetOptionBytes: traced!(
+                DatabaseGetOptionBytes,
+                FuncDatabaseGetOptionBytes,
+                database_get_option_bytes
+            ),
+            DatabaseGetOptionDouble: traced!(
+                DatabaseGetOptionDouble,
+                FuncDatabaseGetOptionDouble,
+                database_get_option_double
+            ),
+            DatabaseGetOptionInt: traced!(
+                DatabaseGetOptionInt,
+                FuncDatabaseGetOptionInt,
+                database_get_option_int
+            ),
+            DatabaseSetOptionBytes: traced!(
+                DatabaseSetOptionBytes,
+                FuncDatabaseSetOptionBytes,
+                database_set_option_bytes
+            ),
+            DatabaseSetOptionDouble: traced!(
+                DatabaseSetOptionDouble,
+                FuncDatabaseSetOptionDouble,
+                database_set_option_double
+            ),
+            DatabaseSetOptionInt: traced!(
+                DatabaseSetOptionInt,
+                FuncDatabaseSetOptionInt,
+                database_set_option_int
+            ),
+            ConnectionCancel: traced!(ConnectionCancel, FuncConnectionCancel, connection_cancel),
+            ConnectionGetOption: traced!(
+                ConnectionGetOption,
+                FuncConnectionGetOption,
+                connection_get_option
+            ),
+            ConnectionGetOptionBytes: traced!(
+                ConnectionGetOptionBytes,
+                FuncConnectionGetOptionBytes,
+                connection_get_option_bytes
+            ),
+            ConnectionGetOptionDouble: traced!(
+                ConnectionGetOptionDouble,
+                FuncConnectionGetOptionDouble,
+                connection_get_option_double
+            ),
+            ConnectionGetOptionInt: traced!(
+                ConnectionGetOptionInt,
+                FuncConnectionGetOptionInt,
+                connection_get_option_int
+            ),
+            ConnectionGetStatistics: traced!(
+                ConnectionGetStatistics,
+                FuncConnectionGetStatistics,
+                connection_get_statistics
+            ),
+            ConnectionGetStatisticNames: traced!(
+                ConnectionGetStatisticNames,
+                FuncConnectionGetStatisticNames,
+                connection_get_statistic_names
+            ),
+            ConnectionSetOptionBytes: traced!(
+                ConnectionSetOptionBytes,
+                FuncConnectionSetOptionBytes,
+                connection_set_option_bytes
+            ),
+            ConnectionSetOptionDouble: traced!(
+                ConnectionSetOptionDouble,
+                FuncConnectionSetOptionDouble,
+                connection_set_option_double
+            ),
+            ConnectionSetOptionInt: traced!(
+                ConnectionSetOptionInt,
+                FuncConnectionSetOptionInt,
+                connection_set_option_int
+            ),
+            StatementCancel: traced!(StatementCancel, FuncStatementCancel, statement_cancel),
+            StatementExecuteSchema: traced!(
+                StatementExecuteSchema,
+                FuncStatementExecuteSchema,
+                statement_execute_schema
+            ),
+            StatementGetOption: traced!(
+                StatementGetOption,
+                FuncStatementGetOption,
+                statement_get_option
+            ),
+            StatementGetOptionBytes: traced!(
+                StatementGetOptionBytes,
+                FuncStatementGetOptionBytes,
+                statement_get_option_bytes
+            ),
+            StatementGetOptionDouble: traced!(
+                StatementGetOptionDouble,
+                FuncStatementGetOptionDouble,
+                statement_get_option_double
+            ),
+            StatementGetOptionInt: traced!(
+                StatementGetOptionInt,
+                FuncStatementGetOptionInt,
+                statement_get_option_int
+            ),
+            StatementSetOptionBytes: traced!(
+                StatementSetOptionBytes,
+                FuncStatementSetOptionBytes,
+                statement_set_option_bytes
+            ),
+            StatementSetOptionDouble: traced!(
+                StatementSetOptionDouble,
+                FuncStatementSetOptionDouble,
+                statement_set_option_double
+            ),
+            StatementSetOptionInt: traced!(
+                StatementSetOptionInt,
+                FuncStatementSetOptionInt,
+                statement_set_option_int
+            ),
         }
     }
 }
@@ -151,15 +379,15 @@ impl<DriverType: Driver + Default + 'static> FFIDriver for DriverType {
 /// # Parameters
 ///
 /// - `$func_name` - Driver's initialization function name. The recommended name
-/// is `AdbcDriverInit`, or a name derived from the name of the driver's shared
-/// library as follows: remove the `lib` prefix (on Unix systems) and all file
-/// extensions, then `PascalCase` the driver name, append `Init`, and prepend
-/// `Adbc` (if not already there). For example:
+///   is `AdbcDriverInit`, or a name derived from the name of the driver's shared
+///   library as follows: remove the `lib` prefix (on Unix systems) and all file
+///   extensions, then `PascalCase` the driver name, append `Init`, and prepend
+///   `Adbc` (if not already there). For example:
 ///     - `libadbc_driver_sqlite.so.2.0.0` -> `AdbcDriverSqliteInit`
 ///     - `adbc_driver_sqlite.dll` -> `AdbcDriverSqliteInit`
 ///     - `proprietary_driver.dll` -> `AdbcProprietaryDriverInit`
 /// - `$driver_type` - Driver's type which must implement [Driver] and [Default].
-/// Currently, the Rust driver is exported as an ADBC 1.1.0 C driver.
+///   Currently, the Rust driver is exported as an ADBC 1.1.0 C driver.
 #[macro_export]
 macro_rules! export_driver {
     ($func_name:ident, $driver_type:ty) => {
@@ -283,6 +511,34 @@ unsafe fn copy_bytes(src: &[u8], dst: *mut u8, length: *mut usize) {
     *length = n;
 }
 
+// Read a canonical option with the type declared by its spec when the object
+// does not store it with the requested type, e.g. an integer set as a string.
+fn get_option_converted<'a, T, Object>(object: &Object, key: &'a str, error: Error) -> Result<T>
+where
+    T: TypedValue,
+    Object: Optionable,
+    Object::Option: From<&'a str>,
+{
+    match OptionSpec::lookup(key) {
+        Some(spec)
+            if spec.value_type != T::TYPE
+                && matches!(error.status, Status::InvalidData | Status::InvalidState) =>
+        {
+            object.get_typed(key.into())
+        }
+        _ => Err(error),
+    }
+}
+
+// Convert the value of a canonical option to the type declared by its spec, so
+// that drivers get the same value whichever `*SetOption*` function was used.
+fn convert_option(key: &str, value: OptionValue) -> Result<OptionValue> {
+    match OptionSpec::lookup(key) {
+        Some(spec) => spec.convert(value),
+        None => Ok(value),
+    }
+}
+
 // SAFETY: Will panic if `key` is null.
 unsafe fn get_option_int<'a, OptionType, Object>(
     object: Option<&mut Object>,
@@ -317,7 +573,9 @@ where
         }
     } else {
         let object = object.expect("Broken invariant");
-        let optvalue = object.get_option_int(key.into())?;
+        let optvalue = object
+            .get_option_int(key.into())
+            .or_else(|error| get_option_converted(object, key, error))?;
         Ok(optvalue)
     }
 }
@@ -356,7 +614,9 @@ where
         }
     } else {
         let object = object.expect("Broken invariant");
-        let optvalue = object.get_option_double(key.into())?;
+        let optvalue = object
+            .get_option_double(key.into())
+            .or_else(|error| get_option_converted(object, key, error))?;
         Ok(optvalue)
     }
 }
@@ -395,7 +655,9 @@ where
         }
     } else {
         let object = object.expect("Broken invariant");
-        let optvalue = object.get_option_string(key.into())?;
+        let optvalue = object
+            .get_option_string(key.into())
+            .or_else(|error| get_option_converted(object, key, error))?;
         Ok(optvalue)
     }
 }
@@ -434,7 +696,9 @@ where
         }
     } else {
         let object = object.expect("Broken invariant");
-        let optvalue = object.get_option_bytes(key.into())?;
+        let optvalue = object
+            .get_option_bytes(key.into())
+            .or_else(|error| get_option_converted(object, key, error))?;
         Ok(optvalue)
     }
 }
@@ -472,7 +736,8 @@ unsafe fn database_set_option_impl<DriverType: Driver, Value: Into<OptionValue>>
             options.insert(key.into(), value.into());
         }
         ExportedDatabase::Database(database) => {
-            check_err!(database.set_option(key.into(), value.into()), error);
+            let value = check_err!(convert_option(key, value.into()), error);
+            check_err!(database.set_option(key.into(), value), error);
         }
     }
 
@@ -715,7 +980,8 @@ unsafe fn connection_set_option_impl<DriverType: Driver, Value: Into<OptionValue
             options.insert(key.into(), value.into());
         }
         ExportedConnection::Connection(connection) => {
-            check_err!(connection.set_option(key.into(), value.into()), error);
+            let value = check_err!(convert_option(key, value.into()), error);
+            check_err!(connection.set_option(key.into(), value), error);
         }
     }
 
@@ -941,7 +1207,7 @@ unsafe extern "C" fn connection_get_table_types<DriverType: Driver + 'static>(
     let connection = check_err!(exported.try_connection(), error);
 
     let reader = check_err!(connection.get_table_types(), error);
-    let reader = Box::new(reader);
+    let reader = Box::new(trace::TracedReader::new(reader));
     let reader = FFI_ArrowArrayStream::new(reader);
     std::ptr::write_unaligned(out, reader);
 
@@ -999,7 +1265,7 @@ unsafe extern "C" fn connection_get_info<DriverType: Driver + 'static>(
     };
 
     let reader = check_err!(connection.get_info(info_codes), error);
-    let reader = Box::new(reader);
+    let reader = Box::new(trace::TracedReader::new(reader));
     let reader = FFI_ArrowArrayStream::new(reader);
     std::ptr::write_unaligned(out, reader);
 
@@ -1057,7 +1323,7 @@ unsafe extern "C" fn connection_get_statistic_names<DriverType: Driver + 'static
     let connection = check_err!(exported.try_connection(), error);
 
     let reader = check_err!(connection.get_statistic_names(), error);
-    let reader = Box::new(reader);
+    let reader = Box::new(trace::TracedReader::new(reader));
     let reader = FFI_ArrowArrayStream::new(reader);
     std::ptr::write_unaligned(out, reader);
 
@@ -1080,7 +1346,7 @@ unsafe extern "C" fn connection_read_partition<DriverType: Driver + 'static>(
 
     let partition = std::slice::from_raw_parts(serialized_partition, serialized_length);
     let reader = check_err!(connection.read_partition(partition), error);
-    let reader = Box::new(reader);
+    let reader = Box::new(trace::TracedReader::new(reader));
     let reader = FFI_ArrowArrayStream::new(reader);
     std::ptr::write_unaligned(out, reader);
 
@@ -1109,7 +1375,7 @@ unsafe extern "C" fn connection_get_statistics<DriverType: Driver + 'static>(
 
     let reader = connection.get_statistics(catalog, db_schema, table_name, approximate);
     let reader = check_err!(reader, error);
-    let reader = Box::new(reader);
+    let reader = Box::new(trace::TracedReader::new(reader));
     let reader = FFI_ArrowArrayStream::new(reader);
     std::ptr::write_unaligned(out, reader);
 
@@ -1161,7 +1427,7 @@ unsafe extern "C" fn connection_get_objects<DriverType: Driver + 'static>(
         column_name,
     );
     let reader = check_err!(reader, error);
-    let reader = Box::new(reader);
+    let reader = Box::new(trace::TracedReader::new(reader));
     let reader = FFI_ArrowArrayStream::new(reader);
     std::ptr::write_unaligned(out, reader);
 
@@ -1195,7 +1461,8 @@ unsafe fn statement_set_option_impl<DriverType: Driver, Value: Into<OptionValue>
 
     let exported = check_err!(statement_private_data::<DriverType>(statement), error);
     let key = check_err!(CStr::from_ptr(key).to_str(), error);
-    check_err!(exported.0.set_option(key.into(), value.into()), error);
+    let value = check_err!(convert_option(key, value.into()), error);
+    check_err!(exported.statement.set_option(key.into(), value), error);
     ADBC_STATUS_OK
 }
 
@@ -1213,7 +1480,10 @@ unsafe extern "C" fn statement_new<DriverType: Driver>(
     let statement = statement.as_mut().unwrap();
     let inner_statement = check_err!(inner_connection.new_statement(), error);
 
-    let exported = Box::new(ExportedStatement::<DriverType>(inner_statement));
+    let exported = Box::new(ExportedStatement::<DriverType> {
+        statement: inner_statement,
+        query: None,
+    });
     statement.private_data = Box::into_raw(exported) as *mut c_void;
 
     ADBC_STATUS_OK
@@ -1308,7 +1578,7 @@ unsafe extern "C" fn statement_get_option<DriverType: Driver>(
     check_not_null!(length, error);
 
     let exported = check_err!(statement_private_data::<DriverType>(statement), error);
-    let optvalue = get_option(Some(&mut exported.0), None, key);
+    let optvalue = get_option(Some(&mut exported.statement), None, key);
     let optvalue = check_err!(optvalue, error);
     check_err!(copy_string(&optvalue, value, length), error);
 
@@ -1326,7 +1596,10 @@ unsafe extern "C" fn statement_get_option_int<DriverType: Driver>(
     check_not_null!(value, error);
 
     let exported = check_err!(statement_private_data::<DriverType>(statement), error);
-    let optvalue = check_err!(get_option_int(Some(&mut exported.0), None, key), error);
+    let optvalue = check_err!(
+        get_option_int(Some(&mut exported.statement), None, key),
+        error
+    );
     std::ptr::write_unaligned(value, optvalue);
 
     ADBC_STATUS_OK
@@ -1343,7 +1616,10 @@ unsafe extern "C" fn statement_get_option_double<DriverType: Driver>(
     check_not_null!(value, error);
 
     let exported = check_err!(statement_private_data::<DriverType>(statement), error);
-    let optvalue = check_err!(get_option_double(Some(&mut exported.0), None, key), error);
+    let optvalue = check_err!(
+        get_option_double(Some(&mut exported.statement), None, key),
+        error
+    );
     std::ptr::write_unaligned(value, optvalue);
 
     ADBC_STATUS_OK
@@ -1362,7 +1638,7 @@ unsafe extern "C" fn statement_get_option_bytes<DriverType: Driver>(
     check_not_null!(length, error);
 
     let exported = check_err!(statement_private_data::<DriverType>(statement), error);
-    let optvalue = get_option_bytes(Some(&mut exported.0), None, key);
+    let optvalue = get_option_bytes(Some(&mut exported.statement), None, key);
     let optvalue = check_err!(optvalue, error);
     copy_bytes(&optvalue, value, length);
     ADBC_STATUS_OK
@@ -1379,7 +1655,7 @@ unsafe extern "C" fn statement_bind<DriverType: Driver>(
     check_not_null!(schema, error);
 
     let exported = check_err!(statement_private_data::<DriverType>(statement), error);
-    let statement = &mut exported.0;
+    let statement = &mut exported.statement;
 
     let schema = schema.as_ref().unwrap();
     let data = FFI_ArrowArray::from_raw(values);
@@ -1410,7 +1686,7 @@ unsafe extern "C" fn statement_bind_stream<DriverType: Driver>(
     check_not_null!(stream, error);
 
     let exported = check_err!(statement_private_data::<DriverType>(statement), error);
-    let statement = &mut exported.0;
+    let statement = &mut exported.statement;
 
     let reader = check_err!(ArrowArrayStreamReader::from_raw(stream), error);
     let reader = Box::new(reader);
@@ -1426,7 +1702,7 @@ unsafe extern "C" fn statement_cancel<DriverType: Driver>(
     check_not_null!(statement, error);
 
     let exported = check_err!(statement_private_data::<DriverType>(statement), error);
-    let statement = &mut exported.0;
+    let statement = &mut exported.statement;
 
     check_err!(statement.cancel(), error);
 
@@ -1442,11 +1718,12 @@ unsafe extern "C" fn statement_execute_query<DriverType: Driver + 'static>(
     check_not_null!(statement, error);
 
     let exported = check_err!(statement_private_data::<DriverType>(statement), error);
-    let statement = &mut exported.0;
+    trace::record_query(exported.query.as_deref());
+    let statement = &mut exported.statement;
 
     if !out.is_null() {
         let reader = check_err!(statement.execute(), error);
-        let reader = Box::new(reader);
+        let reader = Box::new(trace::TracedReader::new(reader));
         let reader = FFI_ArrowArrayStream::new(reader);
         std::ptr::write_unaligned(out, reader);
     } else {
@@ -1468,7 +1745,8 @@ unsafe extern "C" fn statement_execute_schema<DriverType: Driver>(
     check_not_null!(schema, error);
 
     let exported = check_err!(statement_private_data::<DriverType>(statement), error);
-    let statement = &mut exported.0;
+    trace::record_query(exported.query.as_deref());
+    let statement = &mut exported.statement;
 
     let schema_value = check_err!(statement.execute_schema(), error);
     let schema_value: FFI_ArrowSchema = check_err!(schema_value.try_into(), error);
@@ -1489,7 +1767,8 @@ unsafe extern "C" fn statement_execute_partitions<DriverType: Driver>(
     check_not_null!(partitions, error);
 
     let exported = check_err!(statement_private_data::<DriverType>(statement), error);
-    let statement = &mut exported.0;
+    trace::record_query(exported.query.as_deref());
+    let statement = &mut exported.statement;
 
     let result = check_err!(statement.execute_partitions(), error);
 
@@ -1513,7 +1792,8 @@ unsafe extern "C" fn statement_prepare<DriverType: Driver>(
     check_not_null!(statement, error);
 
     let exported = check_err!(statement_private_data::<DriverType>(statement), error);
-    let statement = &mut exported.0;
+    trace::record_query(exported.query.as_deref());
+    let statement = &mut exported.statement;
     check_err!(statement.prepare(), error);
     ADBC_STATUS_OK
 }
@@ -1527,10 +1807,12 @@ unsafe extern "C" fn statement_set_sql_query<DriverType: Driver>(
     check_not_null!(query, error);
 
     let exported = check_err!(statement_private_data::<DriverType>(statement), error);
-    let statement = &mut exported.0;
+    let statement = &mut exported.statement;
 
     let query = check_err!(CStr::from_ptr(query).to_str(), error);
+    trace::record_query(Some(query));
     check_err!(statement.set_sql_query(query), error);
+    exported.query = Some(query.to_string());
 
     ADBC_STATUS_OK
 }
@@ -1545,10 +1827,11 @@ unsafe extern "C" fn statement_set_substrait_plan<DriverType: Driver>(
     check_not_null!(plan, error);
 
     let exported = check_err!(statement_private_data::<DriverType>(statement), error);
-    let statement = &mut exported.0;
+    let statement = &mut exported.statement;
 
     let plan = std::slice::from_raw_parts(plan, length);
     check_err!(statement.set_substrait_plan(plan), error);
+    exported.query = None;
 
     ADBC_STATUS_OK
 }
@@ -1562,7 +1845,7 @@ unsafe extern "C" fn statement_get_parameter_schema<DriverType: Driver>(
     check_not_null!(schema, error);
 
     let exported = check_err!(statement_private_data::<DriverType>(statement), error);
-    let statement = &exported.0;
+    let statement = &exported.statement;
 
     let schema_value = check_err!(statement.get_parameter_schema(), error);
     let schema_value: FFI_ArrowSchema = check_err!(schema_value.try_into(), error);
@@ -1575,19 +1858,15 @@ unsafe extern "C" fn statement_get_parameter_schema<DriverType: Driver>(
 
 unsafe extern "C" fn error_get_detail_count(error: *const FFI_AdbcError) -> c_int {
     match error.as_ref() {
-        None => 0,
-        Some(error) => {
-            if !error.private_data.is_null() {
-                let private_data = error.private_data as *const ErrorPrivateData;
-                (*private_data)
-                    .keys
-                    .len()
-                    .try_into()
-                    .expect("Overflow with error detail count")
-            } else {
-                0
-            }
+        Some(error) if !error.private_data.is_null() => {
+            let private_data = &*(error.private_data as *const ErrorPrivateData);
+            private_data
+                .keys
+                .len()
+                .try_into()
+                .expect("Overflow with error detail count")
         }
+        _ => 0,
     }
 }
 
@@ -1613,11 +1892,11 @@ unsafe extern "C" fn error_get_detail(
             if error.private_data.is_null() {
                 return default;
             }
-            let private_data = error.private_data as *const ErrorPrivateData;
+            let private_data = &*(error.private_data as *const ErrorPrivateData);
 
-            let key = (*private_data).keys[index].as_ptr();
-            let value = (*private_data).values[index].as_ptr();
-            let value_length = (*private_data).values[index].len();
+            let key = private_data.keys[index].as_ptr();
+            let value = private_data.values[index].as_ptr();
+            let value_length = private_data.values[index].len();
 
             FFI_AdbcErrorDetail {
                 key,
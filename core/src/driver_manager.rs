@@ -6,9 +6,10 @@
 //!
 //! There are two ways that drivers can be used:
 //! 1. By linking (either statically or dynamically) the driver implementation
-//! at link-time and then using [ManagedDriver::load_static].
+//!    at link-time and then using [ManagedDriver::load_static].
 //! 2. By loading the driver implementation at run-time (with `dlopen/LoadLibrary`)
-//! using [ManagedDriver::load_dynamic].
+//!    using [ManagedDriver::load_dynamic_from_filename] or
+//!    [ManagedDriver::load_dynamic_from_name].
 //!
 //! Drivers are initialized using a function provided by the driver as a main
 //! entrypoint, canonically called `AdbcDriverInit`. Although many will use a
@@ -18,10 +19,25 @@
 //! ## Using across threads
 //!
 //! [ManagedDriver], [ManagedDatabase], [ManagedConnection] and [ManagedStatement]
-//! can be used across threads though all of their operations are serialized
-//! under the hood. They hold their inner implementations within [std::sync::Arc],
+//! can be used across threads. Operations on the same object are serialized
+//! under the hood, while different objects of the same driver, such as two
+//! connections, can be used concurrently. A running call can be cancelled
+//! from another thread with a [CancelHandle]. They hold their inner implementations within [std::sync::Arc],
 //! so they are cheaply clonable.
 //!
+//! ## Closing
+//!
+//! Objects are released when their last handle is dropped, failures being
+//! reported through the hook installed with [set_release_hook]. To handle
+//! failures instead, objects can be released with their `close` method.
+//!
+//! ## Timeouts
+//!
+//! A hung driver would block its callers forever, so [ManagedConnection] and
+//! [ManagedStatement] accept a timeout with `set_timeout`. Calls and result
+//! set reads running longer are cancelled from a watchdog thread and fail with
+//! [Status::Timeout].
+//!
 //! ## Example
 //!
 //! ```rust
@@ -79,35 +95,115 @@
 // thread may make a call. They do not allow concurrent access from multiple
 // threads.
 //
-// In order to implement this semantics, all FFI objects are wrapped into
-// `Mutex`. Hence, we need to deal with multiple locks at once, so care must
-// be taken to avoid deadlock and in particular we must avoid "lock inversion".
-// The general convention chosen here is to first acquire lock to the driver
-// and then acquire lock to the specific object under implementation.
+// In order to implement this semantics, FFI databases, connections and
+// statements are wrapped into `Mutex`. A call only acquires the lock of the
+// object under implementation, so that different objects of the same driver
+// can be used concurrently. The FFI driver is not locked: its function table
+// is immutable once initialized.
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::ffi::{CStr, CString, OsStr};
-use std::ops::DerefMut;
+use std::ops::{Deref, DerefMut};
 use std::os::raw::{c_char, c_void};
+use std::path::Path;
 use std::ptr::{null, null_mut};
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, RwLock};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
 
 use arrow::array::{Array, RecordBatch, RecordBatchReader, StructArray};
+use arrow::datatypes::SchemaRef;
+use arrow::error::ArrowError;
 use arrow::ffi::{to_ffi, FFI_ArrowSchema};
 use arrow::ffi_stream::{ArrowArrayStreamReader, FFI_ArrowArrayStream};
-
+use once_cell::sync::Lazy;
+
+use crate::capabilities::Capabilities;
+use crate::credentials::{self, CredentialProvider};
+use crate::metrics::{self, Flow, MeteredReader};
+#[cfg(feature = "ipc")]
+use crate::partitions::PartitionEnvelope;
+use crate::secret::{self, Secret};
+use crate::uri::DatabaseUri;
 use crate::{
     error::{Error, Status},
-    options::{self, AdbcVersion, InfoCode, OptionValue},
+    options::{self, AdbcVersion, InfoCode, OptionDatabase, OptionSpec, OptionType, OptionValue},
     PartitionedResult, Result,
 };
-use crate::{ffi, ffi::types::driver_method, Optionable};
+use crate::{ffi, ffi::types::driver_method, trace, Optionable};
 use crate::{Connection, Database, Driver, Statement};
 
 const ERR_ONLY_STRING_OPT: &str = "Only string option value are supported with ADBC 1.0.0";
 const ERR_CANCEL_UNSUPPORTED: &str =
     "Canceling connection or statement is not supported with ADBC 1.0.0";
 const ERR_STATISTICS_UNSUPPORTED: &str = "Statistics are not supported with ADBC 1.0.0";
+const ERR_TIMEOUT_UNSUPPORTED: &str = "Timeouts are not supported with ADBC 1.0.0";
+
+/// Wrap a result set returned by `method` to trace and meter it.
+fn wrap_reader<R: RecordBatchReader>(
+    reader: R,
+    method: &str,
+    driver: &str,
+) -> trace::TracedReader<MeteredReader<R>> {
+    let reader = MeteredReader::new(reader, metrics::recorder(), Flow::Stream, method, driver);
+    trace::TracedReader::new(reader)
+}
+
+/// Wrap the methods of an impl block marked with `#[trace(method)]` or
+/// `#[trace(method, query)]` in [trace::call], leaving their bodies and the
+/// other items as they are. The receiver must have a `driver_name` method.
+macro_rules! traced {
+    (impl $trait:ident for $type:ident { $($items:tt)* }) => {
+        impl $trait for $type {
+            traced!(@items $($items)*);
+        }
+    };
+    (impl $type:ident { $($items:tt)* }) => {
+        impl $type {
+            traced!(@items $($items)*);
+        }
+    };
+    (@items) => {};
+    (@items
+        #[trace($method:expr $(, $query:expr)?)]
+        $(#[$attr:meta])*
+        $vis:vis fn $name:ident($($args:tt)*) -> $ret:ty $body:block
+        $($rest:tt)*
+    ) => {
+        $(#[$attr])*
+        $vis fn $name($($args)*) -> $ret {
+            traced!(@call ($($args)*) $method, traced!(@query $($query)?), $body)
+        }
+        traced!(@items $($rest)*);
+    };
+    (@items
+        $(#[$attr:meta])*
+        $vis:vis fn $name:ident$(<$($generic:ident),*>)?($($args:tt)*) $(-> $ret:ty)? $body:block
+        $($rest:tt)*
+    ) => {
+        $(#[$attr])*
+        $vis fn $name$(<$($generic),*>)?($($args)*) $(-> $ret)? $body
+        traced!(@items $($rest)*);
+    };
+    (@items type $name:ident = $type:ty; $($rest:tt)*) => {
+        type $name = $type;
+        traced!(@items $($rest)*);
+    };
+    // The receiver is taken from the arguments for hygiene.
+    (@call (&mut $self:tt $($args:tt)*) $($call:tt)*) => {
+        traced!(@call (&$self) $($call)*)
+    };
+    (@call (&$self:tt $($args:tt)*) $method:expr, $query:expr, $body:block) => {
+        trace::call($method, $self.driver_name(), $query, || $body)
+    };
+    (@query) => {
+        None
+    };
+    (@query $query:expr) => {
+        $query
+    };
+}
 
 fn check_status(status: ffi::FFI_AdbcStatusCode, error: ffi::FFI_AdbcError) -> Result<()> {
     match status {
@@ -120,6 +216,330 @@ fn check_status(status: ffi::FFI_AdbcStatusCode, error: ffi::FFI_AdbcError) -> R
     }
 }
 
+/// Hook called with the release method, the driver name and the error when
+/// releasing a dropped object fails, see [set_release_hook].
+pub type ReleaseHook = dyn Fn(&str, &str, &Error) + Send + Sync;
+
+static RELEASE_HOOK: RwLock<Option<Arc<ReleaseHook>>> = RwLock::new(None);
+
+/// Install the process-wide hook reporting failures to release dropped
+/// drivers, databases, connections and statements, replacing the previous one.
+///
+/// Without a hook, failures are logged as warnings with the `tracing`
+/// feature, and written to the standard error otherwise. Objects closed
+/// explicitly with their `close` method return failures instead.
+pub fn set_release_hook(hook: Arc<ReleaseHook>) {
+    *RELEASE_HOOK.write().unwrap() = Some(hook);
+}
+
+/// Remove the process-wide release hook, reporting failures by default again.
+pub fn clear_release_hook() {
+    *RELEASE_HOOK.write().unwrap() = None;
+}
+
+fn report_release_failure(method: &str, driver: &str, error: &Error) {
+    match RELEASE_HOOK.read().unwrap().as_ref() {
+        Some(hook) => hook(method, driver, error),
+        #[cfg(feature = "tracing")]
+        None => tracing::warn!(method, driver, %error, "Release failed"),
+        #[cfg(not(feature = "tracing"))]
+        None => eprintln!("{method} failed for driver {driver}: {error}"),
+    }
+}
+
+fn shared_error(object: &str) -> Error {
+    Error::with_message_and_status(
+        format!(
+            "Cannot close the {object}: it is still used by clones or by objects created from it"
+        ),
+        Status::InvalidState,
+    )
+}
+
+/// Cancels the calls of a connection or statement.
+///
+/// A running call holds the object lock, so the cancel function and the
+/// object are captured on creation and used without locking.
+#[derive(Clone, Copy)]
+enum Canceller {
+    Connection(
+        ffi::methods::FuncConnectionCancel,
+        *mut ffi::FFI_AdbcConnection,
+    ),
+    Statement(
+        ffi::methods::FuncStatementCancel,
+        *mut ffi::FFI_AdbcStatement,
+    ),
+}
+
+// SAFETY: cancel functions are meant to be called from another thread than
+// the one running the call to cancel, and the objects outlive the cancellers
+// since their owners are kept alive alongside them.
+unsafe impl Send for Canceller {}
+unsafe impl Sync for Canceller {}
+
+impl Canceller {
+    fn connection(driver: &ffi::FFI_AdbcDriver, connection: &mut ffi::FFI_AdbcConnection) -> Self {
+        Self::Connection(driver_method!(driver, ConnectionCancel), connection)
+    }
+
+    fn statement(driver: &ffi::FFI_AdbcDriver, statement: &mut ffi::FFI_AdbcStatement) -> Self {
+        Self::Statement(driver_method!(driver, StatementCancel), statement)
+    }
+
+    fn cancel(self, error: *mut ffi::FFI_AdbcError) -> ffi::FFI_AdbcStatusCode {
+        unsafe {
+            match self {
+                Self::Connection(method, connection) => method(connection, error),
+                Self::Statement(method, statement) => method(statement, error),
+            }
+        }
+    }
+
+    fn method(self) -> &'static str {
+        match self {
+            Self::Connection(..) => "AdbcConnectionCancel",
+            Self::Statement(..) => "AdbcStatementCancel",
+        }
+    }
+}
+
+/// Cancels the calls of a [ManagedConnection] or [ManagedStatement] from any
+/// thread.
+///
+/// Unlike [Connection::cancel] and [Statement::cancel], which need exclusive
+/// access to the object, a handle can be used while another thread is running
+/// a call, bypassing the serialization of calls as the ADBC specification
+/// permits for cancellation.
+#[derive(Clone)]
+pub struct CancelHandle {
+    canceller: Canceller,
+    driver: Arc<ManagedDriverInner>,
+    // Keeps the connection or statement alive while it may be cancelled.
+    _owner: Arc<dyn Send + Sync>,
+}
+
+impl CancelHandle {
+    /// Cancel the running call of the connection or statement, if any.
+    pub fn cancel(&self) -> Result<()> {
+        trace::call(self.canceller.method(), &self.driver.name, None, || {
+            if let AdbcVersion::V100 = self.driver.version {
+                return Err(Error::with_message_and_status(
+                    ERR_CANCEL_UNSUPPORTED,
+                    Status::NotImplemented,
+                ));
+            }
+            let mut error = ffi::FFI_AdbcError::with_driver(&self.driver.driver);
+            let status = self.canceller.cancel(&mut error);
+            check_status(status, error)
+        })
+    }
+}
+
+#[derive(Default)]
+struct WatchState {
+    done: bool,
+    expired: bool,
+}
+
+/// A call watched by the watchdog thread.
+struct Watched {
+    canceller: Canceller,
+    driver: Arc<ManagedDriverInner>,
+    state: Mutex<WatchState>,
+}
+
+impl Watched {
+    /// Cancel the call unless it completed.
+    fn expire(&self) {
+        let mut state = self.state.lock().unwrap();
+        if !state.done {
+            state.expired = true;
+            // The outcome of the cancellation is reported by the timed out
+            // call, hence the error is only released here.
+            let mut error = ffi::FFI_AdbcError::with_driver(&self.driver.driver);
+            self.canceller.cancel(&mut error);
+        }
+    }
+
+    /// Mark the call as completed, returning whether it was cancelled.
+    fn complete(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        state.done = true;
+        state.expired
+    }
+}
+
+/// Deadlines of the watched calls, keyed by deadline and call identifier.
+#[derive(Default)]
+struct Deadlines {
+    queue: Mutex<BTreeMap<(Instant, u64), Arc<Watched>>>,
+    condvar: Condvar,
+}
+
+static NEXT_WATCHED_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Shared by all the watchdogs, and spawning the watchdog thread when a
+/// call is first watched.
+static DEADLINES: Lazy<Arc<Deadlines>> = Lazy::new(|| {
+    let deadlines = Arc::new(Deadlines::default());
+    let watched = deadlines.clone();
+    thread::Builder::new()
+        .name("adbc-watchdog".into())
+        .spawn(move || watched.run())
+        .expect("Cannot spawn the watchdog thread");
+    deadlines
+});
+
+impl Deadlines {
+    /// Cancel the calls whose deadline passed, forever.
+    fn run(&self) {
+        let mut queue = self.queue.lock().unwrap();
+        loop {
+            let now = Instant::now();
+            queue = match queue.first_key_value() {
+                None => self.condvar.wait(queue).unwrap(),
+                Some((&(deadline, _), _)) if deadline > now => {
+                    self.condvar.wait_timeout(queue, deadline - now).unwrap().0
+                }
+                Some(_) => {
+                    let (_, watched) = queue.pop_first().unwrap();
+                    drop(queue);
+                    watched.expire();
+                    self.queue.lock().unwrap()
+                }
+            };
+        }
+    }
+}
+
+/// Cancels a call if it does not complete within a timeout.
+struct Watchdog {
+    timeout: Duration,
+    // Deadline and identifier of the call, and the call itself.
+    watched: Option<((Instant, u64), Arc<Watched>)>,
+}
+
+impl Watchdog {
+    /// Start watching a call, which is never cancelled without `timeout`.
+    fn start(
+        timeout: Option<Duration>,
+        canceller: Canceller,
+        driver: &Arc<ManagedDriverInner>,
+    ) -> Self {
+        let Some(timeout) = timeout else {
+            return Self {
+                timeout: Duration::ZERO,
+                watched: None,
+            };
+        };
+        let key = (
+            Instant::now() + timeout,
+            NEXT_WATCHED_ID.fetch_add(1, Ordering::Relaxed),
+        );
+        let watched = Arc::new(Watched {
+            canceller,
+            driver: driver.clone(),
+            state: Mutex::new(WatchState::default()),
+        });
+        let deadlines = &*DEADLINES;
+        let mut queue = deadlines.queue.lock().unwrap();
+        queue.insert(key, watched.clone());
+        if queue
+            .first_key_value()
+            .is_some_and(|(first, _)| *first == key)
+        {
+            deadlines.condvar.notify_one();
+        }
+        Self {
+            timeout,
+            watched: Some((key, watched)),
+        }
+    }
+
+    /// Stop watching the call, returning whether it was cancelled.
+    fn stop(&mut self) -> bool {
+        let Some((key, watched)) = self.watched.take() else {
+            return false;
+        };
+        DEADLINES.queue.lock().unwrap().remove(&key);
+        // Waits for the cancellation of the call if it is in progress.
+        watched.complete()
+    }
+
+    /// Stop watching the call, turning its failure into [Status::Timeout] if
+    /// it was cancelled.
+    fn check<T>(mut self, result: Result<T>) -> Result<T> {
+        match result {
+            Err(_) if self.stop() => Err(self.error()),
+            result => result,
+        }
+    }
+
+    fn error(&self) -> Error {
+        Error::with_message_and_status(
+            format!("Call timed out after {:?}", self.timeout),
+            Status::Timeout,
+        )
+    }
+}
+
+impl Drop for Watchdog {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// A result set whose batches must each be read within the timeout, if any.
+struct TimeoutReader<R> {
+    // Declared first so that the stream is released before its owner.
+    reader: R,
+    timeout: Option<Duration>,
+    canceller: Canceller,
+    // Keep the driver library loaded and the connection or statement alive
+    // while the stream may be read or cancelled.
+    driver: Arc<ManagedDriverInner>,
+    _owner: Arc<dyn Send + Sync>,
+}
+
+impl<R: RecordBatchReader + Send + 'static> TimeoutReader<R> {
+    fn wrap(
+        reader: R,
+        timeout: Option<Duration>,
+        canceller: Canceller,
+        driver: &Arc<ManagedDriverInner>,
+        owner: Arc<dyn Send + Sync>,
+    ) -> Box<dyn RecordBatchReader + Send> {
+        Box::new(Self {
+            reader,
+            timeout,
+            canceller,
+            driver: driver.clone(),
+            _owner: owner,
+        })
+    }
+}
+
+impl<R: RecordBatchReader> Iterator for TimeoutReader<R> {
+    type Item = std::result::Result<RecordBatch, ArrowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut watchdog = Watchdog::start(self.timeout, self.canceller, &self.driver);
+        match self.reader.next() {
+            Some(Err(_)) if watchdog.stop() => {
+                Some(Err(ArrowError::ExternalError(Box::new(watchdog.error()))))
+            }
+            batch => batch,
+        }
+    }
+}
+
+impl<R: RecordBatchReader> RecordBatchReader for TimeoutReader<R> {
+    fn schema(&self) -> SchemaRef {
+        self.reader.schema()
+    }
+}
+
 impl From<libloading::Error> for Error {
     fn from(value: libloading::Error) -> Self {
         Self {
@@ -133,8 +553,15 @@ impl From<libloading::Error> for Error {
 }
 
 struct ManagedDriverInner {
-    driver: Mutex<ffi::FFI_AdbcDriver>,
+    // The function table is immutable once initialized, hence read without locking.
+    driver: ffi::FFI_AdbcDriver,
     version: AdbcVersion, // Driver version
+    name: String,         // Driver name, reported in traces
+    // Filename and entrypoint of the dynamic library, reported in partition envelopes.
+    #[cfg(feature = "ipc")]
+    filename: Option<String>,
+    #[cfg(feature = "ipc")]
+    entrypoint: Option<String>,
     // The dynamic library must be kept loaded for the entire lifetime of the driver.
     // To avoid complex lifetimes we prefer to store it as part of this struct.
     // Besides, the `library` field must always appear after `driver` because of drop order:
@@ -145,22 +572,204 @@ struct ManagedDriverInner {
     _library: Option<libloading::Library>,
 }
 
+/// A function of the ADBC C API and whether a driver populated it, see
+/// [ManagedDriver::functions].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DriverFunction {
+    /// Name of the function, e.g. `AdbcStatementExecuteQuery`.
+    pub name: &'static str,
+    /// Version of ADBC which introduced the function.
+    pub version: AdbcVersion,
+    /// Whether the driver populated the function. Functions it did not
+    /// populate are replaced by stubs failing with [Status::NotImplemented].
+    pub implemented: bool,
+}
+
+macro_rules! driver_functions {
+    ($driver:expr, $($version:ident => [$($method:ident),+ $(,)?]),+ $(,)?) => {
+        vec![$($(DriverFunction {
+            name: concat!("Adbc", stringify!($method)),
+            version: AdbcVersion::$version,
+            implemented: $driver.$method.is_some(),
+        }),+),+]
+    };
+}
+
+// Functions of ADBC 1.1.0 which are not required in strict mode, since the
+// driver manager does not call them.
+const OPTIONAL_FUNCTIONS: [&str; 1] = ["AdbcErrorFromArrayStream"];
+
+impl ManagedDriverInner {
+    fn release(&mut self) -> Result<()> {
+        let Some(release) = self.driver.release else {
+            return Ok(());
+        };
+        trace::call("AdbcDriverRelease", &self.name, None, || {
+            let mut error = ffi::FFI_AdbcError::with_driver(&self.driver);
+            let status = unsafe { release(&mut self.driver, &mut error) };
+            // The driver is not released again even if this attempt failed.
+            self.driver.release = None;
+            check_status(status, error)
+        })
+    }
+}
+
+impl Drop for ManagedDriverInner {
+    fn drop(&mut self) {
+        if let Err(error) = self.release() {
+            report_release_failure("AdbcDriverRelease", &self.name, &error);
+        }
+    }
+}
+
 /// Implementation of [Driver].
 #[derive(Clone)]
 pub struct ManagedDriver {
     inner: Arc<ManagedDriverInner>,
+    credentials: Option<Arc<dyn CredentialProvider>>,
 }
 
 impl ManagedDriver {
+    /// Release the driver, returning failures instead of reporting them
+    /// through the [release hook][set_release_hook] as dropping it does.
+    ///
+    /// Fails with [Status::InvalidState] if the driver is still used by
+    /// clones of this handle or by its databases.
+    pub fn close(mut self) -> Result<()> {
+        Arc::get_mut(&mut self.inner)
+            .ok_or_else(|| shared_error("driver"))?
+            .release()
+    }
+
     /// Load a driver from an initialization function.
+    ///
+    /// The driver is reported as `static` in traces and metrics, see
+    /// [ManagedDriver::load_static_with_name].
     pub fn load_static(init: &ffi::FFI_AdbcDriverInitFunc, version: AdbcVersion) -> Result<Self> {
+        Self::load_static_with_name("static", init, version)
+    }
+
+    /// Load a driver from an initialization function, reported as `name` in
+    /// traces and metrics.
+    pub fn load_static_with_name(
+        name: &str,
+        init: &ffi::FFI_AdbcDriverInitFunc,
+        version: AdbcVersion,
+    ) -> Result<Self> {
         let driver = Self::load_impl(init, version)?;
         let inner = Arc::new(ManagedDriverInner {
-            driver: Mutex::new(driver),
+            driver,
             version,
+            name: name.into(),
+            #[cfg(feature = "ipc")]
+            filename: None,
+            #[cfg(feature = "ipc")]
+            entrypoint: None,
             _library: None,
         });
-        Ok(ManagedDriver { inner })
+        Ok(ManagedDriver {
+            inner,
+            credentials: None,
+        })
+    }
+
+    /// Fail unless a driver loaded with [AdbcVersion::V110] populates the
+    /// functions introduced by ADBC 1.1.0, rather than relying on stubs
+    /// failing with [Status::NotImplemented] when they are called.
+    ///
+    /// ```no_run
+    /// # use adbc_core::{driver_manager::ManagedDriver, options::AdbcVersion};
+    /// let driver = ManagedDriver::load_dynamic_from_name("adbc_driver_sqlite", None, AdbcVersion::V110)?
+    ///     .strict()?;
+    /// # Ok::<(), adbc_core::error::Error>(())
+    /// ```
+    pub fn strict(self) -> Result<Self> {
+        if let AdbcVersion::V100 = self.inner.version {
+            return Ok(self);
+        }
+        let missing: Vec<&str> = self
+            .functions()
+            .into_iter()
+            .filter(|f| f.version == AdbcVersion::V110 && !f.implemented)
+            .filter(|f| !OPTIONAL_FUNCTIONS.contains(&f.name))
+            .map(|f| f.name)
+            .collect();
+        if missing.is_empty() {
+            return Ok(self);
+        }
+        Err(Error::with_message_and_status(
+            format!(
+                "Driver does not implement functions of ADBC 1.1.0: {}",
+                missing.join(", ")
+            ),
+            Status::NotImplemented,
+        ))
+    }
+
+    /// Report which functions of the ADBC C API the driver populated.
+    pub fn functions(&self) -> Vec<DriverFunction> {
+        driver_functions!(
+            self.inner.driver,
+            V100 => [
+                DatabaseInit,
+                DatabaseNew,
+                DatabaseSetOption,
+                DatabaseRelease,
+                ConnectionCommit,
+                ConnectionGetInfo,
+                ConnectionGetObjects,
+                ConnectionGetTableSchema,
+                ConnectionGetTableTypes,
+                ConnectionInit,
+                ConnectionNew,
+                ConnectionSetOption,
+                ConnectionReadPartition,
+                ConnectionRelease,
+                ConnectionRollback,
+                StatementBind,
+                StatementBindStream,
+                StatementExecuteQuery,
+                StatementExecutePartitions,
+                StatementGetParameterSchema,
+                StatementNew,
+                StatementPrepare,
+                StatementRelease,
+                StatementSetOption,
+                StatementSetSqlQuery,
+                StatementSetSubstraitPlan,
+            ],
+            V110 => [
+                ErrorGetDetailCount,
+                ErrorGetDetail,
+                ErrorFromArrayStream,
+                DatabaseGetOption,
+                DatabaseGetOptionBytes,
+                DatabaseGetOptionDouble,
+                DatabaseGetOptionInt,
+                DatabaseSetOptionBytes,
+                DatabaseSetOptionDouble,
+                DatabaseSetOptionInt,
+                ConnectionCancel,
+                ConnectionGetOption,
+                ConnectionGetOptionBytes,
+                ConnectionGetOptionDouble,
+                ConnectionGetOptionInt,
+                ConnectionGetStatistics,
+                ConnectionGetStatisticNames,
+                ConnectionSetOptionBytes,
+                ConnectionSetOptionDouble,
+                ConnectionSetOptionInt,
+                StatementCancel,
+                StatementExecuteSchema,
+                StatementGetOption,
+                StatementGetOptionBytes,
+                StatementGetOptionDouble,
+                StatementGetOptionInt,
+                StatementSetOptionBytes,
+                StatementSetOptionDouble,
+                StatementSetOptionInt,
+            ],
+        )
     }
 
     /// Load a driver from a dynamic library filename.
@@ -178,17 +787,29 @@ impl ManagedDriver {
         entrypoint: Option<&[u8]>,
         version: AdbcVersion,
     ) -> Result<Self> {
-        let entrypoint = entrypoint.unwrap_or(b"AdbcDriverInit");
         let library = unsafe { libloading::Library::new(filename.as_ref())? };
         let init: libloading::Symbol<ffi::FFI_AdbcDriverInitFunc> =
-            unsafe { library.get(entrypoint)? };
+            unsafe { library.get(entrypoint.unwrap_or(b"AdbcDriverInit"))? };
         let driver = Self::load_impl(&init, version)?;
+        let name = Path::new(filename.as_ref())
+            .file_stem()
+            .unwrap_or(filename.as_ref())
+            .to_string_lossy()
+            .into_owned();
         let inner = Arc::new(ManagedDriverInner {
-            driver: Mutex::new(driver),
+            driver,
             version,
+            name,
+            #[cfg(feature = "ipc")]
+            filename: Some(filename.as_ref().to_string_lossy().into_owned()),
+            #[cfg(feature = "ipc")]
+            entrypoint: entrypoint.map(|e| String::from_utf8_lossy(e).into_owned()),
             _library: Some(library),
         });
-        Ok(ManagedDriver { inner })
+        Ok(ManagedDriver {
+            inner,
+            credentials: None,
+        })
     }
 
     /// Load a driver from a dynamic library name.
@@ -208,6 +829,98 @@ impl ManagedDriver {
         Self::load_dynamic_from_filename(filename, entrypoint, version)
     }
 
+    /// Load the driver handling the scheme of `uri`, see [DatabaseUri::driver].
+    ///
+    /// The database can then be created with the options of the URI, see
+    /// [DatabaseUri::options].
+    pub fn load_from_uri(uri: &DatabaseUri, version: AdbcVersion) -> Result<Self> {
+        Self::load_dynamic_from_name(uri.driver(), None, version)
+    }
+
+    /// Obtain credentials from `provider` when creating databases, and again
+    /// before creating connections once the credentials of their database
+    /// are about to expire, see [credentials::REFRESH_MARGIN].
+    ///
+    /// The options of the credentials are set after those given to
+    /// [Driver::new_database_with_opts]. Since drivers only accept them
+    /// before the database is initialized, refreshing them replaces the
+    /// database with a new one, on which all the options set on the database
+    /// are set again. The replaced database is released once its connections
+    /// are.
+    pub fn with_credentials(mut self, provider: impl CredentialProvider + 'static) -> Self {
+        self.credentials = Some(Arc::new(provider));
+        self
+    }
+
+    /// Wrap the partitions of `result`, obtained from a database with the
+    /// given `uri`, into envelopes which workers can read with
+    /// [ManagedDriver::read_envelope].
+    ///
+    /// Fails with [Status::InvalidState] if the driver was not loaded from a
+    /// dynamic library, since workers could not load it.
+    #[cfg(feature = "ipc")]
+    pub fn envelopes(
+        &self,
+        result: &PartitionedResult,
+        uri: Option<&str>,
+    ) -> Result<Vec<PartitionEnvelope>> {
+        let filename = self.inner.filename.as_ref().ok_or_else(|| {
+            Error::with_message_and_status(
+                "Cannot describe the partitions of a statically loaded driver",
+                Status::InvalidState,
+            )
+        })?;
+        let envelopes = result.partitions.iter().map(|partition| {
+            let mut envelope = PartitionEnvelope::new(
+                filename,
+                self.inner.version,
+                partition.clone(),
+                result.schema.clone(),
+            );
+            envelope.entrypoint = self.inner.entrypoint.clone();
+            match uri {
+                Some(uri) => envelope.with_uri(uri),
+                None => envelope,
+            }
+        });
+        Ok(envelopes.collect())
+    }
+
+    /// Read the partition of `envelope` with this driver, connecting to the
+    /// database of the envelope with `opts` set after its URI.
+    ///
+    /// The driver named by the envelope is never loaded, since envelopes may
+    /// come from untrusted sources: workers load the driver themselves, and
+    /// this fails with [Status::InvalidArguments] unless the envelope names
+    /// its library and entrypoint.
+    ///
+    /// The database and connection are released once the returned reader is
+    /// dropped.
+    #[cfg(feature = "ipc")]
+    pub fn read_envelope(
+        &self,
+        envelope: &PartitionEnvelope,
+        opts: impl IntoIterator<Item = (options::OptionDatabase, OptionValue)>,
+    ) -> Result<impl RecordBatchReader + Send> {
+        let library = |filename: &str| Path::new(filename).file_name().map(OsStr::to_owned);
+        let filename = self.inner.filename.as_deref();
+        if filename.map(library) != Some(library(&envelope.driver))
+            || self.inner.entrypoint != envelope.entrypoint
+        {
+            return Err(Error::with_message_and_status(
+                format!(
+                    "Cannot read a partition of driver {} with driver {}",
+                    envelope.driver,
+                    filename.unwrap_or(self.driver_name())
+                ),
+                Status::InvalidArguments,
+            ));
+        }
+        let mut driver = self.clone();
+        let connection = envelope.new_database(&mut driver, opts)?.new_connection()?;
+        connection.read_partition_owned(&envelope.partition)
+    }
+
     fn load_impl(
         init: &ffi::FFI_AdbcDriverInitFunc,
         version: AdbcVersion,
@@ -224,8 +937,13 @@ impl ManagedDriver {
         check_status(status, error)?;
         Ok(driver)
     }
+
+    fn driver_name(&self) -> &str {
+        &self.inner.name
+    }
 }
 
+traced! {
 impl Driver for ManagedDriver {
     type DatabaseType = ManagedDatabase;
 
@@ -233,35 +951,169 @@ impl Driver for ManagedDriver {
         self.new_database_with_opts(None)
     }
 
+    #[trace("AdbcDatabaseInit")]
     fn new_database_with_opts(
         &mut self,
         opts: impl IntoIterator<Item = (<Self::DatabaseType as Optionable>::Option, OptionValue)>,
     ) -> Result<Self::DatabaseType> {
-        let driver = &self.inner.driver.lock().unwrap();
-        let mut database = ffi::FFI_AdbcDatabase::default();
+        let (database, rotation) = match &self.credentials {
+            None => (init_database(&self.inner, opts)?, None),
+            Some(provider) => {
+                let mut rotation = Rotation {
+                    provider: provider.clone(),
+                    expires_at: None,
+                    options: opts.into_iter().collect(),
+                    post_init: Vec::new(),
+                    users: Arc::default(),
+                    retired: Vec::new(),
+                };
+                (rotation.database(&self.inner)?, Some(Mutex::new(rotation)))
+            }
+        };
+        let inner = Arc::new(ManagedDatabaseInner {
+            database: Mutex::new(database),
+            id: NEXT_DATABASE_ID.fetch_add(1, Ordering::Relaxed),
+            open_connections: AtomicUsize::new(0),
+            driver: self.inner.clone(),
+            rotation,
+        });
+        Ok(Self::DatabaseType { inner })
+    }
+}
+}
 
-        // DatabaseNew
-        let mut error = ffi::FFI_AdbcError::with_driver(driver);
-        let method = driver_method!(driver, DatabaseNew);
-        let status = unsafe { method(&mut database, &mut error) };
-        check_status(status, error)?;
+/// Create a database, set `opts` on it and initialize it.
+fn init_database(
+    driver: &ManagedDriverInner,
+    opts: impl IntoIterator<Item = (OptionDatabase, OptionValue)>,
+) -> Result<ffi::FFI_AdbcDatabase> {
+    let mut database = ffi::FFI_AdbcDatabase::default();
+
+    // DatabaseNew
+    let mut error = ffi::FFI_AdbcError::with_driver(&driver.driver);
+    let method = driver_method!(&driver.driver, DatabaseNew);
+    let status = unsafe { method(&mut database, &mut error) };
+    check_status(status, error)?;
 
+    let init = |database: &mut ffi::FFI_AdbcDatabase| {
         // DatabaseSetOption
         for (key, value) in opts {
-            set_option_database(driver, &mut database, self.inner.version, key, value)?;
+            set_option_database(&driver.driver, database, driver.version, key, value)?;
         }
 
         // DatabaseInit
-        let mut error = ffi::FFI_AdbcError::with_driver(driver);
-        let method = driver_method!(driver, DatabaseInit);
-        let status = unsafe { method(&mut database, &mut error) };
-        check_status(status, error)?;
+        let mut error = ffi::FFI_AdbcError::with_driver(&driver.driver);
+        let method = driver_method!(&driver.driver, DatabaseInit);
+        let status = unsafe { method(database, &mut error) };
+        check_status(status, error)
+    };
+    if let Err(error) = init(&mut database) {
+        if let Err(error) = release_database(driver, &mut database) {
+            report_release_failure("AdbcDatabaseRelease", &driver.name, &error);
+        }
+        return Err(error);
+    }
+    Ok(database)
+}
 
-        let inner = Arc::new(ManagedDatabaseInner {
-            database: Mutex::new(database),
-            driver: self.inner.clone(),
+fn release_database(
+    driver: &ManagedDriverInner,
+    database: &mut ffi::FFI_AdbcDatabase,
+) -> Result<()> {
+    if database.private_data.is_null() {
+        return Ok(());
+    }
+    trace::call("AdbcDatabaseRelease", &driver.name, None, || {
+        let mut error = ffi::FFI_AdbcError::with_driver(&driver.driver);
+        let method = driver_method!(&driver.driver, DatabaseRelease);
+        let status = unsafe { method(database, &mut error) };
+        // The database is not released again even if this attempt failed.
+        database.private_data = null_mut();
+        check_status(status, error)
+    })
+}
+
+/// The credentials of a database, which is replaced by a new database when
+/// they expire: drivers only accept credentials before `AdbcDatabaseInit`.
+struct Rotation {
+    provider: Arc<dyn CredentialProvider>,
+    expires_at: Option<SystemTime>, // Expiry of the credentials of the database
+    // Options set on the database before and after its initialization, set
+    // again on the new databases.
+    options: Vec<(OptionDatabase, OptionValue)>,
+    post_init: Vec<(OptionDatabase, OptionValue)>,
+    // Held by the connections of the database.
+    users: Arc<()>,
+    // Replaced databases, released once their connections are.
+    retired: Vec<(ffi::FFI_AdbcDatabase, Arc<()>)>,
+}
+
+impl Rotation {
+    /// Create a database with fresh credentials.
+    fn database(&mut self, driver: &ManagedDriverInner) -> Result<ffi::FFI_AdbcDatabase> {
+        let credentials = self.provider.credentials()?;
+        let opts = self.options.iter().chain(credentials.options()).cloned();
+        let mut database = init_database(driver, opts)?;
+        for (key, value) in &self.post_init {
+            let set = set_option_database(
+                &driver.driver,
+                &mut database,
+                driver.version,
+                key,
+                value.clone(),
+            );
+            if let Err(error) = set {
+                if let Err(error) = release_database(driver, &mut database) {
+                    report_release_failure("AdbcDatabaseRelease", &driver.name, &error);
+                }
+                return Err(error);
+            }
+        }
+        self.expires_at = credentials.expiry();
+        Ok(database)
+    }
+
+    /// Remember an option set on the initialized database.
+    fn remember(&mut self, key: OptionDatabase, value: OptionValue) {
+        match self.post_init.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, v)) => *v = value,
+            None => self.post_init.push((key, value)),
+        }
+    }
+
+    /// Release the replaced databases which no longer have connections.
+    fn release_retired(&mut self, driver: &ManagedDriverInner) {
+        self.retired.retain_mut(|(database, users)| {
+            if Arc::strong_count(users) > 1 {
+                return true;
+            }
+            if let Err(error) = release_database(driver, database) {
+                report_release_failure("AdbcDatabaseRelease", &driver.name, &error);
+            }
+            false
         });
-        Ok(Self::DatabaseType { inner })
+    }
+}
+
+impl Drop for Rotation {
+    fn drop(&mut self) {
+        let options = self.options.iter_mut().chain(self.post_init.iter_mut());
+        secret::zeroize_secrets(options.map(|(key, value)| (&*key, value)));
+    }
+}
+
+// Drivers implementing ADBC 1.0.0 only accept string values, which is how
+// canonical options are encoded when they are not strings or bytes.
+fn to_version_value(version: AdbcVersion, key: &str, value: OptionValue) -> OptionValue {
+    match (version, OptionSpec::lookup(key)) {
+        (AdbcVersion::V100, Some(spec))
+            if spec.value_type != OptionType::Bytes && !matches!(value, OptionValue::String(_)) =>
+        {
+            spec.convert(value.clone())
+                .and_then(|value| value.convert(OptionType::String))
+                .unwrap_or(value)
+        }
+        _ => value,
     }
 }
 
@@ -272,11 +1124,14 @@ fn set_option_database(
     key: impl AsRef<str>,
     value: OptionValue,
 ) -> Result<()> {
+    // The value may be secret, so it is zeroized along with its C copy.
+    let value = Secret::new(to_version_value(version, key.as_ref(), value));
     let key = CString::new(key.as_ref())?;
     let mut error = ffi::FFI_AdbcError::with_driver(driver);
-    let status = match (version, value) {
+    let status = match (version, value.expose()) {
         (_, OptionValue::String(value)) => {
-            let value = CString::new(value)?;
+            let value = Secret::new(CString::new(value.as_str())?);
+            let value = value.expose();
             let method = driver_method!(driver, DatabaseSetOption);
             unsafe { method(database, key.as_ptr(), value.as_ptr(), &mut error) }
         }
@@ -294,11 +1149,11 @@ fn set_option_database(
         }
         (AdbcVersion::V110, OptionValue::Int(value)) => {
             let method = driver_method!(driver, DatabaseSetOptionInt);
-            unsafe { method(database, key.as_ptr(), value, &mut error) }
+            unsafe { method(database, key.as_ptr(), *value, &mut error) }
         }
         (AdbcVersion::V110, OptionValue::Double(value)) => {
             let method = driver_method!(driver, DatabaseSetOptionDouble);
-            unsafe { method(database, key.as_ptr(), value, &mut error) }
+            unsafe { method(database, key.as_ptr(), *value, &mut error) }
         }
         (AdbcVersion::V100, _) => Err(Error::with_message_and_status(
             ERR_ONLY_STRING_OPT,
@@ -383,19 +1238,37 @@ where
     Ok(value.to_string_lossy().to_string())
 }
 
+static NEXT_DATABASE_ID: AtomicU64 = AtomicU64::new(0);
+
 struct ManagedDatabaseInner {
     database: Mutex<ffi::FFI_AdbcDatabase>,
+    id: u64,                       // Database identifier, reported in metrics
+    open_connections: AtomicUsize, // Number of connections not yet released
     driver: Arc<ManagedDriverInner>,
+    rotation: Option<Mutex<Rotation>>, // Set if credentials are provided
+}
+
+impl ManagedDatabaseInner {
+    fn release(&mut self) -> Result<()> {
+        // No connection is left, so the replaced databases can be released.
+        let retired = match &mut self.rotation {
+            Some(rotation) => std::mem::take(&mut rotation.get_mut().unwrap().retired),
+            None => Vec::new(),
+        };
+        let mut result = Ok(());
+        for (mut database, _) in retired {
+            result = result.and(release_database(&self.driver, &mut database));
+        }
+        let database = self.database.get_mut().unwrap();
+        result.and(release_database(&self.driver, database))
+    }
 }
 
 impl Drop for ManagedDatabaseInner {
     fn drop(&mut self) {
-        let driver = &self.driver.driver.lock().unwrap();
-        let mut database = self.database.lock().unwrap();
-        let method = driver_method!(driver, DatabaseRelease);
-        // TODO(alexandreyc): how should we handle `DatabaseRelease` failing?
-        // See: https://github.com/apache/arrow-adbc/pull/1742#discussion_r1574388409
-        unsafe { method(database.deref_mut(), null_mut()) };
+        if let Err(error) = self.release() {
+            report_release_failure("AdbcDatabaseRelease", &self.driver.name, &error);
+        }
     }
 }
 
@@ -406,16 +1279,56 @@ pub struct ManagedDatabase {
 }
 
 impl ManagedDatabase {
+    /// Release the database, returning failures instead of reporting them
+    /// through the [release hook][set_release_hook] as dropping it does.
+    ///
+    /// Fails with [Status::InvalidState] if the database is still used by
+    /// clones of this handle or by its connections.
+    pub fn close(mut self) -> Result<()> {
+        Arc::get_mut(&mut self.inner)
+            .ok_or_else(|| shared_error("database"))?
+            .release()
+    }
+
     fn driver_version(&self) -> AdbcVersion {
         self.inner.driver.version
     }
+
+    fn driver_name(&self) -> &str {
+        &self.inner.driver.name
+    }
+
+    /// Number of connections created from this database and not yet released.
+    pub fn open_connections(&self) -> usize {
+        self.inner.open_connections.load(Ordering::Relaxed)
+    }
+
+    /// Replace the database with one having fresh credentials if those of
+    /// the database are about to expire, and return the token held by the
+    /// connections of the database.
+    fn refresh_credentials(&self, database: &mut ffi::FFI_AdbcDatabase) -> Result<Option<Arc<()>>> {
+        let Some(rotation) = &self.inner.rotation else {
+            return Ok(None);
+        };
+        let mut rotation = rotation.lock().unwrap();
+        if credentials::is_expired(rotation.expires_at) {
+            let fresh = rotation.database(&self.inner.driver)?;
+            let retired = std::mem::replace(database, fresh);
+            let users = std::mem::take(&mut rotation.users);
+            rotation.retired.push((retired, users));
+            rotation.release_retired(&self.inner.driver);
+        }
+        Ok(Some(rotation.users.clone()))
+    }
 }
 
+traced! {
 impl Optionable for ManagedDatabase {
     type Option = options::OptionDatabase;
 
+    #[trace("AdbcDatabaseGetOptionBytes")]
     fn get_option_bytes(&self, key: Self::Option) -> Result<Vec<u8>> {
-        let driver = &self.inner.driver.driver.lock().unwrap();
+        let driver = &self.inner.driver.driver;
         let database = &mut self.inner.database.lock().unwrap();
         let method = driver_method!(driver, DatabaseGetOptionBytes);
         let populate = |key: *const c_char,
@@ -427,8 +1340,9 @@ impl Optionable for ManagedDatabase {
         get_option_bytes(key, populate, driver)
     }
 
+    #[trace("AdbcDatabaseGetOptionDouble")]
     fn get_option_double(&self, key: Self::Option) -> Result<f64> {
-        let driver = &self.inner.driver.driver.lock().unwrap();
+        let driver = &self.inner.driver.driver;
         let mut database = self.inner.database.lock().unwrap();
         let key = CString::new(key.as_ref())?;
         let mut error = ffi::FFI_AdbcError::with_driver(driver);
@@ -439,8 +1353,9 @@ impl Optionable for ManagedDatabase {
         Ok(value)
     }
 
+    #[trace("AdbcDatabaseGetOptionInt")]
     fn get_option_int(&self, key: Self::Option) -> Result<i64> {
-        let driver = &self.inner.driver.driver.lock().unwrap();
+        let driver = &self.inner.driver.driver;
         let mut database = self.inner.database.lock().unwrap();
         let key = CString::new(key.as_ref())?;
         let mut error = ffi::FFI_AdbcError::with_driver(driver);
@@ -451,8 +1366,9 @@ impl Optionable for ManagedDatabase {
         Ok(value)
     }
 
+    #[trace("AdbcDatabaseGetOption")]
     fn get_option_string(&self, key: Self::Option) -> Result<String> {
-        let driver = &self.inner.driver.driver.lock().unwrap();
+        let driver = &self.inner.driver.driver;
         let mut database = self.inner.database.lock().unwrap();
         let method = driver_method!(driver, DatabaseGetOption);
         let populate = |key: *const c_char,
@@ -464,19 +1380,33 @@ impl Optionable for ManagedDatabase {
         get_option_string(key, populate, driver)
     }
 
+    #[trace("AdbcDatabaseSetOption")]
     fn set_option(&mut self, key: Self::Option, value: OptionValue) -> Result<()> {
-        let driver = &self.inner.driver.driver.lock().unwrap();
+        let driver = &self.inner.driver.driver;
         let mut database = self.inner.database.lock().unwrap();
+        let Some(rotation) = &self.inner.rotation else {
+            return set_option_database(
+                driver,
+                database.deref_mut(),
+                self.driver_version(),
+                key,
+                value,
+            );
+        };
         set_option_database(
             driver,
             database.deref_mut(),
             self.driver_version(),
-            key,
-            value,
-        )
+            key.as_ref(),
+            value.clone(),
+        )?;
+        rotation.lock().unwrap().remember(key, value);
+        Ok(())
     }
 }
+}
 
+traced! {
 impl Database for ManagedDatabase {
     type ConnectionType = ManagedConnection;
 
@@ -484,12 +1414,14 @@ impl Database for ManagedDatabase {
         self.new_connection_with_opts(None)
     }
 
+    #[trace("AdbcConnectionInit")]
     fn new_connection_with_opts(
         &mut self,
         opts: impl IntoIterator<Item = (<Self::ConnectionType as Optionable>::Option, OptionValue)>,
     ) -> Result<Self::ConnectionType> {
-        let driver = &self.inner.driver.driver.lock().unwrap();
+        let driver = &self.inner.driver.driver;
         let mut database = self.inner.database.lock().unwrap();
+        let users = self.refresh_credentials(database.deref_mut())?;
         let mut connection = ffi::FFI_AdbcConnection::default();
         let mut error = ffi::FFI_AdbcError::with_driver(driver);
         let method = driver_method!(driver, ConnectionNew);
@@ -505,16 +1437,23 @@ impl Database for ManagedDatabase {
         let status = unsafe { method(&mut connection, database.deref_mut(), &mut error) };
         check_status(status, error)?;
 
-        let inner = ManagedConnectionInner {
+        let mut inner = Arc::new(ManagedConnectionInner {
             connection: Mutex::new(connection),
+            canceller: None,
+            timeout: Mutex::new(None),
             database: self.inner.clone(),
-        };
+            _users: users,
+        });
+        let inner_mut = Arc::get_mut(&mut inner).unwrap();
+        let connection = inner_mut.connection.get_mut().unwrap();
+        inner_mut.canceller = Some(Canceller::connection(driver, connection));
+        self.inner.open_connections.fetch_add(1, Ordering::Relaxed);
+        metrics::record_connections(self.driver_name(), self.inner.id, 1);
 
-        Ok(Self::ConnectionType {
-            inner: Arc::new(inner),
-        })
+        Ok(Self::ConnectionType { inner })
     }
 }
+}
 
 fn set_option_connection(
     driver: &ffi::FFI_AdbcDriver,
@@ -523,11 +1462,14 @@ fn set_option_connection(
     key: impl AsRef<str>,
     value: OptionValue,
 ) -> Result<()> {
+    // The value may be secret, so it is zeroized along with its C copy.
+    let value = Secret::new(to_version_value(version, key.as_ref(), value));
     let key = CString::new(key.as_ref())?;
     let mut error = ffi::FFI_AdbcError::with_driver(driver);
-    let status = match (version, value) {
+    let status = match (version, value.expose()) {
         (_, OptionValue::String(value)) => {
-            let value = CString::new(value)?;
+            let value = Secret::new(CString::new(value.as_str())?);
+            let value = value.expose();
             let method = driver_method!(driver, ConnectionSetOption);
             unsafe { method(connection, key.as_ptr(), value.as_ptr(), &mut error) }
         }
@@ -545,11 +1487,11 @@ fn set_option_connection(
         }
         (AdbcVersion::V110, OptionValue::Int(value)) => {
             let method = driver_method!(driver, ConnectionSetOptionInt);
-            unsafe { method(connection, key.as_ptr(), value, &mut error) }
+            unsafe { method(connection, key.as_ptr(), *value, &mut error) }
         }
         (AdbcVersion::V110, OptionValue::Double(value)) => {
             let method = driver_method!(driver, ConnectionSetOptionDouble);
-            unsafe { method(connection, key.as_ptr(), value, &mut error) }
+            unsafe { method(connection, key.as_ptr(), *value, &mut error) }
         }
         (AdbcVersion::V100, _) => Err(Error::with_message_and_status(
             ERR_ONLY_STRING_OPT,
@@ -561,17 +1503,50 @@ fn set_option_connection(
 
 struct ManagedConnectionInner {
     connection: Mutex<ffi::FFI_AdbcConnection>,
+    canceller: Option<Canceller>, // Set once the connection has its final address
+    timeout: Mutex<Option<Duration>>,
     database: Arc<ManagedDatabaseInner>,
+    _users: Option<Arc<()>>, // Keeps the database, if replaced, until released
+}
+
+impl ManagedConnectionInner {
+    fn canceller(&self) -> Canceller {
+        self.canceller.unwrap()
+    }
+}
+
+impl ManagedConnectionInner {
+    fn release(&mut self) -> Result<()> {
+        let driver = &self.database.driver.driver;
+        let connection = self.connection.get_mut().unwrap();
+        if connection.private_data.is_null() {
+            return Ok(());
+        }
+        trace::call(
+            "AdbcConnectionRelease",
+            &self.database.driver.name,
+            None,
+            || {
+                let mut error = ffi::FFI_AdbcError::with_driver(driver);
+                let method = driver_method!(driver, ConnectionRelease);
+                let status = unsafe { method(connection, &mut error) };
+                // The connection is not released again even if this attempt failed.
+                connection.private_data = null_mut();
+                check_status(status, error)
+            },
+        )
+    }
 }
 
 impl Drop for ManagedConnectionInner {
     fn drop(&mut self) {
-        let driver = &self.database.driver.driver.lock().unwrap();
-        let mut connection = self.connection.lock().unwrap();
-        let method = driver_method!(driver, ConnectionRelease);
-        // TODO(alexandreyc): how should we handle `ConnectionRelease` failing?
-        // See: https://github.com/apache/arrow-adbc/pull/1742#discussion_r1574388409
-        unsafe { method(connection.deref_mut(), null_mut()) };
+        if let Err(error) = self.release() {
+            report_release_failure("AdbcConnectionRelease", &self.database.driver.name, &error);
+        }
+        self.database
+            .open_connections
+            .fetch_sub(1, Ordering::Relaxed);
+        metrics::record_connections(&self.database.driver.name, self.database.id, -1);
     }
 }
 
@@ -581,17 +1556,108 @@ pub struct ManagedConnection {
     inner: Arc<ManagedConnectionInner>,
 }
 
+traced! {
 impl ManagedConnection {
+    /// Release the connection, returning failures instead of reporting them
+    /// through the [release hook][set_release_hook] as dropping it does.
+    ///
+    /// Fails with [Status::InvalidState] if the connection is still used by
+    /// clones of this handle or by its statements, result sets or cancel handles.
+    pub fn close(mut self) -> Result<()> {
+        Arc::get_mut(&mut self.inner)
+            .ok_or_else(|| shared_error("connection"))?
+            .release()
+    }
+
     fn driver_version(&self) -> AdbcVersion {
         self.inner.database.driver.version
     }
+
+    fn driver_name(&self) -> &str {
+        &self.inner.database.driver.name
+    }
+
+    /// Set the timeout of calls on this connection and of reading their
+    /// result sets, which also applies to its statements by default.
+    ///
+    /// A call still running after `timeout` is cancelled with
+    /// `ConnectionCancel` from a watchdog thread and fails with
+    /// [Status::Timeout]. Timeouts are not supported with ADBC 1.0.0.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) -> Result<()> {
+        if let AdbcVersion::V100 = self.driver_version() {
+            return Err(Error::with_message_and_status(
+                ERR_TIMEOUT_UNSUPPORTED,
+                Status::NotImplemented,
+            ));
+        }
+        *self.inner.timeout.lock().unwrap() = timeout;
+        Ok(())
+    }
+
+    /// Get the timeout of calls on this connection, if any.
+    pub fn timeout(&self) -> Option<Duration> {
+        *self.inner.timeout.lock().unwrap()
+    }
+
+    fn watchdog(&self, canceller: Canceller) -> Watchdog {
+        Watchdog::start(self.timeout(), canceller, &self.inner.database.driver)
+    }
+
+    fn timeout_reader(
+        &self,
+        reader: ArrowArrayStreamReader,
+        canceller: Canceller,
+    ) -> Box<dyn RecordBatchReader + Send> {
+        let driver = &self.inner.database.driver;
+        TimeoutReader::wrap(reader, self.timeout(), canceller, driver, self.inner.clone())
+    }
+
+    /// Get a handle cancelling the calls of this connection from any thread.
+    pub fn cancel_handle(&self) -> CancelHandle {
+        CancelHandle {
+            canceller: self.inner.canceller(),
+            driver: self.inner.database.driver.clone(),
+            _owner: self.inner.clone(),
+        }
+    }
+
+    #[trace("AdbcConnectionReadPartition")]
+    // The reader keeps the connection alive, hence does not borrow it.
+    fn read_partition_owned(&self, partition: &[u8]) -> Result<impl RecordBatchReader + Send> {
+        let mut stream = FFI_ArrowArrayStream::empty();
+        let driver = &self.inner.database.driver.driver;
+        let mut connection = self.inner.connection.lock().unwrap();
+        let mut error = ffi::FFI_AdbcError::with_driver(driver);
+        let method = driver_method!(driver, ConnectionReadPartition);
+        let canceller = self.inner.canceller();
+        let watchdog = self.watchdog(canceller);
+        let status = unsafe {
+            method(
+                connection.deref_mut(),
+                partition.as_ptr(),
+                partition.len(),
+                &mut stream,
+                &mut error,
+            )
+        };
+        watchdog.check(check_status(status, error))?;
+        let reader = self.timeout_reader(ArrowArrayStreamReader::try_new(stream)?, canceller);
+        Ok(wrap_reader(
+            reader,
+            "AdbcConnectionReadPartition",
+            self.driver_name(),
+        ))
+    }
+}
 }
 
+traced! {
 impl Optionable for ManagedConnection {
     type Option = options::OptionConnection;
 
+    #[trace("AdbcConnectionGetOptionBytes")]
     fn get_option_bytes(&self, key: Self::Option) -> Result<Vec<u8>> {
-        let driver = &self.inner.database.driver.driver.lock().unwrap();
+        let driver = &self.inner.database.driver.driver;
         let mut connection = self.inner.connection.lock().unwrap();
         let method = driver_method!(driver, ConnectionGetOptionBytes);
         let populate = |key: *const c_char,
@@ -603,10 +1669,11 @@ impl Optionable for ManagedConnection {
         get_option_bytes(key, populate, driver)
     }
 
+    #[trace("AdbcConnectionGetOptionDouble")]
     fn get_option_double(&self, key: Self::Option) -> Result<f64> {
         let key = CString::new(key.as_ref())?;
         let mut value: f64 = f64::default();
-        let driver = &self.inner.database.driver.driver.lock().unwrap();
+        let driver = &self.inner.database.driver.driver;
         let mut connection = self.inner.connection.lock().unwrap();
         let mut error = ffi::FFI_AdbcError::with_driver(driver);
         let method = driver_method!(driver, ConnectionGetOptionDouble);
@@ -616,10 +1683,11 @@ impl Optionable for ManagedConnection {
         Ok(value)
     }
 
+    #[trace("AdbcConnectionGetOptionInt")]
     fn get_option_int(&self, key: Self::Option) -> Result<i64> {
         let key = CString::new(key.as_ref())?;
         let mut value: i64 = 0;
-        let driver = &self.inner.database.driver.driver.lock().unwrap();
+        let driver = &self.inner.database.driver.driver;
         let mut connection = self.inner.connection.lock().unwrap();
         let mut error = ffi::FFI_AdbcError::with_driver(driver);
         let method = driver_method!(driver, ConnectionGetOptionInt);
@@ -629,8 +1697,9 @@ impl Optionable for ManagedConnection {
         Ok(value)
     }
 
+    #[trace("AdbcConnectionGetOption")]
     fn get_option_string(&self, key: Self::Option) -> Result<String> {
-        let driver = &self.inner.database.driver.driver.lock().unwrap();
+        let driver = &self.inner.database.driver.driver;
         let mut connection = self.inner.connection.lock().unwrap();
         let method = driver_method!(driver, ConnectionGetOption);
         let populate = |key: *const c_char,
@@ -642,8 +1711,9 @@ impl Optionable for ManagedConnection {
         get_option_string(key, populate, driver)
     }
 
+    #[trace("AdbcConnectionSetOption")]
     fn set_option(&mut self, key: Self::Option, value: OptionValue) -> Result<()> {
-        let driver = &self.inner.database.driver.driver.lock().unwrap();
+        let driver = &self.inner.database.driver.driver;
         let mut connection = self.inner.connection.lock().unwrap();
         set_option_connection(
             driver,
@@ -654,12 +1724,15 @@ impl Optionable for ManagedConnection {
         )
     }
 }
+}
 
+traced! {
 impl Connection for ManagedConnection {
     type StatementType = ManagedStatement;
 
+    #[trace("AdbcStatementNew")]
     fn new_statement(&mut self) -> Result<Self::StatementType> {
-        let driver = &self.inner.database.driver.driver.lock().unwrap();
+        let driver = &self.inner.database.driver.driver;
         let mut connection = self.inner.connection.lock().unwrap();
         let mut statement = ffi::FFI_AdbcStatement::default();
         let mut error = ffi::FFI_AdbcError::with_driver(driver);
@@ -667,47 +1740,49 @@ impl Connection for ManagedConnection {
         let status = unsafe { method(connection.deref_mut(), &mut statement, &mut error) };
         check_status(status, error)?;
 
-        let inner = Arc::new(ManagedStatementInner {
+        let mut inner = Arc::new(ManagedStatementInner {
             statement: Mutex::new(statement),
+            canceller: None,
+            query: Mutex::new(None),
+            timeout: Mutex::new(None),
             connection: self.inner.clone(),
         });
+        let inner_mut = Arc::get_mut(&mut inner).unwrap();
+        let statement = inner_mut.statement.get_mut().unwrap();
+        inner_mut.canceller = Some(Canceller::statement(driver, statement));
 
         Ok(Self::StatementType { inner })
     }
 
     fn cancel(&mut self) -> Result<()> {
-        if let AdbcVersion::V100 = self.driver_version() {
-            return Err(Error::with_message_and_status(
-                ERR_CANCEL_UNSUPPORTED,
-                Status::NotImplemented,
-            ));
-        }
-        let driver = &self.inner.database.driver.driver.lock().unwrap();
-        let mut connection = self.inner.connection.lock().unwrap();
-        let mut error = ffi::FFI_AdbcError::with_driver(driver);
-        let method = driver_method!(driver, ConnectionCancel);
-        let status = unsafe { method(connection.deref_mut(), &mut error) };
-        check_status(status, error)
+        self.cancel_handle().cancel()
     }
 
+    #[trace("AdbcConnectionCommit")]
     fn commit(&mut self) -> Result<()> {
-        let driver = &self.inner.database.driver.driver.lock().unwrap();
+        let driver = &self.inner.database.driver.driver;
         let mut connection = self.inner.connection.lock().unwrap();
         let mut error = ffi::FFI_AdbcError::with_driver(driver);
         let method = driver_method!(driver, ConnectionCommit);
+        let canceller = self.inner.canceller();
+        let watchdog = self.watchdog(canceller);
         let status = unsafe { method(connection.deref_mut(), &mut error) };
-        check_status(status, error)
+        watchdog.check(check_status(status, error))
     }
 
+    #[trace("AdbcConnectionRollback")]
     fn rollback(&mut self) -> Result<()> {
-        let driver = &self.inner.database.driver.driver.lock().unwrap();
+        let driver = &self.inner.database.driver.driver;
         let mut connection = self.inner.connection.lock().unwrap();
         let mut error = ffi::FFI_AdbcError::with_driver(driver);
         let method = driver_method!(driver, ConnectionRollback);
+        let canceller = self.inner.canceller();
+        let watchdog = self.watchdog(canceller);
         let status = unsafe { method(connection.deref_mut(), &mut error) };
-        check_status(status, error)
+        watchdog.check(check_status(status, error))
     }
 
+    #[trace("AdbcConnectionGetInfo")]
     fn get_info(&self, codes: Option<HashSet<InfoCode>>) -> Result<impl RecordBatchReader> {
         let mut stream = FFI_ArrowArrayStream::empty();
         let codes: Option<Vec<u32>> =
@@ -716,10 +1791,12 @@ impl Connection for ManagedConnection {
             .as_ref()
             .map(|c| (c.as_ptr(), c.len()))
             .unwrap_or((null(), 0));
-        let driver = &self.inner.database.driver.driver.lock().unwrap();
+        let driver = &self.inner.database.driver.driver;
         let mut connection = self.inner.connection.lock().unwrap();
         let mut error = ffi::FFI_AdbcError::with_driver(driver);
         let method = driver_method!(driver, ConnectionGetInfo);
+        let canceller = self.inner.canceller();
+        let watchdog = self.watchdog(canceller);
         let status = unsafe {
             method(
                 connection.deref_mut(),
@@ -729,11 +1806,16 @@ impl Connection for ManagedConnection {
                 &mut error,
             )
         };
-        check_status(status, error)?;
-        let reader = ArrowArrayStreamReader::try_new(stream)?;
-        Ok(reader)
+        watchdog.check(check_status(status, error))?;
+        let reader = self.timeout_reader(ArrowArrayStreamReader::try_new(stream)?, canceller);
+        Ok(wrap_reader(
+            reader,
+            "AdbcConnectionGetInfo",
+            self.driver_name(),
+        ))
     }
 
+    #[trace("AdbcConnectionGetObjects")]
     fn get_objects(
         &self,
         depth: crate::options::ObjectDepth,
@@ -772,12 +1854,14 @@ impl Connection for ManagedConnection {
             }
         };
 
-        let driver = &self.inner.database.driver.driver.lock().unwrap();
+        let driver = &self.inner.database.driver.driver;
         let mut connection = self.inner.connection.lock().unwrap();
         let mut error = ffi::FFI_AdbcError::with_driver(driver);
         let method = driver_method!(driver, ConnectionGetObjects);
+        let canceller = self.inner.canceller();
         let mut stream = FFI_ArrowArrayStream::empty();
 
+        let watchdog = self.watchdog(canceller);
         let status = unsafe {
             method(
                 connection.deref_mut(),
@@ -791,12 +1875,17 @@ impl Connection for ManagedConnection {
                 &mut error,
             )
         };
-        check_status(status, error)?;
-
-        let reader = ArrowArrayStreamReader::try_new(stream)?;
-        Ok(reader)
+        watchdog.check(check_status(status, error))?;
+
+        let reader = self.timeout_reader(ArrowArrayStreamReader::try_new(stream)?, canceller);
+        Ok(wrap_reader(
+            reader,
+            "AdbcConnectionGetObjects",
+            self.driver_name(),
+        ))
     }
 
+    #[trace("AdbcConnectionGetStatistics")]
     fn get_statistics(
         &self,
         catalog: Option<&str>,
@@ -820,10 +1909,12 @@ impl Connection for ManagedConnection {
         let table_name_ptr = table_name.as_ref().map(|c| c.as_ptr()).unwrap_or(null());
 
         let mut stream = FFI_ArrowArrayStream::empty();
-        let driver = &self.inner.database.driver.driver.lock().unwrap();
+        let driver = &self.inner.database.driver.driver;
         let mut connection = self.inner.connection.lock().unwrap();
         let mut error = ffi::FFI_AdbcError::with_driver(driver);
         let method = driver_method!(driver, ConnectionGetStatistics);
+        let canceller = self.inner.canceller();
+        let watchdog = self.watchdog(canceller);
         let status = unsafe {
             method(
                 connection.deref_mut(),
@@ -835,11 +1926,16 @@ impl Connection for ManagedConnection {
                 &mut error,
             )
         };
-        check_status(status, error)?;
-        let reader = ArrowArrayStreamReader::try_new(stream)?;
-        Ok(reader)
+        watchdog.check(check_status(status, error))?;
+        let reader = self.timeout_reader(ArrowArrayStreamReader::try_new(stream)?, canceller);
+        Ok(wrap_reader(
+            reader,
+            "AdbcConnectionGetStatistics",
+            self.driver_name(),
+        ))
     }
 
+    #[trace("AdbcConnectionGetStatisticNames")]
     fn get_statistic_names(&self) -> Result<impl RecordBatchReader> {
         if let AdbcVersion::V100 = self.driver_version() {
             return Err(Error::with_message_and_status(
@@ -848,16 +1944,23 @@ impl Connection for ManagedConnection {
             ));
         }
         let mut stream = FFI_ArrowArrayStream::empty();
-        let driver = &self.inner.database.driver.driver.lock().unwrap();
+        let driver = &self.inner.database.driver.driver;
         let mut connection = self.inner.connection.lock().unwrap();
         let mut error = ffi::FFI_AdbcError::with_driver(driver);
         let method = driver_method!(driver, ConnectionGetStatisticNames);
+        let canceller = self.inner.canceller();
+        let watchdog = self.watchdog(canceller);
         let status = unsafe { method(connection.deref_mut(), &mut stream, &mut error) };
-        check_status(status, error)?;
-        let reader = ArrowArrayStreamReader::try_new(stream)?;
-        Ok(reader)
+        watchdog.check(check_status(status, error))?;
+        let reader = self.timeout_reader(ArrowArrayStreamReader::try_new(stream)?, canceller);
+        Ok(wrap_reader(
+            reader,
+            "AdbcConnectionGetStatisticNames",
+            self.driver_name(),
+        ))
     }
 
+    #[trace("AdbcConnectionGetTableSchema")]
     fn get_table_schema(
         &self,
         catalog: Option<&str>,
@@ -873,10 +1976,12 @@ impl Connection for ManagedConnection {
         let table_name_ptr = table_name.as_ptr();
 
         let mut schema = FFI_ArrowSchema::empty();
-        let driver = &self.inner.database.driver.driver.lock().unwrap();
+        let driver = &self.inner.database.driver.driver;
         let mut connection = self.inner.connection.lock().unwrap();
         let mut error = ffi::FFI_AdbcError::with_driver(driver);
         let method = driver_method!(driver, ConnectionGetTableSchema);
+        let canceller = self.inner.canceller();
+        let watchdog = self.watchdog(canceller);
         let status = unsafe {
             method(
                 connection.deref_mut(),
@@ -887,43 +1992,56 @@ impl Connection for ManagedConnection {
                 &mut error,
             )
         };
-        check_status(status, error)?;
+        watchdog.check(check_status(status, error))?;
         Ok((&schema).try_into()?)
     }
 
+    #[trace("AdbcConnectionGetTableTypes")]
     fn get_table_types(&self) -> Result<impl RecordBatchReader> {
         let mut stream = FFI_ArrowArrayStream::empty();
-        let driver = &self.inner.database.driver.driver.lock().unwrap();
+        let driver = &self.inner.database.driver.driver;
         let mut connection = self.inner.connection.lock().unwrap();
         let mut error = ffi::FFI_AdbcError::with_driver(driver);
         let method = driver_method!(driver, ConnectionGetTableTypes);
+        let canceller = self.inner.canceller();
+        let watchdog = self.watchdog(canceller);
         let status = unsafe { method(connection.deref_mut(), &mut stream, &mut error) };
-        check_status(status, error)?;
-        let reader = ArrowArrayStreamReader::try_new(stream)?;
-        Ok(reader)
+        watchdog.check(check_status(status, error))?;
+        let reader = self.timeout_reader(ArrowArrayStreamReader::try_new(stream)?, canceller);
+        Ok(wrap_reader(
+            reader,
+            "AdbcConnectionGetTableTypes",
+            self.driver_name(),
+        ))
     }
 
     fn read_partition(&self, partition: impl AsRef<[u8]>) -> Result<impl RecordBatchReader> {
-        let mut stream = FFI_ArrowArrayStream::empty();
-        let driver = &self.inner.database.driver.driver.lock().unwrap();
-        let mut connection = self.inner.connection.lock().unwrap();
-        let mut error = ffi::FFI_AdbcError::with_driver(driver);
-        let method = driver_method!(driver, ConnectionReadPartition);
-        let partition = partition.as_ref();
-        let status = unsafe {
-            method(
-                connection.deref_mut(),
-                partition.as_ptr(),
-                partition.len(),
-                &mut stream,
-                &mut error,
-            )
+        self.read_partition_owned(partition.as_ref())
+    }
+
+    /// Derive the capabilities from the functions populated by the driver,
+    /// see [ManagedDriver::functions], before refining them with
+    /// [Capabilities::with_info]. Isolation levels and ingest modes cannot
+    /// be derived, so they are left empty.
+    fn capabilities(&self) -> Result<Capabilities> {
+        let driver = &self.inner.database.driver.driver;
+        let declared = Capabilities {
+            partitions: driver.StatementExecutePartitions.is_some()
+                && driver.ConnectionReadPartition.is_some(),
+            substrait: driver.StatementSetSubstraitPlan.is_some(),
+            statistics: driver.ConnectionGetStatistics.is_some()
+                && driver.ConnectionGetStatisticNames.is_some(),
+            cancellation: driver.ConnectionCancel.is_some() && driver.StatementCancel.is_some(),
+            transactions: driver.ConnectionCommit.is_some() && driver.ConnectionRollback.is_some(),
+            ..Default::default()
         };
-        check_status(status, error)?;
-        let reader = ArrowArrayStreamReader::try_new(stream)?;
-        Ok(reader)
+        let mut capabilities = declared.clone().with_info(self)?;
+        // A driver reporting Substrait support must still implement the function.
+        capabilities.substrait &= declared.substrait;
+        Ok(capabilities)
     }
 }
+}
 
 fn set_option_statement(
     driver: &ffi::FFI_AdbcDriver,
@@ -932,11 +2050,14 @@ fn set_option_statement(
     key: impl AsRef<str>,
     value: OptionValue,
 ) -> Result<()> {
+    // The value may be secret, so it is zeroized along with its C copy.
+    let value = Secret::new(to_version_value(version, key.as_ref(), value));
     let key = CString::new(key.as_ref())?;
     let mut error = ffi::FFI_AdbcError::with_driver(driver);
-    let status = match (version, value) {
+    let status = match (version, value.expose()) {
         (_, OptionValue::String(value)) => {
-            let value = CString::new(value)?;
+            let value = Secret::new(CString::new(value.as_str())?);
+            let value = value.expose();
             let method = driver_method!(driver, StatementSetOption);
             unsafe { method(statement, key.as_ptr(), value.as_ptr(), &mut error) }
         }
@@ -954,11 +2075,11 @@ fn set_option_statement(
         }
         (AdbcVersion::V110, OptionValue::Int(value)) => {
             let method = driver_method!(driver, StatementSetOptionInt);
-            unsafe { method(statement, key.as_ptr(), value, &mut error) }
+            unsafe { method(statement, key.as_ptr(), *value, &mut error) }
         }
         (AdbcVersion::V110, OptionValue::Double(value)) => {
             let method = driver_method!(driver, StatementSetOptionDouble);
-            unsafe { method(statement, key.as_ptr(), value, &mut error) }
+            unsafe { method(statement, key.as_ptr(), *value, &mut error) }
         }
         (AdbcVersion::V100, _) => Err(Error::with_message_and_status(
             ERR_ONLY_STRING_OPT,
@@ -970,6 +2091,9 @@ fn set_option_statement(
 
 struct ManagedStatementInner {
     statement: Mutex<ffi::FFI_AdbcStatement>,
+    canceller: Option<Canceller>, // Set once the statement has its final address
+    query: Mutex<Option<String>>, // SQL query, reported in traces
+    timeout: Mutex<Option<Duration>>,
     connection: Arc<ManagedConnectionInner>,
 }
 /// Implementation of [Statement].
@@ -979,15 +2103,92 @@ pub struct ManagedStatement {
 }
 
 impl ManagedStatement {
+    /// Release the statement, returning failures instead of reporting them
+    /// through the [release hook][set_release_hook] as dropping it does.
+    ///
+    /// Fails with [Status::InvalidState] if the statement is still used by
+    /// clones of this handle or by its result sets or cancel handles.
+    pub fn close(mut self) -> Result<()> {
+        Arc::get_mut(&mut self.inner)
+            .ok_or_else(|| shared_error("statement"))?
+            .release()
+    }
+
     fn driver_version(&self) -> AdbcVersion {
         self.inner.connection.database.driver.version
     }
+
+    fn driver_name(&self) -> &str {
+        &self.inner.connection.database.driver.name
+    }
+
+    fn query(&self) -> Option<String> {
+        self.inner.query.lock().unwrap().clone()
+    }
+
+    /// Set the timeout of executing this statement and of reading its result
+    /// sets, overriding the timeout of its connection unless `None`.
+    ///
+    /// An execution still running after `timeout` is cancelled with
+    /// `StatementCancel` from a watchdog thread and fails with
+    /// [Status::Timeout]. Timeouts are not supported with ADBC 1.0.0.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) -> Result<()> {
+        if let AdbcVersion::V100 = self.driver_version() {
+            return Err(Error::with_message_and_status(
+                ERR_TIMEOUT_UNSUPPORTED,
+                Status::NotImplemented,
+            ));
+        }
+        *self.inner.timeout.lock().unwrap() = timeout;
+        Ok(())
+    }
+
+    /// Get the timeout of executing this statement, if any.
+    pub fn timeout(&self) -> Option<Duration> {
+        let timeout = *self.inner.timeout.lock().unwrap();
+        timeout.or_else(|| *self.inner.connection.timeout.lock().unwrap())
+    }
+
+    fn watchdog(&self, canceller: Canceller) -> Watchdog {
+        Watchdog::start(
+            self.timeout(),
+            canceller,
+            &self.inner.connection.database.driver,
+        )
+    }
+
+    fn timeout_reader(
+        &self,
+        reader: ArrowArrayStreamReader,
+        canceller: Canceller,
+    ) -> Box<dyn RecordBatchReader + Send> {
+        let driver = &self.inner.connection.database.driver;
+        TimeoutReader::wrap(
+            reader,
+            self.timeout(),
+            canceller,
+            driver,
+            self.inner.clone(),
+        )
+    }
+
+    /// Get a handle cancelling the execution of this statement from any
+    /// thread.
+    pub fn cancel_handle(&self) -> CancelHandle {
+        CancelHandle {
+            canceller: self.inner.canceller(),
+            driver: self.inner.connection.database.driver.clone(),
+            _owner: self.inner.clone(),
+        }
+    }
 }
 
+traced! {
 impl Statement for ManagedStatement {
+    #[trace("AdbcStatementBind")]
     fn bind(&mut self, batch: RecordBatch) -> Result<()> {
-        let driver = &self.inner.connection.database.driver.driver.lock().unwrap();
-        let mut statement = self.inner.statement.lock().unwrap();
+        let driver = &self.inner.connection.database.driver.driver;
+        let mut statement = self.inner.lock();
         let mut error = ffi::FFI_AdbcError::with_driver(driver);
         let method = driver_method!(driver, StatementBind);
         let batch: StructArray = batch.into();
@@ -997,61 +2198,71 @@ impl Statement for ManagedStatement {
         Ok(())
     }
 
+    #[trace("AdbcStatementBindStream")]
     fn bind_stream(&mut self, reader: Box<dyn RecordBatchReader + Send>) -> Result<()> {
-        let driver = &self.inner.connection.database.driver.driver.lock().unwrap();
-        let mut statement = self.inner.statement.lock().unwrap();
+        let driver = &self.inner.connection.database.driver.driver;
+        let mut statement = self.inner.lock();
         let mut error = ffi::FFI_AdbcError::with_driver(driver);
         let method = driver_method!(driver, StatementBindStream);
-        let mut stream = FFI_ArrowArrayStream::new(reader);
+        let reader = MeteredReader::new(
+            reader,
+            metrics::recorder(),
+            Flow::Ingest,
+            "AdbcStatementBindStream",
+            self.driver_name(),
+        );
+        let mut stream = FFI_ArrowArrayStream::new(Box::new(reader));
         let status = unsafe { method(statement.deref_mut(), &mut stream, &mut error) };
         check_status(status, error)?;
         Ok(())
     }
 
     fn cancel(&mut self) -> Result<()> {
-        if let AdbcVersion::V100 = self.driver_version() {
-            return Err(Error::with_message_and_status(
-                ERR_CANCEL_UNSUPPORTED,
-                Status::NotImplemented,
-            ));
-        }
-        let driver = &self.inner.connection.database.driver.driver.lock().unwrap();
-        let mut statement = self.inner.statement.lock().unwrap();
-        let mut error = ffi::FFI_AdbcError::with_driver(driver);
-        let method = driver_method!(driver, StatementCancel);
-        let status = unsafe { method(statement.deref_mut(), &mut error) };
-        check_status(status, error)
+        self.cancel_handle().cancel()
     }
 
+    #[trace("AdbcStatementExecuteQuery", self.query().as_deref())]
     fn execute(&mut self) -> Result<impl RecordBatchReader> {
-        let driver = &self.inner.connection.database.driver.driver.lock().unwrap();
-        let mut statement = self.inner.statement.lock().unwrap();
+        let driver = &self.inner.connection.database.driver.driver;
+        let mut statement = self.inner.lock();
         let mut error = ffi::FFI_AdbcError::with_driver(driver);
         let method = driver_method!(driver, StatementExecuteQuery);
+        let canceller = self.inner.canceller();
         let mut stream = FFI_ArrowArrayStream::empty();
+        let watchdog = self.watchdog(canceller);
         let status = unsafe { method(statement.deref_mut(), &mut stream, null_mut(), &mut error) };
-        check_status(status, error)?;
-        let reader = ArrowArrayStreamReader::try_new(stream)?;
-        Ok(reader)
+        watchdog.check(check_status(status, error))?;
+        let reader = self.timeout_reader(ArrowArrayStreamReader::try_new(stream)?, canceller);
+        Ok(wrap_reader(
+            reader,
+            "AdbcStatementExecuteQuery",
+            self.driver_name(),
+        ))
     }
 
+    #[trace("AdbcStatementExecuteSchema", self.query().as_deref())]
     fn execute_schema(&mut self) -> Result<arrow::datatypes::Schema> {
-        let driver = &self.inner.connection.database.driver.driver.lock().unwrap();
-        let mut statement = self.inner.statement.lock().unwrap();
+        let driver = &self.inner.connection.database.driver.driver;
+        let mut statement = self.inner.lock();
         let mut error = ffi::FFI_AdbcError::with_driver(driver);
         let method = driver_method!(driver, StatementExecuteSchema);
+        let canceller = self.inner.canceller();
         let mut schema = FFI_ArrowSchema::empty();
+        let watchdog = self.watchdog(canceller);
         let status = unsafe { method(statement.deref_mut(), &mut schema, &mut error) };
-        check_status(status, error)?;
+        watchdog.check(check_status(status, error))?;
         Ok((&schema).try_into()?)
     }
 
+    #[trace("AdbcStatementExecuteQuery", self.query().as_deref())]
     fn execute_update(&mut self) -> Result<Option<i64>> {
-        let driver = &self.inner.connection.database.driver.driver.lock().unwrap();
-        let mut statement = self.inner.statement.lock().unwrap();
+        let driver = &self.inner.connection.database.driver.driver;
+        let mut statement = self.inner.lock();
         let mut error = ffi::FFI_AdbcError::with_driver(driver);
         let method = driver_method!(driver, StatementExecuteQuery);
+        let canceller = self.inner.canceller();
         let mut rows_affected: i64 = -1;
+        let watchdog = self.watchdog(canceller);
         let status = unsafe {
             method(
                 statement.deref_mut(),
@@ -1060,18 +2271,21 @@ impl Statement for ManagedStatement {
                 &mut error,
             )
         };
-        check_status(status, error)?;
+        watchdog.check(check_status(status, error))?;
         Ok((rows_affected != -1).then_some(rows_affected))
     }
 
+    #[trace("AdbcStatementExecutePartitions", self.query().as_deref())]
     fn execute_partitions(&mut self) -> Result<PartitionedResult> {
-        let driver = &self.inner.connection.database.driver.driver.lock().unwrap();
-        let mut statement = self.inner.statement.lock().unwrap();
+        let driver = &self.inner.connection.database.driver.driver;
+        let mut statement = self.inner.lock();
         let mut error = ffi::FFI_AdbcError::with_driver(driver);
         let method = driver_method!(driver, StatementExecutePartitions);
+        let canceller = self.inner.canceller();
         let mut schema = FFI_ArrowSchema::empty();
         let mut partitions = ffi::FFI_AdbcPartitions::default();
         let mut rows_affected: i64 = -1;
+        let watchdog = self.watchdog(canceller);
         let status = unsafe {
             method(
                 statement.deref_mut(),
@@ -1081,7 +2295,7 @@ impl Statement for ManagedStatement {
                 &mut error,
             )
         };
-        check_status(status, error)?;
+        watchdog.check(check_status(status, error))?;
 
         let result = PartitionedResult {
             partitions: partitions.into(),
@@ -1092,9 +2306,10 @@ impl Statement for ManagedStatement {
         Ok(result)
     }
 
+    #[trace("AdbcStatementGetParameterSchema")]
     fn get_parameter_schema(&self) -> Result<arrow::datatypes::Schema> {
-        let driver = &self.inner.connection.database.driver.driver.lock().unwrap();
-        let mut statement = self.inner.statement.lock().unwrap();
+        let driver = &self.inner.connection.database.driver.driver;
+        let mut statement = self.inner.lock();
         let mut error = ffi::FFI_AdbcError::with_driver(driver);
         let method = driver_method!(driver, StatementGetParameterSchema);
         let mut schema = FFI_ArrowSchema::empty();
@@ -1103,9 +2318,10 @@ impl Statement for ManagedStatement {
         Ok((&schema).try_into()?)
     }
 
+    #[trace("AdbcStatementPrepare", self.query().as_deref())]
     fn prepare(&mut self) -> Result<()> {
-        let driver = &self.inner.connection.database.driver.driver.lock().unwrap();
-        let mut statement = self.inner.statement.lock().unwrap();
+        let driver = &self.inner.connection.database.driver.driver;
+        let mut statement = self.inner.lock();
         let mut error = ffi::FFI_AdbcError::with_driver(driver);
         let method = driver_method!(driver, StatementPrepare);
         let status = unsafe { method(statement.deref_mut(), &mut error) };
@@ -1113,36 +2329,44 @@ impl Statement for ManagedStatement {
         Ok(())
     }
 
+    #[trace("AdbcStatementSetSqlQuery", Some(query.as_ref()))]
     fn set_sql_query(&mut self, query: impl AsRef<str>) -> Result<()> {
-        let query = CString::new(query.as_ref())?;
-        let driver = &self.inner.connection.database.driver.driver.lock().unwrap();
-        let mut statement = self.inner.statement.lock().unwrap();
+        let query = query.as_ref();
+        let c_query = CString::new(query)?;
+        let driver = &self.inner.connection.database.driver.driver;
+        let mut statement = self.inner.lock();
         let mut error = ffi::FFI_AdbcError::with_driver(driver);
         let method = driver_method!(driver, StatementSetSqlQuery);
-        let status = unsafe { method(statement.deref_mut(), query.as_ptr(), &mut error) };
+        let status = unsafe { method(statement.deref_mut(), c_query.as_ptr(), &mut error) };
         check_status(status, error)?;
+        *self.inner.query.lock().unwrap() = Some(query.to_string());
         Ok(())
     }
 
+    #[trace("AdbcStatementSetSubstraitPlan")]
     fn set_substrait_plan(&mut self, plan: impl AsRef<[u8]>) -> Result<()> {
-        let driver = &self.inner.connection.database.driver.driver.lock().unwrap();
-        let mut statement = self.inner.statement.lock().unwrap();
+        let driver = &self.inner.connection.database.driver.driver;
+        let mut statement = self.inner.lock();
         let mut error = ffi::FFI_AdbcError::with_driver(driver);
         let method = driver_method!(driver, StatementSetSubstraitPlan);
         let plan = plan.as_ref();
         let status =
             unsafe { method(statement.deref_mut(), plan.as_ptr(), plan.len(), &mut error) };
         check_status(status, error)?;
+        *self.inner.query.lock().unwrap() = None;
         Ok(())
     }
 }
+}
 
+traced! {
 impl Optionable for ManagedStatement {
     type Option = options::OptionStatement;
 
+    #[trace("AdbcStatementGetOptionBytes")]
     fn get_option_bytes(&self, key: Self::Option) -> Result<Vec<u8>> {
-        let driver = &self.inner.connection.database.driver.driver.lock().unwrap();
-        let mut statement = self.inner.statement.lock().unwrap();
+        let driver = &self.inner.connection.database.driver.driver;
+        let mut statement = self.inner.lock();
         let method = driver_method!(driver, StatementGetOptionBytes);
         let populate = |key: *const c_char,
                         value: *mut u8,
@@ -1153,11 +2377,12 @@ impl Optionable for ManagedStatement {
         get_option_bytes(key, populate, driver)
     }
 
+    #[trace("AdbcStatementGetOptionDouble")]
     fn get_option_double(&self, key: Self::Option) -> Result<f64> {
         let key = CString::new(key.as_ref())?;
         let mut value: f64 = f64::default();
-        let driver = &self.inner.connection.database.driver.driver.lock().unwrap();
-        let mut statement = self.inner.statement.lock().unwrap();
+        let driver = &self.inner.connection.database.driver.driver;
+        let mut statement = self.inner.lock();
         let mut error = ffi::FFI_AdbcError::with_driver(driver);
         let method = driver_method!(driver, StatementGetOptionDouble);
         let status = unsafe { method(statement.deref_mut(), key.as_ptr(), &mut value, &mut error) };
@@ -1165,11 +2390,12 @@ impl Optionable for ManagedStatement {
         Ok(value)
     }
 
+    #[trace("AdbcStatementGetOptionInt")]
     fn get_option_int(&self, key: Self::Option) -> Result<i64> {
         let key = CString::new(key.as_ref())?;
         let mut value: i64 = 0;
-        let driver = &self.inner.connection.database.driver.driver.lock().unwrap();
-        let mut statement = self.inner.statement.lock().unwrap();
+        let driver = &self.inner.connection.database.driver.driver;
+        let mut statement = self.inner.lock();
         let mut error = ffi::FFI_AdbcError::with_driver(driver);
         let method = driver_method!(driver, StatementGetOptionInt);
         let status = unsafe { method(statement.deref_mut(), key.as_ptr(), &mut value, &mut error) };
@@ -1177,9 +2403,10 @@ impl Optionable for ManagedStatement {
         Ok(value)
     }
 
+    #[trace("AdbcStatementGetOption")]
     fn get_option_string(&self, key: Self::Option) -> Result<String> {
-        let driver = &self.inner.connection.database.driver.driver.lock().unwrap();
-        let mut statement = self.inner.statement.lock().unwrap();
+        let driver = &self.inner.connection.database.driver.driver;
+        let mut statement = self.inner.lock();
         let method = driver_method!(driver, StatementGetOption);
         let populate = |key: *const c_char,
                         value: *mut c_char,
@@ -1190,9 +2417,10 @@ impl Optionable for ManagedStatement {
         get_option_string(key, populate, driver)
     }
 
+    #[trace("AdbcStatementSetOption")]
     fn set_option(&mut self, key: Self::Option, value: OptionValue) -> Result<()> {
-        let driver = &self.inner.connection.database.driver.driver.lock().unwrap();
-        let mut statement = self.inner.statement.lock().unwrap();
+        let driver = &self.inner.connection.database.driver.driver;
+        let mut statement = self.inner.lock();
         set_option_statement(
             driver,
             statement.deref_mut(),
@@ -1202,14 +2430,139 @@ impl Optionable for ManagedStatement {
         )
     }
 }
+}
+
+/// A locked statement, along with its connection: drivers need not support
+/// concurrent calls on the statements of a connection.
+struct StatementGuard<'a> {
+    statement: MutexGuard<'a, ffi::FFI_AdbcStatement>,
+    _connection: MutexGuard<'a, ffi::FFI_AdbcConnection>,
+}
+
+impl Deref for StatementGuard<'_> {
+    type Target = ffi::FFI_AdbcStatement;
+
+    fn deref(&self) -> &Self::Target {
+        &self.statement
+    }
+}
+
+impl DerefMut for StatementGuard<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.statement
+    }
+}
 
-impl Drop for ManagedStatement {
+impl ManagedStatementInner {
+    fn canceller(&self) -> Canceller {
+        self.canceller.unwrap()
+    }
+
+    /// Lock the statement for a call, which is not cancellation.
+    fn lock(&self) -> StatementGuard<'_> {
+        // The connection is always locked first.
+        let connection = self.connection.connection.lock().unwrap();
+        StatementGuard {
+            statement: self.statement.lock().unwrap(),
+            _connection: connection,
+        }
+    }
+
+    fn release(&mut self) -> Result<()> {
+        let driver = &self.connection.database.driver.driver;
+        let _connection = self.connection.connection.lock().unwrap();
+        let statement = self.statement.get_mut().unwrap();
+        if statement.private_data.is_null() {
+            return Ok(());
+        }
+        let name = &self.connection.database.driver.name;
+        trace::call("AdbcStatementRelease", name, None, || {
+            let mut error = ffi::FFI_AdbcError::with_driver(driver);
+            let method = driver_method!(driver, StatementRelease);
+            let status = unsafe { method(statement, &mut error) };
+            // The statement is not released again even if this attempt failed.
+            statement.private_data = null_mut();
+            check_status(status, error)
+        })
+    }
+}
+
+impl Drop for ManagedStatementInner {
     fn drop(&mut self) {
-        let driver = &self.inner.connection.database.driver.driver.lock().unwrap();
-        let mut statement = self.inner.statement.lock().unwrap();
-        let method = driver_method!(driver, StatementRelease);
-        // TODO(alexandreyc): how should we handle `StatementRelease` failing?
-        // See: https://github.com/apache/arrow-adbc/pull/1742#discussion_r1574388409
-        unsafe { method(statement.deref_mut(), null_mut()) };
+        if let Err(error) = self.release() {
+            let name = &self.connection.database.driver.name;
+            report_release_failure("AdbcStatementRelease", name, &error);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::raw::c_int;
+
+    use super::*;
+
+    unsafe extern "C" fn failing_release(
+        _driver: *mut ffi::FFI_AdbcDriver,
+        _error: *mut ffi::FFI_AdbcError,
+    ) -> ffi::FFI_AdbcStatusCode {
+        ffi::constants::ADBC_STATUS_IO
+    }
+
+    unsafe extern "C" fn init(
+        _version: c_int,
+        driver: *mut c_void,
+        _error: *mut ffi::FFI_AdbcError,
+    ) -> ffi::FFI_AdbcStatusCode {
+        (*(driver as *mut ffi::FFI_AdbcDriver)).release = Some(failing_release);
+        ffi::constants::ADBC_STATUS_OK
+    }
+
+    fn load(name: &str) -> ManagedDriver {
+        let init: ffi::FFI_AdbcDriverInitFunc = init;
+        ManagedDriver::load_static_with_name(name, &init, AdbcVersion::V110).unwrap()
+    }
+
+    #[test]
+    fn test_release_failures() {
+        let driver = load("failing");
+        let clone = driver.clone();
+        assert_eq!(driver.close().unwrap_err().status, Status::InvalidState);
+        assert_eq!(clone.close().unwrap_err().status, Status::IO);
+
+        // The hook is process-wide, so failures of the drivers dropped by
+        // concurrent tests are ignored.
+        let failures = Arc::new(Mutex::new(Vec::new()));
+        set_release_hook(Arc::new({
+            let failures = failures.clone();
+            move |method: &str, driver: &str, error: &Error| {
+                if driver == "release_failures" {
+                    let failure = (method.to_string(), driver.to_string(), error.status);
+                    failures.lock().unwrap().push(failure);
+                }
+            }
+        }));
+        drop(load("release_failures"));
+        clear_release_hook();
+        let expected = (
+            "AdbcDriverRelease".to_string(),
+            "release_failures".to_string(),
+            Status::IO,
+        );
+        assert_eq!(*failures.lock().unwrap(), [expected]);
+    }
+
+    #[test]
+    fn test_functions() {
+        let driver = load("failing");
+        let functions = driver.functions();
+        assert_eq!(functions.len(), 55);
+        assert!(functions.iter().all(|f| !f.implemented));
+
+        let error = driver.strict().err().unwrap();
+        assert_eq!(error.status, Status::NotImplemented);
+        assert!(error.message.contains("AdbcStatementCancel"));
+        assert!(!error.message.contains("AdbcStatementNew"));
+        assert!(!error.message.contains("AdbcErrorFromArrayStream"));
     }
 }
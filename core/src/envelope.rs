@@ -0,0 +1,176 @@
+//! Partition envelopes, see the [partitions][crate::partitions] module.
+
+use std::io::Cursor;
+use std::sync::Arc;
+
+use arrow::array::{Array, ArrayRef, AsArray, BinaryArray, Int32Array, StringArray};
+use arrow::datatypes::{DataType, Field, Int32Type, Schema, SchemaRef};
+use arrow::ipc::reader::StreamReader;
+use arrow::ipc::writer::StreamWriter;
+use arrow::record_batch::{RecordBatch, RecordBatchReader};
+
+use crate::error::{Error, Result, Status};
+use crate::options::{AdbcVersion, OptionDatabase, OptionValue};
+use crate::uri;
+use crate::{Connection, Driver};
+
+// Schema metadata identifying serialized envelopes and their format.
+const ENVELOPE_VERSION_KEY: &str = "adbc.partition.envelope";
+const ENVELOPE_VERSION: &str = "1";
+
+/// A partition along with what is needed to read it from another process,
+/// see the [module][crate::partitions] documentation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartitionEnvelope {
+    /// The driver reading the partition, e.g. the filename of its library.
+    pub driver: String,
+    /// The entrypoint of the driver, if not the default one.
+    pub entrypoint: Option<String>,
+    /// The URI of the database, without credentials.
+    pub uri: Option<String>,
+    /// The ADBC version the driver is loaded with.
+    pub version: AdbcVersion,
+    /// The partition descriptor, as returned by
+    /// [Statement::execute_partitions][crate::Statement::execute_partitions].
+    pub partition: Vec<u8>,
+    /// The schema of the result set.
+    pub schema: Schema,
+}
+
+impl PartitionEnvelope {
+    /// Describe `partition` of a result set of the given `schema`, to be read
+    /// with `driver` loaded with `version`.
+    pub fn new(
+        driver: impl Into<String>,
+        version: AdbcVersion,
+        partition: Vec<u8>,
+        schema: Schema,
+    ) -> Self {
+        Self {
+            driver: driver.into(),
+            entrypoint: None,
+            uri: None,
+            version,
+            partition,
+            schema,
+        }
+    }
+
+    /// Set the entrypoint of the driver.
+    pub fn with_entrypoint(mut self, entrypoint: impl Into<String>) -> Self {
+        self.entrypoint = Some(entrypoint.into());
+        self
+    }
+
+    /// Set the URI of the database, from which the password and the query
+    /// parameters holding credentials are removed.
+    pub fn with_uri(mut self, uri: &str) -> Self {
+        self.uri = Some(uri::redact(uri));
+        self
+    }
+
+    /// Serialize the envelope as an Arrow IPC stream.
+    pub fn serialize(&self) -> Result<Vec<u8>> {
+        let mut schema = Vec::new();
+        StreamWriter::try_new(&mut schema, &self.schema)?.finish()?;
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(StringArray::from(vec![self.driver.as_str()])),
+            Arc::new(StringArray::from(vec![self.entrypoint.as_deref()])),
+            Arc::new(StringArray::from(vec![self.uri.as_deref()])),
+            Arc::new(Int32Array::from(vec![i32::from(self.version)])),
+            Arc::new(BinaryArray::from_vec(vec![&self.partition])),
+            Arc::new(BinaryArray::from_vec(vec![&schema])),
+        ];
+        let batch = RecordBatch::try_new(envelope_schema(), columns)?;
+        let mut writer = StreamWriter::try_new(Vec::new(), &batch.schema())?;
+        writer.write(&batch)?;
+        writer.finish()?;
+        Ok(writer.into_inner()?)
+    }
+
+    /// Deserialize an envelope serialized with [PartitionEnvelope::serialize].
+    ///
+    /// Envelopes are not authenticated, so the driver they name must not be
+    /// loaded unless their source is trusted. Workers rather load their
+    /// driver and check that envelopes name it.
+    pub fn deserialize(bytes: &[u8]) -> Result<Self> {
+        let invalid = |message: &str| {
+            Error::with_message_and_status(
+                format!("Invalid partition envelope: {message}"),
+                Status::InvalidData,
+            )
+        };
+        let mut reader = StreamReader::try_new(Cursor::new(bytes), None)?;
+        match reader.schema().metadata().get(ENVELOPE_VERSION_KEY) {
+            Some(version) if version == ENVELOPE_VERSION => {}
+            Some(version) => return Err(invalid(&format!("unsupported format {version}"))),
+            None => return Err(invalid("missing format")),
+        }
+        let batch = reader.next().ok_or_else(|| invalid("no data"))??;
+        if batch.num_rows() != 1 || batch.num_columns() != 6 {
+            return Err(invalid("unexpected shape"));
+        }
+        let string = |index: usize| {
+            let array = batch.column(index).as_string_opt::<i32>()?;
+            Some(array.is_valid(0).then(|| array.value(0).to_string()))
+        };
+        let binary = |index: usize| {
+            let array = batch.column(index).as_binary_opt::<i32>()?;
+            Some(array.value(0).to_vec())
+        };
+        let (Some(Some(driver)), Some(entrypoint), Some(uri), Some(partition), Some(schema)) =
+            (string(0), string(1), string(2), binary(4), binary(5))
+        else {
+            return Err(invalid("unexpected columns"));
+        };
+        let version = batch
+            .column(3)
+            .as_primitive_opt::<Int32Type>()
+            .ok_or_else(|| invalid("unexpected columns"))?
+            .value(0);
+        let schema = StreamReader::try_new(Cursor::new(schema), None)?.schema();
+        Ok(Self {
+            driver,
+            entrypoint,
+            uri,
+            version: AdbcVersion::try_from(version)?,
+            partition,
+            schema: Arc::unwrap_or_clone(schema),
+        })
+    }
+
+    /// Create a database from `driver`, setting the URI of the envelope
+    /// before `opts`, which may override it and provide credentials.
+    pub fn new_database<D: Driver>(
+        &self,
+        driver: &mut D,
+        opts: impl IntoIterator<Item = (OptionDatabase, OptionValue)>,
+    ) -> Result<D::DatabaseType> {
+        let uri = self
+            .uri
+            .clone()
+            .map(|uri| (OptionDatabase::Uri, OptionValue::from(uri)));
+        driver.new_database_with_opts(uri.into_iter().chain(opts))
+    }
+
+    /// Read the partition on `connection`.
+    pub fn read<'a, C: Connection>(
+        &self,
+        connection: &'a C,
+    ) -> Result<impl RecordBatchReader + Send + 'a> {
+        connection.read_partition(self.partition.clone())
+    }
+}
+
+fn envelope_schema() -> SchemaRef {
+    let schema = Schema::new(vec![
+        Field::new("driver", DataType::Utf8, false),
+        Field::new("entrypoint", DataType::Utf8, true),
+        Field::new("uri", DataType::Utf8, true),
+        Field::new("version", DataType::Int32, false),
+        Field::new("partition", DataType::Binary, false),
+        Field::new("schema", DataType::Binary, false),
+    ])
+    .with_metadata([(ENVELOPE_VERSION_KEY.into(), ENVELOPE_VERSION.into())].into());
+    Arc::new(schema)
+}
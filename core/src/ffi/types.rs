@@ -177,6 +177,13 @@ pub struct FFI_AdbcDriver {
 }
 
 unsafe impl Send for FFI_AdbcDriver {}
+// SAFETY: The function table is not modified after initialization, and the
+// ADBC specification allows methods to be called concurrently on distinct
+// connections of the same driver, but not on a connection and its statements.
+// The driver manager serializes the calls on a database, and on a connection
+// and its statements, with the mutex of the database and of the connection
+// respectively; only the cancel methods bypass them.
+unsafe impl Sync for FFI_AdbcDriver {}
 
 macro_rules! driver_method {
     ($driver:expr, $method:ident) => {
@@ -623,9 +630,9 @@ impl Drop for FFI_AdbcError {
 
 impl Drop for FFI_AdbcDriver {
     fn drop(&mut self) {
+        // The driver manager releases drivers itself to report failures, so
+        // this is only a fallback for drivers used directly.
         if let Some(release) = self.release {
-            // TODO(alexandreyc): how should we handle `release` failing?
-            // See: https://github.com/apache/arrow-adbc/pull/1742#discussion_r1574388409
             unsafe { release(self, null_mut()) };
         }
     }
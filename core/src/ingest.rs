@@ -0,0 +1,232 @@
+//! Bulk ingestion of Arrow data into a table.
+//!
+//! [Ingest] wraps the statement options driving a bulk insert, i.e.
+//! [OptionStatement::TargetTable] and friends, behind a typed builder
+//! obtained with [Connection::ingest]. Before any data is sent, the schema of
+//! the data can be checked against the one of an existing table, and large
+//! batches can be split to bound the size of the batches the driver gets.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! # use arrow::record_batch::RecordBatchReader;
+//! # use adbc_core::options::IngestMode;
+//! # use adbc_core::Connection;
+//! # fn run(
+//! #     connection: &mut impl Connection,
+//! #     reader: impl RecordBatchReader + Send + 'static,
+//! # ) -> adbc_core::error::Result<()> {
+//! let rows = connection
+//!     .ingest("events")
+//!     .mode(IngestMode::CreateAppend)
+//!     .schema("analytics")
+//!     .temporary(false)
+//!     .from_reader(reader)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+
+use arrow::datatypes::{Schema, SchemaRef};
+use arrow::error::ArrowError;
+use arrow::record_batch::{RecordBatch, RecordBatchReader};
+
+use crate::error::{Error, Result, Status};
+use crate::options::{IngestMode, OptionStatement, TypedOptionable};
+use crate::{Connection, Statement};
+
+/// Builder of a bulk insert into a table, created with [Connection::ingest].
+///
+/// By default, the table is created ([IngestMode::Create]) in the current
+/// catalog and database schema of the connection, batches are passed as they
+/// are and the schema of the data is checked when appending to a table.
+pub struct Ingest<'a, C: ?Sized> {
+    connection: &'a mut C,
+    table: String,
+    mode: IngestMode,
+    catalog: Option<String>,
+    schema: Option<String>,
+    temporary: bool,
+    chunk_size: Option<usize>,
+    check_schema: bool,
+}
+
+impl<'a, C: Connection + ?Sized> Ingest<'a, C> {
+    /// Create a builder ingesting into `table` through `connection`.
+    pub fn new(connection: &'a mut C, table: impl Into<String>) -> Self {
+        Self {
+            connection,
+            table: table.into(),
+            mode: IngestMode::Create,
+            catalog: None,
+            schema: None,
+            temporary: false,
+            chunk_size: None,
+            check_schema: true,
+        }
+    }
+
+    /// Set how the target table is created or appended to.
+    pub fn mode(mut self, mode: IngestMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Set the catalog of the target table.
+    pub fn catalog(mut self, catalog: impl Into<String>) -> Self {
+        self.catalog = Some(catalog.into());
+        self
+    }
+
+    /// Set the database schema of the target table.
+    pub fn schema(mut self, schema: impl Into<String>) -> Self {
+        self.schema = Some(schema.into());
+        self
+    }
+
+    /// Set whether the target table is a temporary table.
+    pub fn temporary(mut self, temporary: bool) -> Self {
+        self.temporary = temporary;
+        self
+    }
+
+    /// Split batches with more than `rows` rows before passing them to the
+    /// driver.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rows` is zero.
+    pub fn chunk_size(mut self, rows: usize) -> Self {
+        assert!(rows > 0, "Chunk size must be positive");
+        self.chunk_size = Some(rows);
+        self
+    }
+
+    /// Set whether the schema of the data is checked against the schema of
+    /// the target table, as reported by [Connection::get_table_schema],
+    /// before appending to it.
+    ///
+    /// The check is skipped when the driver cannot report table schemas.
+    pub fn check_schema(mut self, check_schema: bool) -> Self {
+        self.check_schema = check_schema;
+        self
+    }
+
+    /// Ingest the batches of `reader` and return the number of rows ingested,
+    /// as reported by the driver or else counted while reading `reader`.
+    ///
+    /// Fails with [Status::NotFound] when appending to a table which does not
+    /// exist, and with [Status::InvalidArguments] when the schema of the data
+    /// does not match the one of the table, without sending any data.
+    pub fn from_reader(self, reader: impl RecordBatchReader + Send + 'static) -> Result<i64> {
+        if self.check_schema {
+            self.check_table_schema(&reader.schema())?;
+        }
+
+        let mut statement = self.connection.new_statement()?;
+        statement.set_typed(OptionStatement::TargetTable, self.table)?;
+        statement.set_typed(OptionStatement::IngestMode, self.mode)?;
+        if let Some(catalog) = self.catalog {
+            statement.set_typed(OptionStatement::TargetCatalog, catalog)?;
+        }
+        if let Some(schema) = self.schema {
+            statement.set_typed(OptionStatement::TargetDbSchema, schema)?;
+        }
+        // Only set when needed, since drivers may not support the option.
+        if self.temporary {
+            statement.set_typed(OptionStatement::Temporary, true)?;
+        }
+
+        let rows = Arc::new(AtomicI64::new(0));
+        let reader = ChunkedReader {
+            inner: reader,
+            chunk_size: self.chunk_size,
+            pending: None,
+            rows: rows.clone(),
+        };
+        statement.bind_stream(Box::new(reader))?;
+        // Rely on the rows read from the stream when the driver cannot tell.
+        let ingested = statement.execute_update()?;
+        Ok(ingested.unwrap_or_else(|| rows.load(Ordering::Relaxed)))
+    }
+
+    fn check_table_schema(&self, schema: &Schema) -> Result<()> {
+        let check = match self.mode {
+            IngestMode::Append | IngestMode::CreateAppend => !self.temporary,
+            _ => false,
+        };
+        if !check {
+            return Ok(());
+        }
+        let table = match self.connection.get_table_schema(
+            self.catalog.as_deref(),
+            self.schema.as_deref(),
+            &self.table,
+        ) {
+            Ok(table) => table,
+            Err(error) if error.status == Status::NotFound && self.mode == IngestMode::Append => {
+                return Err(error)
+            }
+            // The table is created, or the driver cannot tell.
+            Err(_) => return Ok(()),
+        };
+        let compatible = table.fields().len() == schema.fields().len()
+            && table
+                .fields()
+                .iter()
+                .zip(schema.fields())
+                .all(|(a, b)| a.name() == b.name() && a.data_type() == b.data_type());
+        if compatible {
+            Ok(())
+        } else {
+            Err(Error::with_message_and_status(
+                format!(
+                    "Schema of the data does not match the schema of table {:?}",
+                    self.table
+                ),
+                Status::InvalidArguments,
+            ))
+        }
+    }
+}
+
+/// Reader splitting the batches of another reader and counting their rows.
+struct ChunkedReader<R> {
+    inner: R,
+    chunk_size: Option<usize>,
+    /// Remainder of a batch larger than the chunk size.
+    pending: Option<RecordBatch>,
+    rows: Arc<AtomicI64>,
+}
+
+impl<R: RecordBatchReader> Iterator for ChunkedReader<R> {
+    type Item = std::result::Result<RecordBatch, ArrowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let batch = match self.pending.take() {
+            Some(batch) => batch,
+            None => match self.inner.next()? {
+                Ok(batch) => batch,
+                Err(error) => return Some(Err(error)),
+            },
+        };
+        let batch = match self.chunk_size {
+            Some(size) if batch.num_rows() > size => {
+                self.pending = Some(batch.slice(size, batch.num_rows() - size));
+                batch.slice(0, size)
+            }
+            _ => batch,
+        };
+        self.rows
+            .fetch_add(batch.num_rows() as i64, Ordering::Relaxed);
+        Some(Ok(batch))
+    }
+}
+
+impl<R: RecordBatchReader> RecordBatchReader for ChunkedReader<R> {
+    fn schema(&self) -> SchemaRef {
+        self.inner.schema()
+    }
+}
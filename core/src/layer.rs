@@ -0,0 +1,730 @@
+//! Middleware for composing behaviour around any [Driver].
+//!
+//! A [Layer] observes and alters calls made to a driver without forking it:
+//! it is invoked before and after each method of the driver's databases,
+//! connections and statements, can rewrite SQL queries and can wrap the
+//! result sets they return. Wrapping a driver with [Layered] produces a new
+//! [Driver], so layers stack by nesting, the outermost layer seeing calls
+//! first, and the result can be exported with [export_driver][crate::export_driver]
+//! as long as both the driver and the layer implement [Default].
+//!
+//! ## Example
+//!
+//! ```rust
+//! # use std::time::Duration;
+//! # use adbc_core::{
+//! #     error::{Error, Result, Status},
+//! #     layer::{Call, Layer, Layered, Method},
+//! #     Driver,
+//! # };
+//! /// Logs the duration of every call.
+//! #[derive(Default)]
+//! struct Logging;
+//!
+//! impl Layer for Logging {
+//!     fn after(&self, call: &Call<'_>, result: std::result::Result<(), &Error>, elapsed: Duration) {
+//!         eprintln!("{} took {elapsed:?}: {result:?}", call.method.name());
+//!     }
+//! }
+//!
+//! /// Rejects bulk ingestion.
+//! #[derive(Default)]
+//! struct ReadOnly;
+//!
+//! impl Layer for ReadOnly {
+//!     fn before(&self, call: &Call<'_>) -> Result<()> {
+//!         match call.method {
+//!             Method::StatementBind | Method::StatementBindStream => Err(
+//!                 Error::with_message_and_status("Ingestion is disabled", Status::Unauthorized),
+//!             ),
+//!             _ => Ok(()),
+//!         }
+//!     }
+//! }
+//!
+//! fn wrap<D: Driver>(driver: D) -> Layered<Layered<D, ReadOnly>, Logging> {
+//!     Layered::new(Layered::new(driver, ReadOnly), Logging)
+//! }
+//! ```
+
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use arrow::datatypes::Schema;
+use arrow::record_batch::{RecordBatch, RecordBatchReader};
+
+use crate::capabilities::Capabilities;
+use crate::error::{Error, Result};
+use crate::options::{
+    InfoCode, ObjectDepth, OptionConnection, OptionDatabase, OptionStatement, OptionValue,
+};
+use crate::secret;
+use crate::{Connection, Database, Driver, Optionable, PartitionedResult, Statement};
+
+/// A method of the ADBC API intercepted by a [Layer].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Method {
+    DatabaseInit,
+    DatabaseSetOption,
+    DatabaseGetOption,
+    ConnectionInit,
+    ConnectionSetOption,
+    ConnectionGetOption,
+    ConnectionCancel,
+    ConnectionGetInfo,
+    ConnectionGetObjects,
+    ConnectionGetTableSchema,
+    ConnectionGetTableTypes,
+    ConnectionGetStatisticNames,
+    ConnectionGetStatistics,
+    ConnectionCommit,
+    ConnectionRollback,
+    ConnectionReadPartition,
+    StatementNew,
+    StatementSetOption,
+    StatementGetOption,
+    StatementBind,
+    StatementBindStream,
+    /// [Statement::execute].
+    StatementExecuteQuery,
+    /// [Statement::execute_update].
+    StatementExecuteUpdate,
+    StatementExecuteSchema,
+    StatementExecutePartitions,
+    StatementGetParameterSchema,
+    StatementPrepare,
+    StatementSetSqlQuery,
+    StatementSetSubstraitPlan,
+    StatementCancel,
+}
+
+impl Method {
+    /// Name of the corresponding function of the C API, e.g. `AdbcStatementPrepare`.
+    ///
+    /// [Method::StatementExecuteUpdate] is reported as `AdbcStatementExecuteQuery`
+    /// since both are the same function in the C API.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::DatabaseInit => "AdbcDatabaseInit",
+            Self::DatabaseSetOption => "AdbcDatabaseSetOption",
+            Self::DatabaseGetOption => "AdbcDatabaseGetOption",
+            Self::ConnectionInit => "AdbcConnectionInit",
+            Self::ConnectionSetOption => "AdbcConnectionSetOption",
+            Self::ConnectionGetOption => "AdbcConnectionGetOption",
+            Self::ConnectionCancel => "AdbcConnectionCancel",
+            Self::ConnectionGetInfo => "AdbcConnectionGetInfo",
+            Self::ConnectionGetObjects => "AdbcConnectionGetObjects",
+            Self::ConnectionGetTableSchema => "AdbcConnectionGetTableSchema",
+            Self::ConnectionGetTableTypes => "AdbcConnectionGetTableTypes",
+            Self::ConnectionGetStatisticNames => "AdbcConnectionGetStatisticNames",
+            Self::ConnectionGetStatistics => "AdbcConnectionGetStatistics",
+            Self::ConnectionCommit => "AdbcConnectionCommit",
+            Self::ConnectionRollback => "AdbcConnectionRollback",
+            Self::ConnectionReadPartition => "AdbcConnectionReadPartition",
+            Self::StatementNew => "AdbcStatementNew",
+            Self::StatementSetOption => "AdbcStatementSetOption",
+            Self::StatementGetOption => "AdbcStatementGetOption",
+            Self::StatementBind => "AdbcStatementBind",
+            Self::StatementBindStream => "AdbcStatementBindStream",
+            Self::StatementExecuteQuery | Self::StatementExecuteUpdate => {
+                "AdbcStatementExecuteQuery"
+            }
+            Self::StatementExecuteSchema => "AdbcStatementExecuteSchema",
+            Self::StatementExecutePartitions => "AdbcStatementExecutePartitions",
+            Self::StatementGetParameterSchema => "AdbcStatementGetParameterSchema",
+            Self::StatementPrepare => "AdbcStatementPrepare",
+            Self::StatementSetSqlQuery => "AdbcStatementSetSqlQuery",
+            Self::StatementSetSubstraitPlan => "AdbcStatementSetSubstraitPlan",
+            Self::StatementCancel => "AdbcStatementCancel",
+        }
+    }
+}
+
+/// Description of an intercepted call.
+///
+/// [Debug][fmt::Debug] masks the value of [secret] options.
+#[derive(Clone, Copy)]
+#[non_exhaustive]
+pub struct Call<'a> {
+    /// The method being called.
+    pub method: Method,
+    /// The SQL query of the statement, if any.
+    ///
+    /// For [Method::StatementSetSqlQuery], this is the query as given by the
+    /// caller, before [Layer::rewrite_query] is applied.
+    pub query: Option<&'a str>,
+    /// The option key, for methods setting or getting an option.
+    pub option: Option<&'a str>,
+    /// The option value, for methods setting an option.
+    pub value: Option<&'a OptionValue>,
+}
+
+impl<'a> Call<'a> {
+    /// A call to `method` with no further details.
+    pub fn new(method: Method) -> Self {
+        Self {
+            method,
+            query: None,
+            option: None,
+            value: None,
+        }
+    }
+
+    fn with_query(mut self, query: Option<&'a str>) -> Self {
+        self.query = query;
+        self
+    }
+
+    fn with_option(mut self, option: &'a str, value: Option<&'a OptionValue>) -> Self {
+        self.option = Some(option);
+        self.value = value;
+        self
+    }
+}
+
+impl fmt::Debug for Call<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = self
+            .value
+            .map(|value| secret::redacted(self.option.unwrap_or_default(), value));
+        f.debug_struct("Call")
+            .field("method", &self.method)
+            .field("query", &self.query)
+            .field("option", &self.option)
+            .field("value", &value)
+            .finish()
+    }
+}
+
+/// Behaviour wrapped around the methods of a driver.
+///
+/// All hooks have a default implementation doing nothing, so layers only
+/// implement those they need. Layers are shared by all objects created from
+/// a [Layered] driver, hence hooks take `&self`.
+pub trait Layer {
+    /// Called before a method is forwarded to the wrapped object.
+    ///
+    /// Returning an error aborts the call, which then fails with that error
+    /// without reaching the wrapped object.
+    fn before(&self, call: &Call<'_>) -> Result<()> {
+        let _ = call;
+        Ok(())
+    }
+
+    /// Called once the wrapped object returned, with the outcome of the call
+    /// and the time it took.
+    ///
+    /// Calls aborted by [Layer::before] are not reported.
+    fn after(&self, call: &Call<'_>, result: std::result::Result<(), &Error>, elapsed: Duration) {
+        let _ = (call, result, elapsed);
+    }
+
+    /// Rewrite a SQL query before it is set on the wrapped statement.
+    fn rewrite_query(&self, query: String) -> Result<String> {
+        Ok(query)
+    }
+
+    /// Wrap a result set returned by a successful call.
+    ///
+    /// The returned reader may not borrow from `call`; layers needing details
+    /// of the call while the result set is consumed must copy them.
+    /// Implementations declare this with `+ use<R>` on their return type.
+    fn wrap_reader<R: RecordBatchReader + Send>(
+        &self,
+        call: &Call<'_>,
+        reader: R,
+    ) -> impl RecordBatchReader + Send + use<Self, R> {
+        let _ = call;
+        reader
+    }
+
+    /// Wrap a stream bound to a statement, before it is passed to the wrapped
+    /// statement.
+    fn wrap_bind_stream(
+        &self,
+        call: &Call<'_>,
+        reader: Box<dyn RecordBatchReader + Send>,
+    ) -> Box<dyn RecordBatchReader + Send> {
+        let _ = call;
+        reader
+    }
+}
+
+impl<L: Layer + ?Sized> Layer for Arc<L> {
+    fn before(&self, call: &Call<'_>) -> Result<()> {
+        (**self).before(call)
+    }
+
+    fn after(&self, call: &Call<'_>, result: std::result::Result<(), &Error>, elapsed: Duration) {
+        (**self).after(call, result, elapsed)
+    }
+
+    fn rewrite_query(&self, query: String) -> Result<String> {
+        (**self).rewrite_query(query)
+    }
+
+    fn wrap_reader<R: RecordBatchReader + Send>(
+        &self,
+        call: &Call<'_>,
+        reader: R,
+    ) -> impl RecordBatchReader + Send + use<L, R> {
+        (**self).wrap_reader(call, reader)
+    }
+
+    fn wrap_bind_stream(
+        &self,
+        call: &Call<'_>,
+        reader: Box<dyn RecordBatchReader + Send>,
+    ) -> Box<dyn RecordBatchReader + Send> {
+        (**self).wrap_bind_stream(call, reader)
+    }
+}
+
+/// Run `f` between the [Layer::before] and [Layer::after] hooks.
+fn intercept<L: Layer, T>(layer: &L, call: &Call<'_>, f: impl FnOnce() -> Result<T>) -> Result<T> {
+    layer.before(call)?;
+    let start = Instant::now();
+    let result = f();
+    layer.after(call, result.as_ref().map(|_| ()), start.elapsed());
+    result
+}
+
+/// A driver wrapped by a [Layer].
+#[derive(Debug, Default, Clone)]
+pub struct Layered<D, L> {
+    inner: D,
+    layer: Arc<L>,
+}
+
+impl<D, L> Layered<D, L> {
+    /// Wrap `inner` with `layer`.
+    pub fn new(inner: D, layer: L) -> Self {
+        Self {
+            inner,
+            layer: Arc::new(layer),
+        }
+    }
+
+    /// The wrapped driver.
+    pub fn inner(&self) -> &D {
+        &self.inner
+    }
+
+    /// The wrapped driver.
+    pub fn inner_mut(&mut self) -> &mut D {
+        &mut self.inner
+    }
+
+    /// The layer shared by all objects created from this driver.
+    pub fn layer(&self) -> &L {
+        &self.layer
+    }
+}
+
+impl<D: Driver, L: Layer> Driver for Layered<D, L> {
+    type DatabaseType = LayeredDatabase<D::DatabaseType, L>;
+
+    fn new_database(&mut self) -> Result<Self::DatabaseType> {
+        let inner = intercept(&self.layer, &Call::new(Method::DatabaseInit), || {
+            self.inner.new_database()
+        })?;
+        Ok(LayeredDatabase {
+            inner,
+            layer: self.layer.clone(),
+        })
+    }
+
+    fn new_database_with_opts(
+        &mut self,
+        opts: impl IntoIterator<Item = (OptionDatabase, OptionValue)>,
+    ) -> Result<Self::DatabaseType> {
+        let inner = intercept(&self.layer, &Call::new(Method::DatabaseInit), || {
+            self.inner.new_database_with_opts(opts)
+        })?;
+        Ok(LayeredDatabase {
+            inner,
+            layer: self.layer.clone(),
+        })
+    }
+}
+
+/// A database created by a [Layered] driver.
+#[derive(Debug)]
+pub struct LayeredDatabase<D, L> {
+    inner: D,
+    layer: Arc<L>,
+}
+
+impl<D, L> LayeredDatabase<D, L> {
+    /// The wrapped database.
+    pub fn inner(&self) -> &D {
+        &self.inner
+    }
+}
+
+impl<D: Database, L: Layer> Optionable for LayeredDatabase<D, L> {
+    type Option = OptionDatabase;
+
+    fn set_option(&mut self, key: Self::Option, value: OptionValue) -> Result<()> {
+        let call = Call::new(Method::DatabaseSetOption).with_option(key.as_ref(), Some(&value));
+        intercept(&self.layer, &call, || {
+            self.inner.set_option(key.clone(), value.clone())
+        })
+    }
+
+    fn get_option_string(&self, key: Self::Option) -> Result<String> {
+        let call = Call::new(Method::DatabaseGetOption).with_option(key.as_ref(), None);
+        intercept(&self.layer, &call, || {
+            self.inner.get_option_string(key.clone())
+        })
+    }
+
+    fn get_option_bytes(&self, key: Self::Option) -> Result<Vec<u8>> {
+        let call = Call::new(Method::DatabaseGetOption).with_option(key.as_ref(), None);
+        intercept(&self.layer, &call, || {
+            self.inner.get_option_bytes(key.clone())
+        })
+    }
+
+    fn get_option_int(&self, key: Self::Option) -> Result<i64> {
+        let call = Call::new(Method::DatabaseGetOption).with_option(key.as_ref(), None);
+        intercept(&self.layer, &call, || {
+            self.inner.get_option_int(key.clone())
+        })
+    }
+
+    fn get_option_double(&self, key: Self::Option) -> Result<f64> {
+        let call = Call::new(Method::DatabaseGetOption).with_option(key.as_ref(), None);
+        intercept(&self.layer, &call, || {
+            self.inner.get_option_double(key.clone())
+        })
+    }
+}
+
+impl<D: Database, L: Layer> Database for LayeredDatabase<D, L> {
+    type ConnectionType = LayeredConnection<D::ConnectionType, L>;
+
+    fn new_connection(&mut self) -> Result<Self::ConnectionType> {
+        let inner = intercept(&self.layer, &Call::new(Method::ConnectionInit), || {
+            self.inner.new_connection()
+        })?;
+        Ok(LayeredConnection {
+            inner,
+            layer: self.layer.clone(),
+        })
+    }
+
+    fn new_connection_with_opts(
+        &mut self,
+        opts: impl IntoIterator<Item = (OptionConnection, OptionValue)>,
+    ) -> Result<Self::ConnectionType> {
+        let inner = intercept(&self.layer, &Call::new(Method::ConnectionInit), || {
+            self.inner.new_connection_with_opts(opts)
+        })?;
+        Ok(LayeredConnection {
+            inner,
+            layer: self.layer.clone(),
+        })
+    }
+}
+
+/// A connection created by a [Layered] driver.
+#[derive(Debug)]
+pub struct LayeredConnection<C, L> {
+    inner: C,
+    layer: Arc<L>,
+}
+
+impl<C, L> LayeredConnection<C, L> {
+    /// The wrapped connection.
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+}
+
+impl<C: Connection, L: Layer> Optionable for LayeredConnection<C, L> {
+    type Option = OptionConnection;
+
+    fn set_option(&mut self, key: Self::Option, value: OptionValue) -> Result<()> {
+        let call = Call::new(Method::ConnectionSetOption).with_option(key.as_ref(), Some(&value));
+        intercept(&self.layer, &call, || {
+            self.inner.set_option(key.clone(), value.clone())
+        })
+    }
+
+    fn get_option_string(&self, key: Self::Option) -> Result<String> {
+        let call = Call::new(Method::ConnectionGetOption).with_option(key.as_ref(), None);
+        intercept(&self.layer, &call, || {
+            self.inner.get_option_string(key.clone())
+        })
+    }
+
+    fn get_option_bytes(&self, key: Self::Option) -> Result<Vec<u8>> {
+        let call = Call::new(Method::ConnectionGetOption).with_option(key.as_ref(), None);
+        intercept(&self.layer, &call, || {
+            self.inner.get_option_bytes(key.clone())
+        })
+    }
+
+    fn get_option_int(&self, key: Self::Option) -> Result<i64> {
+        let call = Call::new(Method::ConnectionGetOption).with_option(key.as_ref(), None);
+        intercept(&self.layer, &call, || {
+            self.inner.get_option_int(key.clone())
+        })
+    }
+
+    fn get_option_double(&self, key: Self::Option) -> Result<f64> {
+        let call = Call::new(Method::ConnectionGetOption).with_option(key.as_ref(), None);
+        intercept(&self.layer, &call, || {
+            self.inner.get_option_double(key.clone())
+        })
+    }
+}
+
+impl<C: Connection, L: Layer> Connection for LayeredConnection<C, L> {
+    type StatementType = LayeredStatement<C::StatementType, L>;
+
+    fn new_statement(&mut self) -> Result<Self::StatementType> {
+        let inner = intercept(&self.layer, &Call::new(Method::StatementNew), || {
+            self.inner.new_statement()
+        })?;
+        Ok(LayeredStatement {
+            inner,
+            layer: self.layer.clone(),
+            query: None,
+        })
+    }
+
+    fn cancel(&mut self) -> Result<()> {
+        intercept(&self.layer, &Call::new(Method::ConnectionCancel), || {
+            self.inner.cancel()
+        })
+    }
+
+    fn get_info(&self, codes: Option<HashSet<InfoCode>>) -> Result<impl RecordBatchReader + Send> {
+        let call = Call::new(Method::ConnectionGetInfo);
+        let reader = intercept(&self.layer, &call, || self.inner.get_info(codes))?;
+        Ok(self.layer.wrap_reader(&call, reader))
+    }
+
+    fn get_objects(
+        &self,
+        depth: ObjectDepth,
+        catalog: Option<&str>,
+        db_schema: Option<&str>,
+        table_name: Option<&str>,
+        table_type: Option<Vec<&str>>,
+        column_name: Option<&str>,
+    ) -> Result<impl RecordBatchReader + Send> {
+        let call = Call::new(Method::ConnectionGetObjects);
+        let reader = intercept(&self.layer, &call, || {
+            self.inner.get_objects(
+                depth,
+                catalog,
+                db_schema,
+                table_name,
+                table_type,
+                column_name,
+            )
+        })?;
+        Ok(self.layer.wrap_reader(&call, reader))
+    }
+
+    fn get_table_schema(
+        &self,
+        catalog: Option<&str>,
+        db_schema: Option<&str>,
+        table_name: &str,
+    ) -> Result<Schema> {
+        let call = Call::new(Method::ConnectionGetTableSchema);
+        intercept(&self.layer, &call, || {
+            self.inner.get_table_schema(catalog, db_schema, table_name)
+        })
+    }
+
+    fn get_table_types(&self) -> Result<impl RecordBatchReader + Send> {
+        let call = Call::new(Method::ConnectionGetTableTypes);
+        let reader = intercept(&self.layer, &call, || self.inner.get_table_types())?;
+        Ok(self.layer.wrap_reader(&call, reader))
+    }
+
+    fn get_statistic_names(&self) -> Result<impl RecordBatchReader + Send> {
+        let call = Call::new(Method::ConnectionGetStatisticNames);
+        let reader = intercept(&self.layer, &call, || self.inner.get_statistic_names())?;
+        Ok(self.layer.wrap_reader(&call, reader))
+    }
+
+    fn get_statistics(
+        &self,
+        catalog: Option<&str>,
+        db_schema: Option<&str>,
+        table_name: Option<&str>,
+        approximate: bool,
+    ) -> Result<impl RecordBatchReader + Send> {
+        let call = Call::new(Method::ConnectionGetStatistics);
+        let reader = intercept(&self.layer, &call, || {
+            self.inner
+                .get_statistics(catalog, db_schema, table_name, approximate)
+        })?;
+        Ok(self.layer.wrap_reader(&call, reader))
+    }
+
+    fn commit(&mut self) -> Result<()> {
+        intercept(&self.layer, &Call::new(Method::ConnectionCommit), || {
+            self.inner.commit()
+        })
+    }
+
+    fn rollback(&mut self) -> Result<()> {
+        intercept(&self.layer, &Call::new(Method::ConnectionRollback), || {
+            self.inner.rollback()
+        })
+    }
+
+    fn read_partition(&self, partition: impl AsRef<[u8]>) -> Result<impl RecordBatchReader + Send> {
+        let call = Call::new(Method::ConnectionReadPartition);
+        let partition = partition.as_ref().to_vec();
+        let reader = intercept(&self.layer, &call, || self.inner.read_partition(partition))?;
+        Ok(self.layer.wrap_reader(&call, reader))
+    }
+
+    fn capabilities(&self) -> Result<Capabilities> {
+        self.inner.capabilities()
+    }
+}
+
+/// A statement created by a [Layered] driver.
+#[derive(Debug)]
+pub struct LayeredStatement<S, L> {
+    inner: S,
+    layer: Arc<L>,
+    /// The query last set on the wrapped statement, after rewriting.
+    query: Option<String>,
+}
+
+impl<S, L> LayeredStatement<S, L> {
+    /// The wrapped statement.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+}
+
+impl<S: Statement, L: Layer> Optionable for LayeredStatement<S, L> {
+    type Option = OptionStatement;
+
+    fn set_option(&mut self, key: Self::Option, value: OptionValue) -> Result<()> {
+        let call = Call::new(Method::StatementSetOption)
+            .with_query(self.query.as_deref())
+            .with_option(key.as_ref(), Some(&value));
+        intercept(&self.layer, &call, || {
+            self.inner.set_option(key.clone(), value.clone())
+        })
+    }
+
+    fn get_option_string(&self, key: Self::Option) -> Result<String> {
+        let call = Call::new(Method::StatementGetOption)
+            .with_query(self.query.as_deref())
+            .with_option(key.as_ref(), None);
+        intercept(&self.layer, &call, || {
+            self.inner.get_option_string(key.clone())
+        })
+    }
+
+    fn get_option_bytes(&self, key: Self::Option) -> Result<Vec<u8>> {
+        let call = Call::new(Method::StatementGetOption)
+            .with_query(self.query.as_deref())
+            .with_option(key.as_ref(), None);
+        intercept(&self.layer, &call, || {
+            self.inner.get_option_bytes(key.clone())
+        })
+    }
+
+    fn get_option_int(&self, key: Self::Option) -> Result<i64> {
+        let call = Call::new(Method::StatementGetOption)
+            .with_query(self.query.as_deref())
+            .with_option(key.as_ref(), None);
+        intercept(&self.layer, &call, || {
+            self.inner.get_option_int(key.clone())
+        })
+    }
+
+    fn get_option_double(&self, key: Self::Option) -> Result<f64> {
+        let call = Call::new(Method::StatementGetOption)
+            .with_query(self.query.as_deref())
+            .with_option(key.as_ref(), None);
+        intercept(&self.layer, &call, || {
+            self.inner.get_option_double(key.clone())
+        })
+    }
+}
+
+impl<S: Statement, L: Layer> Statement for LayeredStatement<S, L> {
+    fn bind(&mut self, batch: RecordBatch) -> Result<()> {
+        let call = Call::new(Method::StatementBind).with_query(self.query.as_deref());
+        intercept(&self.layer, &call, || self.inner.bind(batch))
+    }
+
+    fn bind_stream(&mut self, reader: Box<dyn RecordBatchReader + Send>) -> Result<()> {
+        let call = Call::new(Method::StatementBindStream).with_query(self.query.as_deref());
+        let reader = self.layer.wrap_bind_stream(&call, reader);
+        intercept(&self.layer, &call, || self.inner.bind_stream(reader))
+    }
+
+    fn execute(&mut self) -> Result<impl RecordBatchReader + Send> {
+        let call = Call::new(Method::StatementExecuteQuery).with_query(self.query.as_deref());
+        let reader = intercept(&self.layer, &call, || self.inner.execute())?;
+        Ok(self.layer.wrap_reader(&call, reader))
+    }
+
+    fn execute_update(&mut self) -> Result<Option<i64>> {
+        let call = Call::new(Method::StatementExecuteUpdate).with_query(self.query.as_deref());
+        intercept(&self.layer, &call, || self.inner.execute_update())
+    }
+
+    fn execute_schema(&mut self) -> Result<Schema> {
+        let call = Call::new(Method::StatementExecuteSchema).with_query(self.query.as_deref());
+        intercept(&self.layer, &call, || self.inner.execute_schema())
+    }
+
+    fn execute_partitions(&mut self) -> Result<PartitionedResult> {
+        let call = Call::new(Method::StatementExecutePartitions).with_query(self.query.as_deref());
+        intercept(&self.layer, &call, || self.inner.execute_partitions())
+    }
+
+    fn get_parameter_schema(&self) -> Result<Schema> {
+        let call = Call::new(Method::StatementGetParameterSchema).with_query(self.query.as_deref());
+        intercept(&self.layer, &call, || self.inner.get_parameter_schema())
+    }
+
+    fn prepare(&mut self) -> Result<()> {
+        let call = Call::new(Method::StatementPrepare).with_query(self.query.as_deref());
+        intercept(&self.layer, &call, || self.inner.prepare())
+    }
+
+    fn set_sql_query(&mut self, query: impl AsRef<str>) -> Result<()> {
+        let query = query.as_ref();
+        let call = Call::new(Method::StatementSetSqlQuery).with_query(Some(query));
+        let rewritten = intercept(&self.layer, &call, || {
+            let query = self.layer.rewrite_query(query.to_string())?;
+            self.inner.set_sql_query(&query)?;
+            Ok(query)
+        })?;
+        self.query = Some(rewritten);
+        Ok(())
+    }
+
+    fn set_substrait_plan(&mut self, plan: impl AsRef<[u8]>) -> Result<()> {
+        let call = Call::new(Method::StatementSetSubstraitPlan).with_query(self.query.as_deref());
+        intercept(&self.layer, &call, || self.inner.set_substrait_plan(plan))?;
+        self.query = None;
+        Ok(())
+    }
+
+    fn cancel(&mut self) -> Result<()> {
+        let call = Call::new(Method::StatementCancel).with_query(self.query.as_deref());
+        intercept(&self.layer, &call, || self.inner.cancel())
+    }
+}
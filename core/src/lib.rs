@@ -11,9 +11,9 @@
 //! This library currently provides:
 //! - An abstract Rust API to be implemented by vendor-specific drivers.
 //! - A driver manager which implements this same API, but dynamically loads
-//! drivers internally and forwards calls appropriately using the [C API](https://github.com/apache/arrow-adbc/blob/main/adbc.h).
+//!   drivers internally and forwards calls appropriately using the [C API](https://github.com/apache/arrow-adbc/blob/main/adbc.h).
 //! - A driver exporter that takes an implementation of the abstract API and
-//! turns it into an object file that implements the C API.
+//!   turns it into an object file that implements the C API.
 //!
 //! # Native Rust drivers
 //!
@@ -40,24 +40,115 @@
 //! implementation of [Driver], provided that it also implements [Default], you
 //! can build it as an object file implementing the C API with the
 //! [export_driver] macro.
+//!
+//! # Layers
+//!
+//! The [layer] module allows wrapping any [Driver] with middleware hooking
+//! into the methods of its databases, connections and statements, producing
+//! a new [Driver] which can itself be wrapped or exported.
+//!
+//! # Ingestion
+//!
+//! The [ingest] module provides a typed builder for bulk inserts, obtained
+//! with [Connection::ingest].
+//!
+//! # Prepared statements
+//!
+//! The [statement_cache] module keeps the statements prepared on a
+//! connection, keyed by their SQL query.
+//!
+//! # Partitions
+//!
+//! The [partitions] module executes queries incrementally and reads the
+//! partitions returned by [Statement::execute_partitions] concurrently, on
+//! several connections. With the `ipc` feature flag, partitions are also
+//! shipped in envelopes to be read by other processes.
+//!
+//! # Retries
+//!
+//! The [retry] module allows wrapping any [Driver] so that its connections
+//! reconnect after transient failures and its idempotent calls are retried.
+//!
+//! # Capabilities
+//!
+//! The [capabilities] module describes the features supported by a
+//! connection, as reported by [Connection::capabilities].
+//!
+//! # Credentials
+//!
+//! The [credentials] module defines providers of short-lived credentials,
+//! which the driver manager obtains when creating databases and connections.
+//!
+//! # Profiles
+//!
+//! With the `profile` feature flag, the `profile` module opens databases
+//! described by named profiles, read from TOML or YAML files.
+//!
+//! # URIs
+//!
+//! The [uri] module parses database URIs into the canonical database options
+//! and the name of the driver handling their scheme.
+//!
+//! # Secrets
+//!
+//! The [secret] module masks the values of secret options, such as
+//! passwords and tokens, when options are formatted. The driver manager,
+//! profiles, URIs and the retry layer zeroize the secret values they hold
+//! once they no longer need them, while drivers are responsible for the
+//! values they are given.
+//!
+//! # Tracing
+//!
+//! With the `tracing` feature flag, the driver manager and the driver
+//! exporter emit a [tracing](https://docs.rs/tracing) span for every ADBC
+//! call, see the `trace` module.
+//!
+//! # Metrics
+//!
+//! The [metrics] module defines a pluggable [metrics::Recorder] to which
+//! the driver manager and [metrics::MetricsLayer] report call latencies,
+//! error counts, open connections and the volume of data streamed.
 
+pub mod capabilities;
+pub mod credentials;
 mod driver_exporter;
 #[doc(hidden)]
 pub use driver_exporter::FFIDriver;
 #[cfg(feature = "driver_manager")]
 pub mod driver_manager;
+#[cfg(feature = "ipc")]
+mod envelope;
 pub mod error;
 pub mod ffi;
+pub mod ingest;
+pub mod layer;
+pub mod metrics;
 pub mod options;
+pub mod partitions;
+#[cfg(feature = "profile")]
+pub mod profile;
+pub mod retry;
 pub mod schemas;
+pub mod secret;
+pub mod statement_cache;
+#[cfg(feature = "tracing")]
+pub mod trace;
+#[cfg(not(feature = "tracing"))]
+mod trace;
+pub mod uri;
 
 use std::collections::HashSet;
 
 use arrow::datatypes::Schema;
 use arrow::record_batch::{RecordBatch, RecordBatchReader};
 
+use capabilities::Capabilities;
 use error::Result;
-use options::{OptionConnection, OptionDatabase, OptionStatement, OptionValue};
+use ingest::Ingest;
+use options::{
+    IngestMode, IsolationLevel, OptionConnection, OptionDatabase, OptionStatement, OptionValue,
+    TypedOptionable,
+};
 
 /// Ability to configure an object by setting/getting options.
 pub trait Optionable {
@@ -318,9 +409,9 @@ pub trait Connection: Optionable<Option = OptionConnection> {
     /// - `db_schema` - The database schema (or `None` if not applicable). May be a search pattern
     /// - `table_name` - The table name (or `None` if not applicable). May be a search pattern
     /// - `approximate` - If false, request exact values of statistics, else
-    /// allow for best-effort, approximate, or cached values. The database may
-    /// return approximate values regardless, as indicated in the result.
-    /// Requesting exact values may be expensive or unsupported.
+    ///   allow for best-effort, approximate, or cached values. The database may
+    ///   return approximate values regardless, as indicated in the result.
+    ///   Requesting exact values may be expensive or unsupported.
     ///
     /// # Result
     ///
@@ -353,7 +444,7 @@ pub trait Connection: Optionable<Option = OptionConnection> {
     ///    dictionary type). Values in [0, 1024) are reserved for ADBC.  Other
     ///    values are for implementation-specific statistics.  For the definitions
     ///    of predefined statistic types, TODO (change this when statistics enum is added )
-    ///     see \ref adbc-table-statistics. To get
+    ///    see \ref adbc-table-statistics. To get
     ///    driver-specific statistic names, use [Connection::get_statistic_names].
     /// 3. If true, then the value is approximate or best-effort.
     ///
@@ -395,6 +486,48 @@ pub trait Connection: Optionable<Option = OptionConnection> {
     ///
     /// - `partition` - The partition descriptor.
     fn read_partition(&self, partition: impl AsRef<[u8]>) -> Result<impl RecordBatchReader + Send>;
+
+    /// Get the features supported by the connection and its statements.
+    ///
+    /// The default implementation only refines [Capabilities::default] with
+    /// [Connection::get_info], so drivers should override it to declare the
+    /// features they implement, see the [capabilities] module.
+    fn capabilities(&self) -> Result<Capabilities> {
+        Capabilities::default().with_info(self)
+    }
+
+    /// Start building a bulk insert into `table`, see [Ingest].
+    fn ingest(&mut self, table: impl Into<String>) -> Ingest<'_, Self> {
+        Ingest::new(self, table)
+    }
+
+    /// Get whether autocommit is enabled, see [OptionConnection::AutoCommit].
+    fn autocommit(&self) -> Result<bool> {
+        self.get_typed(OptionConnection::AutoCommit)
+    }
+
+    /// Get whether the connection is read-only, see [OptionConnection::ReadOnly].
+    fn read_only(&self) -> Result<bool> {
+        self.get_typed(OptionConnection::ReadOnly)
+    }
+
+    /// Get the isolation level of the connection, see
+    /// [OptionConnection::IsolationLevel].
+    fn isolation_level(&self) -> Result<IsolationLevel> {
+        self.get_typed(OptionConnection::IsolationLevel)
+    }
+
+    /// Get the catalog used by the connection, see
+    /// [OptionConnection::CurrentCatalog].
+    fn current_catalog(&self) -> Result<String> {
+        self.get_typed(OptionConnection::CurrentCatalog)
+    }
+
+    /// Get the database schema used by the connection, see
+    /// [OptionConnection::CurrentSchema].
+    fn current_schema(&self) -> Result<String> {
+        self.get_typed(OptionConnection::CurrentSchema)
+    }
 }
 
 /// A handle to an ADBC statement.
@@ -495,6 +628,16 @@ pub trait Statement: Optionable<Option = OptionStatement> {
     ///
     /// ADBC API revision 1.1.0
     fn cancel(&mut self) -> Result<()>;
+
+    /// Get the ingest mode of bulk inserts, see [OptionStatement::IngestMode].
+    fn ingest_mode(&self) -> Result<IngestMode> {
+        self.get_typed(OptionStatement::IngestMode)
+    }
+
+    /// Get the target table of bulk inserts, see [OptionStatement::TargetTable].
+    fn target_table(&self) -> Result<String> {
+        self.get_typed(OptionStatement::TargetTable)
+    }
 }
 
 /// Each data partition is described by an opaque byte array and can be
@@ -0,0 +1,420 @@
+//! Metrics of ADBC calls, result sets and ingestion.
+//!
+//! Metrics are reported to a [Recorder], the backend forwarding them to a
+//! monitoring system. The recorder is installed process-wide with
+//! [set_recorder] and is then fed by the [driver manager][crate::driver_manager]
+//! and by [MetricsLayer], which collects the same metrics around any
+//! [Driver][crate::Driver]. An [InMemoryRecorder] is provided for tests.
+//!
+//! The following metrics are reported:
+//!
+//! Name                             | Kind      | Labels                       | Description
+//! ---------------------------------|-----------|------------------------------|--------------------------------------------
+//! `adbc.calls.duration`            | histogram | `method`, `driver`, `status` | Duration of calls, in seconds.
+//! `adbc.calls.errors`              | counter   | `method`, `driver`, `status` | Number of failed calls.
+//! `adbc.connections.open`          | gauge     | `driver`, `database`         | Number of open connections of a database.
+//! `adbc.stream.rows`               | counter   | `method`, `driver`           | Number of rows read from result sets.
+//! `adbc.stream.batches`            | counter   | `method`, `driver`           | Number of batches read from result sets.
+//! `adbc.stream.bytes`              | counter   | `method`, `driver`           | In-memory size of batches read from result sets.
+//! `adbc.stream.duration`           | histogram | `method`, `driver`           | Time spent reading result sets, in seconds.
+//! `adbc.ingest.rows`               | counter   | `method`, `driver`           | Number of rows consumed from bound streams.
+//! `adbc.ingest.batches`            | counter   | `method`, `driver`           | Number of batches consumed from bound streams.
+//! `adbc.ingest.bytes`              | counter   | `method`, `driver`           | In-memory size of batches consumed from bound streams.
+//! `adbc.ingest.duration`           | histogram | `method`, `driver`           | Time spent consuming bound streams, in seconds.
+//! `adbc.statement_cache.hits`      | counter   |                              | Number of queries served by a prepared statement cache.
+//! `adbc.statement_cache.misses`    | counter   |                              | Number of queries prepared by a prepared statement cache.
+//! `adbc.statement_cache.evictions` | counter   |                              | Number of statements evicted from prepared statement caches.
+//!
+//! `method` is the name of the C API function, e.g. `AdbcStatementExecuteQuery`,
+//! and `status` is `Ok` or the [Status][crate::error::Status] of the error,
+//! e.g. `InvalidArguments`. Result sets and bound streams are timed from their
+//! first batch to their end, so ingestion throughput is
+//! `adbc.ingest.bytes` over `adbc.ingest.duration`.
+//!
+//! ## Example
+//!
+//! ```rust
+//! # use std::sync::Arc;
+//! # use adbc_core::metrics::{self, InMemoryRecorder};
+//! let recorder = Arc::new(InMemoryRecorder::default());
+//! metrics::set_recorder(recorder.clone());
+//! // Use drivers...
+//! let errors = recorder.counter(metrics::CALLS_ERRORS, &[("status", "Timeout")]);
+//! # assert_eq!(errors, 0);
+//! # metrics::clear_recorder();
+//! ```
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use arrow::datatypes::SchemaRef;
+use arrow::error::ArrowError;
+use arrow::record_batch::{RecordBatch, RecordBatchReader};
+
+use crate::error::Error;
+use crate::layer::{Call, Layer};
+
+/// Duration of calls, in seconds.
+pub const CALLS_DURATION: &str = "adbc.calls.duration";
+/// Number of failed calls.
+pub const CALLS_ERRORS: &str = "adbc.calls.errors";
+/// Number of open connections of a database.
+pub const CONNECTIONS_OPEN: &str = "adbc.connections.open";
+/// Number of rows read from result sets.
+pub const STREAM_ROWS: &str = "adbc.stream.rows";
+/// Number of batches read from result sets.
+pub const STREAM_BATCHES: &str = "adbc.stream.batches";
+/// In-memory size of batches read from result sets.
+pub const STREAM_BYTES: &str = "adbc.stream.bytes";
+/// Time spent reading result sets, in seconds.
+pub const STREAM_DURATION: &str = "adbc.stream.duration";
+/// Number of rows consumed from bound streams.
+pub const INGEST_ROWS: &str = "adbc.ingest.rows";
+/// Number of batches consumed from bound streams.
+pub const INGEST_BATCHES: &str = "adbc.ingest.batches";
+/// In-memory size of batches consumed from bound streams.
+pub const INGEST_BYTES: &str = "adbc.ingest.bytes";
+/// Time spent consuming bound streams, in seconds.
+pub const INGEST_DURATION: &str = "adbc.ingest.duration";
+
+/// Number of queries served by a prepared statement cache, see
+/// [StatementCache][crate::statement_cache::StatementCache].
+pub const STATEMENT_CACHE_HITS: &str = "adbc.statement_cache.hits";
+/// Number of queries prepared by a prepared statement cache.
+pub const STATEMENT_CACHE_MISSES: &str = "adbc.statement_cache.misses";
+/// Number of statements evicted from prepared statement caches, because the
+/// cache was full or the statement was invalidated.
+pub const STATEMENT_CACHE_EVICTIONS: &str = "adbc.statement_cache.evictions";
+
+/// Labels of a metric, as key-value pairs.
+pub type Labels<'a> = [(&'static str, &'a str)];
+
+/// A metrics backend.
+pub trait Recorder: Send + Sync {
+    /// Add `value` to a counter.
+    fn increment_counter(&self, name: &'static str, labels: &Labels<'_>, value: u64);
+
+    /// Add `delta` to a gauge.
+    fn update_gauge(&self, name: &'static str, labels: &Labels<'_>, delta: i64);
+
+    /// Record an observation in a histogram.
+    fn record_histogram(&self, name: &'static str, labels: &Labels<'_>, value: f64);
+}
+
+static RECORDER: RwLock<Option<Arc<dyn Recorder>>> = RwLock::new(None);
+
+/// Install the process-wide recorder, replacing the previous one.
+///
+/// Gauges are updated relative to their current value, so the recorder
+/// should be installed before any database is created.
+pub fn set_recorder(recorder: Arc<dyn Recorder>) {
+    *RECORDER.write().unwrap() = Some(recorder);
+}
+
+/// Remove the process-wide recorder, disabling metrics.
+pub fn clear_recorder() {
+    *RECORDER.write().unwrap() = None;
+}
+
+/// Get the process-wide recorder, if any.
+pub fn recorder() -> Option<Arc<dyn Recorder>> {
+    RECORDER.read().unwrap().clone()
+}
+
+type Series = (&'static str, Vec<(String, String)>);
+
+fn series(name: &'static str, labels: &Labels<'_>) -> Series {
+    let mut labels: Vec<_> = labels
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+    labels.sort();
+    (name, labels)
+}
+
+/// Whether the labels of `series` contain all of `labels`.
+fn matches(series: &Series, name: &str, labels: &Labels<'_>) -> bool {
+    series.0 == name
+        && labels
+            .iter()
+            .all(|(key, value)| series.1.iter().any(|(k, v)| k == key && v == value))
+}
+
+/// A [Recorder] keeping metrics in memory.
+///
+/// Metrics are queried by name and by a subset of their labels, and are
+/// aggregated over all the series matching these labels.
+#[derive(Debug, Default)]
+pub struct InMemoryRecorder {
+    counters: Mutex<HashMap<Series, u64>>,
+    gauges: Mutex<HashMap<Series, i64>>,
+    histograms: Mutex<HashMap<Series, Vec<f64>>>,
+}
+
+impl InMemoryRecorder {
+    /// Sum of the counters matching `labels`.
+    pub fn counter(&self, name: &str, labels: &Labels<'_>) -> u64 {
+        let counters = self.counters.lock().unwrap();
+        counters
+            .iter()
+            .filter(|(series, _)| matches(series, name, labels))
+            .map(|(_, value)| value)
+            .sum()
+    }
+
+    /// Sum of the gauges matching `labels`.
+    pub fn gauge(&self, name: &str, labels: &Labels<'_>) -> i64 {
+        let gauges = self.gauges.lock().unwrap();
+        gauges
+            .iter()
+            .filter(|(series, _)| matches(series, name, labels))
+            .map(|(_, value)| value)
+            .sum()
+    }
+
+    /// Observations of the histograms matching `labels`.
+    pub fn histogram(&self, name: &str, labels: &Labels<'_>) -> Vec<f64> {
+        let histograms = self.histograms.lock().unwrap();
+        histograms
+            .iter()
+            .filter(|(series, _)| matches(series, name, labels))
+            .flat_map(|(_, values)| values.iter().copied())
+            .collect()
+    }
+
+    /// Remove all metrics.
+    pub fn clear(&self) {
+        self.counters.lock().unwrap().clear();
+        self.gauges.lock().unwrap().clear();
+        self.histograms.lock().unwrap().clear();
+    }
+}
+
+impl Recorder for InMemoryRecorder {
+    fn increment_counter(&self, name: &'static str, labels: &Labels<'_>, value: u64) {
+        let mut counters = self.counters.lock().unwrap();
+        *counters.entry(series(name, labels)).or_default() += value;
+    }
+
+    fn update_gauge(&self, name: &'static str, labels: &Labels<'_>, delta: i64) {
+        let mut gauges = self.gauges.lock().unwrap();
+        *gauges.entry(series(name, labels)).or_default() += delta;
+    }
+
+    fn record_histogram(&self, name: &'static str, labels: &Labels<'_>, value: f64) {
+        let mut histograms = self.histograms.lock().unwrap();
+        histograms
+            .entry(series(name, labels))
+            .or_default()
+            .push(value);
+    }
+}
+
+/// Record the duration and the outcome of a call.
+pub(crate) fn record_call(
+    recorder: Option<&dyn Recorder>,
+    method: &str,
+    driver: &str,
+    result: std::result::Result<(), &Error>,
+    elapsed: Duration,
+) {
+    let Some(recorder) = recorder else {
+        return;
+    };
+    let status = match result {
+        Ok(()) => "Ok".to_string(),
+        Err(error) => format!("{:?}", error.status),
+    };
+    let labels = [("method", method), ("driver", driver), ("status", &status)];
+    recorder.record_histogram(CALLS_DURATION, &labels, elapsed.as_secs_f64());
+    if result.is_err() {
+        recorder.increment_counter(CALLS_ERRORS, &labels, 1);
+    }
+}
+
+/// Add `delta` to the number of open connections of a database.
+#[cfg_attr(not(feature = "driver_manager"), allow(dead_code))]
+pub(crate) fn record_connections(driver: &str, database: u64, delta: i64) {
+    if let Some(recorder) = recorder() {
+        let database = database.to_string();
+        let labels = [("driver", driver), ("database", database.as_str())];
+        recorder.update_gauge(CONNECTIONS_OPEN, &labels, delta);
+    }
+}
+
+/// Increment a counter of the prepared statement caches.
+pub(crate) fn record_statement_cache(name: &'static str) {
+    if let Some(recorder) = recorder() {
+        recorder.increment_counter(name, &[], 1);
+    }
+}
+
+/// Direction of the data flowing through a [MeteredReader].
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Flow {
+    /// A result set read by the application.
+    Stream,
+    /// A bound stream consumed by the driver.
+    Ingest,
+}
+
+impl Flow {
+    fn names(self) -> [&'static str; 4] {
+        match self {
+            Flow::Stream => [STREAM_ROWS, STREAM_BATCHES, STREAM_BYTES, STREAM_DURATION],
+            Flow::Ingest => [INGEST_ROWS, INGEST_BATCHES, INGEST_BYTES, INGEST_DURATION],
+        }
+    }
+}
+
+/// A reader reporting the data read from it.
+///
+/// Counters are updated for every batch, and the duration is recorded once
+/// the reader is exhausted, fails or is dropped.
+pub(crate) struct MeteredReader<R> {
+    inner: R,
+    recorder: Option<Arc<dyn Recorder>>,
+    flow: Flow,
+    method: String,
+    driver: String,
+    start: Option<Instant>,
+    done: bool,
+}
+
+impl<R> MeteredReader<R> {
+    pub(crate) fn new(
+        inner: R,
+        recorder: Option<Arc<dyn Recorder>>,
+        flow: Flow,
+        method: &str,
+        driver: &str,
+    ) -> Self {
+        Self {
+            inner,
+            recorder,
+            flow,
+            method: method.to_string(),
+            driver: driver.to_string(),
+            start: None,
+            done: false,
+        }
+    }
+
+    fn labels(&self) -> [(&'static str, &str); 2] {
+        [("method", &self.method), ("driver", &self.driver)]
+    }
+
+    fn finish(&mut self) {
+        if self.done {
+            return;
+        }
+        self.done = true;
+        if let (Some(recorder), Some(start)) = (&self.recorder, self.start) {
+            let [.., duration] = self.flow.names();
+            recorder.record_histogram(duration, &self.labels(), start.elapsed().as_secs_f64());
+        }
+    }
+}
+
+impl<R: RecordBatchReader> Iterator for MeteredReader<R> {
+    type Item = std::result::Result<RecordBatch, ArrowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.start.get_or_insert_with(Instant::now);
+        let batch = self.inner.next();
+        match &batch {
+            Some(Ok(batch)) => {
+                if let Some(recorder) = &self.recorder {
+                    let [rows, batches, bytes, _] = self.flow.names();
+                    let labels = self.labels();
+                    recorder.increment_counter(rows, &labels, batch.num_rows() as u64);
+                    recorder.increment_counter(batches, &labels, 1);
+                    let size = batch.get_array_memory_size() as u64;
+                    recorder.increment_counter(bytes, &labels, size);
+                }
+            }
+            Some(Err(_)) | None => self.finish(),
+        }
+        batch
+    }
+}
+
+impl<R: RecordBatchReader> RecordBatchReader for MeteredReader<R> {
+    fn schema(&self) -> SchemaRef {
+        self.inner.schema()
+    }
+}
+
+impl<R> Drop for MeteredReader<R> {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+/// A [Layer] reporting the metrics of calls, result sets and bound streams.
+///
+/// Connections are not counted, as closing them cannot be observed by a
+/// layer. The `driver` label is set to the name given at construction,
+/// empty by default.
+#[derive(Default, Clone)]
+pub struct MetricsLayer {
+    driver: String,
+    recorder: Option<Arc<dyn Recorder>>,
+}
+
+impl MetricsLayer {
+    /// Report metrics with the given `driver` label to the process-wide recorder.
+    pub fn new(driver: impl Into<String>) -> Self {
+        Self {
+            driver: driver.into(),
+            recorder: None,
+        }
+    }
+
+    /// Report metrics to `recorder` instead of the process-wide recorder.
+    pub fn with_recorder(mut self, recorder: Arc<dyn Recorder>) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    fn recorder(&self) -> Option<Arc<dyn Recorder>> {
+        self.recorder.clone().or_else(recorder)
+    }
+}
+
+impl std::fmt::Debug for MetricsLayer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MetricsLayer")
+            .field("driver", &self.driver)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Layer for MetricsLayer {
+    fn after(&self, call: &Call<'_>, result: std::result::Result<(), &Error>, elapsed: Duration) {
+        let recorder = self.recorder();
+        let method = call.method.name();
+        record_call(recorder.as_deref(), method, &self.driver, result, elapsed);
+    }
+
+    fn wrap_reader<R: RecordBatchReader + Send>(
+        &self,
+        call: &Call<'_>,
+        reader: R,
+    ) -> impl RecordBatchReader + Send + use<R> {
+        let method = call.method.name();
+        MeteredReader::new(reader, self.recorder(), Flow::Stream, method, &self.driver)
+    }
+
+    fn wrap_bind_stream(
+        &self,
+        call: &Call<'_>,
+        reader: Box<dyn RecordBatchReader + Send>,
+    ) -> Box<dyn RecordBatchReader + Send> {
+        let method = call.method.name();
+        let reader =
+            MeteredReader::new(reader, self.recorder(), Flow::Ingest, method, &self.driver);
+        Box::new(reader)
+    }
+}
@@ -1,10 +1,18 @@
 //! Various option and configuration types.
+//!
+//! Canonical option keys are described by an [OptionSpec] declaring the type
+//! of their value, the values they accept and when they can be set. The
+//! [TypedOptionable] extension trait uses it to set and get options as Rust
+//! values, and the driver manager and exporter use it to convert values set
+//! with another type, following [OptionValue::convert].
 
 use std::os::raw::c_int;
+use std::str::FromStr;
 
 use crate::{
-    error::{Error, Status},
+    error::{self, Error, Status},
     ffi::constants,
+    secret, Optionable,
 };
 
 /// Option value.
@@ -113,7 +121,7 @@ impl TryFrom<c_int> for AdbcVersion {
 }
 
 /// Info codes for database/driver metadata.
-#[derive(Debug, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[non_exhaustive]
 pub enum InfoCode {
     /// The database vendor/product name (type: utf8).
@@ -122,6 +130,18 @@ pub enum InfoCode {
     VendorVersion,
     /// The database vendor/product Arrow library version (type: utf8).
     VendorArrowVersion,
+    /// Whether the database supports SQL queries (type: bool).
+    ///
+    /// # Since
+    ///
+    /// ADBC API revision 1.1.0
+    VendorSql,
+    /// Whether the database supports Substrait queries (type: bool).
+    ///
+    /// # Since
+    ///
+    /// ADBC API revision 1.1.0
+    VendorSubstrait,
     /// The driver name (type: utf8).
     DriverName,
     /// The driver version (type: utf8).
@@ -143,6 +163,8 @@ impl From<&InfoCode> for u32 {
             InfoCode::VendorName => constants::ADBC_INFO_VENDOR_NAME,
             InfoCode::VendorVersion => constants::ADBC_INFO_VENDOR_VERSION,
             InfoCode::VendorArrowVersion => constants::ADBC_INFO_VENDOR_ARROW_VERSION,
+            InfoCode::VendorSql => constants::ADBC_INFO_VENDOR_SQL,
+            InfoCode::VendorSubstrait => constants::ADBC_INFO_VENDOR_SUBSTRAIT,
             InfoCode::DriverName => constants::ADBC_INFO_DRIVER_NAME,
             InfoCode::DriverVersion => constants::ADBC_INFO_DRIVER_VERSION,
             InfoCode::DriverArrowVersion => constants::ADBC_INFO_DRIVER_ARROW_VERSION,
@@ -159,6 +181,8 @@ impl TryFrom<u32> for InfoCode {
             constants::ADBC_INFO_VENDOR_NAME => Ok(InfoCode::VendorName),
             constants::ADBC_INFO_VENDOR_VERSION => Ok(InfoCode::VendorVersion),
             constants::ADBC_INFO_VENDOR_ARROW_VERSION => Ok(InfoCode::VendorArrowVersion),
+            constants::ADBC_INFO_VENDOR_SQL => Ok(InfoCode::VendorSql),
+            constants::ADBC_INFO_VENDOR_SUBSTRAIT => Ok(InfoCode::VendorSubstrait),
             constants::ADBC_INFO_DRIVER_NAME => Ok(InfoCode::DriverName),
             constants::ADBC_INFO_DRIVER_VERSION => Ok(InfoCode::DriverVersion),
             constants::ADBC_INFO_DRIVER_ARROW_VERSION => Ok(InfoCode::DriverArrowVersion),
@@ -172,7 +196,7 @@ impl TryFrom<u32> for InfoCode {
 }
 
 /// Depth parameter for [get_objects][crate::Connection::get_objects] method.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 #[non_exhaustive]
 pub enum ObjectDepth {
     /// Catalogs, schemas, tables, and columns.
@@ -320,6 +344,26 @@ pub enum OptionStatement {
     IngestMode,
     /// The name of the target table for a bulk insert.
     TargetTable,
+    /// The catalog of the target table for a bulk insert.
+    ///
+    /// # Since
+    ///
+    /// ADBC API revision 1.1.0
+    TargetCatalog,
+    /// The database schema of the target table for a bulk insert.
+    ///
+    /// # Since
+    ///
+    /// ADBC API revision 1.1.0
+    TargetDbSchema,
+    /// Whether a bulk insert targets a temporary table. Temporary tables
+    /// live in their own namespace, so it's an error to also set
+    /// [OptionStatement::TargetCatalog] or [OptionStatement::TargetDbSchema].
+    ///
+    /// # Since
+    ///
+    /// ADBC API revision 1.1.0
+    Temporary,
     /// Whether query execution is nonblocking. By default, execution is blocking.
     ///
     /// When enabled, [execute_partitions][crate::Statement::execute_partitions]
@@ -368,6 +412,9 @@ impl AsRef<str> for OptionStatement {
         match self {
             Self::IngestMode => constants::ADBC_INGEST_OPTION_MODE,
             Self::TargetTable => constants::ADBC_INGEST_OPTION_TARGET_TABLE,
+            Self::TargetCatalog => constants::ADBC_INGEST_OPTION_TARGET_CATALOG,
+            Self::TargetDbSchema => constants::ADBC_INGEST_OPTION_TARGET_DB_SCHEMA,
+            Self::Temporary => constants::ADBC_INGEST_OPTION_TEMPORARY,
             Self::Incremental => constants::ADBC_STATEMENT_OPTION_INCREMENTAL,
             Self::Progress => constants::ADBC_STATEMENT_OPTION_PROGRESS,
             Self::MaxProgress => constants::ADBC_STATEMENT_OPTION_MAX_PROGRESS,
@@ -381,6 +428,9 @@ impl From<&str> for OptionStatement {
         match value {
             constants::ADBC_INGEST_OPTION_MODE => Self::IngestMode,
             constants::ADBC_INGEST_OPTION_TARGET_TABLE => Self::TargetTable,
+            constants::ADBC_INGEST_OPTION_TARGET_CATALOG => Self::TargetCatalog,
+            constants::ADBC_INGEST_OPTION_TARGET_DB_SCHEMA => Self::TargetDbSchema,
+            constants::ADBC_INGEST_OPTION_TEMPORARY => Self::Temporary,
             constants::ADBC_STATEMENT_OPTION_INCREMENTAL => Self::Incremental,
             constants::ADBC_STATEMENT_OPTION_PROGRESS => Self::Progress,
             constants::ADBC_STATEMENT_OPTION_MAX_PROGRESS => Self::MaxProgress,
@@ -390,7 +440,7 @@ impl From<&str> for OptionStatement {
 }
 
 /// Isolation level value for key [OptionConnection::IsolationLevel].
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[non_exhaustive]
 pub enum IsolationLevel {
     /// Use database or driver default isolation level.
@@ -465,8 +515,44 @@ impl From<IsolationLevel> for OptionValue {
     }
 }
 
+impl TryFrom<&str> for IsolationLevel {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            constants::ADBC_OPTION_ISOLATION_LEVEL_DEFAULT => Ok(Self::Default),
+            constants::ADBC_OPTION_ISOLATION_LEVEL_READ_UNCOMMITTED => Ok(Self::ReadUncommitted),
+            constants::ADBC_OPTION_ISOLATION_LEVEL_READ_COMMITTED => Ok(Self::ReadCommitted),
+            constants::ADBC_OPTION_ISOLATION_LEVEL_REPEATABLE_READ => Ok(Self::RepeatableRead),
+            constants::ADBC_OPTION_ISOLATION_LEVEL_SNAPSHOT => Ok(Self::Snapshot),
+            constants::ADBC_OPTION_ISOLATION_LEVEL_SERIALIZABLE => Ok(Self::Serializable),
+            constants::ADBC_OPTION_ISOLATION_LEVEL_LINEARIZABLE => Ok(Self::Linearizable),
+            value => Err(Error::with_message_and_status(
+                format!("Unknown isolation level: {value}"),
+                Status::InvalidArguments,
+            )),
+        }
+    }
+}
+
+impl FromStr for IsolationLevel {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.try_into()
+    }
+}
+
+impl TryFrom<OptionValue> for IsolationLevel {
+    type Error = Error;
+
+    fn try_from(value: OptionValue) -> Result<Self, Self::Error> {
+        String::from_value(value)?.parse()
+    }
+}
+
 /// Ingestion mode value for key [OptionStatement::IngestMode].
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[non_exhaustive]
 pub enum IngestMode {
     /// Create the table and insert data; error if the table exists.
@@ -507,3 +593,536 @@ impl From<IngestMode> for OptionValue {
         Self::String(value.into())
     }
 }
+
+impl TryFrom<&str> for IngestMode {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            constants::ADBC_INGEST_OPTION_MODE_CREATE => Ok(Self::Create),
+            constants::ADBC_INGEST_OPTION_MODE_APPEND => Ok(Self::Append),
+            constants::ADBC_INGEST_OPTION_MODE_REPLACE => Ok(Self::Replace),
+            constants::ADBC_INGEST_OPTION_MODE_CREATE_APPEND => Ok(Self::CreateAppend),
+            value => Err(Error::with_message_and_status(
+                format!("Unknown ingest mode: {value}"),
+                Status::InvalidArguments,
+            )),
+        }
+    }
+}
+
+impl FromStr for IngestMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.try_into()
+    }
+}
+
+impl TryFrom<OptionValue> for IngestMode {
+    type Error = Error;
+
+    fn try_from(value: OptionValue) -> Result<Self, Self::Error> {
+        String::from_value(value)?.parse()
+    }
+}
+
+/// Type of the value expected for an option key.
+///
+/// Booleans have no [OptionValue] variant of their own and are stored as the
+/// strings `"true"` and `"false"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OptionType {
+    String,
+    Bytes,
+    Int,
+    Double,
+    Bool,
+}
+
+/// When an option can be set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OptionAccess {
+    /// The option can be set and read at any time.
+    ReadWrite,
+    /// The option is reported by the driver and cannot be set.
+    ReadOnly,
+    /// The option can only be set before the object is initialized, e.g. with
+    /// [Driver::new_database_with_opts][crate::Driver::new_database_with_opts].
+    PreInit,
+}
+
+/// Descriptor of a canonical option key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct OptionSpec {
+    /// The option key.
+    pub key: &'static str,
+    /// The type of the option's value.
+    pub value_type: OptionType,
+    /// The values accepted by the option, any value is accepted if empty.
+    pub allowed: &'static [&'static str],
+    /// When the option can be set.
+    pub access: OptionAccess,
+}
+
+const ISOLATION_LEVELS: &[&str] = &[
+    constants::ADBC_OPTION_ISOLATION_LEVEL_DEFAULT,
+    constants::ADBC_OPTION_ISOLATION_LEVEL_READ_UNCOMMITTED,
+    constants::ADBC_OPTION_ISOLATION_LEVEL_READ_COMMITTED,
+    constants::ADBC_OPTION_ISOLATION_LEVEL_REPEATABLE_READ,
+    constants::ADBC_OPTION_ISOLATION_LEVEL_SNAPSHOT,
+    constants::ADBC_OPTION_ISOLATION_LEVEL_SERIALIZABLE,
+    constants::ADBC_OPTION_ISOLATION_LEVEL_LINEARIZABLE,
+];
+
+const INGEST_MODES: &[&str] = &[
+    constants::ADBC_INGEST_OPTION_MODE_CREATE,
+    constants::ADBC_INGEST_OPTION_MODE_APPEND,
+    constants::ADBC_INGEST_OPTION_MODE_REPLACE,
+    constants::ADBC_INGEST_OPTION_MODE_CREATE_APPEND,
+];
+
+const fn spec(
+    key: &'static str,
+    value_type: OptionType,
+    allowed: &'static [&'static str],
+    access: OptionAccess,
+) -> OptionSpec {
+    OptionSpec {
+        key,
+        value_type,
+        allowed,
+        access,
+    }
+}
+
+const SPECS: &[OptionSpec] = &[
+    // Database
+    spec(
+        constants::ADBC_OPTION_URI,
+        OptionType::String,
+        &[],
+        OptionAccess::PreInit,
+    ),
+    spec(
+        constants::ADBC_OPTION_USERNAME,
+        OptionType::String,
+        &[],
+        OptionAccess::PreInit,
+    ),
+    spec(
+        constants::ADBC_OPTION_PASSWORD,
+        OptionType::String,
+        &[],
+        OptionAccess::PreInit,
+    ),
+    // Connection
+    spec(
+        constants::ADBC_CONNECTION_OPTION_AUTOCOMMIT,
+        OptionType::Bool,
+        &[],
+        OptionAccess::ReadWrite,
+    ),
+    spec(
+        constants::ADBC_CONNECTION_OPTION_READ_ONLY,
+        OptionType::Bool,
+        &[],
+        OptionAccess::ReadWrite,
+    ),
+    spec(
+        constants::ADBC_CONNECTION_OPTION_CURRENT_CATALOG,
+        OptionType::String,
+        &[],
+        OptionAccess::ReadWrite,
+    ),
+    spec(
+        constants::ADBC_CONNECTION_OPTION_CURRENT_DB_SCHEMA,
+        OptionType::String,
+        &[],
+        OptionAccess::ReadWrite,
+    ),
+    spec(
+        constants::ADBC_CONNECTION_OPTION_ISOLATION_LEVEL,
+        OptionType::String,
+        ISOLATION_LEVELS,
+        OptionAccess::ReadWrite,
+    ),
+    // Statement
+    spec(
+        constants::ADBC_INGEST_OPTION_MODE,
+        OptionType::String,
+        INGEST_MODES,
+        OptionAccess::ReadWrite,
+    ),
+    spec(
+        constants::ADBC_INGEST_OPTION_TARGET_TABLE,
+        OptionType::String,
+        &[],
+        OptionAccess::ReadWrite,
+    ),
+    spec(
+        constants::ADBC_INGEST_OPTION_TARGET_CATALOG,
+        OptionType::String,
+        &[],
+        OptionAccess::ReadWrite,
+    ),
+    spec(
+        constants::ADBC_INGEST_OPTION_TARGET_DB_SCHEMA,
+        OptionType::String,
+        &[],
+        OptionAccess::ReadWrite,
+    ),
+    spec(
+        constants::ADBC_INGEST_OPTION_TEMPORARY,
+        OptionType::Bool,
+        &[],
+        OptionAccess::ReadWrite,
+    ),
+    spec(
+        constants::ADBC_STATEMENT_OPTION_INCREMENTAL,
+        OptionType::Bool,
+        &[],
+        OptionAccess::ReadWrite,
+    ),
+    spec(
+        constants::ADBC_STATEMENT_OPTION_PROGRESS,
+        OptionType::Double,
+        &[],
+        OptionAccess::ReadOnly,
+    ),
+    spec(
+        constants::ADBC_STATEMENT_OPTION_MAX_PROGRESS,
+        OptionType::Double,
+        &[],
+        OptionAccess::ReadOnly,
+    ),
+];
+
+impl OptionSpec {
+    /// Get the descriptor of a canonical option key, driver-specific keys
+    /// have none.
+    pub fn lookup(key: &str) -> Option<&'static OptionSpec> {
+        SPECS.iter().find(|spec| spec.key == key)
+    }
+
+    /// Convert `value` to the type of the option and check that it is one of
+    /// the allowed values.
+    ///
+    /// The access of the option is not checked.
+    pub fn convert(&self, value: OptionValue) -> error::Result<OptionValue> {
+        let value = value.convert(self.value_type).map_err(|error| {
+            if secret::is_secret(self.key) {
+                Error::with_message_and_status(
+                    format!(
+                        "Cannot convert value of option {:?} to {:?}",
+                        self.key, self.value_type
+                    ),
+                    error.status,
+                )
+            } else {
+                error
+            }
+        })?;
+        if let OptionValue::String(string) = &value {
+            if !self.allowed.is_empty() && !self.allowed.contains(&string.as_str()) {
+                return Err(Error::with_message_and_status(
+                    format!("Invalid value for option {:?}: {string:?}", self.key),
+                    Status::InvalidArguments,
+                ));
+            }
+        }
+        Ok(value)
+    }
+}
+
+fn conversion_error(value: &OptionValue, to: OptionType) -> Error {
+    Error::with_message_and_status(
+        format!(
+            "Cannot convert {} option value {value:?} to {to:?}",
+            value.get_type()
+        ),
+        Status::InvalidArguments,
+    )
+}
+
+impl OptionValue {
+    /// Convert the value to type `to`.
+    ///
+    /// Strings are parsed into numbers and booleans, numbers and booleans are
+    /// formatted into strings, integers and doubles are converted into each
+    /// other when no precision is lost, the integers 0 and 1 are converted
+    /// into booleans and UTF-8 bytes into strings.
+    pub fn convert(self, to: OptionType) -> error::Result<Self> {
+        let converted = match (&self, to) {
+            (Self::String(_), OptionType::String)
+            | (Self::Bytes(_), OptionType::Bytes)
+            | (Self::Int(_), OptionType::Int)
+            | (Self::Double(_), OptionType::Double) => Some(self.clone()),
+
+            (Self::String(value), OptionType::Bool) => match value.as_str() {
+                "true" | "false" => Some(self.clone()),
+                _ => None,
+            },
+            (Self::Int(value), OptionType::Bool) => match value {
+                0 => Some(Self::String("false".into())),
+                1 => Some(Self::String("true".into())),
+                _ => None,
+            },
+
+            (Self::String(value), OptionType::Int) => match value.as_str() {
+                "true" => Some(Self::Int(1)),
+                "false" => Some(Self::Int(0)),
+                value => value.parse().ok().map(Self::Int),
+            },
+            (Self::Double(value), OptionType::Int) => {
+                let int = *value as i64;
+                (int as f64 == *value).then_some(Self::Int(int))
+            }
+
+            (Self::String(value), OptionType::Double) => value.parse().ok().map(Self::Double),
+            (Self::Int(value), OptionType::Double) => {
+                let double = *value as f64;
+                (double as i64 == *value).then_some(Self::Double(double))
+            }
+
+            (Self::Int(value), OptionType::String) => Some(Self::String(value.to_string())),
+            (Self::Double(value), OptionType::String) => Some(Self::String(value.to_string())),
+            (Self::Bytes(value), OptionType::String) => {
+                String::from_utf8(value.clone()).ok().map(Self::String)
+            }
+            (Self::String(value), OptionType::Bytes) => Some(Self::Bytes(value.clone().into())),
+
+            _ => None,
+        };
+        converted.ok_or_else(|| conversion_error(&self, to))
+    }
+}
+
+/// Rust type of an option value.
+pub trait TypedValue: Sized {
+    /// The option type the Rust type is mapped to.
+    const TYPE: OptionType;
+
+    /// Convert the Rust value into an option value.
+    fn into_value(self) -> OptionValue;
+
+    /// Convert an option value, of any type, into the Rust value.
+    fn from_value(value: OptionValue) -> error::Result<Self>;
+}
+
+macro_rules! typed_value {
+    ($rust:ty, $variant:ident) => {
+        impl TypedValue for $rust {
+            const TYPE: OptionType = OptionType::$variant;
+
+            fn into_value(self) -> OptionValue {
+                OptionValue::$variant(self)
+            }
+
+            fn from_value(value: OptionValue) -> error::Result<Self> {
+                match value.convert(Self::TYPE)? {
+                    OptionValue::$variant(value) => Ok(value),
+                    _ => unreachable!("Broken conversion to {:?}", Self::TYPE),
+                }
+            }
+        }
+    };
+}
+
+typed_value!(String, String);
+typed_value!(Vec<u8>, Bytes);
+typed_value!(i64, Int);
+typed_value!(f64, Double);
+
+impl TypedValue for IsolationLevel {
+    const TYPE: OptionType = OptionType::String;
+
+    fn into_value(self) -> OptionValue {
+        self.into()
+    }
+
+    fn from_value(value: OptionValue) -> error::Result<Self> {
+        value.try_into()
+    }
+}
+
+impl TypedValue for IngestMode {
+    const TYPE: OptionType = OptionType::String;
+
+    fn into_value(self) -> OptionValue {
+        self.into()
+    }
+
+    fn from_value(value: OptionValue) -> error::Result<Self> {
+        value.try_into()
+    }
+}
+
+impl TypedValue for bool {
+    const TYPE: OptionType = OptionType::Bool;
+
+    fn into_value(self) -> OptionValue {
+        OptionValue::String(self.to_string())
+    }
+
+    fn from_value(value: OptionValue) -> error::Result<Self> {
+        match value.convert(Self::TYPE)? {
+            OptionValue::String(value) => Ok(value == "true"),
+            _ => unreachable!("Broken conversion to {:?}", Self::TYPE),
+        }
+    }
+}
+
+/// Read `key` from `object` with the getter of type `value_type`.
+fn get_option<O: Optionable + ?Sized>(
+    object: &O,
+    key: O::Option,
+    value_type: OptionType,
+) -> error::Result<OptionValue> {
+    Ok(match value_type {
+        OptionType::String | OptionType::Bool => object.get_option_string(key)?.into(),
+        OptionType::Bytes => object.get_option_bytes(key)?.into(),
+        OptionType::Int => object.get_option_int(key)?.into(),
+        OptionType::Double => object.get_option_double(key)?.into(),
+    })
+}
+
+/// Typed access to the options of an object, validated against the
+/// [OptionSpec] of canonical keys.
+///
+/// Implemented for every [Optionable].
+pub trait TypedOptionable: Optionable {
+    /// Set a post-init option, converting `value` to the type of the option.
+    ///
+    /// Fails with [Status::InvalidArguments] if the value cannot be converted,
+    /// is not allowed, or if the option is read-only, and with
+    /// [Status::InvalidState] if the option can only be set before
+    /// initialization.
+    fn set_typed<T: TypedValue>(&mut self, key: Self::Option, value: T) -> error::Result<()> {
+        let mut value = value.into_value();
+        if let Some(spec) = OptionSpec::lookup(key.as_ref()) {
+            match spec.access {
+                OptionAccess::ReadWrite => {}
+                OptionAccess::ReadOnly => {
+                    return Err(Error::with_message_and_status(
+                        format!("Option {:?} is read-only", spec.key),
+                        Status::InvalidArguments,
+                    ))
+                }
+                OptionAccess::PreInit => {
+                    return Err(Error::with_message_and_status(
+                        format!(
+                            "Option {:?} can only be set before initialization",
+                            spec.key
+                        ),
+                        Status::InvalidState,
+                    ))
+                }
+            }
+            value = spec.convert(value)?;
+        }
+        self.set_option(key, value)
+    }
+
+    /// Get an option value, read with the type of the option and converted
+    /// to `T`.
+    fn get_typed<T: TypedValue>(&self, key: Self::Option) -> error::Result<T> {
+        let value_type = OptionSpec::lookup(key.as_ref()).map_or(T::TYPE, |spec| spec.value_type);
+        T::from_value(get_option(self, key, value_type)?)
+    }
+}
+
+impl<O: Optionable + ?Sized> TypedOptionable for O {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert() {
+        let convert = |value: OptionValue, to| value.convert(to).ok();
+        assert!(matches!(
+            convert(1.into(), OptionType::Bool),
+            Some(OptionValue::String(v)) if v == "true"
+        ));
+        assert!(convert(2.into(), OptionType::Bool).is_none());
+        assert!(convert("yes".into(), OptionType::Bool).is_none());
+        assert!(matches!(
+            convert("false".into(), OptionType::Int),
+            Some(OptionValue::Int(0))
+        ));
+        assert!(matches!(
+            convert("-7".into(), OptionType::Int),
+            Some(OptionValue::Int(-7))
+        ));
+        assert!(matches!(
+            convert(2.0.into(), OptionType::Int),
+            Some(OptionValue::Int(2))
+        ));
+        assert!(convert(2.5.into(), OptionType::Int).is_none());
+        assert!(matches!(
+            convert("2.5".into(), OptionType::Double),
+            Some(OptionValue::Double(v)) if v == 2.5
+        ));
+        assert!(matches!(
+            convert(b"abc".into(), OptionType::String),
+            Some(OptionValue::String(v)) if v == "abc"
+        ));
+        assert!(convert(b"\xff".into(), OptionType::String).is_none());
+        assert!(convert(1.into(), OptionType::Bytes).is_none());
+    }
+
+    #[test]
+    fn test_parse() {
+        let levels = [
+            IsolationLevel::Default,
+            IsolationLevel::ReadUncommitted,
+            IsolationLevel::ReadCommitted,
+            IsolationLevel::RepeatableRead,
+            IsolationLevel::Snapshot,
+            IsolationLevel::Serializable,
+            IsolationLevel::Linearizable,
+        ];
+        for level in levels {
+            assert_eq!(
+                String::from(level).parse::<IsolationLevel>().unwrap(),
+                level
+            );
+        }
+        let modes = [
+            IngestMode::Create,
+            IngestMode::Append,
+            IngestMode::Replace,
+            IngestMode::CreateAppend,
+        ];
+        for mode in modes {
+            assert_eq!(IngestMode::try_from(OptionValue::from(mode)).unwrap(), mode);
+        }
+        let error = "adbc.ingest.mode.merge".parse::<IngestMode>().unwrap_err();
+        assert_eq!(error.status, Status::InvalidArguments);
+        assert!(IsolationLevel::try_from(OptionValue::from(1)).is_err());
+    }
+
+    #[test]
+    fn test_spec() {
+        let spec = OptionSpec::lookup(constants::ADBC_INGEST_OPTION_MODE).unwrap();
+        assert!(spec.convert(IngestMode::Replace.into()).is_ok());
+        assert!(spec.convert("adbc.ingest.mode.merge".into()).is_err());
+        assert!(OptionSpec::lookup("driver.specific").is_none());
+    }
+
+    #[test]
+    fn test_statement_keys() {
+        let keys = [
+            OptionStatement::TargetTable,
+            OptionStatement::TargetCatalog,
+            OptionStatement::TargetDbSchema,
+            OptionStatement::Temporary,
+        ];
+        for key in keys {
+            assert_eq!(OptionStatement::from(key.as_ref()), key);
+            assert!(OptionSpec::lookup(key.as_ref()).is_some());
+        }
+    }
+}
@@ -0,0 +1,445 @@
+//! Executing queries incrementally and reading the partitions of their result
+//! sets concurrently.
+//!
+//! # Incremental execution
+//!
+//! With [OptionStatement::Incremental], drivers return partitions from
+//! [Statement::execute_partitions] as soon as they are available, until an
+//! empty set of partitions marks the end of the result set. An
+//! [IncrementalExecution] enables this mode and iterates over the partitions
+//! as they arrive, optionally reporting the [Progress] of the query after
+//! each of them.
+//!
+//! ```rust,no_run
+//! # use adbc_core::{partitions::IncrementalExecution, Statement};
+//! # fn run(statement: &mut impl Statement) -> adbc_core::error::Result<()> {
+//! let execution = IncrementalExecution::new(statement)?
+//!     .with_progress(|progress| println!("{:?}", progress.fraction()));
+//! for result in execution {
+//!     for partition in result?.partitions {
+//!         // Read partition with Connection::read_partition...
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Parallel reads
+//!
+//! [Statement::execute_partitions] returns opaque partitions which are
+//! otherwise read one by one with [Connection::read_partition]. A
+//! [ParallelReader] instead opens several connections from a [Database] and
+//! reads the partitions on as many threads, exposing them as a single
+//! [RecordBatchReader]:
+//! - Batches are returned in the order of the partitions, or as soon as they
+//!   are read with [ParallelOptions::with_ordered].
+//! - Threads stop reading while [ParallelOptions::with_buffer] batches are
+//!   waiting to be returned, per partition when ordered.
+//! - The first error stops all threads and is returned by the reader, which
+//!   then ends. Dropping the reader stops the threads as well.
+//!
+//! Dropping the reader waits for the threads: a thread stopped while reading
+//! a partition finishes the call in progress before closing its connection.
+//!
+//! ```rust,no_run
+//! # use adbc_core::{partitions::{ParallelOptions, ParallelReader}, Database, Statement};
+//! # fn run<D>(database: &mut D, statement: &mut impl Statement) -> adbc_core::error::Result<()>
+//! # where D: Database, D::ConnectionType: Send + 'static {
+//! let result = statement.execute_partitions()?;
+//! let options = ParallelOptions::new().with_connections(8).with_ordered(false);
+//! for batch in ParallelReader::new(database, result, options)? {
+//!     let batch = batch?;
+//!     // Process batch...
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Distributed reads
+//!
+//! A [PartitionEnvelope] describes a partition along with what is needed to
+//! read it from another process: the driver reading it, the database URI
+//! with its credentials removed, the ADBC version and the schema of the
+//! result set. Envelopes are serialized with [PartitionEnvelope::serialize],
+//! and workers read them with the driver they loaded, using
+//! [ManagedDriver::read_envelope][crate::driver_manager::ManagedDriver::read_envelope] or,
+//! for native drivers, [PartitionEnvelope::new_database] and
+//! [PartitionEnvelope::read]. Credentials are given to the workers with the
+//! database options, as they are never part of an envelope.
+//!
+//! Envelopes are gated behind the `ipc` feature flag.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use arrow::datatypes::SchemaRef;
+use arrow::error::ArrowError;
+use arrow::record_batch::{RecordBatch, RecordBatchReader};
+
+use crate::error::{Error, Result, Status};
+use crate::options::OptionStatement;
+use crate::{Connection, Database, PartitionedResult, Statement};
+
+#[cfg(feature = "ipc")]
+pub use crate::envelope::PartitionEnvelope;
+
+/// Options of a [ParallelReader].
+#[derive(Debug, Clone)]
+pub struct ParallelOptions {
+    connections: usize,
+    buffer: usize,
+    ordered: bool,
+}
+
+impl Default for ParallelOptions {
+    fn default() -> Self {
+        Self {
+            connections: 4,
+            buffer: 8,
+            ordered: true,
+        }
+    }
+}
+
+impl ParallelOptions {
+    /// The default options, reading up to 4 partitions at once, buffering up
+    /// to 8 batches and preserving the order of the partitions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open up to `connections` connections, each read by its own thread.
+    /// No more connections than partitions are opened.
+    pub fn with_connections(mut self, connections: usize) -> Self {
+        self.connections = connections.max(1);
+        self
+    }
+
+    /// Buffer up to `buffer` batches which have been read but not returned
+    /// yet, per partition when ordered.
+    pub fn with_buffer(mut self, buffer: usize) -> Self {
+        self.buffer = buffer.max(1);
+        self
+    }
+
+    /// Whether batches are returned in the order of the partitions, rather
+    /// than as soon as they are read.
+    pub fn with_ordered(mut self, ordered: bool) -> Self {
+        self.ordered = ordered;
+        self
+    }
+}
+
+/// State shared by a [ParallelReader] and its threads.
+#[derive(Debug)]
+struct Shared {
+    abort: AtomicBool,
+    error: Mutex<Option<ArrowError>>,
+    // Where to send the batches of each partition until it is read. Threads
+    // only hold a sender while sending, so that removing them all wakes the
+    // reader.
+    senders: Mutex<Vec<Option<SyncSender<RecordBatch>>>>,
+}
+
+impl Shared {
+    fn aborted(&self) -> bool {
+        self.abort.load(Ordering::SeqCst)
+    }
+
+    /// Record the first error and stop all threads.
+    fn fail(&self, error: ArrowError) {
+        self.error.lock().unwrap().get_or_insert(error);
+        self.abort.store(true, Ordering::SeqCst);
+        self.senders.lock().unwrap().clear();
+    }
+
+    fn sender(&self, partition: usize) -> Option<SyncSender<RecordBatch>> {
+        self.senders.lock().unwrap().get(partition)?.clone()
+    }
+
+    /// Release the sender of a partition which has been read.
+    fn close(&self, partition: usize) {
+        if let Some(sender) = self.senders.lock().unwrap().get_mut(partition) {
+            *sender = None;
+        }
+    }
+
+    fn take_error(&self) -> Option<ArrowError> {
+        self.error.lock().unwrap().take()
+    }
+}
+
+/// Fails the read if its thread panics, which would otherwise leave the
+/// reader waiting for the partition being read.
+struct PanicGuard<'a>(&'a Shared);
+
+impl Drop for PanicGuard<'_> {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            let error = Error::with_message_and_status(
+                "A thread reading partitions panicked",
+                Status::Internal,
+            );
+            self.0.fail(ArrowError::ExternalError(Box::new(error)));
+        }
+    }
+}
+
+/// A partition to read, along with its index in [Shared::senders].
+type Task = (Vec<u8>, usize);
+
+/// A [RecordBatchReader] reading the partitions of a [PartitionedResult] on
+/// several connections concurrently, see the [module][self] documentation.
+#[derive(Debug)]
+pub struct ParallelReader {
+    schema: SchemaRef,
+    // Channels are read in order and dropped once all their senders are gone.
+    receivers: VecDeque<Receiver<RecordBatch>>,
+    shared: Arc<Shared>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl ParallelReader {
+    /// Start reading the partitions of `result` on connections created from
+    /// `database`, which may be dropped once this returns.
+    pub fn new<D>(
+        database: &mut D,
+        result: PartitionedResult,
+        options: ParallelOptions,
+    ) -> Result<Self>
+    where
+        D: Database,
+        D::ConnectionType: Send + 'static,
+    {
+        let count = options.connections.min(result.partitions.len());
+        let connections = (0..count)
+            .map(|_| database.new_connection())
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut receivers = VecDeque::new();
+        let mut senders = Vec::new();
+        if options.ordered {
+            for _ in &result.partitions {
+                let (sender, receiver) = mpsc::sync_channel(options.buffer);
+                senders.push(Some(sender));
+                receivers.push_back(receiver);
+            }
+        } else {
+            let (sender, receiver) = mpsc::sync_channel(options.buffer);
+            senders.resize(result.partitions.len(), Some(sender));
+            receivers.push_back(receiver);
+        }
+        let tasks: VecDeque<_> = result.partitions.into_iter().zip(0..).collect();
+
+        let tasks = Arc::new(Mutex::new(tasks));
+        let shared = Arc::new(Shared {
+            abort: AtomicBool::new(false),
+            error: Mutex::new(None),
+            senders: Mutex::new(senders),
+        });
+        let workers = connections
+            .into_iter()
+            .map(|connection| {
+                let tasks = tasks.clone();
+                let shared = shared.clone();
+                thread::spawn(move || work(connection, &tasks, &shared))
+            })
+            .collect();
+
+        Ok(Self {
+            schema: Arc::new(result.schema),
+            receivers,
+            shared,
+            workers,
+        })
+    }
+
+    /// Stop the threads.
+    fn stop(&mut self) {
+        self.shared.abort.store(true, Ordering::SeqCst);
+        // Threads blocked on a full channel are released by dropping it.
+        self.receivers.clear();
+    }
+
+    /// End the reader, returning the error which stopped the threads if any.
+    fn finish(&mut self) -> Option<std::result::Result<RecordBatch, ArrowError>> {
+        let error = self.shared.take_error();
+        self.stop();
+        error.map(Err)
+    }
+}
+
+/// Read partitions on `connection` until there are none left or a thread
+/// failed.
+fn work<C: Connection>(connection: C, tasks: &Mutex<VecDeque<Task>>, shared: &Shared) {
+    let _guard = PanicGuard(shared);
+    while !shared.aborted() {
+        let Some((partition, index)) = tasks.lock().unwrap().pop_front() else {
+            return;
+        };
+        let reader = match connection.read_partition(partition) {
+            Ok(reader) => reader,
+            Err(error) => return shared.fail(ArrowError::ExternalError(Box::new(error))),
+        };
+        for batch in reader {
+            if shared.aborted() {
+                return;
+            }
+            match batch {
+                Ok(batch) => {
+                    let sent = shared.sender(index).map(|sender| sender.send(batch));
+                    if !matches!(sent, Some(Ok(()))) {
+                        // The reader was dropped or another thread failed.
+                        return;
+                    }
+                }
+                Err(error) => return shared.fail(error),
+            }
+        }
+        shared.close(index);
+    }
+}
+
+impl Iterator for ParallelReader {
+    type Item = std::result::Result<RecordBatch, ArrowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.shared.aborted() {
+                return self.finish();
+            }
+            let receiver = self.receivers.front()?;
+            match receiver.recv() {
+                Ok(batch) => return Some(Ok(batch)),
+                Err(_) => {
+                    // The partition is complete, unless it was interrupted by
+                    // a failure which is then returned on the next iteration.
+                    self.receivers.pop_front();
+                }
+            }
+        }
+    }
+}
+
+impl RecordBatchReader for ParallelReader {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+impl Drop for ParallelReader {
+    fn drop(&mut self) {
+        self.stop();
+        for worker in self.workers.drain(..) {
+            // Panics of the threads are not propagated.
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Progress of a query, as reported by the driver.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Progress {
+    /// Value of [OptionStatement::Progress], in units chosen by the driver.
+    pub progress: f64,
+    /// Value of [OptionStatement::MaxProgress] if it is known, i.e. if the
+    /// driver reports it and it is positive.
+    pub max_progress: Option<f64>,
+}
+
+impl Progress {
+    /// The fraction of the query which has been executed, between 0 and 1,
+    /// if the maximum progress is known.
+    pub fn fraction(&self) -> Option<f64> {
+        self.max_progress
+            .map(|max| (self.progress / max).clamp(0.0, 1.0))
+    }
+}
+
+/// An iterator over the partitions of a query executed incrementally, see the
+/// [module][self] documentation.
+///
+/// Each item is the result of a call to [Statement::execute_partitions]. The
+/// iterator ends after the first failure or once the driver returns an empty
+/// set of partitions. The statement is left in incremental mode.
+pub struct IncrementalExecution<'a, S> {
+    statement: &'a mut S,
+    callback: Option<Box<dyn FnMut(Progress) + 'a>>,
+    done: bool,
+}
+
+impl<'a, S: Statement> IncrementalExecution<'a, S> {
+    /// Enable [OptionStatement::Incremental] on `statement`, whose query is
+    /// executed when iterating.
+    pub fn new(statement: &'a mut S) -> Result<Self> {
+        statement.set_option(OptionStatement::Incremental, "true".into())?;
+        Ok(Self {
+            statement,
+            callback: None,
+            done: false,
+        })
+    }
+
+    /// Call `callback` with the progress of the query after each call to
+    /// [Statement::execute_partitions], unless the driver does not report
+    /// it.
+    pub fn with_progress(mut self, callback: impl FnMut(Progress) + 'a) -> Self {
+        self.callback = Some(Box::new(callback));
+        self
+    }
+
+    /// Poll the progress of the query.
+    pub fn progress(&self) -> Result<Progress> {
+        poll_progress(self.statement)
+    }
+}
+
+fn poll_progress(statement: &impl Statement) -> Result<Progress> {
+    let progress = statement.get_option_double(OptionStatement::Progress)?;
+    let max_progress = statement
+        .get_option_double(OptionStatement::MaxProgress)
+        .ok()
+        .filter(|max| *max > 0.0);
+    Ok(Progress {
+        progress,
+        max_progress,
+    })
+}
+
+impl<S: Statement> Iterator for IncrementalExecution<'_, S> {
+    type Item = Result<PartitionedResult>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = self.statement.execute_partitions();
+        if let Some(callback) = &mut self.callback {
+            if let Ok(progress) = poll_progress(self.statement) {
+                callback(progress);
+            }
+        }
+        match result {
+            Ok(result) if !result.partitions.is_empty() => Some(Ok(result)),
+            Ok(_) => {
+                self.done = true;
+                None
+            }
+            Err(error) => {
+                self.done = true;
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+impl<S> std::fmt::Debug for IncrementalExecution<'_, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IncrementalExecution")
+            .field("done", &self.done)
+            .finish_non_exhaustive()
+    }
+}
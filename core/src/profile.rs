@@ -0,0 +1,336 @@
+//! Named connection profiles loaded from configuration files.
+//!
+//! A profile names a driver along with the options of the database, the
+//! connection and the statements created from it, so that services refer to
+//! databases by name instead of hard-coding options. Profiles are read from
+//! TOML or YAML files, where option keys are the ADBC option names and values
+//! are strings, integers, floats or booleans:
+//!
+//! ```toml
+//! [profiles.warehouse]
+//! driver = "adbc_driver_postgresql"
+//! # Optional, default to the driver's `AdbcDriverInit` and to "1.1.0".
+//! entrypoint = "AdbcDriverInit"
+//! version = "1.1.0"
+//!
+//! [profiles.warehouse.database]
+//! uri = "postgresql://analytics@db.example.com/warehouse"
+//! password = "${WAREHOUSE_PASSWORD}"
+//!
+//! [profiles.warehouse.connection]
+//! "adbc.connection.autocommit" = false
+//! "adbc.connection.transaction.isolation_level" = "adbc.connection.transaction.isolation_level.serializable"
+//!
+//! [profiles.warehouse.statement]
+//! "adbc.ingest.mode" = "adbc.ingest.mode.create_append"
+//! ```
+//!
+//! `${VARIABLE}` in string values is replaced by the value of the environment
+//! variable, which keeps secrets out of the files, and `$$` by `$`.
+//!
+//! [open] looks for profiles in the file named by the `ADBC_PROFILES`
+//! environment variable, or else in `adbc.toml`, `adbc.yaml` or `adbc.yml` in
+//! the current directory and then in `~/.config/adbc/`, see
+//! [Profiles::discover].
+//!
+//! This module is gated behind the `profile` feature flag.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::driver_manager::{ManagedConnection, ManagedDatabase, ManagedDriver, ManagedStatement};
+use crate::error::{Error, Result, Status};
+use crate::ffi::constants;
+use crate::options::{AdbcVersion, OptionConnection, OptionDatabase, OptionStatement, OptionValue};
+use crate::secret;
+use crate::{Connection, Database, Driver, Optionable};
+
+/// Environment variable naming the file profiles are read from.
+pub const PROFILES_ENV: &str = "ADBC_PROFILES";
+
+const FILE_NAMES: [&str; 3] = ["adbc.toml", "adbc.yaml", "adbc.yml"];
+
+fn invalid(message: impl Into<String>) -> Error {
+    Error::with_message_and_status(message, Status::InvalidArguments)
+}
+
+#[derive(Deserialize)]
+struct RawProfiles {
+    #[serde(default)]
+    profiles: HashMap<String, RawProfile>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawProfile {
+    driver: String,
+    entrypoint: Option<String>,
+    version: Option<String>,
+    #[serde(default)]
+    database: BTreeMap<String, RawValue>,
+    #[serde(default)]
+    connection: BTreeMap<String, RawValue>,
+    #[serde(default)]
+    statement: BTreeMap<String, RawValue>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawValue {
+    Bool(bool),
+    Int(i64),
+    Double(f64),
+    String(String),
+}
+
+impl RawValue {
+    fn into_value(self) -> Result<OptionValue> {
+        Ok(match self {
+            Self::Bool(value) => value.to_string().into(),
+            Self::Int(value) => value.into(),
+            Self::Double(value) => value.into(),
+            Self::String(value) => interpolate(&value)?.into(),
+        })
+    }
+}
+
+/// Replace `${VARIABLE}` with the value of the environment variable and `$$`
+/// with `$`.
+fn interpolate(value: &str) -> Result<String> {
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(index) = rest.find('$') {
+        result.push_str(&rest[..index]);
+        rest = &rest[index + 1..];
+        if let Some(after) = rest.strip_prefix('$') {
+            result.push('$');
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix('{') {
+            let (name, after) = after
+                .split_once('}')
+                .ok_or_else(|| invalid(format!("Unterminated variable in {value:?}")))?;
+            let variable = std::env::var(name).map_err(|_| {
+                invalid(format!(
+                    "Environment variable {name} used by a profile is not set"
+                ))
+            })?;
+            result.push_str(&variable);
+            rest = after;
+        } else {
+            result.push('$');
+        }
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// Convert options read from a file, with the URI first since drivers may
+/// expect it to be set before the others.
+fn options<K: for<'a> From<&'a str>>(
+    raw: BTreeMap<String, RawValue>,
+) -> Result<Vec<(K, OptionValue)>> {
+    let (uri, others): (Vec<_>, Vec<_>) = raw
+        .into_iter()
+        .partition(|(key, _)| key == constants::ADBC_OPTION_URI);
+    uri.into_iter()
+        .chain(others)
+        .map(|(key, value)| Ok((K::from(key.as_str()), value.into_value()?)))
+        .collect()
+}
+
+/// A named connection profile.
+///
+/// The values of [secret] options are masked by [Debug][fmt::Debug]
+/// and zeroized on drop.
+#[derive(Clone)]
+#[non_exhaustive]
+pub struct Profile {
+    /// The name of the driver's dynamic library, see
+    /// [ManagedDriver::load_dynamic_from_name].
+    pub driver: String,
+    /// The symbol initializing the driver.
+    pub entrypoint: Option<String>,
+    /// The ADBC version requested from the driver.
+    pub version: AdbcVersion,
+    /// Options of the database.
+    pub database: Vec<(OptionDatabase, OptionValue)>,
+    /// Options of the connection.
+    pub connection: Vec<(OptionConnection, OptionValue)>,
+    /// Options set on every statement created by [Session::new_statement].
+    pub statement: Vec<(OptionStatement, OptionValue)>,
+}
+
+impl Profile {
+    fn from_raw(raw: RawProfile) -> Result<Self> {
+        let version = match raw.version.as_deref() {
+            None | Some("1.1.0") => AdbcVersion::V110,
+            Some("1.0.0") => AdbcVersion::V100,
+            Some(version) => return Err(invalid(format!("Unsupported ADBC version {version}"))),
+        };
+        Ok(Self {
+            driver: interpolate(&raw.driver)?,
+            entrypoint: raw.entrypoint.as_deref().map(interpolate).transpose()?,
+            version,
+            database: options(raw.database)?,
+            connection: options(raw.connection)?,
+            statement: options(raw.statement)?,
+        })
+    }
+
+    /// Load the driver and open a database and a connection with the
+    /// options of the profile.
+    pub fn open(&self) -> Result<Session> {
+        let entrypoint = self.entrypoint.as_ref().map(String::as_bytes);
+        let mut driver =
+            ManagedDriver::load_dynamic_from_name(&self.driver, entrypoint, self.version)?;
+        self.open_with(&mut driver)
+    }
+
+    /// Open a database and a connection with the options of the profile on an
+    /// already loaded driver, ignoring the driver of the profile.
+    pub fn open_with(&self, driver: &mut ManagedDriver) -> Result<Session> {
+        let mut database = driver.new_database_with_opts(self.database.clone())?;
+        let connection = database.new_connection_with_opts(self.connection.clone())?;
+        Ok(Session {
+            database,
+            connection,
+            statement: self.statement.clone(),
+        })
+    }
+}
+
+impl fmt::Debug for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Profile")
+            .field("driver", &self.driver)
+            .field("entrypoint", &self.entrypoint)
+            .field("version", &self.version)
+            .field("database", &secret::redacted_options(&self.database))
+            .field("connection", &secret::redacted_options(&self.connection))
+            .field("statement", &secret::redacted_options(&self.statement))
+            .finish()
+    }
+}
+
+impl Drop for Profile {
+    fn drop(&mut self) {
+        secret::zeroize_secrets(self.database.iter_mut().map(|(key, value)| (&*key, value)));
+        secret::zeroize_secrets(
+            self.connection
+                .iter_mut()
+                .map(|(key, value)| (&*key, value)),
+        );
+        secret::zeroize_secrets(self.statement.iter_mut().map(|(key, value)| (&*key, value)));
+    }
+}
+
+/// A set of named profiles.
+#[derive(Debug, Clone, Default)]
+pub struct Profiles {
+    profiles: HashMap<String, Profile>,
+}
+
+impl Profiles {
+    fn from_raw(raw: RawProfiles) -> Result<Self> {
+        let profiles = raw
+            .profiles
+            .into_iter()
+            .map(|(name, profile)| {
+                let profile = Profile::from_raw(profile)
+                    .map_err(|error| invalid(format!("Profile {name}: {}", error.message)))?;
+                Ok((name, profile))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { profiles })
+    }
+
+    /// Parse profiles from a TOML document.
+    pub fn from_toml(document: &str) -> Result<Self> {
+        let raw = toml::from_str(document)
+            .map_err(|error| invalid(format!("Invalid TOML profiles: {error}")))?;
+        Self::from_raw(raw)
+    }
+
+    /// Parse profiles from a YAML document.
+    pub fn from_yaml(document: &str) -> Result<Self> {
+        let raw = serde_yaml::from_str(document)
+            .map_err(|error| invalid(format!("Invalid YAML profiles: {error}")))?;
+        Self::from_raw(raw)
+    }
+
+    /// Read profiles from a file, parsed as YAML if its extension is `yaml`
+    /// or `yml` and as TOML otherwise.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let document = std::fs::read_to_string(path).map_err(|error| {
+            Error::with_message_and_status(
+                format!("Cannot read profiles from {}: {error}", path.display()),
+                Status::IO,
+            )
+        })?;
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("yaml" | "yml") => Self::from_yaml(&document),
+            _ => Self::from_toml(&document),
+        }
+    }
+
+    /// Read profiles from the file named by [PROFILES_ENV] or else from the
+    /// first of `adbc.toml`, `adbc.yaml` and `adbc.yml` found in the current
+    /// directory and then in `~/.config/adbc/`.
+    ///
+    /// Fails with [Status::NotFound] if there is no such file.
+    pub fn discover() -> Result<Self> {
+        if let Some(path) = std::env::var_os(PROFILES_ENV) {
+            return Self::load(path);
+        }
+        let home = std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config/adbc"));
+        let path = [Some(PathBuf::new()), home]
+            .into_iter()
+            .flatten()
+            .flat_map(|directory| FILE_NAMES.map(|name| directory.join(name)))
+            .find(|path| path.is_file())
+            .ok_or_else(|| {
+                Error::with_message_and_status("No profiles file found", Status::NotFound)
+            })?;
+        Self::load(path)
+    }
+
+    /// Get a profile by name, failing with [Status::NotFound] if there is none.
+    pub fn get(&self, name: &str) -> Result<&Profile> {
+        self.profiles.get(name).ok_or_else(|| {
+            Error::with_message_and_status(format!("Unknown profile {name}"), Status::NotFound)
+        })
+    }
+
+    /// The names of the profiles.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.profiles.keys().map(String::as_str)
+    }
+}
+
+/// A database and a connection opened from a [Profile].
+pub struct Session {
+    pub database: ManagedDatabase,
+    pub connection: ManagedConnection,
+    statement: Vec<(OptionStatement, OptionValue)>,
+}
+
+impl Session {
+    /// Create a statement with the statement options of the profile.
+    pub fn new_statement(&mut self) -> Result<ManagedStatement> {
+        let mut statement = self.connection.new_statement()?;
+        for (key, value) in &self.statement {
+            statement.set_option(key.clone(), value.clone())?;
+        }
+        Ok(statement)
+    }
+}
+
+/// Open the profile `name`, read from the profiles found by
+/// [Profiles::discover].
+pub fn open(name: &str) -> Result<Session> {
+    Profiles::discover()?.get(name)?.open()
+}
@@ -0,0 +1,904 @@
+//! Retrying calls failing with transient errors, reconnecting when needed.
+//!
+//! Wrapping a driver with [RetryDriver] produces a new [Driver] whose
+//! databases, connections and statements survive transient failures, such as
+//! the database server restarting:
+//! - Errors are classified by [RetryPolicy::with_classifier], by default with
+//!   [classify], into permanent errors, errors worth retrying and errors
+//!   breaking the connection.
+//! - A broken connection is transparently replaced by a new connection
+//!   created from its database, on which the options previously set on the
+//!   connection are replayed. Statements created from it are recreated in turn,
+//!   replaying their options, query, preparation and bound parameters.
+//! - Idempotent calls are retried with an exponential backoff: metadata
+//!   calls, schemas, partitions, and queries considered read-only by
+//!   [RetryPolicy::with_read_only], by default with [is_read_only].
+//!   Updates, commits and rollbacks are never retried.
+//! - Nothing is retried or reconnected while autocommit is disabled, since
+//!   the transaction in progress would be silently lost. A connection broken
+//!   inside a transaction is reconnected once an option is set on it, for
+//!   instance when enabling autocommit again.
+//!
+//! Result sets returned by connection methods and by executing statements
+//! whose execution is retried are read to completion before being returned,
+//! so that failures while reading them are retried too. The result sets of
+//! other statements are streamed.
+//!
+//! ## Example
+//!
+//! ```rust
+//! # use std::time::Duration;
+//! # use adbc_core::{retry::{RetryDriver, RetryPolicy}, Driver};
+//! fn resilient<D: Driver>(driver: D) -> RetryDriver<D> {
+//!     let policy = RetryPolicy::new()
+//!         .with_max_attempts(5)
+//!         .with_backoff(Duration::from_millis(50), Duration::from_secs(1));
+//!     RetryDriver::new(driver, policy)
+//! }
+//! ```
+
+use std::collections::HashSet;
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use arrow::array::RecordBatchIterator;
+use arrow::datatypes::Schema;
+use arrow::error::ArrowError;
+use arrow::record_batch::{RecordBatch, RecordBatchReader};
+
+use crate::capabilities::Capabilities;
+use crate::error::{Error, Result, Status};
+use crate::options::{
+    InfoCode, ObjectDepth, OptionConnection, OptionDatabase, OptionStatement, OptionValue,
+    TypedValue,
+};
+use crate::secret;
+use crate::{Connection, Database, Driver, Optionable, PartitionedResult, Statement};
+
+type ConnectionType<D> = <D as Database>::ConnectionType;
+type StatementType<D> = <<D as Database>::ConnectionType as Connection>::StatementType;
+
+/// A result set read to completion.
+type MemoryReader =
+    RecordBatchIterator<std::vec::IntoIter<std::result::Result<RecordBatch, ArrowError>>>;
+
+/// A result set read to completion, or streamed from the wrapped statement.
+enum ResultSet<R> {
+    Buffered(MemoryReader),
+    Streamed(R),
+}
+
+impl<R: RecordBatchReader> Iterator for ResultSet<R> {
+    type Item = std::result::Result<RecordBatch, ArrowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Self::Buffered(reader) => reader.next(),
+            Self::Streamed(reader) => reader.next(),
+        }
+    }
+}
+
+impl<R: RecordBatchReader> RecordBatchReader for ResultSet<R> {
+    fn schema(&self) -> Arc<Schema> {
+        match self {
+            Self::Buffered(reader) => reader.schema(),
+            Self::Streamed(reader) => reader.schema(),
+        }
+    }
+}
+
+/// How a failed call is handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorClass {
+    /// The error is returned to the caller.
+    Permanent,
+    /// The call is retried on the same connection, if idempotent.
+    Retry,
+    /// The connection is broken: it is replaced by a new connection and the
+    /// call is retried, if idempotent.
+    Reconnect,
+}
+
+/// Default classification of errors.
+///
+/// Connection exceptions (SQLSTATE class `08`), PostgreSQL shutdowns
+/// (`57P01`, `57P02` and `57P03`) and other errors with [Status::IO] break
+/// the connection, serialization failures (`40001`) and deadlocks (`40P01`)
+/// are retried, and all other errors are permanent.
+pub fn classify(error: &Error) -> ErrorClass {
+    let sqlstate = error.sqlstate.map(|c| c as u8);
+    match &sqlstate {
+        [b'0', b'8', ..] | b"57P01" | b"57P02" | b"57P03" => ErrorClass::Reconnect,
+        b"40001" | b"40P01" => ErrorClass::Retry,
+        _ if error.status == Status::IO => ErrorClass::Reconnect,
+        _ => ErrorClass::Permanent,
+    }
+}
+
+/// Default test of whether a SQL query only reads data, and can hence be
+/// retried.
+///
+/// This is a heuristic checking that the query starts with `SELECT`, `SHOW`,
+/// `VALUES`, `TABLE` or `DESCRIBE`; queries with side effects such as
+/// `SELECT nextval('seq')` should be excluded with a custom test.
+pub fn is_read_only(query: &str) -> bool {
+    let mut query = query.trim_start();
+    loop {
+        if let Some(rest) = query.strip_prefix("--") {
+            query = rest.split_once('\n').map_or("", |(_, rest)| rest);
+        } else if let Some(rest) = query.strip_prefix('(') {
+            query = rest;
+        } else {
+            break;
+        }
+        query = query.trim_start();
+    }
+    let keyword = query
+        .split(|c: char| !c.is_ascii_alphabetic())
+        .next()
+        .unwrap_or_default();
+    ["SELECT", "SHOW", "VALUES", "TABLE", "DESCRIBE"]
+        .iter()
+        .any(|k| k.eq_ignore_ascii_case(keyword))
+}
+
+/// Which errors are retried, how many times and how long to wait in between.
+#[derive(Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: f64,
+    classifier: Arc<dyn Fn(&Error) -> ErrorClass + Send + Sync>,
+    read_only: Arc<dyn Fn(&str) -> bool + Send + Sync>,
+}
+
+impl Debug for RetryPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("initial_backoff", &self.initial_backoff)
+            .field("max_backoff", &self.max_backoff)
+            .field("multiplier", &self.multiplier)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(2),
+            multiplier: 2.0,
+            classifier: Arc::new(classify),
+            read_only: Arc::new(is_read_only),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The default policy, making up to 3 attempts with a backoff starting
+    /// at 100ms and doubling up to 2s.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Make up to `max_attempts` attempts per call, including the first one.
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Wait `initial` before the first retry, multiplying the delay by the
+    /// multiplier after each retry without exceeding `max`.
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Multiply the delay by `multiplier` after each retry.
+    pub fn with_multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    /// Classify errors with `classifier` instead of [classify].
+    pub fn with_classifier(
+        mut self,
+        classifier: impl Fn(&Error) -> ErrorClass + Send + Sync + 'static,
+    ) -> Self {
+        self.classifier = Arc::new(classifier);
+        self
+    }
+
+    /// Test whether queries only read data with `read_only` instead of
+    /// [is_read_only].
+    pub fn with_read_only(
+        mut self,
+        read_only: impl Fn(&str) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.read_only = Arc::new(read_only);
+        self
+    }
+
+    /// The delay before retrying a call after its `attempt`-th attempt.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let factor = self.multiplier.powi(attempt as i32 - 1);
+        self.initial_backoff.mul_f64(factor).min(self.max_backoff)
+    }
+
+    /// Call `f` until it succeeds, fails with an error for which `retryable`
+    /// returns false, or runs out of attempts. `retryable` is called for
+    /// every error, including the last one.
+    fn retry<T>(
+        &self,
+        mut f: impl FnMut() -> Result<T>,
+        mut retryable: impl FnMut(&Error) -> bool,
+    ) -> Result<T> {
+        let mut attempt = 1;
+        loop {
+            match f() {
+                Err(error) if retryable(&error) && attempt < self.max_attempts => {
+                    thread::sleep(self.backoff(attempt));
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+fn from_arrow_error(error: ArrowError) -> Error {
+    match error {
+        ArrowError::ExternalError(error) => match error.downcast::<Error>() {
+            Ok(error) => *error,
+            Err(error) => Error::with_message_and_status(error.to_string(), Status::IO),
+        },
+        error => error.into(),
+    }
+}
+
+/// Read a result set to completion.
+fn collect(reader: impl RecordBatchReader) -> Result<MemoryReader> {
+    let schema = reader.schema();
+    let batches = reader
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(from_arrow_error)?;
+    let batches: Vec<_> = batches.into_iter().map(Ok).collect();
+    Ok(RecordBatchIterator::new(batches.into_iter(), schema))
+}
+
+/// Whether autocommit is enabled by setting `key` to `value`, if `key` is
+/// the autocommit option.
+fn autocommit_value(key: &OptionConnection, value: &OptionValue) -> Option<bool> {
+    match key {
+        OptionConnection::AutoCommit => bool::from_value(value.clone()).ok(),
+        _ => None,
+    }
+}
+
+/// A driver retrying the calls failing with transient errors.
+#[derive(Debug, Default, Clone)]
+pub struct RetryDriver<D> {
+    inner: D,
+    policy: RetryPolicy,
+}
+
+impl<D> RetryDriver<D> {
+    /// Retry the calls made to `inner` according to `policy`.
+    pub fn new(inner: D, policy: RetryPolicy) -> Self {
+        Self { inner, policy }
+    }
+
+    /// The wrapped driver.
+    pub fn inner(&self) -> &D {
+        &self.inner
+    }
+
+    /// The policy shared by all objects created from this driver.
+    pub fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+}
+
+impl<D: Driver> Driver for RetryDriver<D> {
+    type DatabaseType = RetryDatabase<D::DatabaseType>;
+
+    fn new_database(&mut self) -> Result<Self::DatabaseType> {
+        self.new_database_with_opts(None)
+    }
+
+    fn new_database_with_opts(
+        &mut self,
+        opts: impl IntoIterator<Item = (OptionDatabase, OptionValue)>,
+    ) -> Result<Self::DatabaseType> {
+        let opts: Vec<_> = opts.into_iter().collect();
+        let policy = &self.policy;
+        let inner = policy.retry(
+            || self.inner.new_database_with_opts(opts.clone()),
+            |error| (policy.classifier)(error) != ErrorClass::Permanent,
+        )?;
+        Ok(RetryDatabase {
+            inner: Arc::new(Mutex::new(inner)),
+            policy: self.policy.clone(),
+        })
+    }
+}
+
+/// A database created by a [RetryDriver].
+#[derive(Debug)]
+pub struct RetryDatabase<D> {
+    /// Shared with connections, which reconnect through it.
+    inner: Arc<Mutex<D>>,
+    policy: RetryPolicy,
+}
+
+impl<D: Database> Optionable for RetryDatabase<D> {
+    type Option = OptionDatabase;
+
+    fn set_option(&mut self, key: Self::Option, value: OptionValue) -> Result<()> {
+        self.inner.lock().unwrap().set_option(key, value)
+    }
+
+    fn get_option_string(&self, key: Self::Option) -> Result<String> {
+        self.inner.lock().unwrap().get_option_string(key)
+    }
+
+    fn get_option_bytes(&self, key: Self::Option) -> Result<Vec<u8>> {
+        self.inner.lock().unwrap().get_option_bytes(key)
+    }
+
+    fn get_option_int(&self, key: Self::Option) -> Result<i64> {
+        self.inner.lock().unwrap().get_option_int(key)
+    }
+
+    fn get_option_double(&self, key: Self::Option) -> Result<f64> {
+        self.inner.lock().unwrap().get_option_double(key)
+    }
+}
+
+impl<D: Database> Database for RetryDatabase<D> {
+    type ConnectionType = RetryConnection<D>;
+
+    fn new_connection(&mut self) -> Result<Self::ConnectionType> {
+        self.new_connection_with_opts(None)
+    }
+
+    fn new_connection_with_opts(
+        &mut self,
+        opts: impl IntoIterator<Item = (OptionConnection, OptionValue)>,
+    ) -> Result<Self::ConnectionType> {
+        let mut session = Session {
+            database: self.inner.clone(),
+            connection: None,
+            options: Vec::new(),
+            autocommit: true,
+            broken: true,
+            generation: 0,
+        };
+        for (key, value) in opts {
+            session.remember(key, value);
+        }
+        let policy = &self.policy;
+        policy.retry(
+            || session.check(),
+            |error| (policy.classifier)(error) != ErrorClass::Permanent,
+        )?;
+        Ok(RetryConnection {
+            session: Arc::new(Mutex::new(session)),
+            policy: self.policy.clone(),
+        })
+    }
+}
+
+/// The connection wrapped by a [RetryConnection], shared with its statements.
+struct Session<D: Database> {
+    database: Arc<Mutex<D>>,
+    /// Only `None` before the first connection.
+    connection: Option<ConnectionType<D>>,
+    /// Options replayed on reconnection, in the order they were first set.
+    options: Vec<(OptionConnection, OptionValue)>,
+    autocommit: bool,
+    /// Whether the connection must be replaced.
+    broken: bool,
+    /// Incremented by each reconnection, to let statements know they must be
+    /// recreated.
+    generation: u64,
+}
+
+impl<D: Database> Drop for Session<D> {
+    fn drop(&mut self) {
+        secret::zeroize_secrets(self.options.iter_mut().map(|(key, value)| (&*key, value)));
+    }
+}
+
+impl<D: Database> Session<D> {
+    fn connection(&mut self) -> &mut ConnectionType<D> {
+        self.connection
+            .as_mut()
+            .expect("connection was established")
+    }
+
+    fn remember(&mut self, key: OptionConnection, value: OptionValue) {
+        if let Some(enabled) = autocommit_value(&key, &value) {
+            self.autocommit = enabled;
+        }
+        match self.options.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, v)) => *v = value,
+            None => self.options.push((key, value)),
+        }
+    }
+
+    fn reconnect(&mut self) -> Result<()> {
+        let connection = self
+            .database
+            .lock()
+            .unwrap()
+            .new_connection_with_opts(self.options.clone())?;
+        self.connection = Some(connection);
+        self.broken = false;
+        self.generation += 1;
+        Ok(())
+    }
+
+    /// Reconnect if the connection is broken, unless inside a transaction.
+    fn check(&mut self) -> Result<()> {
+        if self.broken && (self.autocommit || self.connection.is_none()) {
+            self.reconnect()?;
+        }
+        Ok(())
+    }
+
+    /// Handle the failure of an attempt, returning whether to retry.
+    fn failed(&mut self, policy: &RetryPolicy, error: &Error, idempotent: bool) -> bool {
+        let class = (policy.classifier)(error);
+        if class == ErrorClass::Reconnect {
+            self.broken = true;
+        }
+        idempotent && self.autocommit && class != ErrorClass::Permanent
+    }
+}
+
+/// A connection created by a [RetryDriver].
+pub struct RetryConnection<D: Database> {
+    session: Arc<Mutex<Session<D>>>,
+    policy: RetryPolicy,
+}
+
+impl<D: Database> RetryConnection<D> {
+    /// Number of times the connection was replaced by a new connection.
+    pub fn reconnections(&self) -> u64 {
+        self.session.lock().unwrap().generation - 1
+    }
+
+    fn call<T>(
+        &self,
+        idempotent: bool,
+        mut f: impl FnMut(&mut ConnectionType<D>) -> Result<T>,
+    ) -> Result<T> {
+        self.policy.retry(
+            || {
+                let mut session = self.session.lock().unwrap();
+                session.check()?;
+                f(session.connection())
+            },
+            |error| {
+                let mut session = self.session.lock().unwrap();
+                session.failed(&self.policy, error, idempotent)
+            },
+        )
+    }
+}
+
+impl<D: Database> Optionable for RetryConnection<D> {
+    type Option = OptionConnection;
+
+    fn set_option(&mut self, key: Self::Option, value: OptionValue) -> Result<()> {
+        let mut session = self.session.lock().unwrap();
+        if session.broken {
+            // Reconnecting replays the option along with the previous ones.
+            let (mut options, autocommit) = (session.options.clone(), session.autocommit);
+            session.remember(key, value);
+            return match session.reconnect() {
+                Ok(()) => {
+                    secret::zeroize_secrets(options.iter_mut().map(|(key, value)| (&*key, value)));
+                    Ok(())
+                }
+                Err(error) => {
+                    session.options = options;
+                    session.autocommit = autocommit;
+                    Err(error)
+                }
+            };
+        }
+        drop(session);
+        self.call(true, |connection| {
+            connection.set_option(key.clone(), value.clone())
+        })?;
+        self.session.lock().unwrap().remember(key, value);
+        Ok(())
+    }
+
+    fn get_option_string(&self, key: Self::Option) -> Result<String> {
+        self.call(true, |connection| connection.get_option_string(key.clone()))
+    }
+
+    fn get_option_bytes(&self, key: Self::Option) -> Result<Vec<u8>> {
+        self.call(true, |connection| connection.get_option_bytes(key.clone()))
+    }
+
+    fn get_option_int(&self, key: Self::Option) -> Result<i64> {
+        self.call(true, |connection| connection.get_option_int(key.clone()))
+    }
+
+    fn get_option_double(&self, key: Self::Option) -> Result<f64> {
+        self.call(true, |connection| connection.get_option_double(key.clone()))
+    }
+}
+
+impl<D: Database> Connection for RetryConnection<D> {
+    type StatementType = RetryStatement<D>;
+
+    fn new_statement(&mut self) -> Result<Self::StatementType> {
+        let inner = self.call(true, |connection| connection.new_statement())?;
+        let generation = self.session.lock().unwrap().generation;
+        Ok(RetryStatement {
+            inner,
+            session: self.session.clone(),
+            generation,
+            policy: self.policy.clone(),
+            state: StatementState::default(),
+        })
+    }
+
+    fn cancel(&mut self) -> Result<()> {
+        self.session.lock().unwrap().connection().cancel()
+    }
+
+    fn get_info(&self, codes: Option<HashSet<InfoCode>>) -> Result<impl RecordBatchReader + Send> {
+        self.call(true, |connection| {
+            collect(connection.get_info(codes.clone())?)
+        })
+    }
+
+    fn get_objects(
+        &self,
+        depth: ObjectDepth,
+        catalog: Option<&str>,
+        db_schema: Option<&str>,
+        table_name: Option<&str>,
+        table_type: Option<Vec<&str>>,
+        column_name: Option<&str>,
+    ) -> Result<impl RecordBatchReader + Send> {
+        self.call(true, |connection| {
+            collect(connection.get_objects(
+                depth,
+                catalog,
+                db_schema,
+                table_name,
+                table_type.clone(),
+                column_name,
+            )?)
+        })
+    }
+
+    fn get_table_schema(
+        &self,
+        catalog: Option<&str>,
+        db_schema: Option<&str>,
+        table_name: &str,
+    ) -> Result<Schema> {
+        self.call(true, |connection| {
+            connection.get_table_schema(catalog, db_schema, table_name)
+        })
+    }
+
+    fn get_table_types(&self) -> Result<impl RecordBatchReader + Send> {
+        self.call(true, |connection| collect(connection.get_table_types()?))
+    }
+
+    fn get_statistic_names(&self) -> Result<impl RecordBatchReader + Send> {
+        self.call(true, |connection| {
+            collect(connection.get_statistic_names()?)
+        })
+    }
+
+    fn get_statistics(
+        &self,
+        catalog: Option<&str>,
+        db_schema: Option<&str>,
+        table_name: Option<&str>,
+        approximate: bool,
+    ) -> Result<impl RecordBatchReader + Send> {
+        self.call(true, |connection| {
+            collect(connection.get_statistics(catalog, db_schema, table_name, approximate)?)
+        })
+    }
+
+    fn commit(&mut self) -> Result<()> {
+        self.call(false, |connection| connection.commit())
+    }
+
+    fn rollback(&mut self) -> Result<()> {
+        self.call(false, |connection| connection.rollback())
+    }
+
+    fn read_partition(&self, partition: impl AsRef<[u8]>) -> Result<impl RecordBatchReader + Send> {
+        let partition = partition.as_ref();
+        self.call(true, |connection| {
+            collect(connection.read_partition(partition)?)
+        })
+    }
+
+    fn capabilities(&self) -> Result<Capabilities> {
+        self.call(true, |connection| connection.capabilities())
+    }
+}
+
+/// What a statement executes.
+#[derive(Debug, Clone)]
+enum Plan {
+    Query(String),
+    Substrait(Vec<u8>),
+}
+
+/// What is bound to a statement.
+#[derive(Debug, Clone)]
+enum Bound {
+    Batch(RecordBatch),
+    /// A stream cannot be replayed, since it is consumed by the statement.
+    Stream,
+}
+
+/// State of a statement replayed when recreating it after a reconnection.
+#[derive(Debug, Default)]
+struct StatementState {
+    options: Vec<(OptionStatement, OptionValue)>,
+    plan: Option<Plan>,
+    prepared: bool,
+    bound: Option<Bound>,
+}
+
+impl Drop for StatementState {
+    fn drop(&mut self) {
+        secret::zeroize_secrets(self.options.iter_mut().map(|(key, value)| (&*key, value)));
+    }
+}
+
+impl StatementState {
+    fn replay(&self, statement: &mut impl Statement) -> Result<()> {
+        for (key, value) in &self.options {
+            statement.set_option(key.clone(), value.clone())?;
+        }
+        match &self.plan {
+            Some(Plan::Query(query)) => statement.set_sql_query(query)?,
+            Some(Plan::Substrait(plan)) => statement.set_substrait_plan(plan)?,
+            None => {}
+        }
+        if self.prepared {
+            statement.prepare()?;
+        }
+        match &self.bound {
+            Some(Bound::Batch(batch)) => statement.bind(batch.clone())?,
+            Some(Bound::Stream) => {
+                return Err(Error::with_message_and_status(
+                    "Cannot recreate a statement bound to a stream after reconnecting",
+                    Status::InvalidState,
+                ))
+            }
+            None => {}
+        }
+        Ok(())
+    }
+}
+
+/// A statement created by a [RetryDriver].
+pub struct RetryStatement<D: Database> {
+    inner: StatementType<D>,
+    session: Arc<Mutex<Session<D>>>,
+    /// Generation of the connection the wrapped statement was created from.
+    generation: u64,
+    policy: RetryPolicy,
+    state: StatementState,
+}
+
+impl<D: Database> RetryStatement<D> {
+    /// Recreate the wrapped statement if its connection was replaced.
+    fn sync(&mut self) -> Result<()> {
+        let mut session = self.session.lock().unwrap();
+        session.check()?;
+        if session.generation != self.generation {
+            let mut inner = session.connection().new_statement()?;
+            let generation = session.generation;
+            drop(session);
+            self.state.replay(&mut inner)?;
+            self.inner = inner;
+            self.generation = generation;
+        }
+        Ok(())
+    }
+
+    fn failed(&self, error: &Error, idempotent: bool) -> bool {
+        let mut session = self.session.lock().unwrap();
+        session.failed(&self.policy, error, idempotent)
+    }
+
+    /// Whether executing the statement can be retried.
+    fn read_only(&self) -> bool {
+        let read_only = match &self.state.plan {
+            Some(Plan::Query(query)) => (self.policy.read_only)(query),
+            _ => false,
+        };
+        read_only && !matches!(self.state.bound, Some(Bound::Stream))
+    }
+
+    fn call<T>(
+        &mut self,
+        idempotent: bool,
+        mut f: impl FnMut(&mut StatementType<D>) -> Result<T>,
+    ) -> Result<T> {
+        let mut attempt = 1;
+        loop {
+            let result = self.sync().and_then(|_| f(&mut self.inner));
+            match result {
+                Err(error)
+                    if self.failed(&error, idempotent) && attempt < self.policy.max_attempts =>
+                {
+                    thread::sleep(self.policy.backoff(attempt));
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+impl<D: Database> Optionable for RetryStatement<D> {
+    type Option = OptionStatement;
+
+    fn set_option(&mut self, key: Self::Option, value: OptionValue) -> Result<()> {
+        self.call(true, |statement| {
+            statement.set_option(key.clone(), value.clone())
+        })?;
+        match self.state.options.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, v)) => *v = value,
+            None => self.state.options.push((key, value)),
+        }
+        Ok(())
+    }
+
+    fn get_option_string(&self, key: Self::Option) -> Result<String> {
+        self.inner.get_option_string(key)
+    }
+
+    fn get_option_bytes(&self, key: Self::Option) -> Result<Vec<u8>> {
+        self.inner.get_option_bytes(key)
+    }
+
+    fn get_option_int(&self, key: Self::Option) -> Result<i64> {
+        self.inner.get_option_int(key)
+    }
+
+    fn get_option_double(&self, key: Self::Option) -> Result<f64> {
+        self.inner.get_option_double(key)
+    }
+}
+
+impl<D: Database> Statement for RetryStatement<D> {
+    fn bind(&mut self, batch: RecordBatch) -> Result<()> {
+        self.call(true, |statement| statement.bind(batch.clone()))?;
+        self.state.bound = Some(Bound::Batch(batch));
+        Ok(())
+    }
+
+    fn bind_stream(&mut self, reader: Box<dyn RecordBatchReader + Send>) -> Result<()> {
+        let mut reader = Some(reader);
+        self.call(false, |statement| match reader.take() {
+            Some(reader) => statement.bind_stream(reader),
+            None => unreachable!("non-idempotent calls are not retried"),
+        })?;
+        self.state.bound = Some(Bound::Stream);
+        Ok(())
+    }
+
+    fn execute(&mut self) -> Result<impl RecordBatchReader + Send> {
+        if self.read_only() && self.session.lock().unwrap().autocommit {
+            let reader = self.call(true, |statement| collect(statement.execute()?))?;
+            return Ok(ResultSet::Buffered(reader));
+        }
+        // The execution is not retried, so its result set is not buffered.
+        self.sync()?;
+        match self.inner.execute() {
+            Ok(reader) => Ok(ResultSet::Streamed(reader)),
+            Err(error) => {
+                let mut session = self.session.lock().unwrap();
+                session.failed(&self.policy, &error, false);
+                Err(error)
+            }
+        }
+    }
+
+    fn execute_update(&mut self) -> Result<Option<i64>> {
+        self.call(false, |statement| statement.execute_update())
+    }
+
+    fn execute_schema(&mut self) -> Result<Schema> {
+        self.call(true, |statement| statement.execute_schema())
+    }
+
+    fn execute_partitions(&mut self) -> Result<PartitionedResult> {
+        let idempotent = self.read_only();
+        self.call(idempotent, |statement| statement.execute_partitions())
+    }
+
+    fn get_parameter_schema(&self) -> Result<Schema> {
+        self.inner.get_parameter_schema()
+    }
+
+    fn prepare(&mut self) -> Result<()> {
+        self.call(true, |statement| statement.prepare())?;
+        self.state.prepared = true;
+        Ok(())
+    }
+
+    fn set_sql_query(&mut self, query: impl AsRef<str>) -> Result<()> {
+        let query = query.as_ref();
+        self.call(true, |statement| statement.set_sql_query(query))?;
+        self.state.plan = Some(Plan::Query(query.into()));
+        self.state.prepared = false;
+        Ok(())
+    }
+
+    fn set_substrait_plan(&mut self, plan: impl AsRef<[u8]>) -> Result<()> {
+        let plan = plan.as_ref();
+        self.call(true, |statement| statement.set_substrait_plan(plan))?;
+        self.state.plan = Some(Plan::Substrait(plan.into()));
+        self.state.prepared = false;
+        Ok(())
+    }
+
+    fn cancel(&mut self) -> Result<()> {
+        self.inner.cancel()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        let mut error = Error::with_message_and_status("", Status::Internal);
+        assert_eq!(classify(&error), ErrorClass::Permanent);
+        error.status = Status::IO;
+        assert_eq!(classify(&error), ErrorClass::Reconnect);
+        error.status = Status::InvalidState;
+        for (sqlstate, class) in [
+            (b"08006", ErrorClass::Reconnect),
+            (b"57P01", ErrorClass::Reconnect),
+            (b"40001", ErrorClass::Retry),
+            (b"42P01", ErrorClass::Permanent),
+        ] {
+            error.sqlstate = sqlstate.map(|c| c as _);
+            assert_eq!(classify(&error), class);
+        }
+    }
+
+    #[test]
+    fn test_is_read_only() {
+        assert!(is_read_only("SELECT 1"));
+        assert!(is_read_only("  -- comment\n(select 1) UNION (SELECT 2)"));
+        assert!(is_read_only("show search_path"));
+        assert!(!is_read_only(
+            "WITH t AS (DELETE FROM t RETURNING *) SELECT * FROM t"
+        ));
+        assert!(!is_read_only("INSERT INTO t VALUES (1)"));
+        assert!(!is_read_only("SELECTED"));
+        assert!(!is_read_only(""));
+    }
+}
@@ -0,0 +1,259 @@
+//! Caching prepared statements by query.
+//!
+//! Services issuing the same parameterized queries over and over would
+//! otherwise create, configure and prepare a statement for each execution.
+//! [StatementCache] wraps a connection and keeps the statements it prepared,
+//! keyed by their SQL query, up to a capacity beyond which the least recently
+//! used statement is released. [StatementCache::prepare] returns a
+//! [CachedStatement] which binds parameters and executes the prepared
+//! statement.
+//!
+//! A statement failing with [Status::InvalidState], e.g. because the table
+//! it queries was altered, is evicted from the cache, so that the next
+//! [StatementCache::prepare] of its query prepares it again.
+//!
+//! Hits, misses and evictions are counted by [StatementCache::stats] and
+//! reported to the [metrics] recorder, the hit rate being
+//! [metrics::STATEMENT_CACHE_HITS] over the sum of hits and
+//! [metrics::STATEMENT_CACHE_MISSES].
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! # use arrow::record_batch::RecordBatch;
+//! # use adbc_core::statement_cache::StatementCache;
+//! # use adbc_core::Connection;
+//! # fn run(connection: impl Connection, parameters: RecordBatch) -> adbc_core::error::Result<()> {
+//! let mut cache = StatementCache::new(connection, 128);
+//! let mut statement = cache.prepare("SELECT name FROM users WHERE id = $1")?;
+//! statement.bind(parameters)?;
+//! let names = statement.execute()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+
+use arrow::record_batch::{RecordBatch, RecordBatchReader};
+
+use crate::error::{Result, Status};
+use crate::{metrics, Connection, Statement};
+
+/// Counters of a [StatementCache].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Number of queries whose statement was found in the cache.
+    pub hits: u64,
+    /// Number of queries whose statement was prepared.
+    pub misses: u64,
+    /// Number of statements evicted because the cache was full or because
+    /// they failed with [Status::InvalidState].
+    pub evictions: u64,
+}
+
+impl CacheStats {
+    /// Fraction of the queries whose statement was found in the cache, or
+    /// zero if no query was prepared.
+    pub fn hit_rate(&self) -> f64 {
+        match self.hits + self.misses {
+            0 => 0.0,
+            total => self.hits as f64 / total as f64,
+        }
+    }
+}
+
+struct Entry<S> {
+    statement: S,
+    /// Value of the clock of the cache when the statement was last used.
+    last_used: u64,
+}
+
+/// A connection keeping the statements it prepared, keyed by SQL query.
+pub struct StatementCache<C: Connection> {
+    // Statements are released before the connection, hence declared first.
+    entries: HashMap<String, Entry<C::StatementType>>,
+    connection: C,
+    capacity: usize,
+    clock: u64,
+    stats: CacheStats,
+}
+
+impl<C: Connection> StatementCache<C> {
+    /// Wrap `connection`, keeping up to `capacity` prepared statements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(connection: C, capacity: usize) -> Self {
+        assert!(capacity > 0, "Statement cache capacity must be positive");
+        Self {
+            entries: HashMap::new(),
+            connection,
+            capacity,
+            clock: 0,
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// Get the wrapped connection.
+    pub fn connection(&self) -> &C {
+        &self.connection
+    }
+
+    /// Get the wrapped connection mutably, e.g. to run statements which are
+    /// not cached.
+    pub fn connection_mut(&mut self) -> &mut C {
+        &mut self.connection
+    }
+
+    /// Release the cached statements and return the wrapped connection.
+    pub fn into_inner(self) -> C {
+        self.connection
+    }
+
+    /// Get the statement prepared for `query`, creating and preparing it if
+    /// it is not cached.
+    ///
+    /// Statements which fail to be prepared are not cached.
+    pub fn prepare(&mut self, query: &str) -> Result<CachedStatement<'_, C>> {
+        // The statement is taken out of the cache while borrowed, and put back
+        // when the handle is dropped.
+        let statement = match self.entries.remove(query) {
+            Some(entry) => {
+                self.stats.hits += 1;
+                metrics::record_statement_cache(metrics::STATEMENT_CACHE_HITS);
+                entry.statement
+            }
+            None => {
+                let mut statement = self.connection.new_statement()?;
+                statement.set_sql_query(query)?;
+                statement.prepare()?;
+                self.stats.misses += 1;
+                metrics::record_statement_cache(metrics::STATEMENT_CACHE_MISSES);
+                if self.entries.len() >= self.capacity {
+                    self.evict_least_recently_used();
+                }
+                statement
+            }
+        };
+        Ok(CachedStatement {
+            cache: self,
+            query: query.to_string(),
+            statement: Some(statement),
+            evicted: false,
+        })
+    }
+
+    fn evict_least_recently_used(&mut self) {
+        let query = self
+            .entries
+            .iter()
+            .min_by_key(|(_, entry)| entry.last_used)
+            .map(|(query, _)| query.clone());
+        if let Some(query) = query {
+            self.invalidate(&query);
+        }
+    }
+
+    /// Release the statement cached for `query`, if any.
+    pub fn invalidate(&mut self, query: &str) {
+        if self.entries.remove(query).is_some() {
+            self.stats.evictions += 1;
+            metrics::record_statement_cache(metrics::STATEMENT_CACHE_EVICTIONS);
+        }
+    }
+
+    /// Release all the cached statements.
+    pub fn clear(&mut self) {
+        for query in self.entries.keys().cloned().collect::<Vec<_>>() {
+            self.invalidate(&query);
+        }
+    }
+
+    /// Number of cached statements.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether no statement is cached.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Get the counters of the cache.
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+}
+
+/// A prepared statement borrowed from a [StatementCache], returned to the
+/// cache when dropped.
+///
+/// Methods failing with [Status::InvalidState] evict the statement from the
+/// cache.
+pub struct CachedStatement<'a, C: Connection> {
+    cache: &'a mut StatementCache<C>,
+    query: String,
+    /// Only taken when dropped.
+    statement: Option<C::StatementType>,
+    evicted: bool,
+}
+
+impl<C: Connection> CachedStatement<'_, C> {
+    /// Get the cached statement, e.g. to set options on it.
+    pub fn statement(&mut self) -> &mut C::StatementType {
+        self.statement.as_mut().unwrap()
+    }
+
+    /// Evict the statement if `result` failed with [Status::InvalidState].
+    fn check<T>(cache: &mut StatementCache<C>, evicted: &mut bool, result: Result<T>) -> Result<T> {
+        if let Err(error) = &result {
+            if error.status == Status::InvalidState && !*evicted {
+                *evicted = true;
+                cache.stats.evictions += 1;
+                metrics::record_statement_cache(metrics::STATEMENT_CACHE_EVICTIONS);
+            }
+        }
+        result
+    }
+
+    /// Bind a batch of parameters, see [Statement::bind].
+    pub fn bind(&mut self, batch: RecordBatch) -> Result<()> {
+        let result = self.statement().bind(batch);
+        Self::check(self.cache, &mut self.evicted, result)
+    }
+
+    /// Bind a stream of parameters, see [Statement::bind_stream].
+    pub fn bind_stream(&mut self, reader: Box<dyn RecordBatchReader + Send>) -> Result<()> {
+        let result = self.statement().bind_stream(reader);
+        Self::check(self.cache, &mut self.evicted, result)
+    }
+
+    /// Execute the statement and get the result set, see [Statement::execute].
+    pub fn execute(&mut self) -> Result<impl RecordBatchReader + Send + '_> {
+        let result = self.statement.as_mut().unwrap().execute();
+        Self::check(self.cache, &mut self.evicted, result)
+    }
+
+    /// Execute the statement and get the number of affected rows, see
+    /// [Statement::execute_update].
+    pub fn execute_update(&mut self) -> Result<Option<i64>> {
+        let result = self.statement().execute_update();
+        Self::check(self.cache, &mut self.evicted, result)
+    }
+}
+
+impl<C: Connection> Drop for CachedStatement<'_, C> {
+    fn drop(&mut self) {
+        if let (Some(statement), false) = (self.statement.take(), self.evicted) {
+            self.cache.clock += 1;
+            let entry = Entry {
+                statement,
+                last_used: self.cache.clock,
+            };
+            self.cache
+                .entries
+                .insert(std::mem::take(&mut self.query), entry);
+        }
+    }
+}
@@ -0,0 +1,338 @@
+//! Tracing instrumentation of ADBC calls.
+//!
+//! With the `tracing` feature enabled, the [driver manager][crate::driver_manager]
+//! and the [driver exporter][crate::export_driver] open a span named `adbc`
+//! at the `INFO` level for every ADBC call. Spans have the following fields:
+//!
+//! Field            | Description
+//! -----------------|------------------------------------------------------------
+//! `method`         | Name of the C API function, e.g. `AdbcStatementExecuteQuery`.
+//! `driver`         | Name of the driver.
+//! `query`          | Text of the statement's SQL query, see [set_query_text].
+//! `duration_us`    | Duration of the call, in microseconds.
+//! `status`         | Status of the call, e.g. `Ok` or `InvalidArguments`.
+//! `rows`           | Number of rows read from the returned result set.
+//! `batches`        | Number of batches read from the returned result set.
+//! `stream_status`  | `Ok` if the result set was exhausted, `Error` if reading failed
+//!                  | and `Dropped` if it was released before being exhausted.
+//!
+//! For calls returning a result set, the span stays open until the result
+//! set is released, and `rows`, `batches` and `stream_status` are recorded
+//! at that point.
+//!
+//! Without the feature, this module is not available and calls are not
+//! instrumented.
+
+#[cfg(feature = "tracing")]
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::Instant;
+
+use arrow::datatypes::SchemaRef;
+use arrow::error::ArrowError;
+use arrow::record_batch::{RecordBatch, RecordBatchReader};
+
+use crate::error::Result;
+use crate::ffi::FFI_AdbcStatusCode;
+use crate::metrics;
+
+/// How query text is reported in spans.
+#[cfg(feature = "tracing")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum QueryText {
+    /// Report queries as they are.
+    Full,
+    /// Report queries with string and numeric literals replaced by `?`.
+    #[default]
+    Redacted,
+    /// Do not report queries.
+    Omitted,
+}
+
+#[cfg(feature = "tracing")]
+static QUERY_TEXT: AtomicU8 = AtomicU8::new(QueryText::Redacted as u8);
+
+/// Set how query text is reported in spans, [QueryText::Redacted] by default.
+#[cfg(feature = "tracing")]
+pub fn set_query_text(query_text: QueryText) {
+    QUERY_TEXT.store(query_text as u8, Ordering::Relaxed);
+}
+
+/// Get how query text is reported in spans.
+#[cfg(feature = "tracing")]
+pub fn query_text() -> QueryText {
+    match QUERY_TEXT.load(Ordering::Relaxed) {
+        value if value == QueryText::Full as u8 => QueryText::Full,
+        value if value == QueryText::Omitted as u8 => QueryText::Omitted,
+        _ => QueryText::Redacted,
+    }
+}
+
+/// Replace string and numeric literals of a SQL query with `?`.
+///
+/// Quoted identifiers and comments are preserved.
+#[cfg(feature = "tracing")]
+pub fn redact(query: &str) -> String {
+    let mut redacted = String::with_capacity(query.len());
+    let mut chars = query.chars().peekable();
+    // Whether the previous character may end an identifier or keyword, in
+    // which case digits belong to it.
+    let mut in_word = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                while let Some(c) = chars.next() {
+                    if c == '\'' {
+                        if chars.peek() == Some(&'\'') {
+                            chars.next();
+                        } else {
+                            break;
+                        }
+                    }
+                }
+                redacted.push('?');
+                in_word = false;
+            }
+            '"' => {
+                redacted.push(c);
+                for c in chars.by_ref() {
+                    redacted.push(c);
+                    if c == '"' {
+                        break;
+                    }
+                }
+                in_word = true;
+            }
+            '-' if chars.peek() == Some(&'-') => {
+                redacted.push(c);
+                for c in chars.by_ref() {
+                    redacted.push(c);
+                    if c == '\n' {
+                        break;
+                    }
+                }
+                in_word = false;
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                redacted.push(c);
+                let mut previous = None;
+                for c in chars.by_ref() {
+                    redacted.push(c);
+                    if previous == Some('*') && c == '/' {
+                        break;
+                    }
+                    previous = Some(c);
+                }
+                in_word = false;
+            }
+            c if c.is_ascii_digit() && !in_word => {
+                while chars
+                    .peek()
+                    .is_some_and(|c| c.is_ascii_alphanumeric() || *c == '.' || *c == '_')
+                {
+                    chars.next();
+                }
+                redacted.push('?');
+            }
+            c => {
+                redacted.push(c);
+                in_word = c.is_alphanumeric() || c == '_' || c == '$';
+            }
+        }
+    }
+    redacted
+}
+
+/// Run an ADBC call within a span and record its [metrics][crate::metrics].
+#[cfg_attr(not(feature = "driver_manager"), allow(dead_code))]
+pub(crate) fn call<T>(
+    method: &'static str,
+    driver: &str,
+    query: Option<&str>,
+    f: impl FnOnce() -> Result<T>,
+) -> Result<T> {
+    let start = Instant::now();
+    #[cfg(feature = "tracing")]
+    let result = {
+        let span = new_span(method, driver, query);
+        let result = span.in_scope(f);
+        span.record("duration_us", start.elapsed().as_micros() as u64);
+        match &result {
+            Ok(_) => span.record("status", "Ok"),
+            Err(error) => span.record("status", tracing::field::debug(error.status)),
+        };
+        result
+    };
+    #[cfg(not(feature = "tracing"))]
+    let result = {
+        let _ = query;
+        f()
+    };
+    let recorder = metrics::recorder();
+    let outcome = result.as_ref().map(|_| ());
+    metrics::record_call(
+        recorder.as_deref(),
+        method,
+        driver,
+        outcome,
+        start.elapsed(),
+    );
+    result
+}
+
+/// Run an exported ADBC function within a span.
+pub(crate) fn ffi_call(
+    method: &'static str,
+    driver: &str,
+    f: impl FnOnce() -> FFI_AdbcStatusCode,
+) -> FFI_AdbcStatusCode {
+    #[cfg(feature = "tracing")]
+    {
+        let span = new_span(method, driver, None);
+        let start = Instant::now();
+        let status = span.in_scope(f);
+        span.record("duration_us", start.elapsed().as_micros() as u64);
+        match crate::error::Status::try_from(status) {
+            Ok(status) => span.record("status", tracing::field::debug(status)),
+            Err(_) => span.record("status", status),
+        };
+        status
+    }
+    #[cfg(not(feature = "tracing"))]
+    {
+        let _ = (method, driver);
+        f()
+    }
+}
+
+/// Record the query of the statement on the current span.
+pub(crate) fn record_query(query: Option<&str>) {
+    #[cfg(feature = "tracing")]
+    if let Some(query) = query {
+        record_query_on(&tracing::Span::current(), query);
+    }
+    #[cfg(not(feature = "tracing"))]
+    let _ = query;
+}
+
+#[cfg(feature = "tracing")]
+fn new_span(method: &'static str, driver: &str, query: Option<&str>) -> tracing::Span {
+    let span = tracing::info_span!(
+        "adbc",
+        method,
+        driver,
+        query = tracing::field::Empty,
+        duration_us = tracing::field::Empty,
+        status = tracing::field::Empty,
+        rows = tracing::field::Empty,
+        batches = tracing::field::Empty,
+        stream_status = tracing::field::Empty,
+    );
+    if let Some(query) = query {
+        record_query_on(&span, query);
+    }
+    span
+}
+
+#[cfg(feature = "tracing")]
+fn record_query_on(span: &tracing::Span, query: &str) {
+    match query_text() {
+        QueryText::Full => span.record("query", query),
+        QueryText::Redacted => span.record("query", redact(query).as_str()),
+        QueryText::Omitted => span,
+    };
+}
+
+/// A result set keeping the span of the call which returned it open, and
+/// recording how much data was read from it.
+pub(crate) struct TracedReader<R> {
+    inner: R,
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+    rows: usize,
+    batches: usize,
+    done: bool,
+    start: Instant,
+}
+
+impl<R> TracedReader<R> {
+    /// Wrap a result set returned within the span of the current call.
+    pub(crate) fn new(inner: R) -> Self {
+        Self {
+            inner,
+            #[cfg(feature = "tracing")]
+            span: tracing::Span::current(),
+            rows: 0,
+            batches: 0,
+            done: false,
+            start: Instant::now(),
+        }
+    }
+
+    fn finish(&mut self, stream_status: &str) {
+        if self.done {
+            return;
+        }
+        self.done = true;
+        #[cfg(feature = "tracing")]
+        {
+            self.span.record("rows", self.rows as u64);
+            self.span.record("batches", self.batches as u64);
+            self.span.record("stream_status", stream_status);
+            tracing::debug!(
+                parent: &self.span,
+                stream_duration_us = self.start.elapsed().as_micros() as u64,
+                "result set released"
+            );
+        }
+        #[cfg(not(feature = "tracing"))]
+        let _ = (stream_status, self.start);
+    }
+}
+
+impl<R: RecordBatchReader> Iterator for TracedReader<R> {
+    type Item = std::result::Result<RecordBatch, ArrowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let batch = self.inner.next();
+        match &batch {
+            Some(Ok(batch)) => {
+                self.rows += batch.num_rows();
+                self.batches += 1;
+            }
+            Some(Err(_)) => self.finish("Error"),
+            None => self.finish("Ok"),
+        }
+        batch
+    }
+}
+
+impl<R: RecordBatchReader> RecordBatchReader for TracedReader<R> {
+    fn schema(&self) -> SchemaRef {
+        self.inner.schema()
+    }
+}
+
+impl<R> Drop for TracedReader<R> {
+    fn drop(&mut self) {
+        self.finish("Dropped");
+    }
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact() {
+        assert_eq!(
+            redact("SELECT * FROM t1 WHERE name = 'O''Brien' AND id IN (1, 2.5e3, -3)"),
+            "SELECT * FROM t1 WHERE name = ? AND id IN (?, ?, -?)"
+        );
+        assert_eq!(
+            redact(r#"SELECT "col 1" FROM x -- 'kept'"#),
+            r#"SELECT "col 1" FROM x -- 'kept'"#
+        );
+        assert_eq!(redact("SELECT $1, /* 42 */ 42"), "SELECT $1, /* 42 */ ?");
+    }
+}
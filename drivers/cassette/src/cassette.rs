@@ -0,0 +1,568 @@
+//! Cassette storage and its Arrow IPC file format.
+
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Cursor, Read, Seek, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use arrow::array::{
+    Array, ArrayRef, AsArray, BinaryBuilder, Int32Builder, Int64Builder, ListBuilder, RecordBatch,
+    RecordBatchReader, StringBuilder, UInt8Builder,
+};
+use arrow::datatypes::{DataType, Field, Int32Type, Int64Type, Schema, SchemaRef, UInt8Type};
+use arrow::error::ArrowError;
+use arrow::ipc::reader::{FileReader, StreamReader};
+use arrow::ipc::writer::{FileWriter, StreamWriter};
+
+use adbc_core::error::{Error, Result, Status};
+use adbc_core::ffi::FFI_AdbcStatusCode;
+use adbc_core::layer::Method;
+use adbc_core::options::{InfoCode, ObjectDepth, OptionDatabase, OptionValue};
+use adbc_core::secret;
+
+/// Schema metadata key holding the version of the cassette format.
+const VERSION_KEY: &str = "adbc.cassette.version";
+const VERSION: &str = "1";
+
+/// What identifies a call: replayed calls are matched against recorded ones
+/// on all of these fields.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Request {
+    /// Name of the [Method] of the call.
+    pub method: String,
+    /// Query text of statements, or canonical arguments of connection methods.
+    pub key: Option<String>,
+    /// Options in effect, see [describe_option].
+    pub options: Vec<String>,
+    /// Bound parameters, as an Arrow IPC stream.
+    pub parameters: Option<Vec<u8>>,
+}
+
+impl Request {
+    pub(crate) fn new(method: Method) -> Self {
+        Self {
+            method: format!("{method:?}"),
+            key: None,
+            options: Vec::new(),
+            parameters: None,
+        }
+    }
+
+    pub(crate) fn with_key(mut self, key: impl Into<String>) -> Self {
+        self.key = Some(key.into());
+        self
+    }
+
+    pub(crate) fn with_options(mut self, options: Vec<String>) -> Self {
+        self.options = options;
+        self
+    }
+
+    fn describe(&self) -> String {
+        match &self.key {
+            Some(key) => format!("{} ({key})", self.method),
+            None => self.method.clone(),
+        }
+    }
+}
+
+/// What a call returned.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Response {
+    /// Result set or schema, as an Arrow IPC stream.
+    pub result: Option<Vec<u8>>,
+    pub partitions: Option<Vec<Vec<u8>>>,
+    pub rows_affected: Option<i64>,
+    /// Error returned by the call, or raised while reading its result set.
+    pub error: Option<Error>,
+}
+
+impl Response {
+    pub(crate) fn from_error(error: Error) -> Self {
+        Self {
+            error: Some(error),
+            ..Default::default()
+        }
+    }
+
+    /// The error of a call which failed without returning anything.
+    pub(crate) fn check(&self) -> Result<()> {
+        match (&self.error, &self.result) {
+            (Some(error), None) => Err(error.clone()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Interaction {
+    request: Request,
+    response: Response,
+    replayed: bool,
+}
+
+/// A sequence of recorded interactions with a driver.
+///
+/// Cassettes are cheap to clone: clones share the same interactions.
+#[derive(Debug, Clone, Default)]
+pub struct Cassette {
+    interactions: Arc<Mutex<Vec<Interaction>>>,
+}
+
+impl Cassette {
+    /// Create an empty cassette.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load a cassette from an Arrow IPC file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let file = File::open(path.as_ref()).map_err(|error| {
+            Error::with_message_and_status(
+                format!(
+                    "Failed to open cassette {}: {error}",
+                    path.as_ref().display()
+                ),
+                Status::IO,
+            )
+        })?;
+        Self::read(BufReader::new(file))
+    }
+
+    /// Save the cassette to an Arrow IPC file.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let file = File::create(path.as_ref()).map_err(|error| {
+            Error::with_message_and_status(
+                format!(
+                    "Failed to create cassette {}: {error}",
+                    path.as_ref().display()
+                ),
+                Status::IO,
+            )
+        })?;
+        self.write(BufWriter::new(file))
+    }
+
+    /// Read a cassette in the Arrow IPC file format.
+    pub fn read(reader: impl Read + Seek) -> Result<Self> {
+        let reader = FileReader::try_new(reader, None)?;
+        match reader.schema().metadata().get(VERSION_KEY) {
+            Some(version) if version == VERSION => {}
+            version => {
+                return Err(Error::with_message_and_status(
+                    format!("Unsupported cassette version: {version:?}"),
+                    Status::InvalidData,
+                ))
+            }
+        }
+        let mut interactions = Vec::new();
+        for batch in reader {
+            interactions.extend(decode_interactions(&batch?)?);
+        }
+        Ok(Self {
+            interactions: Arc::new(Mutex::new(interactions)),
+        })
+    }
+
+    /// Write the cassette in the Arrow IPC file format.
+    pub fn write(&self, writer: impl Write) -> Result<()> {
+        let schema = Arc::new(cassette_schema());
+        let batch = encode_interactions(&schema, &self.interactions.lock().unwrap())?;
+        let mut writer = FileWriter::try_new(writer, &schema)?;
+        writer.write(&batch)?;
+        writer.finish()?;
+        Ok(())
+    }
+
+    /// Number of interactions.
+    pub fn len(&self) -> usize {
+        self.interactions.lock().unwrap().len()
+    }
+
+    /// Whether the cassette has no interaction.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of interactions which have not been replayed yet.
+    pub fn remaining(&self) -> usize {
+        let interactions = self.interactions.lock().unwrap();
+        interactions.iter().filter(|i| !i.replayed).count()
+    }
+
+    pub(crate) fn record(&self, request: Request, response: Response) {
+        self.interactions.lock().unwrap().push(Interaction {
+            request,
+            response,
+            replayed: false,
+        });
+    }
+
+    /// Take the first interaction matching `request` not replayed yet.
+    pub(crate) fn find(&self, request: &Request) -> Option<Response> {
+        let mut interactions = self.interactions.lock().unwrap();
+        let interaction = interactions
+            .iter_mut()
+            .find(|i| !i.replayed && i.request == *request)?;
+        interaction.replayed = true;
+        Some(interaction.response.clone())
+    }
+
+    /// Like [Cassette::find], but fail if no interaction matches.
+    pub(crate) fn replay(&self, request: &Request) -> Result<Response> {
+        self.find(request).ok_or_else(|| {
+            Error::with_message_and_status(
+                format!("No recorded interaction for {}", request.describe()),
+                Status::NotFound,
+            )
+        })
+    }
+}
+
+/// Canonical description of an option, with credentials redacted.
+pub(crate) fn describe_option(key: &str, value: &OptionValue) -> String {
+    if key == OptionDatabase::Uri.as_ref() || secret::is_secret(key) {
+        format!("{key}=<redacted>")
+    } else {
+        format!("{key}={value:?}")
+    }
+}
+
+/// State of a statement determining the requests of its executions.
+#[derive(Debug, Default)]
+pub(crate) struct StatementState {
+    key: Option<String>,
+    options: BTreeMap<String, String>,
+    parameters: Option<Vec<u8>>,
+}
+
+impl StatementState {
+    pub(crate) fn set_sql_query(&mut self, query: &str) {
+        self.key = Some(query.to_string());
+    }
+
+    pub(crate) fn set_substrait_plan(&mut self, plan: &[u8]) {
+        self.key = Some(format!("substrait:{}", hex(plan)));
+    }
+
+    pub(crate) fn set_option(&mut self, key: &str, value: &OptionValue) {
+        self.options
+            .insert(key.to_string(), describe_option(key, value));
+    }
+
+    pub(crate) fn bind(&mut self, schema: &Schema, batches: &[RecordBatch]) -> Result<()> {
+        self.parameters = Some(encode_batches(schema, batches)?);
+        Ok(())
+    }
+
+    pub(crate) fn request(&self, method: Method) -> Request {
+        Request {
+            key: self.key.clone(),
+            options: self.options.values().cloned().collect(),
+            parameters: self.parameters.clone(),
+            ..Request::new(method)
+        }
+    }
+}
+
+/// Request of [Connection::get_info][adbc_core::Connection::get_info].
+pub(crate) fn get_info_request(codes: &Option<HashSet<InfoCode>>) -> Request {
+    let request = Request::new(Method::ConnectionGetInfo);
+    match codes {
+        Some(codes) => {
+            let mut codes: Vec<u32> = codes.iter().map(u32::from).collect();
+            codes.sort();
+            request.with_key(format!("{codes:?}"))
+        }
+        None => request,
+    }
+}
+
+/// Request of [Connection::get_objects][adbc_core::Connection::get_objects].
+pub(crate) fn get_objects_request(
+    depth: &ObjectDepth,
+    catalog: Option<&str>,
+    db_schema: Option<&str>,
+    table_name: Option<&str>,
+    table_type: &Option<Vec<&str>>,
+    column_name: Option<&str>,
+) -> Request {
+    Request::new(Method::ConnectionGetObjects).with_key(format!(
+        "depth={depth:?} catalog={catalog:?} db_schema={db_schema:?} table_name={table_name:?} \
+         table_type={table_type:?} column_name={column_name:?}"
+    ))
+}
+
+/// Request of [Connection::get_table_schema][adbc_core::Connection::get_table_schema].
+pub(crate) fn get_table_schema_request(
+    catalog: Option<&str>,
+    db_schema: Option<&str>,
+    table_name: &str,
+) -> Request {
+    Request::new(Method::ConnectionGetTableSchema).with_key(format!(
+        "catalog={catalog:?} db_schema={db_schema:?} table_name={table_name:?}"
+    ))
+}
+
+/// Request of [Connection::get_statistics][adbc_core::Connection::get_statistics].
+pub(crate) fn get_statistics_request(
+    catalog: Option<&str>,
+    db_schema: Option<&str>,
+    table_name: Option<&str>,
+    approximate: bool,
+) -> Request {
+    Request::new(Method::ConnectionGetStatistics).with_key(format!(
+        "catalog={catalog:?} db_schema={db_schema:?} table_name={table_name:?} \
+         approximate={approximate}"
+    ))
+}
+
+/// Request of [Connection::read_partition][adbc_core::Connection::read_partition].
+pub(crate) fn read_partition_request(partition: &[u8]) -> Request {
+    Request::new(Method::ConnectionReadPartition).with_key(hex(partition))
+}
+
+/// Request of a call setting a single option, identified by its description.
+pub(crate) fn option_request(method: Method, key: &str, value: &OptionValue) -> Request {
+    Request::new(method).with_options(vec![describe_option(key, value)])
+}
+
+/// Request of a call setting options, identified by their descriptions.
+pub(crate) fn options_request<K: AsRef<str>>(
+    method: Method,
+    options: &[(K, OptionValue)],
+) -> Request {
+    let mut options: Vec<_> = options
+        .iter()
+        .map(|(key, value)| describe_option(key.as_ref(), value))
+        .collect();
+    options.sort();
+    Request::new(method).with_options(options)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Encode batches as an Arrow IPC stream.
+pub(crate) fn encode_batches(schema: &Schema, batches: &[RecordBatch]) -> Result<Vec<u8>> {
+    let mut writer = StreamWriter::try_new(Vec::new(), schema)?;
+    for batch in batches {
+        writer.write(batch)?;
+    }
+    writer.finish()?;
+    Ok(writer.into_inner()?)
+}
+
+/// Decode an Arrow IPC stream.
+pub(crate) fn decode_batches(bytes: &[u8]) -> Result<(SchemaRef, Vec<RecordBatch>)> {
+    let reader = StreamReader::try_new(Cursor::new(bytes), None)?;
+    let schema = reader.schema();
+    let batches = reader.collect::<std::result::Result<_, _>>()?;
+    Ok((schema, batches))
+}
+
+/// Read a result set to completion, keeping the error which ended it if any.
+pub(crate) fn drain(
+    reader: impl RecordBatchReader,
+) -> (SchemaRef, Vec<RecordBatch>, Option<Error>) {
+    let schema = reader.schema();
+    let mut batches = Vec::new();
+    for batch in reader {
+        match batch {
+            Ok(batch) => batches.push(batch),
+            Err(error) => return (schema, batches, Some(from_arrow_error(error))),
+        }
+    }
+    (schema, batches, None)
+}
+
+fn from_arrow_error(error: ArrowError) -> Error {
+    match error {
+        ArrowError::ExternalError(error) => match error.downcast::<Error>() {
+            Ok(error) => *error,
+            Err(error) => Error::with_message_and_status(error.to_string(), Status::Internal),
+        },
+        error => error.into(),
+    }
+}
+
+/// A result set served from memory, ending with an error if reading the
+/// original result set failed.
+pub struct CassetteReader {
+    schema: SchemaRef,
+    batches: VecDeque<RecordBatch>,
+    error: Option<Error>,
+}
+
+impl CassetteReader {
+    pub(crate) fn new(schema: SchemaRef, batches: Vec<RecordBatch>, error: Option<Error>) -> Self {
+        Self {
+            schema,
+            batches: batches.into(),
+            error,
+        }
+    }
+
+    pub(crate) fn from_response(response: Response) -> Result<Self> {
+        response.check()?;
+        let bytes = response.result.unwrap_or_default();
+        let (schema, batches) = decode_batches(&bytes)?;
+        Ok(Self::new(schema, batches, response.error))
+    }
+}
+
+impl Iterator for CassetteReader {
+    type Item = std::result::Result<RecordBatch, ArrowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.batches.pop_front() {
+            Some(batch) => Some(Ok(batch)),
+            None => self
+                .error
+                .take()
+                .map(|error| Err(ArrowError::ExternalError(Box::new(error)))),
+        }
+    }
+}
+
+impl RecordBatchReader for CassetteReader {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+fn cassette_schema() -> Schema {
+    let list = |data_type| DataType::List(Arc::new(Field::new("item", data_type, true)));
+    Schema::new(vec![
+        Field::new("method", DataType::Utf8, false),
+        Field::new("key", DataType::Utf8, true),
+        Field::new("options", list(DataType::Utf8), true),
+        Field::new("parameters", DataType::Binary, true),
+        Field::new("result", DataType::Binary, true),
+        Field::new("partitions", list(DataType::Binary), true),
+        Field::new("rows_affected", DataType::Int64, true),
+        Field::new("status", DataType::UInt8, true),
+        Field::new("message", DataType::Utf8, true),
+        Field::new("vendor_code", DataType::Int32, true),
+        Field::new("sqlstate", DataType::Binary, true),
+    ])
+    .with_metadata([(VERSION_KEY.into(), VERSION.into())].into())
+}
+
+fn encode_interactions(schema: &SchemaRef, interactions: &[Interaction]) -> Result<RecordBatch> {
+    let mut method = StringBuilder::new();
+    let mut key = StringBuilder::new();
+    let mut options = ListBuilder::new(StringBuilder::new());
+    let mut parameters = BinaryBuilder::new();
+    let mut result = BinaryBuilder::new();
+    let mut partitions = ListBuilder::new(BinaryBuilder::new());
+    let mut rows_affected = Int64Builder::new();
+    let mut status = UInt8Builder::new();
+    let mut message = StringBuilder::new();
+    let mut vendor_code = Int32Builder::new();
+    let mut sqlstate = BinaryBuilder::new();
+
+    for Interaction {
+        request, response, ..
+    } in interactions
+    {
+        method.append_value(&request.method);
+        key.append_option(request.key.as_ref());
+        options.append_value(request.options.iter().map(Some));
+        parameters.append_option(request.parameters.as_ref());
+        result.append_option(response.result.as_ref());
+        partitions.append_option(response.partitions.as_ref().map(|p| p.iter().map(Some)));
+        rows_affected.append_option(response.rows_affected);
+        let error = response.error.as_ref();
+        status.append_option(error.map(|e| FFI_AdbcStatusCode::from(e.status)));
+        message.append_option(error.map(|e| &e.message));
+        vendor_code.append_option(error.map(|e| e.vendor_code));
+        sqlstate.append_option(error.map(|e| e.sqlstate.map(|c| c as u8)));
+    }
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(method.finish()),
+        Arc::new(key.finish()),
+        Arc::new(options.finish()),
+        Arc::new(parameters.finish()),
+        Arc::new(result.finish()),
+        Arc::new(partitions.finish()),
+        Arc::new(rows_affected.finish()),
+        Arc::new(status.finish()),
+        Arc::new(message.finish()),
+        Arc::new(vendor_code.finish()),
+        Arc::new(sqlstate.finish()),
+    ];
+    Ok(RecordBatch::try_new(schema.clone(), columns)?)
+}
+
+fn decode_interactions(batch: &RecordBatch) -> Result<Vec<Interaction>> {
+    let column = |name: &str| {
+        batch.column_by_name(name).ok_or_else(|| {
+            Error::with_message_and_status(
+                format!("Cassette is missing column {name}"),
+                Status::InvalidData,
+            )
+        })
+    };
+    let method = column("method")?.as_string::<i32>();
+    let key = column("key")?.as_string::<i32>();
+    let options = column("options")?.as_list::<i32>();
+    let parameters = column("parameters")?.as_binary::<i32>();
+    let result = column("result")?.as_binary::<i32>();
+    let partitions = column("partitions")?.as_list::<i32>();
+    let rows_affected = column("rows_affected")?.as_primitive::<Int64Type>();
+    let status = column("status")?.as_primitive::<UInt8Type>();
+    let message = column("message")?.as_string::<i32>();
+    let vendor_code = column("vendor_code")?.as_primitive::<Int32Type>();
+    let sqlstate = column("sqlstate")?.as_binary::<i32>();
+
+    let optional = |array: &dyn Array, row: usize| array.is_valid(row).then_some(row);
+    (0..batch.num_rows())
+        .map(|row| {
+            let request = Request {
+                method: method.value(row).to_string(),
+                key: optional(key, row).map(|row| key.value(row).to_string()),
+                options: options
+                    .value(row)
+                    .as_string::<i32>()
+                    .iter()
+                    .flatten()
+                    .map(String::from)
+                    .collect(),
+                parameters: optional(parameters, row).map(|row| parameters.value(row).to_vec()),
+            };
+            let error = match optional(status, row) {
+                Some(row) => {
+                    let mut error = Error::with_message_and_status(
+                        message.value(row),
+                        Status::try_from(status.value(row))?,
+                    );
+                    error.vendor_code = vendor_code.value(row);
+                    if let Ok(value) = <[u8; 5]>::try_from(sqlstate.value(row)) {
+                        error.sqlstate = value.map(|c| c as _);
+                    }
+                    Some(error)
+                }
+                None => None,
+            };
+            let response = Response {
+                result: optional(result, row).map(|row| result.value(row).to_vec()),
+                partitions: optional(partitions, row).map(|row| {
+                    let values = partitions.value(row);
+                    let values = values.as_binary::<i32>();
+                    values.iter().flatten().map(<[u8]>::to_vec).collect()
+                }),
+                rows_affected: optional(rows_affected, row).map(|row| rows_affected.value(row)),
+                error,
+            };
+            Ok(Interaction {
+                request,
+                response,
+                replayed: false,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,64 @@
+//! An ADBC driver recording and replaying interactions with other drivers.
+//!
+//! [RecordingDriver] wraps any [Driver][adbc_core::Driver] and records the calls made to it,
+//! along with their results, into a [Cassette]: SQL queries, bound parameters,
+//! options, result sets, row counts and errors. The cassette is saved as an
+//! Arrow IPC file, and [ReplayDriver] later serves the recorded results
+//! without the original database, making tests relying on it deterministic
+//! and runnable offline.
+//!
+//! When replaying, a call is matched against the first interaction not
+//! replayed yet with the same method, query (or arguments for connection
+//! methods), statement options and bound parameters:
+//! - Calls returning data (executions, metadata and schemas) fail with
+//!   [Status::NotFound] if no interaction matches.
+//! - Calls changing state (initialization, options, binding, preparation
+//!   and transactions) replay the recorded error if any, and otherwise succeed.
+//!
+//! Result sets are read to completion when recorded, so an error raised while
+//! reading them is replayed after the batches preceding it. Cancellation is
+//! not recorded, and the values of the URI and password options are redacted.
+//!
+//! The replay driver can be exported, the cassette to replay being given
+//! with the [OPTION_PATH] database option.
+//!
+//! # Example
+//!
+//! ```rust
+//! use adbc_cassette::{Cassette, RecordingDriver, ReplayDriver};
+//! use adbc_core::{Connection, Database, Driver, Statement};
+//! use adbc_dummy::DummyDriver;
+//!
+//! let cassette = Cassette::new();
+//! let mut driver = RecordingDriver::new(DummyDriver::default(), cassette.clone());
+//! let mut statement = driver.new_database()?.new_connection()?.new_statement()?;
+//! statement.set_sql_query("SELECT 1")?;
+//! let recorded: Vec<_> = statement.execute()?.collect();
+//!
+//! let mut buffer = std::io::Cursor::new(Vec::new());
+//! cassette.write(&mut buffer)?;
+//! buffer.set_position(0);
+//!
+//! let mut driver = ReplayDriver::new(Cassette::read(buffer)?);
+//! let mut statement = driver.new_database()?.new_connection()?.new_statement()?;
+//! statement.set_sql_query("SELECT 1")?;
+//! let replayed: Vec<_> = statement.execute()?.collect();
+//! assert_eq!(format!("{recorded:?}"), format!("{replayed:?}"));
+//! # Ok::<(), adbc_core::error::Error>(())
+//! ```
+
+// The driver exporter expects these in scope.
+use adbc_core::error::{Error, Status};
+
+mod cassette;
+mod record;
+mod replay;
+
+pub use cassette::{Cassette, CassetteReader};
+pub use record::{RecordingConnection, RecordingDatabase, RecordingDriver, RecordingStatement};
+pub use replay::{ReplayConnection, ReplayDatabase, ReplayDriver, ReplayStatement};
+
+/// Database option setting the path of the cassette to replay.
+pub const OPTION_PATH: &str = "adbc.cassette.path";
+
+adbc_core::export_driver!(AdbcCassetteInit, ReplayDriver);
@@ -0,0 +1,424 @@
+//! Driver recording interactions with another driver.
+
+use std::collections::HashSet;
+
+use arrow::array::{RecordBatch, RecordBatchReader};
+use arrow::datatypes::Schema;
+
+use adbc_core::error::Result;
+use adbc_core::layer::Method;
+use adbc_core::options::{
+    InfoCode, ObjectDepth, OptionConnection, OptionDatabase, OptionStatement, OptionValue,
+};
+use adbc_core::secret::Secret;
+use adbc_core::{Connection, Database, Driver, Optionable, PartitionedResult, Statement};
+
+use crate::cassette::{
+    drain, encode_batches, get_info_request, get_objects_request, get_statistics_request,
+    get_table_schema_request, option_request, options_request, read_partition_request, Cassette,
+    CassetteReader, Request, Response, StatementState,
+};
+
+/// Record the outcome of a call returning no data.
+fn record<T>(cassette: &Cassette, request: Request, result: Result<T>) -> Result<T> {
+    let response = match &result {
+        Ok(_) => Response::default(),
+        Err(error) => Response::from_error(error.clone()),
+    };
+    cassette.record(request, response);
+    result
+}
+
+/// Record a call returning a result set, which is read to completion.
+fn record_reader(
+    cassette: &Cassette,
+    request: Request,
+    result: Result<impl RecordBatchReader>,
+) -> Result<CassetteReader> {
+    let reader = match result {
+        Ok(reader) => reader,
+        Err(error) => return record(cassette, request, Err(error)),
+    };
+    let (schema, batches, error) = drain(reader);
+    let response = Response {
+        result: Some(encode_batches(&schema, &batches)?),
+        error: error.clone(),
+        ..Default::default()
+    };
+    cassette.record(request, response);
+    Ok(CassetteReader::new(schema, batches, error))
+}
+
+/// Record a call returning a schema.
+fn record_schema(cassette: &Cassette, request: Request, result: Result<Schema>) -> Result<Schema> {
+    let schema = match result {
+        Ok(schema) => schema,
+        Err(error) => return record(cassette, request, Err(error)),
+    };
+    let response = Response {
+        result: Some(encode_batches(&schema, &[])?),
+        ..Default::default()
+    };
+    cassette.record(request, response);
+    Ok(schema)
+}
+
+/// A driver recording the interactions with the driver it wraps into a
+/// [Cassette].
+#[derive(Debug)]
+pub struct RecordingDriver<D> {
+    inner: D,
+    cassette: Cassette,
+}
+
+impl<D> RecordingDriver<D> {
+    /// Record the interactions with `inner` into `cassette`.
+    pub fn new(inner: D, cassette: Cassette) -> Self {
+        Self { inner, cassette }
+    }
+
+    /// The cassette interactions are recorded into.
+    pub fn cassette(&self) -> &Cassette {
+        &self.cassette
+    }
+}
+
+impl<D: Driver> Driver for RecordingDriver<D> {
+    type DatabaseType = RecordingDatabase<D::DatabaseType>;
+
+    fn new_database(&mut self) -> Result<Self::DatabaseType> {
+        self.new_database_with_opts(None)
+    }
+
+    fn new_database_with_opts(
+        &mut self,
+        opts: impl IntoIterator<Item = (OptionDatabase, OptionValue)>,
+    ) -> Result<Self::DatabaseType> {
+        let opts: Vec<_> = opts.into_iter().collect();
+        let request = options_request(Method::DatabaseInit, &opts);
+        let inner = record(
+            &self.cassette,
+            request,
+            self.inner.new_database_with_opts(opts),
+        )?;
+        Ok(RecordingDatabase {
+            inner,
+            cassette: self.cassette.clone(),
+        })
+    }
+}
+
+/// A database recording its interactions.
+#[derive(Debug)]
+pub struct RecordingDatabase<D> {
+    inner: D,
+    cassette: Cassette,
+}
+
+impl<D: Database> Optionable for RecordingDatabase<D> {
+    type Option = OptionDatabase;
+
+    fn set_option(&mut self, key: Self::Option, value: OptionValue) -> Result<()> {
+        let request = option_request(Method::DatabaseSetOption, key.as_ref(), &value);
+        record(&self.cassette, request, self.inner.set_option(key, value))
+    }
+
+    fn get_option_string(&self, key: Self::Option) -> Result<String> {
+        self.inner.get_option_string(key)
+    }
+
+    fn get_option_bytes(&self, key: Self::Option) -> Result<Vec<u8>> {
+        self.inner.get_option_bytes(key)
+    }
+
+    fn get_option_int(&self, key: Self::Option) -> Result<i64> {
+        self.inner.get_option_int(key)
+    }
+
+    fn get_option_double(&self, key: Self::Option) -> Result<f64> {
+        self.inner.get_option_double(key)
+    }
+}
+
+impl<D: Database> Database for RecordingDatabase<D> {
+    type ConnectionType = RecordingConnection<D::ConnectionType>;
+
+    fn new_connection(&mut self) -> Result<Self::ConnectionType> {
+        self.new_connection_with_opts(None)
+    }
+
+    fn new_connection_with_opts(
+        &mut self,
+        opts: impl IntoIterator<Item = (OptionConnection, OptionValue)>,
+    ) -> Result<Self::ConnectionType> {
+        let opts: Vec<_> = opts.into_iter().collect();
+        let request = options_request(Method::ConnectionInit, &opts);
+        let inner = record(
+            &self.cassette,
+            request,
+            self.inner.new_connection_with_opts(opts),
+        )?;
+        Ok(RecordingConnection {
+            inner,
+            cassette: self.cassette.clone(),
+        })
+    }
+}
+
+/// A connection recording its interactions.
+#[derive(Debug)]
+pub struct RecordingConnection<C> {
+    inner: C,
+    cassette: Cassette,
+}
+
+impl<C: Connection> Optionable for RecordingConnection<C> {
+    type Option = OptionConnection;
+
+    fn set_option(&mut self, key: Self::Option, value: OptionValue) -> Result<()> {
+        let request = option_request(Method::ConnectionSetOption, key.as_ref(), &value);
+        record(&self.cassette, request, self.inner.set_option(key, value))
+    }
+
+    fn get_option_string(&self, key: Self::Option) -> Result<String> {
+        self.inner.get_option_string(key)
+    }
+
+    fn get_option_bytes(&self, key: Self::Option) -> Result<Vec<u8>> {
+        self.inner.get_option_bytes(key)
+    }
+
+    fn get_option_int(&self, key: Self::Option) -> Result<i64> {
+        self.inner.get_option_int(key)
+    }
+
+    fn get_option_double(&self, key: Self::Option) -> Result<f64> {
+        self.inner.get_option_double(key)
+    }
+}
+
+impl<C: Connection> Connection for RecordingConnection<C> {
+    type StatementType = RecordingStatement<C::StatementType>;
+
+    fn new_statement(&mut self) -> Result<Self::StatementType> {
+        Ok(RecordingStatement {
+            inner: self.inner.new_statement()?,
+            cassette: self.cassette.clone(),
+            state: StatementState::default(),
+        })
+    }
+
+    fn cancel(&mut self) -> Result<()> {
+        self.inner.cancel()
+    }
+
+    fn get_info(&self, codes: Option<HashSet<InfoCode>>) -> Result<impl RecordBatchReader + Send> {
+        let request = get_info_request(&codes);
+        record_reader(&self.cassette, request, self.inner.get_info(codes))
+    }
+
+    fn get_objects(
+        &self,
+        depth: ObjectDepth,
+        catalog: Option<&str>,
+        db_schema: Option<&str>,
+        table_name: Option<&str>,
+        table_type: Option<Vec<&str>>,
+        column_name: Option<&str>,
+    ) -> Result<impl RecordBatchReader + Send> {
+        let request = get_objects_request(
+            &depth,
+            catalog,
+            db_schema,
+            table_name,
+            &table_type,
+            column_name,
+        );
+        let result = self.inner.get_objects(
+            depth,
+            catalog,
+            db_schema,
+            table_name,
+            table_type,
+            column_name,
+        );
+        record_reader(&self.cassette, request, result)
+    }
+
+    fn get_table_schema(
+        &self,
+        catalog: Option<&str>,
+        db_schema: Option<&str>,
+        table_name: &str,
+    ) -> Result<Schema> {
+        let request = get_table_schema_request(catalog, db_schema, table_name);
+        let result = self.inner.get_table_schema(catalog, db_schema, table_name);
+        record_schema(&self.cassette, request, result)
+    }
+
+    fn get_table_types(&self) -> Result<impl RecordBatchReader + Send> {
+        let request = Request::new(Method::ConnectionGetTableTypes);
+        record_reader(&self.cassette, request, self.inner.get_table_types())
+    }
+
+    fn get_statistic_names(&self) -> Result<impl RecordBatchReader + Send> {
+        let request = Request::new(Method::ConnectionGetStatisticNames);
+        record_reader(&self.cassette, request, self.inner.get_statistic_names())
+    }
+
+    fn get_statistics(
+        &self,
+        catalog: Option<&str>,
+        db_schema: Option<&str>,
+        table_name: Option<&str>,
+        approximate: bool,
+    ) -> Result<impl RecordBatchReader + Send> {
+        let request = get_statistics_request(catalog, db_schema, table_name, approximate);
+        let result = self
+            .inner
+            .get_statistics(catalog, db_schema, table_name, approximate);
+        record_reader(&self.cassette, request, result)
+    }
+
+    fn commit(&mut self) -> Result<()> {
+        let request = Request::new(Method::ConnectionCommit);
+        record(&self.cassette, request, self.inner.commit())
+    }
+
+    fn rollback(&mut self) -> Result<()> {
+        let request = Request::new(Method::ConnectionRollback);
+        record(&self.cassette, request, self.inner.rollback())
+    }
+
+    fn read_partition(&self, partition: impl AsRef<[u8]>) -> Result<impl RecordBatchReader + Send> {
+        let partition = partition.as_ref();
+        let request = read_partition_request(partition);
+        record_reader(
+            &self.cassette,
+            request,
+            self.inner.read_partition(partition),
+        )
+    }
+}
+
+/// A statement recording its interactions.
+#[derive(Debug)]
+pub struct RecordingStatement<S> {
+    inner: S,
+    cassette: Cassette,
+    state: StatementState,
+}
+
+impl<S: Statement> Optionable for RecordingStatement<S> {
+    type Option = OptionStatement;
+
+    fn set_option(&mut self, key: Self::Option, value: OptionValue) -> Result<()> {
+        let request = option_request(Method::StatementSetOption, key.as_ref(), &value);
+        let name = key.as_ref().to_string();
+        let value = Secret::new(value);
+        let result = self.inner.set_option(key, value.expose().clone());
+        record(&self.cassette, request, result)?;
+        self.state.set_option(&name, value.expose());
+        Ok(())
+    }
+
+    fn get_option_string(&self, key: Self::Option) -> Result<String> {
+        self.inner.get_option_string(key)
+    }
+
+    fn get_option_bytes(&self, key: Self::Option) -> Result<Vec<u8>> {
+        self.inner.get_option_bytes(key)
+    }
+
+    fn get_option_int(&self, key: Self::Option) -> Result<i64> {
+        self.inner.get_option_int(key)
+    }
+
+    fn get_option_double(&self, key: Self::Option) -> Result<f64> {
+        self.inner.get_option_double(key)
+    }
+}
+
+impl<S: Statement> Statement for RecordingStatement<S> {
+    fn bind(&mut self, batch: RecordBatch) -> Result<()> {
+        self.state
+            .bind(&batch.schema(), std::slice::from_ref(&batch))?;
+        let request = self.state.request(Method::StatementBind);
+        record(&self.cassette, request, self.inner.bind(batch))
+    }
+
+    fn bind_stream(&mut self, reader: Box<dyn RecordBatchReader + Send>) -> Result<()> {
+        let (schema, batches, error) = drain(reader);
+        self.state.bind(&schema, &batches)?;
+        let request = self.state.request(Method::StatementBindStream);
+        let reader = CassetteReader::new(schema, batches, error);
+        let result = self.inner.bind_stream(Box::new(reader));
+        record(&self.cassette, request, result)
+    }
+
+    fn execute(&mut self) -> Result<impl RecordBatchReader + Send> {
+        let request = self.state.request(Method::StatementExecuteQuery);
+        record_reader(&self.cassette, request, self.inner.execute())
+    }
+
+    fn execute_update(&mut self) -> Result<Option<i64>> {
+        let request = self.state.request(Method::StatementExecuteUpdate);
+        let rows_affected = match self.inner.execute_update() {
+            Ok(rows_affected) => rows_affected,
+            Err(error) => return record(&self.cassette, request, Err(error)),
+        };
+        let response = Response {
+            rows_affected,
+            ..Default::default()
+        };
+        self.cassette.record(request, response);
+        Ok(rows_affected)
+    }
+
+    fn execute_schema(&mut self) -> Result<Schema> {
+        let request = self.state.request(Method::StatementExecuteSchema);
+        record_schema(&self.cassette, request, self.inner.execute_schema())
+    }
+
+    fn execute_partitions(&mut self) -> Result<PartitionedResult> {
+        let request = self.state.request(Method::StatementExecutePartitions);
+        let result = match self.inner.execute_partitions() {
+            Ok(result) => result,
+            Err(error) => return record(&self.cassette, request, Err(error)),
+        };
+        let response = Response {
+            result: Some(encode_batches(&result.schema, &[])?),
+            partitions: Some(result.partitions.clone()),
+            rows_affected: Some(result.rows_affected),
+            error: None,
+        };
+        self.cassette.record(request, response);
+        Ok(result)
+    }
+
+    fn get_parameter_schema(&self) -> Result<Schema> {
+        let request = self.state.request(Method::StatementGetParameterSchema);
+        record_schema(&self.cassette, request, self.inner.get_parameter_schema())
+    }
+
+    fn prepare(&mut self) -> Result<()> {
+        let request = self.state.request(Method::StatementPrepare);
+        record(&self.cassette, request, self.inner.prepare())
+    }
+
+    fn set_sql_query(&mut self, query: impl AsRef<str>) -> Result<()> {
+        self.inner.set_sql_query(query.as_ref())?;
+        self.state.set_sql_query(query.as_ref());
+        Ok(())
+    }
+
+    fn set_substrait_plan(&mut self, plan: impl AsRef<[u8]>) -> Result<()> {
+        self.inner.set_substrait_plan(plan.as_ref())?;
+        self.state.set_substrait_plan(plan.as_ref());
+        Ok(())
+    }
+
+    fn cancel(&mut self) -> Result<()> {
+        self.inner.cancel()
+    }
+}
@@ -0,0 +1,467 @@
+//! Driver replaying recorded interactions.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use arrow::array::{RecordBatch, RecordBatchReader};
+use arrow::datatypes::Schema;
+
+use adbc_core::error::{Error, Result, Status};
+use adbc_core::layer::Method;
+use adbc_core::options::{
+    InfoCode, ObjectDepth, OptionConnection, OptionDatabase, OptionStatement, OptionValue,
+};
+use adbc_core::{secret, Connection, Database, Driver, Optionable, PartitionedResult, Statement};
+
+use crate::cassette::{
+    decode_batches, drain, get_info_request, get_objects_request, get_statistics_request,
+    get_table_schema_request, option_request, options_request, read_partition_request, Cassette,
+    CassetteReader, Request, Response, StatementState,
+};
+use crate::OPTION_PATH;
+
+/// Replay the outcome of a call changing state, which succeeds if it was
+/// not recorded.
+fn replay(cassette: &Cassette, request: Request) -> Result<()> {
+    match cassette.find(&request) {
+        Some(response) => response.check(),
+        None => Ok(()),
+    }
+}
+
+/// Replay a call returning a schema.
+fn replay_schema(cassette: &Cassette, request: Request) -> Result<Schema> {
+    let response = cassette.replay(&request)?;
+    response.check()?;
+    let (schema, _) = decode_batches(&response.result.unwrap_or_default())?;
+    Ok(schema.as_ref().clone())
+}
+
+/// Options set on a replayed object, whose secret values are zeroized on
+/// drop.
+#[derive(Debug)]
+struct Options<K: AsRef<str>> {
+    kind: &'static str,
+    values: HashMap<K, OptionValue>,
+}
+
+impl<K: AsRef<str>> Drop for Options<K> {
+    fn drop(&mut self) {
+        secret::zeroize_secrets(self.values.iter_mut());
+    }
+}
+
+impl<K: AsRef<str> + Hash + Eq> Options<K> {
+    fn new(kind: &'static str) -> Self {
+        Self {
+            kind,
+            values: HashMap::new(),
+        }
+    }
+
+    fn set(&mut self, key: K, value: OptionValue) {
+        self.values.insert(key, value);
+    }
+
+    fn get(&self, key: &K) -> Result<&OptionValue> {
+        self.values.get(key).ok_or_else(|| {
+            Error::with_message_and_status(
+                format!("Unrecognized {} option: {}", self.kind, key.as_ref()),
+                Status::NotFound,
+            )
+        })
+    }
+
+    fn get_string(&self, key: K) -> Result<String> {
+        match self.get(&key)? {
+            OptionValue::String(value) => Ok(value.clone()),
+            _ => Err(invalid_type(key.as_ref(), "string")),
+        }
+    }
+
+    fn get_bytes(&self, key: K) -> Result<Vec<u8>> {
+        match self.get(&key)? {
+            OptionValue::Bytes(value) => Ok(value.clone()),
+            _ => Err(invalid_type(key.as_ref(), "bytes")),
+        }
+    }
+
+    fn get_int(&self, key: K) -> Result<i64> {
+        match self.get(&key)? {
+            OptionValue::Int(value) => Ok(*value),
+            _ => Err(invalid_type(key.as_ref(), "int")),
+        }
+    }
+
+    fn get_double(&self, key: K) -> Result<f64> {
+        match self.get(&key)? {
+            OptionValue::Double(value) => Ok(*value),
+            _ => Err(invalid_type(key.as_ref(), "double")),
+        }
+    }
+}
+
+fn invalid_type(key: &str, expected: &str) -> Error {
+    Error::with_message_and_status(
+        format!("Option {key} is not of type {expected}"),
+        Status::InvalidData,
+    )
+}
+
+/// A driver serving the interactions recorded in a [Cassette].
+///
+/// The cassette is either given at construction or loaded from the path set
+/// with the [OPTION_PATH] database option.
+#[derive(Debug, Default)]
+pub struct ReplayDriver {
+    cassette: Option<Cassette>,
+}
+
+impl ReplayDriver {
+    /// Replay the interactions of `cassette`.
+    pub fn new(cassette: Cassette) -> Self {
+        Self {
+            cassette: Some(cassette),
+        }
+    }
+}
+
+impl Driver for ReplayDriver {
+    type DatabaseType = ReplayDatabase;
+
+    fn new_database(&mut self) -> Result<Self::DatabaseType> {
+        self.new_database_with_opts(None)
+    }
+
+    fn new_database_with_opts(
+        &mut self,
+        opts: impl IntoIterator<Item = (OptionDatabase, OptionValue)>,
+    ) -> Result<Self::DatabaseType> {
+        let mut cassette = self.cassette.clone();
+        let mut recorded = Vec::new();
+        let mut options = Options::new("database");
+        for (key, value) in opts {
+            if key.as_ref() == OPTION_PATH {
+                cassette = Some(Cassette::load(path(&value)?)?);
+            } else {
+                recorded.push((key.clone(), value.clone()));
+            }
+            options.set(key, value);
+        }
+        let cassette = cassette.ok_or_else(|| {
+            Error::with_message_and_status(
+                format!("No cassette to replay, set option {OPTION_PATH}"),
+                Status::InvalidState,
+            )
+        })?;
+        replay(&cassette, options_request(Method::DatabaseInit, &recorded))?;
+        Ok(ReplayDatabase { cassette, options })
+    }
+}
+
+fn path(value: &OptionValue) -> Result<&str> {
+    match value {
+        OptionValue::String(path) => Ok(path),
+        _ => Err(Error::with_message_and_status(
+            format!("Option {OPTION_PATH} must be a string"),
+            Status::InvalidArguments,
+        )),
+    }
+}
+
+/// A database replaying its interactions.
+#[derive(Debug)]
+pub struct ReplayDatabase {
+    cassette: Cassette,
+    options: Options<OptionDatabase>,
+}
+
+impl ReplayDatabase {
+    /// The cassette interactions are replayed from.
+    pub fn cassette(&self) -> &Cassette {
+        &self.cassette
+    }
+}
+
+impl Optionable for ReplayDatabase {
+    type Option = OptionDatabase;
+
+    fn set_option(&mut self, key: Self::Option, value: OptionValue) -> Result<()> {
+        let request = option_request(Method::DatabaseSetOption, key.as_ref(), &value);
+        replay(&self.cassette, request)?;
+        self.options.set(key, value);
+        Ok(())
+    }
+
+    fn get_option_string(&self, key: Self::Option) -> Result<String> {
+        self.options.get_string(key)
+    }
+
+    fn get_option_bytes(&self, key: Self::Option) -> Result<Vec<u8>> {
+        self.options.get_bytes(key)
+    }
+
+    fn get_option_int(&self, key: Self::Option) -> Result<i64> {
+        self.options.get_int(key)
+    }
+
+    fn get_option_double(&self, key: Self::Option) -> Result<f64> {
+        self.options.get_double(key)
+    }
+}
+
+impl Database for ReplayDatabase {
+    type ConnectionType = ReplayConnection;
+
+    fn new_connection(&mut self) -> Result<Self::ConnectionType> {
+        self.new_connection_with_opts(None)
+    }
+
+    fn new_connection_with_opts(
+        &mut self,
+        opts: impl IntoIterator<Item = (OptionConnection, OptionValue)>,
+    ) -> Result<Self::ConnectionType> {
+        let opts: Vec<_> = opts.into_iter().collect();
+        replay(
+            &self.cassette,
+            options_request(Method::ConnectionInit, &opts),
+        )?;
+        let mut options = Options::new("connection");
+        for (key, value) in opts {
+            options.set(key, value);
+        }
+        Ok(ReplayConnection {
+            cassette: self.cassette.clone(),
+            options,
+        })
+    }
+}
+
+/// A connection replaying its interactions.
+#[derive(Debug)]
+pub struct ReplayConnection {
+    cassette: Cassette,
+    options: Options<OptionConnection>,
+}
+
+impl Optionable for ReplayConnection {
+    type Option = OptionConnection;
+
+    fn set_option(&mut self, key: Self::Option, value: OptionValue) -> Result<()> {
+        let request = option_request(Method::ConnectionSetOption, key.as_ref(), &value);
+        replay(&self.cassette, request)?;
+        self.options.set(key, value);
+        Ok(())
+    }
+
+    fn get_option_string(&self, key: Self::Option) -> Result<String> {
+        self.options.get_string(key)
+    }
+
+    fn get_option_bytes(&self, key: Self::Option) -> Result<Vec<u8>> {
+        self.options.get_bytes(key)
+    }
+
+    fn get_option_int(&self, key: Self::Option) -> Result<i64> {
+        self.options.get_int(key)
+    }
+
+    fn get_option_double(&self, key: Self::Option) -> Result<f64> {
+        self.options.get_double(key)
+    }
+}
+
+impl Connection for ReplayConnection {
+    type StatementType = ReplayStatement;
+
+    fn new_statement(&mut self) -> Result<Self::StatementType> {
+        Ok(ReplayStatement {
+            cassette: self.cassette.clone(),
+            options: Options::new("statement"),
+            state: StatementState::default(),
+        })
+    }
+
+    fn cancel(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn get_info(&self, codes: Option<HashSet<InfoCode>>) -> Result<impl RecordBatchReader + Send> {
+        let response = self.cassette.replay(&get_info_request(&codes))?;
+        CassetteReader::from_response(response)
+    }
+
+    fn get_objects(
+        &self,
+        depth: ObjectDepth,
+        catalog: Option<&str>,
+        db_schema: Option<&str>,
+        table_name: Option<&str>,
+        table_type: Option<Vec<&str>>,
+        column_name: Option<&str>,
+    ) -> Result<impl RecordBatchReader + Send> {
+        let request = get_objects_request(
+            &depth,
+            catalog,
+            db_schema,
+            table_name,
+            &table_type,
+            column_name,
+        );
+        CassetteReader::from_response(self.cassette.replay(&request)?)
+    }
+
+    fn get_table_schema(
+        &self,
+        catalog: Option<&str>,
+        db_schema: Option<&str>,
+        table_name: &str,
+    ) -> Result<Schema> {
+        let request = get_table_schema_request(catalog, db_schema, table_name);
+        replay_schema(&self.cassette, request)
+    }
+
+    fn get_table_types(&self) -> Result<impl RecordBatchReader + Send> {
+        let request = Request::new(Method::ConnectionGetTableTypes);
+        CassetteReader::from_response(self.cassette.replay(&request)?)
+    }
+
+    fn get_statistic_names(&self) -> Result<impl RecordBatchReader + Send> {
+        let request = Request::new(Method::ConnectionGetStatisticNames);
+        CassetteReader::from_response(self.cassette.replay(&request)?)
+    }
+
+    fn get_statistics(
+        &self,
+        catalog: Option<&str>,
+        db_schema: Option<&str>,
+        table_name: Option<&str>,
+        approximate: bool,
+    ) -> Result<impl RecordBatchReader + Send> {
+        let request = get_statistics_request(catalog, db_schema, table_name, approximate);
+        CassetteReader::from_response(self.cassette.replay(&request)?)
+    }
+
+    fn commit(&mut self) -> Result<()> {
+        replay(&self.cassette, Request::new(Method::ConnectionCommit))
+    }
+
+    fn rollback(&mut self) -> Result<()> {
+        replay(&self.cassette, Request::new(Method::ConnectionRollback))
+    }
+
+    fn read_partition(&self, partition: impl AsRef<[u8]>) -> Result<impl RecordBatchReader + Send> {
+        let request = read_partition_request(partition.as_ref());
+        CassetteReader::from_response(self.cassette.replay(&request)?)
+    }
+}
+
+/// A statement replaying its interactions.
+#[derive(Debug)]
+pub struct ReplayStatement {
+    cassette: Cassette,
+    options: Options<OptionStatement>,
+    state: StatementState,
+}
+
+impl ReplayStatement {
+    fn replay(&self, method: Method) -> Result<Response> {
+        let response = self.cassette.replay(&self.state.request(method))?;
+        response.check()?;
+        Ok(response)
+    }
+}
+
+impl Optionable for ReplayStatement {
+    type Option = OptionStatement;
+
+    fn set_option(&mut self, key: Self::Option, value: OptionValue) -> Result<()> {
+        let request = option_request(Method::StatementSetOption, key.as_ref(), &value);
+        replay(&self.cassette, request)?;
+        self.state.set_option(key.as_ref(), &value);
+        self.options.set(key, value);
+        Ok(())
+    }
+
+    fn get_option_string(&self, key: Self::Option) -> Result<String> {
+        self.options.get_string(key)
+    }
+
+    fn get_option_bytes(&self, key: Self::Option) -> Result<Vec<u8>> {
+        self.options.get_bytes(key)
+    }
+
+    fn get_option_int(&self, key: Self::Option) -> Result<i64> {
+        self.options.get_int(key)
+    }
+
+    fn get_option_double(&self, key: Self::Option) -> Result<f64> {
+        self.options.get_double(key)
+    }
+}
+
+impl Statement for ReplayStatement {
+    fn bind(&mut self, batch: RecordBatch) -> Result<()> {
+        self.state.bind(&batch.schema(), &[batch])?;
+        replay(&self.cassette, self.state.request(Method::StatementBind))
+    }
+
+    fn bind_stream(&mut self, reader: Box<dyn RecordBatchReader + Send>) -> Result<()> {
+        let (schema, batches, error) = drain(reader);
+        if let Some(error) = error {
+            return Err(error);
+        }
+        self.state.bind(&schema, &batches)?;
+        replay(
+            &self.cassette,
+            self.state.request(Method::StatementBindStream),
+        )
+    }
+
+    fn execute(&mut self) -> Result<impl RecordBatchReader + Send> {
+        let request = self.state.request(Method::StatementExecuteQuery);
+        CassetteReader::from_response(self.cassette.replay(&request)?)
+    }
+
+    fn execute_update(&mut self) -> Result<Option<i64>> {
+        Ok(self.replay(Method::StatementExecuteUpdate)?.rows_affected)
+    }
+
+    fn execute_schema(&mut self) -> Result<Schema> {
+        let request = self.state.request(Method::StatementExecuteSchema);
+        replay_schema(&self.cassette, request)
+    }
+
+    fn execute_partitions(&mut self) -> Result<PartitionedResult> {
+        let response = self.replay(Method::StatementExecutePartitions)?;
+        let (schema, _) = decode_batches(&response.result.unwrap_or_default())?;
+        Ok(PartitionedResult {
+            partitions: response.partitions.unwrap_or_default(),
+            schema: schema.as_ref().clone(),
+            rows_affected: response.rows_affected.unwrap_or(-1),
+        })
+    }
+
+    fn get_parameter_schema(&self) -> Result<Schema> {
+        let request = self.state.request(Method::StatementGetParameterSchema);
+        replay_schema(&self.cassette, request)
+    }
+
+    fn prepare(&mut self) -> Result<()> {
+        replay(&self.cassette, self.state.request(Method::StatementPrepare))
+    }
+
+    fn set_sql_query(&mut self, query: impl AsRef<str>) -> Result<()> {
+        self.state.set_sql_query(query.as_ref());
+        Ok(())
+    }
+
+    fn set_substrait_plan(&mut self, plan: impl AsRef<[u8]>) -> Result<()> {
+        self.state.set_substrait_plan(plan.as_ref());
+        Ok(())
+    }
+
+    fn cancel(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
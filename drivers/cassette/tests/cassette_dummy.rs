@@ -0,0 +1,241 @@
+/// This integration test records interactions with the dummy driver and
+/// checks that they are replayed identically.
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use arrow::array::{Int64Array, RecordBatch, RecordBatchReader};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use arrow::error::ArrowError;
+
+use adbc_cassette::{Cassette, RecordingDriver, ReplayDriver, OPTION_PATH};
+use adbc_core::driver_manager::ManagedDriver;
+use adbc_core::error::Status;
+use adbc_core::ffi::FFI_AdbcDriverInitFunc;
+use adbc_core::layer::{Call, Layer, Layered};
+use adbc_core::options::{AdbcVersion, InfoCode, ObjectDepth, OptionDatabase, OptionValue};
+use adbc_core::{Connection, Database, Driver, Optionable, Statement};
+
+use adbc_dummy::{DummyDriver, SingleBatchReader};
+
+fn cassette_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("adbc_cassette_{}_{name}.arrow", std::process::id()))
+}
+
+fn parameters(value: i64) -> RecordBatch {
+    let schema = Schema::new(vec![Field::new("id", DataType::Int64, false)]);
+    let column = Arc::new(Int64Array::from(vec![value]));
+    RecordBatch::try_new(Arc::new(schema), vec![column]).unwrap()
+}
+
+fn batches(reader: impl RecordBatchReader) -> Vec<RecordBatch> {
+    reader.map(|batch| batch.unwrap()).collect()
+}
+
+/// Make the same calls against any driver, returning a description of the
+/// results.
+fn exercise(driver: &mut impl Driver) -> Vec<String> {
+    let opts = [(OptionDatabase::Uri, OptionValue::from("dummy://secret"))];
+    let mut database = driver.new_database_with_opts(opts).unwrap();
+    let mut connection = database.new_connection().unwrap();
+    let mut results = Vec::new();
+
+    let codes = HashSet::from([InfoCode::VendorName, InfoCode::DriverName]);
+    results.push(format!(
+        "{:?}",
+        batches(connection.get_info(Some(codes)).unwrap())
+    ));
+    let objects = connection
+        .get_objects(ObjectDepth::All, None, None, None, None, None)
+        .unwrap();
+    results.push(format!("{:?}", batches(objects)));
+    let schema = connection.get_table_schema(None, None, "default").unwrap();
+    results.push(format!("{schema:?}"));
+    let error = connection
+        .get_table_schema(None, None, "unknown")
+        .unwrap_err();
+    results.push(format!("{error:?}"));
+    results.push(format!(
+        "{:?}",
+        batches(connection.get_table_types().unwrap())
+    ));
+    connection.commit().unwrap();
+
+    let mut statement = connection.new_statement().unwrap();
+    statement
+        .set_option("adbc.dummy.option".into(), "value".into())
+        .unwrap();
+    statement.set_sql_query("SELECT * FROM default").unwrap();
+    results.push(format!("{:?}", batches(statement.execute().unwrap())));
+    results.push(format!("{:?}", statement.execute_schema().unwrap()));
+    let partitioned = statement.execute_partitions().unwrap();
+    results.push(format!("{partitioned:?}"));
+    let partition = connection
+        .read_partition(&partitioned.partitions[0])
+        .unwrap();
+    results.push(format!("{:?}", batches(partition)));
+
+    statement
+        .set_sql_query("INSERT INTO default VALUES (?)")
+        .unwrap();
+    statement.bind(parameters(1)).unwrap();
+    results.push(format!("{:?}", statement.execute_update().unwrap()));
+    let reader = SingleBatchReader::new(parameters(2));
+    statement.bind_stream(Box::new(reader)).unwrap();
+    results.push(format!("{:?}", statement.execute_update().unwrap()));
+    results
+}
+
+#[test]
+fn test_record_and_replay() {
+    let cassette = Cassette::new();
+    let mut driver = RecordingDriver::new(DummyDriver {}, cassette.clone());
+    let recorded = exercise(&mut driver);
+
+    let path = cassette_path("replay");
+    cassette.save(&path).unwrap();
+    let bytes = std::fs::read(&path).unwrap();
+    assert!(!bytes.windows(6).any(|window| window == b"secret"));
+    let cassette = Cassette::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(cassette.remaining(), cassette.len());
+
+    let mut driver = ReplayDriver::new(cassette.clone());
+    let replayed = exercise(&mut driver);
+    assert_eq!(recorded, replayed);
+    assert_eq!(cassette.remaining(), 0);
+}
+
+#[test]
+fn test_replay_mismatch() {
+    let cassette = Cassette::new();
+    let mut driver = RecordingDriver::new(DummyDriver {}, cassette.clone());
+    exercise(&mut driver);
+
+    let mut driver = ReplayDriver::new(cassette);
+    let mut database = driver.new_database().unwrap();
+    let mut connection = database.new_connection().unwrap();
+    let mut statement = connection.new_statement().unwrap();
+
+    // The statement option is part of the recorded request.
+    statement.set_sql_query("SELECT * FROM default").unwrap();
+    let error = statement.execute().err().unwrap();
+    assert_eq!(error.status, Status::NotFound);
+
+    statement
+        .set_option("adbc.dummy.option".into(), "value".into())
+        .unwrap();
+    statement
+        .set_sql_query("INSERT INTO default VALUES (?)")
+        .unwrap();
+    statement.bind(parameters(3)).unwrap();
+    let error = statement.execute_update().unwrap_err();
+    assert_eq!(error.status, Status::NotFound);
+    statement.bind(parameters(1)).unwrap();
+    assert_eq!(statement.execute_update().unwrap(), Some(0));
+
+    // Interactions are replayed once.
+    statement.bind(parameters(1)).unwrap();
+    let error = statement.execute_update().unwrap_err();
+    assert_eq!(error.status, Status::NotFound);
+}
+
+/// Fails result sets after their first batch.
+#[derive(Default)]
+struct FailingStream;
+
+struct FailingReader<R> {
+    inner: R,
+    batches: usize,
+}
+
+impl<R: RecordBatchReader> Iterator for FailingReader<R> {
+    type Item = std::result::Result<RecordBatch, ArrowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.batches += 1;
+        match self.batches {
+            1 => self.inner.next(),
+            _ => Some(Err(ArrowError::IoError(
+                "connection reset".into(),
+                std::io::ErrorKind::ConnectionReset.into(),
+            ))),
+        }
+    }
+}
+
+impl<R: RecordBatchReader> RecordBatchReader for FailingReader<R> {
+    fn schema(&self) -> SchemaRef {
+        self.inner.schema()
+    }
+}
+
+impl Layer for FailingStream {
+    fn wrap_reader<R: RecordBatchReader + Send>(
+        &self,
+        _call: &Call<'_>,
+        reader: R,
+    ) -> impl RecordBatchReader + Send + use<R> {
+        FailingReader {
+            inner: reader,
+            batches: 0,
+        }
+    }
+}
+
+#[test]
+fn test_replay_stream_error() {
+    let cassette = Cassette::new();
+    let inner = Layered::new(DummyDriver {}, FailingStream);
+    let mut driver = RecordingDriver::new(inner, cassette.clone());
+    let mut database = driver.new_database().unwrap();
+    let mut connection = database.new_connection().unwrap();
+    let mut statement = connection.new_statement().unwrap();
+    statement.set_sql_query("SELECT 1").unwrap();
+    let recorded: Vec<_> = statement.execute().unwrap().collect();
+    assert_eq!(recorded.len(), 2);
+    assert!(recorded[1].is_err());
+
+    let mut driver = ReplayDriver::new(cassette);
+    let mut database = driver.new_database().unwrap();
+    let mut connection = database.new_connection().unwrap();
+    let mut statement = connection.new_statement().unwrap();
+    statement.set_sql_query("SELECT 1").unwrap();
+    let replayed: Vec<_> = statement.execute().unwrap().collect();
+    assert_eq!(replayed.len(), 2);
+    assert_eq!(replayed[0].as_ref().unwrap(), recorded[0].as_ref().unwrap());
+    let error = replayed[1].as_ref().unwrap_err().to_string();
+    assert!(error.contains("connection reset"), "{error}");
+}
+
+#[test]
+fn test_replay_exported() {
+    let cassette = Cassette::new();
+    let mut driver = RecordingDriver::new(DummyDriver {}, cassette.clone());
+    let recorded = exercise(&mut driver);
+    let path = cassette_path("exported");
+    cassette.save(&path).unwrap();
+
+    let init: FFI_AdbcDriverInitFunc = adbc_cassette::AdbcCassetteInit;
+    let mut driver = ManagedDriver::load_static(&init, AdbcVersion::V110).unwrap();
+    let opts = [(
+        OptionDatabase::Other(OPTION_PATH.into()),
+        OptionValue::from(path.to_str().unwrap()),
+    )];
+    let mut database = driver.new_database_with_opts(opts).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(
+        database
+            .get_option_string(OptionDatabase::Other(OPTION_PATH.into()))
+            .unwrap(),
+        path.to_str().unwrap()
+    );
+    let mut connection = database.new_connection().unwrap();
+    let mut statement = connection.new_statement().unwrap();
+    statement
+        .set_option("adbc.dummy.option".into(), "value".into())
+        .unwrap();
+    statement.set_sql_query("SELECT * FROM default").unwrap();
+    let replayed = format!("{:?}", batches(statement.execute().unwrap()));
+    assert_eq!(replayed, recorded[5]);
+}
@@ -1,5 +1,5 @@
 use std::collections::HashSet;
-use std::sync::Arc;
+use std::sync::{Arc, Condvar, Mutex};
 use std::{collections::HashMap, fmt::Debug, hash::Hash};
 
 use arrow::array::{
@@ -12,12 +12,14 @@ use arrow::error::ArrowError;
 use arrow::record_batch::{RecordBatch, RecordBatchReader};
 
 use adbc_core::{
+    capabilities::Capabilities,
     error::{Error, Result, Status},
     ffi::constants,
     options::{
-        InfoCode, ObjectDepth, OptionConnection, OptionDatabase, OptionStatement, OptionValue,
+        InfoCode, IngestMode, IsolationLevel, ObjectDepth, OptionConnection, OptionDatabase,
+        OptionStatement, OptionValue,
     },
-    schemas, Connection, Database, Driver, Optionable, PartitionedResult, Statement,
+    schemas, secret, Connection, Database, Driver, Optionable, PartitionedResult, Statement,
 };
 
 #[derive(Debug)]
@@ -50,6 +52,58 @@ impl RecordBatchReader for SingleBatchReader {
     }
 }
 
+/// Statement option making the statement block until cancelled, like a hung
+/// driver would. With `execute` its executions block, with `stream` reading
+/// their result sets does.
+pub const OPTION_BLOCK: &str = "adbc.dummy.statement.block";
+
+/// Blocks calls until cancelled. A cancellation preceding the call is not
+/// lost, but ends the next call at once.
+#[derive(Clone, Default)]
+struct Blocker(Arc<(Mutex<bool>, Condvar)>);
+
+impl Blocker {
+    fn block(&self) -> Error {
+        let (lock, condvar) = &*self.0;
+        let cancelled = lock.lock().unwrap();
+        let mut cancelled = condvar.wait_while(cancelled, |c| !*c).unwrap();
+        *cancelled = false;
+        Error::with_message_and_status("Blocked call cancelled", Status::Cancelled)
+    }
+
+    fn cancel(&self) {
+        let (lock, condvar) = &*self.0;
+        *lock.lock().unwrap() = true;
+        condvar.notify_all();
+    }
+}
+
+/// A reader blocking until cancelled before yielding its batch.
+struct BlockingReader {
+    blocker: Option<Blocker>,
+    reader: SingleBatchReader,
+}
+
+impl Iterator for BlockingReader {
+    type Item = std::result::Result<RecordBatch, ArrowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.blocker.take() {
+            Some(blocker) => {
+                self.reader.batch = None;
+                Some(Err(ArrowError::ExternalError(Box::new(blocker.block()))))
+            }
+            None => self.reader.next(),
+        }
+    }
+}
+
+impl RecordBatchReader for BlockingReader {
+    fn schema(&self) -> SchemaRef {
+        self.reader.schema()
+    }
+}
+
 fn get_table_schema() -> Schema {
     Schema::new(vec![
         Field::new("a", DataType::UInt32, true),
@@ -177,7 +231,7 @@ impl Driver for DummyDriver {
             options: HashMap::new(),
         };
         for (key, value) in opts {
-            database.set_option(key, value)?;
+            set_option(&mut database.options, key, value)?;
         }
         Ok(database)
     }
@@ -187,11 +241,26 @@ pub struct DummyDatabase {
     options: HashMap<OptionDatabase, OptionValue>,
 }
 
+impl Drop for DummyDatabase {
+    fn drop(&mut self) {
+        secret::zeroize_secrets(self.options.iter_mut());
+    }
+}
+
 impl Optionable for DummyDatabase {
     type Option = OptionDatabase;
 
+    // Like most drivers, only accept credentials before initialization.
     fn set_option(&mut self, key: Self::Option, value: OptionValue) -> Result<()> {
-        set_option(&mut self.options, key, value)
+        match key {
+            OptionDatabase::Uri | OptionDatabase::Username | OptionDatabase::Password => {
+                Err(Error::with_message_and_status(
+                    format!("Cannot set {} on an initialized database", key.as_ref()),
+                    Status::InvalidState,
+                ))
+            }
+            key => set_option(&mut self.options, key, value),
+        }
     }
 
     fn get_option_bytes(&self, key: Self::Option) -> Result<Vec<u8>> {
@@ -266,6 +335,9 @@ impl Connection for DummyConnection {
     fn new_statement(&mut self) -> Result<Self::StatementType> {
         Ok(Self::StatementType {
             options: HashMap::new(),
+            blocker: Blocker::default(),
+            returned: 0,
+            bound_rows: 0,
         })
     }
 
@@ -787,10 +859,51 @@ impl Connection for DummyConnection {
     fn rollback(&mut self) -> Result<()> {
         Ok(())
     }
+
+    fn capabilities(&self) -> Result<Capabilities> {
+        let mut capabilities = Capabilities::default();
+        capabilities.partitions = true;
+        capabilities.substrait = true;
+        capabilities.statistics = true;
+        capabilities.cancellation = true;
+        capabilities.transactions = true;
+        capabilities.isolation_levels = vec![
+            IsolationLevel::Default,
+            IsolationLevel::ReadUncommitted,
+            IsolationLevel::ReadCommitted,
+            IsolationLevel::RepeatableRead,
+            IsolationLevel::Snapshot,
+            IsolationLevel::Serializable,
+            IsolationLevel::Linearizable,
+        ];
+        capabilities.ingest_modes = vec![
+            IngestMode::Create,
+            IngestMode::Append,
+            IngestMode::Replace,
+            IngestMode::CreateAppend,
+        ];
+        capabilities.with_info(self)
+    }
 }
 
 pub struct DummyStatement {
     options: HashMap<OptionStatement, OptionValue>,
+    blocker: Blocker,
+    // Number of partitions returned so far in incremental mode.
+    returned: usize,
+    // Number of rows of the last bound stream.
+    bound_rows: i64,
+}
+
+impl DummyStatement {
+    fn incremental(&self) -> bool {
+        matches!(self.options.get(&OptionStatement::Incremental), Some(OptionValue::String(value)) if value == "true")
+    }
+
+    fn blocks(&self, target: &str) -> bool {
+        let key = OptionStatement::Other(OPTION_BLOCK.into());
+        matches!(self.options.get(&key), Some(OptionValue::String(value)) if value == target)
+    }
 }
 
 impl Optionable for DummyStatement {
@@ -822,23 +935,59 @@ impl Statement for DummyStatement {
         Ok(())
     }
 
-    fn bind_stream(&mut self, _reader: Box<dyn RecordBatchReader + Send>) -> Result<()> {
+    fn bind_stream(&mut self, reader: Box<dyn RecordBatchReader + Send>) -> Result<()> {
+        // Consume the stream as a driver ingesting it would.
+        self.bound_rows = 0;
+        for batch in reader {
+            self.bound_rows += batch?.num_rows() as i64;
+        }
         Ok(())
     }
 
     fn cancel(&mut self) -> Result<()> {
+        self.blocker.cancel();
         Ok(())
     }
 
     fn execute(&mut self) -> Result<impl RecordBatchReader> {
+        if self.blocks("execute") {
+            return Err(self.blocker.block());
+        }
         let batch = get_table_data();
-        let reader = SingleBatchReader::new(batch);
+        let reader = BlockingReader {
+            blocker: self.blocks("stream").then(|| self.blocker.clone()),
+            reader: SingleBatchReader::new(batch),
+        };
         Ok(reader)
     }
 
     fn execute_partitions(&mut self) -> Result<PartitionedResult> {
+        let mut partitions: Vec<Vec<u8>> = vec![b"AAA".into(), b"ZZZZZ".into()];
+        if self.incremental() {
+            // Return the partitions one at a time, then an empty set, with the
+            // number of partitions returned as progress.
+            let total = partitions.len();
+            let progress = match partitions.get(self.returned) {
+                Some(partition) => {
+                    partitions = vec![partition.clone()];
+                    self.returned += 1;
+                    self.returned
+                }
+                None => {
+                    partitions.clear();
+                    // The next execution starts over.
+                    self.returned = 0;
+                    total
+                }
+            };
+            let progress = OptionValue::Double(progress as f64);
+            self.options.insert(OptionStatement::Progress, progress);
+            let max_progress = OptionValue::Double(total as f64);
+            self.options
+                .insert(OptionStatement::MaxProgress, max_progress);
+        }
         Ok(PartitionedResult {
-            partitions: vec![b"AAA".into(), b"ZZZZZ".into()],
+            partitions,
             schema: get_table_schema(),
             rows_affected: 0,
         })
@@ -849,7 +998,10 @@ impl Statement for DummyStatement {
     }
 
     fn execute_update(&mut self) -> Result<Option<i64>> {
-        Ok(Some(0))
+        if self.blocks("execute") {
+            return Err(self.blocker.block());
+        }
+        Ok(Some(self.bound_rows))
     }
 
     fn get_parameter_schema(&self) -> Result<Schema> {
@@ -0,0 +1,34 @@
+/// This integration test checks that the driver manager closes objects of the
+/// dummy driver only once they are no longer shared.
+use adbc_core::driver_manager::ManagedDriver;
+use adbc_core::error::Status;
+use adbc_core::ffi::FFI_AdbcDriverInitFunc;
+use adbc_core::options::AdbcVersion;
+use adbc_core::{Connection, Database, Driver, Statement};
+
+#[test]
+fn test_close() {
+    let init: FFI_AdbcDriverInitFunc = adbc_dummy::DummyDriverInit;
+    let mut driver = ManagedDriver::load_static(&init, AdbcVersion::V110).unwrap();
+    let mut database = driver.new_database().unwrap();
+    let mut connection = database.new_connection().unwrap();
+    let mut statement = connection.new_statement().unwrap();
+
+    // Objects still used by clones or by their children are not closed.
+    let error = statement.clone().close().unwrap_err();
+    assert_eq!(error.status, Status::InvalidState);
+    let error = connection.clone().close().unwrap_err();
+    assert_eq!(error.status, Status::InvalidState);
+    let handle = statement.cancel_handle();
+    let error = statement.clone().close().unwrap_err();
+    assert_eq!(error.status, Status::InvalidState);
+    drop(handle);
+    assert_eq!(statement.execute().unwrap().count(), 1);
+
+    statement.close().unwrap();
+    connection.close().unwrap();
+    let error = driver.clone().close().unwrap_err();
+    assert_eq!(error.status, Status::InvalidState);
+    database.close().unwrap();
+    driver.close().unwrap();
+}
@@ -0,0 +1,112 @@
+/// This integration test checks that the driver manager sets the credentials
+/// of a provider on databases of the exported dummy driver, and refreshes
+/// them once expired.
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use adbc_core::credentials::{Cached, CommandProvider, Credentials, FileProvider};
+use adbc_core::driver_manager::ManagedDriver;
+use adbc_core::error::Status;
+use adbc_core::options::{AdbcVersion, OptionDatabase};
+use adbc_core::{Connection, Database, Driver, Optionable};
+
+fn load() -> ManagedDriver {
+    ManagedDriver::load_dynamic_from_name("adbc_dummy", Some(b"DummyDriverInit"), AdbcVersion::V110)
+        .unwrap()
+}
+
+#[test]
+fn test_credentials_refresh() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let provider = {
+        let calls = calls.clone();
+        move || {
+            let call = calls.fetch_add(1, Ordering::Relaxed);
+            // The first credentials are about to expire, the next ones last.
+            let expires_in = Duration::from_secs(if call == 0 { 1 } else { 3600 });
+            let password = format!("token-{call}");
+            Ok(
+                Credentials::new([(OptionDatabase::Password, password.into())])
+                    .expires_at(SystemTime::now() + expires_in),
+            )
+        }
+    };
+    let mut driver = load().with_credentials(provider);
+
+    let opts = [(OptionDatabase::Password, "static".into())];
+    let mut database = driver.new_database_with_opts(opts).unwrap();
+    let password = database
+        .get_option_string(OptionDatabase::Password)
+        .unwrap();
+    assert_eq!(password, "token-0");
+    let key = OptionDatabase::Other("post.string".into());
+    database.set_option(key.clone(), "kept".into()).unwrap();
+    let error = database
+        .set_option(OptionDatabase::Password, "late".into())
+        .unwrap_err();
+    assert_eq!(error.status, Status::InvalidState);
+
+    // The driver rejects credentials once the database is initialized, so
+    // the database is replaced by one with the fresh credentials.
+    let connection = database.new_connection().unwrap();
+    let _connection = database.new_connection().unwrap();
+    let password = database
+        .get_option_string(OptionDatabase::Password)
+        .unwrap();
+    assert_eq!(password, "token-1");
+    assert_eq!(database.get_option_string(key).unwrap(), "kept");
+    assert_eq!(calls.load(Ordering::Relaxed), 2);
+    assert!(connection.get_table_types().is_ok());
+}
+
+#[test]
+fn test_credentials_file() {
+    let path = std::env::temp_dir().join(format!("adbc_credentials_{}", std::process::id()));
+    std::fs::write(&path, "username=me\npassword=first\nexpires_in=3600\n").unwrap();
+    let provider = Arc::new(Cached::new(FileProvider::new(&path)));
+    let mut driver = load().with_credentials(provider.clone());
+
+    let database = driver.new_database().unwrap();
+    std::fs::write(&path, "username=me\npassword=second\n").unwrap();
+    let other = driver.new_database().unwrap();
+    provider.invalidate();
+    let rotated = driver.new_database().unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    for (database, expected) in [(database, "first"), (other, "first"), (rotated, "second")] {
+        let password = database
+            .get_option_string(OptionDatabase::Password)
+            .unwrap();
+        assert_eq!(password, expected);
+        let username = database
+            .get_option_string(OptionDatabase::Username)
+            .unwrap();
+        assert_eq!(username, "me");
+    }
+
+    provider.invalidate();
+    let error = driver.new_database().err().unwrap();
+    assert_eq!(error.status, Status::IO);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_credentials_command() {
+    let provider = CommandProvider::new("sh").args(["-c", "echo password=from-command"]);
+    let mut driver = load().with_credentials(provider);
+    let database = driver.new_database().unwrap();
+    let password = database
+        .get_option_string(OptionDatabase::Password)
+        .unwrap();
+    assert_eq!(password, "from-command");
+
+    let provider = CommandProvider::new("sh").args(["-c", "echo denied >&2; exit 3"]);
+    let error = load()
+        .with_credentials(provider)
+        .new_database()
+        .err()
+        .unwrap();
+    assert_eq!(error.status, Status::IO);
+    assert!(error.message.contains("denied"));
+}
@@ -3,7 +3,10 @@
 /// driver manager (exported). That allows us to test that data correctly round-trip
 /// between C and Rust.
 use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
 
 use arrow::array::{Array, Float64Array, Int64Array, StringArray};
 use arrow::compute::concat_batches;
@@ -11,19 +14,23 @@ use arrow::datatypes::{DataType, Field, Schema};
 use arrow::record_batch::{RecordBatch, RecordBatchReader};
 
 use adbc_core::driver_manager::{
-    ManagedConnection, ManagedDatabase, ManagedDriver, ManagedStatement,
+    CancelHandle, ManagedConnection, ManagedDatabase, ManagedDriver, ManagedStatement,
 };
+use adbc_core::error::Status;
 use adbc_core::options::{
     AdbcVersion, InfoCode, IngestMode, IsolationLevel, ObjectDepth, OptionConnection,
-    OptionDatabase, OptionStatement,
+    OptionDatabase, OptionStatement, TypedOptionable,
 };
 use adbc_core::Statement;
 use adbc_core::{schemas, Connection, Database, Driver, Optionable};
 
-use adbc_dummy::{DummyConnection, DummyDatabase, DummyDriver, DummyStatement, SingleBatchReader};
+use adbc_dummy::{
+    DummyConnection, DummyDatabase, DummyDriver, DummyStatement, SingleBatchReader, OPTION_BLOCK,
+};
 
 const OPTION_STRING_LONG: &str = "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA";
 const OPTION_BYTES_LONG: &[u8] = b"AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA";
+const TIMEOUT: Duration = Duration::from_millis(100);
 
 pub fn concat_reader(reader: impl RecordBatchReader) -> RecordBatch {
     let schema = reader.schema();
@@ -71,6 +78,21 @@ fn get_native() -> (DummyDriver, DummyDatabase, DummyConnection, DummyStatement)
     (driver, database, connection, statement)
 }
 
+// Driver
+
+#[test]
+fn test_driver_functions() {
+    let (driver, _, _, _) = get_exported();
+    let missing: Vec<&str> = driver
+        .functions()
+        .into_iter()
+        .filter(|f| !f.implemented)
+        .map(|f| f.name)
+        .collect();
+    assert_eq!(missing, ["AdbcErrorFromArrayStream"]);
+    driver.strict().unwrap();
+}
+
 // Database
 
 #[test]
@@ -361,6 +383,25 @@ fn test_connection_cancel() {
     assert_eq!(exported_error, native_error);
 }
 
+#[test]
+fn test_connection_capabilities() {
+    let (_, _, exported_connection, _) = get_exported();
+    let (_, _, native_connection, _) = get_native();
+
+    let exported_capabilities = exported_connection.capabilities().unwrap();
+    let native_capabilities = native_connection.capabilities().unwrap();
+
+    // Isolation levels and ingest modes cannot be derived from the functions
+    // populated by the exported driver.
+    assert!(exported_capabilities.isolation_levels.is_empty());
+    assert!(exported_capabilities.ingest_modes.is_empty());
+    let mut expected = native_capabilities;
+    expected.isolation_levels.clear();
+    expected.ingest_modes.clear();
+    assert_eq!(exported_capabilities, expected);
+    assert!(expected.partitions && expected.cancellation && expected.transactions);
+}
+
 #[test]
 fn test_connection_commit_rollback() {
     let (_, _, mut exported_connection, _) = get_exported();
@@ -481,6 +522,30 @@ fn test_statement_options() {
         .unwrap();
     assert_eq!(value, 42);
 
+    statement
+        .set_option(OptionStatement::TargetCatalog, "catalog".into())
+        .unwrap();
+    let value = statement
+        .get_option_string(OptionStatement::TargetCatalog)
+        .unwrap();
+    assert_eq!(value, "catalog");
+
+    statement
+        .set_option(OptionStatement::TargetDbSchema, "schema".into())
+        .unwrap();
+    let value = statement
+        .get_option_string(OptionStatement::TargetDbSchema)
+        .unwrap();
+    assert_eq!(value, "schema");
+
+    statement
+        .set_option(OptionStatement::Temporary, 1.into())
+        .unwrap();
+    let value = statement
+        .get_option_string(OptionStatement::Temporary)
+        .unwrap();
+    assert_eq!(value, "true");
+
     statement
         .set_option(OptionStatement::MaxProgress, std::f64::consts::PI.into())
         .unwrap();
@@ -528,6 +593,74 @@ fn test_statement_options() {
     assert_eq!(value, OPTION_STRING_LONG);
 }
 
+#[test]
+fn test_typed_options() {
+    let (_, mut database, mut connection, mut statement) = get_exported();
+
+    // Values are converted to the type of the option, whichever setter is used.
+    connection
+        .set_option(OptionConnection::AutoCommit, 0.into())
+        .unwrap();
+    let value = connection
+        .get_option_string(OptionConnection::AutoCommit)
+        .unwrap();
+    assert_eq!(value, "false");
+    connection
+        .set_typed(OptionConnection::AutoCommit, true)
+        .unwrap();
+    let value: bool = connection.get_typed(OptionConnection::AutoCommit).unwrap();
+    assert!(value);
+    let value: i64 = connection.get_typed(OptionConnection::AutoCommit).unwrap();
+    assert_eq!(value, 1);
+
+    statement
+        .set_typed(OptionStatement::TargetTable, 42_i64)
+        .unwrap();
+    let value: String = statement.get_typed(OptionStatement::TargetTable).unwrap();
+    assert_eq!(value, "42");
+
+    // Invalid values and read-only options are rejected.
+    let error = connection
+        .set_typed(OptionConnection::ReadOnly, "maybe".to_string())
+        .unwrap_err();
+    assert_eq!(error.status, Status::InvalidArguments);
+    let error = connection
+        .set_option(OptionConnection::IsolationLevel, "whatever".into())
+        .unwrap_err();
+    assert_eq!(error.status, Status::InvalidArguments);
+    connection
+        .set_typed(OptionConnection::IsolationLevel, IsolationLevel::Snapshot)
+        .unwrap();
+    assert_eq!(
+        connection.isolation_level().unwrap(),
+        IsolationLevel::Snapshot
+    );
+    assert!(connection.autocommit().unwrap());
+    statement
+        .set_option(OptionStatement::IngestMode, IngestMode::Replace.into())
+        .unwrap();
+    assert_eq!(statement.ingest_mode().unwrap(), IngestMode::Replace);
+    let error = statement
+        .set_typed(OptionStatement::Progress, 1.0)
+        .unwrap_err();
+    assert_eq!(error.status, Status::InvalidArguments);
+    let error = database
+        .set_typed(OptionDatabase::Uri, "dummy://".to_string())
+        .unwrap_err();
+    assert_eq!(error.status, Status::InvalidState);
+
+    // Driver-specific options are not converted.
+    statement
+        .set_typed(OptionStatement::Other("double".into()), 1.5)
+        .unwrap();
+    let value = statement.get_typed::<String>(OptionStatement::Other("double".into()));
+    assert!(value.is_err());
+    let value: f64 = statement
+        .get_typed(OptionStatement::Other("double".into()))
+        .unwrap();
+    assert_eq!(value, 1.5);
+}
+
 #[test]
 fn test_statement_bind() {
     let (_, _, _, mut exported_statement) = get_exported();
@@ -636,3 +769,122 @@ fn test_statement_get_parameter_schema() {
     let native_schema = native_statement.get_parameter_schema().unwrap();
     assert_eq!(exported_schema, native_schema);
 }
+
+fn block(statement: &mut ManagedStatement, target: &str) {
+    statement
+        .set_option(OptionStatement::Other(OPTION_BLOCK.into()), target.into())
+        .unwrap();
+}
+
+// Cancellation
+
+#[test]
+fn test_cancel_handle() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<CancelHandle>();
+
+    let (_, _, _, mut statement) = get_exported();
+    block(&mut statement, "execute");
+
+    let handle = statement.cancel_handle();
+    let error = thread::scope(|scope| {
+        scope.spawn(|| handle.cancel().unwrap());
+        statement.execute().err().unwrap()
+    });
+    assert_eq!(error.status, Status::Cancelled);
+
+    // Cancelling through a clone does not wait for the running call either.
+    let mut clone = statement.clone();
+    let error = thread::scope(|scope| {
+        scope.spawn(move || clone.cancel().unwrap());
+        statement.execute_update().unwrap_err()
+    });
+    assert_eq!(error.status, Status::Cancelled);
+}
+
+#[test]
+fn test_cancel_handle_connection() {
+    let (_, _, connection, _) = get_exported();
+    let handle = connection.cancel_handle();
+
+    // The handle keeps the connection alive and reports the errors of the
+    // driver, which the dummy driver always returns when cancelling.
+    drop(connection);
+    let error = handle.cancel().unwrap_err();
+    assert_eq!(error.status, Status::Cancelled);
+    assert_eq!(error.message, "message");
+}
+
+#[test]
+fn test_statements_serialized() {
+    // The statements of a connection are not called concurrently.
+    let (_, _, mut connection, mut blocked) = get_exported();
+    let mut other = connection.new_statement().unwrap();
+    block(&mut blocked, "execute");
+    let handle = blocked.cancel_handle();
+    let done = AtomicBool::new(false);
+    thread::scope(|scope| {
+        scope.spawn(|| blocked.execute_update().unwrap_err());
+        thread::sleep(TIMEOUT);
+        scope.spawn(|| {
+            other.execute_update().unwrap();
+            done.store(true, Ordering::SeqCst);
+        });
+        thread::sleep(TIMEOUT);
+        let early = done.load(Ordering::SeqCst);
+        handle.cancel().unwrap();
+        assert!(!early);
+    });
+    assert!(done.load(Ordering::SeqCst));
+}
+
+// Timeouts
+
+#[test]
+fn test_timeout_execute() {
+    let (_, _, _, mut statement) = get_exported();
+    statement.set_timeout(Some(TIMEOUT)).unwrap();
+    assert_eq!(statement.timeout(), Some(TIMEOUT));
+    assert_eq!(statement.execute().unwrap().count(), 1);
+
+    block(&mut statement, "execute");
+    let start = Instant::now();
+    let error = statement.execute().err().unwrap();
+    assert_eq!(error.status, Status::Timeout);
+    assert!(start.elapsed() >= TIMEOUT);
+    let error = statement.execute_update().unwrap_err();
+    assert_eq!(error.status, Status::Timeout);
+
+    // The statement is usable once the call is cancelled.
+    block(&mut statement, "");
+    assert_eq!(statement.execute_update().unwrap(), Some(0));
+}
+
+#[test]
+fn test_timeout_stream() {
+    let (_, _, _, mut statement) = get_exported();
+    statement.set_timeout(Some(TIMEOUT)).unwrap();
+    block(&mut statement, "stream");
+    let mut reader = statement.execute().unwrap();
+    let error = reader.next().unwrap().unwrap_err();
+    assert!(error.to_string().contains("timed out"), "{error}");
+    assert!(reader.next().is_none());
+}
+
+#[test]
+fn test_timeout_connection() {
+    let (_, _, mut connection, mut statement) = get_exported();
+    assert_eq!(statement.timeout(), None);
+    connection.set_timeout(Some(TIMEOUT)).unwrap();
+    assert_eq!(statement.timeout(), Some(TIMEOUT));
+    let timeout = Duration::from_millis(50);
+    statement.set_timeout(Some(timeout)).unwrap();
+    assert_eq!(statement.timeout(), Some(timeout));
+    assert!(connection.get_table_types().is_ok());
+
+    block(&mut statement, "execute");
+    let start = Instant::now();
+    let error = statement.execute_update().unwrap_err();
+    assert_eq!(error.status, Status::Timeout);
+    assert!(start.elapsed() >= timeout);
+}
@@ -0,0 +1,45 @@
+/// This integration test executes queries incrementally with the dummy driver,
+/// used directly and through the driver manager.
+use adbc_core::driver_manager::ManagedDriver;
+use adbc_core::ffi::FFI_AdbcDriverInitFunc;
+use adbc_core::options::{AdbcVersion, OptionStatement};
+use adbc_core::partitions::{IncrementalExecution, Progress};
+use adbc_core::{Connection, Database, Driver, Statement};
+
+use adbc_dummy::DummyDriver;
+
+fn check_incremental(statement: &mut impl Statement) {
+    let mut fractions = Vec::new();
+    let execution = IncrementalExecution::new(statement)
+        .unwrap()
+        .with_progress(|progress: Progress| fractions.push(progress.fraction().unwrap()));
+    let partitions: Vec<_> = execution.map(|result| result.unwrap().partitions).collect();
+    assert_eq!(partitions, [vec![b"AAA".to_vec()], vec![b"ZZZZZ".to_vec()]]);
+    assert_eq!(fractions, [0.5, 1.0, 1.0]);
+
+    let value = statement
+        .get_option_string(OptionStatement::Incremental)
+        .unwrap();
+    assert_eq!(value, "true");
+    // Executing again starts over.
+    let mut execution = IncrementalExecution::new(statement).unwrap();
+    assert_eq!(execution.by_ref().count(), 2);
+    let progress = execution.progress().unwrap();
+    assert_eq!(progress.progress, 2.0);
+    assert_eq!(progress.max_progress, Some(2.0));
+}
+
+#[test]
+fn test_incremental_native() {
+    let mut driver = DummyDriver {};
+    let mut connection = driver.new_database().unwrap().new_connection().unwrap();
+    check_incremental(&mut connection.new_statement().unwrap());
+}
+
+#[test]
+fn test_incremental_exported() {
+    let init: FFI_AdbcDriverInitFunc = adbc_dummy::DummyDriverInit;
+    let mut driver = ManagedDriver::load_static(&init, AdbcVersion::V110).unwrap();
+    let mut connection = driver.new_database().unwrap().new_connection().unwrap();
+    check_incremental(&mut connection.new_statement().unwrap());
+}
@@ -0,0 +1,151 @@
+/// This integration test checks the options and data passed to the dummy
+/// driver by the bulk ingestion builder.
+use std::sync::{Arc, Mutex};
+
+use arrow::array::{Float64Array, Int64Array, RecordBatchIterator, StringArray, UInt32Array};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::{RecordBatch, RecordBatchReader};
+
+use adbc_core::error::Status;
+use adbc_core::layer::{Call, Layer, Layered, Method};
+use adbc_core::options::{IngestMode, OptionValue};
+use adbc_core::{Connection, Database, Driver};
+
+use adbc_dummy::DummyDriver;
+
+/// Records the statement options set and the size of the batches bound.
+#[derive(Default)]
+struct Recorder {
+    options: Mutex<Vec<(String, String)>>,
+    batches: Mutex<Vec<usize>>,
+}
+
+impl Layer for Recorder {
+    fn before(&self, call: &Call<'_>) -> adbc_core::error::Result<()> {
+        if let (Method::StatementSetOption, Some(key), Some(OptionValue::String(value))) =
+            (call.method, call.option, call.value)
+        {
+            let option = (key.to_string(), value.clone());
+            self.options.lock().unwrap().push(option);
+        }
+        Ok(())
+    }
+
+    fn wrap_bind_stream(
+        &self,
+        _call: &Call<'_>,
+        reader: Box<dyn RecordBatchReader + Send>,
+    ) -> Box<dyn RecordBatchReader + Send> {
+        let schema = reader.schema();
+        let batches: Vec<_> = reader.collect();
+        self.batches.lock().unwrap().extend(
+            batches
+                .iter()
+                .map(|batch| batch.as_ref().map_or(0, RecordBatch::num_rows)),
+        );
+        Box::new(RecordBatchIterator::new(batches, schema))
+    }
+}
+
+fn get_connection(recorder: &Arc<Recorder>) -> impl Connection {
+    let mut driver = Layered::new(DummyDriver {}, recorder.clone());
+    driver.new_database().unwrap().new_connection().unwrap()
+}
+
+/// A reader with the schema of the dummy table and batches of `sizes` rows.
+fn get_reader(sizes: &[u32]) -> impl RecordBatchReader + Send + 'static {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("a", DataType::UInt32, true),
+        Field::new("b", DataType::Float64, false),
+        Field::new("c", DataType::Utf8, true),
+    ]));
+    let batches: Vec<_> = sizes
+        .iter()
+        .map(|&size| {
+            let columns: Vec<Arc<dyn arrow::array::Array>> = vec![
+                Arc::new(UInt32Array::from_iter_values(0..size)),
+                Arc::new(Float64Array::from_iter_values((0..size).map(f64::from))),
+                Arc::new(StringArray::from_iter_values(
+                    (0..size).map(|i| i.to_string()),
+                )),
+            ];
+            Ok(RecordBatch::try_new(schema.clone(), columns).unwrap())
+        })
+        .collect();
+    RecordBatchIterator::new(batches, schema)
+}
+
+#[test]
+fn test_ingest_options() {
+    let recorder = Arc::new(Recorder::default());
+    let mut connection = get_connection(&recorder);
+
+    let rows = connection
+        .ingest("events")
+        .mode(IngestMode::CreateAppend)
+        .schema("analytics")
+        .temporary(true)
+        .chunk_size(2)
+        .from_reader(get_reader(&[5, 1]))
+        .unwrap();
+    assert_eq!(rows, 6);
+    assert_eq!(*recorder.batches.lock().unwrap(), [2, 2, 1, 1]);
+
+    let options = recorder.options.lock().unwrap().clone();
+    let expected = [
+        ("adbc.ingest.target_table", "events"),
+        ("adbc.ingest.mode", "adbc.ingest.mode.create_append"),
+        ("adbc.ingest.target_db_schema", "analytics"),
+        ("adbc.ingest.temporary", "true"),
+    ];
+    let expected: Vec<_> = expected
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+    assert_eq!(options, expected);
+}
+
+#[test]
+fn test_ingest_schema_check() {
+    let recorder = Arc::new(Recorder::default());
+    let mut connection = get_connection(&recorder);
+
+    // The dummy driver only knows the schema of table "default".
+    let rows = connection
+        .ingest("default")
+        .mode(IngestMode::Append)
+        .from_reader(get_reader(&[3]))
+        .unwrap();
+    assert_eq!(rows, 3);
+
+    let error = connection
+        .ingest("missing")
+        .mode(IngestMode::Append)
+        .from_reader(get_reader(&[3]))
+        .unwrap_err();
+    assert_eq!(error.status, Status::NotFound);
+
+    let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, true)]));
+    let batch =
+        RecordBatch::try_new(schema.clone(), vec![Arc::new(Int64Array::from(vec![1]))]).unwrap();
+    let reader = || RecordBatchIterator::new(vec![Ok(batch.clone())], schema.clone());
+    let error = connection
+        .ingest("default")
+        .mode(IngestMode::CreateAppend)
+        .from_reader(reader())
+        .unwrap_err();
+    assert_eq!(error.status, Status::InvalidArguments);
+    assert_eq!(*recorder.batches.lock().unwrap(), [3]);
+
+    // Without the check, or when creating the table, data is sent as is.
+    let rows = connection
+        .ingest("default")
+        .mode(IngestMode::Append)
+        .check_schema(false)
+        .from_reader(reader())
+        .unwrap();
+    assert_eq!(rows, 1);
+    let rows = connection.ingest("default").from_reader(reader()).unwrap();
+    assert_eq!(rows, 1);
+    assert_eq!(*recorder.batches.lock().unwrap(), [3, 1, 1]);
+}
@@ -0,0 +1,248 @@
+/// This integration test checks that layers are invoked around the dummy
+/// driver's methods, both natively and through the exported driver.
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use arrow::datatypes::SchemaRef;
+use arrow::error::ArrowError;
+use arrow::record_batch::{RecordBatch, RecordBatchReader};
+
+use adbc_core::driver_manager::ManagedDriver;
+use adbc_core::error::{Error, Result, Status};
+use adbc_core::ffi::FFI_AdbcDriverInitFunc;
+use adbc_core::layer::{Call, Layer, Layered, Method};
+use adbc_core::options::{AdbcVersion, OptionConnection};
+use adbc_core::{Connection, Database, Driver, Optionable, Statement};
+
+use adbc_dummy::DummyDriver;
+
+type Log = Arc<Mutex<Vec<String>>>;
+
+/// Records every hook invocation into a shared log.
+struct Recorder {
+    name: &'static str,
+    log: Log,
+}
+
+impl Recorder {
+    fn new(name: &'static str, log: &Log) -> Self {
+        Self {
+            name,
+            log: log.clone(),
+        }
+    }
+
+    fn push(&self, entry: String) {
+        self.log.lock().unwrap().push(entry);
+    }
+}
+
+impl Layer for Recorder {
+    fn before(&self, call: &Call<'_>) -> Result<()> {
+        self.push(format!(
+            "{} before {:?} {:?} {:?}",
+            self.name, call.method, call.query, call.option
+        ));
+        Ok(())
+    }
+
+    fn after(&self, call: &Call<'_>, result: std::result::Result<(), &Error>, _: Duration) {
+        self.push(format!(
+            "{} after {:?} {}",
+            self.name,
+            call.method,
+            if result.is_ok() { "ok" } else { "err" }
+        ));
+    }
+}
+
+/// Counts batches read from result sets.
+#[derive(Default)]
+struct BatchCounter {
+    batches: Arc<AtomicUsize>,
+}
+
+struct CountingReader<R> {
+    inner: R,
+    batches: Arc<AtomicUsize>,
+}
+
+impl<R: RecordBatchReader> Iterator for CountingReader<R> {
+    type Item = std::result::Result<RecordBatch, ArrowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let batch = self.inner.next();
+        if let Some(Ok(_)) = batch {
+            self.batches.fetch_add(1, Ordering::SeqCst);
+        }
+        batch
+    }
+}
+
+impl<R: RecordBatchReader> RecordBatchReader for CountingReader<R> {
+    fn schema(&self) -> SchemaRef {
+        self.inner.schema()
+    }
+}
+
+impl Layer for BatchCounter {
+    fn wrap_reader<R: RecordBatchReader + Send>(
+        &self,
+        _call: &Call<'_>,
+        reader: R,
+    ) -> impl RecordBatchReader + Send + use<R> {
+        CountingReader {
+            inner: reader,
+            batches: self.batches.clone(),
+        }
+    }
+}
+
+/// Rejects commits and prefixes queries with a comment.
+#[derive(Default)]
+struct Guard;
+
+impl Layer for Guard {
+    fn before(&self, call: &Call<'_>) -> Result<()> {
+        match call.method {
+            Method::ConnectionCommit => Err(Error::with_message_and_status(
+                "Commit is not allowed",
+                Status::Unauthorized,
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn rewrite_query(&self, query: String) -> Result<String> {
+        Ok(format!("/* guarded */ {query}"))
+    }
+}
+
+#[test]
+fn test_layer_hooks() {
+    let log = Log::default();
+    let mut driver = Layered::new(DummyDriver {}, Recorder::new("recorder", &log));
+    let mut database = driver.new_database().unwrap();
+    let mut connection = database.new_connection().unwrap();
+    connection
+        .set_option(OptionConnection::AutoCommit, "false".into())
+        .unwrap();
+    let mut statement = connection.new_statement().unwrap();
+    statement.set_sql_query("SELECT 1").unwrap();
+    statement.execute_update().unwrap();
+
+    assert_eq!(
+        *log.lock().unwrap(),
+        vec![
+            "recorder before DatabaseInit None None",
+            "recorder after DatabaseInit ok",
+            "recorder before ConnectionInit None None",
+            "recorder after ConnectionInit ok",
+            "recorder before ConnectionSetOption None Some(\"adbc.connection.autocommit\")",
+            "recorder after ConnectionSetOption ok",
+            "recorder before StatementNew None None",
+            "recorder after StatementNew ok",
+            "recorder before StatementSetSqlQuery Some(\"SELECT 1\") None",
+            "recorder after StatementSetSqlQuery ok",
+            "recorder before StatementExecuteUpdate Some(\"SELECT 1\") None",
+            "recorder after StatementExecuteUpdate ok",
+        ]
+    );
+    assert_eq!(
+        Method::StatementExecuteUpdate.name(),
+        "AdbcStatementExecuteQuery"
+    );
+}
+
+#[test]
+fn test_layer_abort_and_rewrite() {
+    let log = Log::default();
+    let inner = Layered::new(DummyDriver {}, Recorder::new("inner", &log));
+    let mut driver = Layered::new(inner, Guard);
+    let mut database = driver.new_database().unwrap();
+    let mut connection = database.new_connection().unwrap();
+    log.lock().unwrap().clear();
+
+    let error = connection.commit().unwrap_err();
+    assert_eq!(error.status, Status::Unauthorized);
+    assert!(log.lock().unwrap().is_empty());
+
+    let mut statement = connection.new_statement().unwrap();
+    statement.set_sql_query("SELECT 1").unwrap();
+    statement.prepare().unwrap();
+    assert_eq!(
+        log.lock().unwrap()[2..],
+        [
+            "inner before StatementSetSqlQuery Some(\"/* guarded */ SELECT 1\") None",
+            "inner after StatementSetSqlQuery ok",
+            "inner before StatementPrepare Some(\"/* guarded */ SELECT 1\") None",
+            "inner after StatementPrepare ok",
+        ]
+    );
+}
+
+#[test]
+fn test_layer_stacking() {
+    let log = Log::default();
+    let inner = Layered::new(DummyDriver {}, Recorder::new("inner", &log));
+    let mut driver = Layered::new(inner, Recorder::new("outer", &log));
+    driver.new_database().unwrap();
+
+    assert_eq!(
+        *log.lock().unwrap(),
+        vec![
+            "outer before DatabaseInit None None",
+            "inner before DatabaseInit None None",
+            "inner after DatabaseInit ok",
+            "outer after DatabaseInit ok",
+        ]
+    );
+}
+
+#[test]
+fn test_layer_wrap_reader() {
+    let counter = Arc::new(BatchCounter::default());
+    let mut driver = Layered::new(DummyDriver {}, counter.clone());
+    let mut database = driver.new_database().unwrap();
+    let mut connection = database.new_connection().unwrap();
+    let mut statement = connection.new_statement().unwrap();
+
+    let reader = statement.execute().unwrap();
+    assert_eq!(counter.batches.load(Ordering::SeqCst), 0);
+    assert_eq!(reader.count(), 1);
+    assert_eq!(counter.batches.load(Ordering::SeqCst), 1);
+
+    connection.get_table_types().unwrap().for_each(drop);
+    assert_eq!(counter.batches.load(Ordering::SeqCst), 2);
+}
+
+static EXPORTED_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Default)]
+struct CallCounter;
+
+impl Layer for CallCounter {
+    fn before(&self, _call: &Call<'_>) -> Result<()> {
+        EXPORTED_CALLS.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+adbc_core::export_driver!(LayeredDummyInit, Layered<DummyDriver, CallCounter>);
+
+#[test]
+fn test_layer_exported() {
+    let init: FFI_AdbcDriverInitFunc = LayeredDummyInit;
+    let mut driver = ManagedDriver::load_static(&init, AdbcVersion::V110).unwrap();
+    let mut database = driver.new_database().unwrap();
+    let mut connection = database.new_connection().unwrap();
+    let mut statement = connection.new_statement().unwrap();
+    statement.set_sql_query("SELECT 1").unwrap();
+    let batches = statement.execute().unwrap().count();
+
+    assert_eq!(batches, 1);
+    // DatabaseInit, ConnectionInit, StatementNew, StatementSetSqlQuery and
+    // StatementExecuteQuery.
+    assert_eq!(EXPORTED_CALLS.load(Ordering::SeqCst), 5);
+}
@@ -0,0 +1,122 @@
+/// This integration test checks the metrics reported by the driver manager
+/// and by the metrics layer around the dummy driver.
+use std::sync::Arc;
+
+use adbc_core::driver_manager::ManagedDriver;
+use adbc_core::error::Status;
+use adbc_core::ffi::FFI_AdbcDriverInitFunc;
+use adbc_core::layer::Layered;
+use adbc_core::metrics::{self, InMemoryRecorder, MetricsLayer};
+use adbc_core::options::AdbcVersion;
+use adbc_core::{Connection, Database, Driver, Statement};
+
+use adbc_dummy::{DummyDriver, SingleBatchReader};
+
+fn bind_batches(statement: &mut impl Statement, batches: usize) {
+    let mut connection = DummyDriver {}
+        .new_database()
+        .unwrap()
+        .new_connection()
+        .unwrap();
+    let mut source = connection.new_statement().unwrap();
+    let batch = source.execute().unwrap().next().unwrap().unwrap();
+    for _ in 0..batches {
+        let reader = Box::new(SingleBatchReader::new(batch.clone()));
+        statement.bind_stream(reader).unwrap();
+    }
+}
+
+#[test]
+fn test_metrics_driver_manager() {
+    let recorder = Arc::new(InMemoryRecorder::default());
+    metrics::set_recorder(recorder.clone());
+
+    let init: FFI_AdbcDriverInitFunc = adbc_dummy::DummyDriverInit;
+    let mut driver =
+        ManagedDriver::load_static_with_name("adbc_dummy", &init, AdbcVersion::V110).unwrap();
+    let mut database = driver.new_database().unwrap();
+    let connection1 = database.new_connection().unwrap();
+    let mut connection2 = database.new_connection().unwrap();
+    assert_eq!(database.open_connections(), 2);
+    assert_eq!(recorder.gauge(metrics::CONNECTIONS_OPEN, &[]), 2);
+    drop(connection1);
+    assert_eq!(database.open_connections(), 1);
+    assert_eq!(recorder.gauge(metrics::CONNECTIONS_OPEN, &[]), 1);
+
+    let mut statement = connection2.new_statement().unwrap();
+    let rows: usize = statement
+        .execute()
+        .unwrap()
+        .map(|b| b.unwrap().num_rows())
+        .sum();
+    let labels = [
+        ("method", "AdbcStatementExecuteQuery"),
+        ("driver", "adbc_dummy"),
+    ];
+    assert_eq!(recorder.counter(metrics::STREAM_ROWS, &labels), rows as u64);
+    assert_eq!(recorder.counter(metrics::STREAM_BATCHES, &labels), 1);
+    assert!(recorder.counter(metrics::STREAM_BYTES, &labels) > 0);
+    assert_eq!(
+        recorder.histogram(metrics::STREAM_DURATION, &labels).len(),
+        1
+    );
+    let labels = [("method", "AdbcStatementExecuteQuery"), ("status", "Ok")];
+    assert_eq!(
+        recorder.histogram(metrics::CALLS_DURATION, &labels).len(),
+        1
+    );
+
+    bind_batches(&mut statement, 2);
+    assert_eq!(recorder.counter(metrics::INGEST_ROWS, &[]), 2 * rows as u64);
+    assert_eq!(recorder.counter(metrics::INGEST_BATCHES, &[]), 2);
+    assert_eq!(recorder.histogram(metrics::INGEST_DURATION, &[]).len(), 2);
+
+    let error = connection2
+        .get_table_schema(None, None, "unknown")
+        .unwrap_err();
+    assert_eq!(error.status, Status::NotFound);
+    let labels = [("status", "NotFound")];
+    assert_eq!(recorder.counter(metrics::CALLS_ERRORS, &labels), 1);
+    assert_eq!(recorder.counter(metrics::CALLS_ERRORS, &[]), 1);
+
+    drop(statement);
+    drop(connection2);
+    assert_eq!(recorder.gauge(metrics::CONNECTIONS_OPEN, &[]), 0);
+    metrics::clear_recorder();
+}
+
+#[test]
+fn test_metrics_layer() {
+    let recorder = Arc::new(InMemoryRecorder::default());
+    let layer = MetricsLayer::new("dummy").with_recorder(recorder.clone());
+    let mut driver = Layered::new(DummyDriver {}, layer);
+    let mut database = driver.new_database().unwrap();
+    let mut connection = database.new_connection().unwrap();
+    let mut statement = connection.new_statement().unwrap();
+
+    let rows: usize = statement
+        .execute()
+        .unwrap()
+        .map(|b| b.unwrap().num_rows())
+        .sum();
+    let labels = [("method", "AdbcStatementExecuteQuery"), ("driver", "dummy")];
+    assert_eq!(recorder.counter(metrics::STREAM_ROWS, &labels), rows as u64);
+    assert_eq!(recorder.counter(metrics::STREAM_BATCHES, &labels), 1);
+
+    bind_batches(&mut statement, 1);
+    let labels = [("method", "AdbcStatementBindStream"), ("driver", "dummy")];
+    assert_eq!(recorder.counter(metrics::INGEST_ROWS, &labels), rows as u64);
+    assert!(recorder.counter(metrics::INGEST_BYTES, &labels) > 0);
+
+    connection
+        .get_table_schema(None, None, "unknown")
+        .unwrap_err();
+    let labels = [
+        ("method", "AdbcConnectionGetTableSchema"),
+        ("status", "NotFound"),
+    ];
+    assert_eq!(recorder.counter(metrics::CALLS_ERRORS, &labels), 1);
+    // DatabaseInit, ConnectionInit, StatementNew, StatementExecuteQuery,
+    // StatementBindStream and ConnectionGetTableSchema.
+    assert_eq!(recorder.histogram(metrics::CALLS_DURATION, &[]).len(), 6);
+}
@@ -0,0 +1,130 @@
+/// This integration test opens the exported dummy driver through profiles
+/// read from TOML and YAML documents.
+use adbc_core::error::Status;
+use adbc_core::options::{AdbcVersion, OptionConnection, OptionDatabase, OptionStatement};
+use adbc_core::profile::{self, Profiles, PROFILES_ENV};
+use adbc_core::Optionable;
+
+const TOML: &str = r#"
+[profiles.warehouse]
+driver = "adbc_dummy"
+entrypoint = "DummyDriverInit"
+
+[profiles.warehouse.database]
+uri = "dummy://warehouse"
+password = "${ADBC_TEST_PROFILE_PASSWORD}"
+"dummy.cost" = "$$42"
+
+[profiles.warehouse.connection]
+"adbc.connection.autocommit" = false
+"dummy.retries" = 3
+
+[profiles.warehouse.statement]
+"adbc.ingest.mode" = "adbc.ingest.mode.create_append"
+"dummy.ratio" = 0.5
+"#;
+
+const YAML: &str = r#"
+profiles:
+  warehouse:
+    driver: adbc_dummy
+    entrypoint: DummyDriverInit
+    database:
+      uri: dummy://warehouse
+      password: ${ADBC_TEST_PROFILE_PASSWORD}
+      dummy.cost: $$42
+    connection:
+      adbc.connection.autocommit: false
+      dummy.retries: 3
+    statement:
+      adbc.ingest.mode: adbc.ingest.mode.create_append
+      dummy.ratio: 0.5
+"#;
+
+fn set_password() {
+    std::env::set_var("ADBC_TEST_PROFILE_PASSWORD", "secret");
+}
+
+#[test]
+fn test_profile_open() {
+    set_password();
+    for profiles in [Profiles::from_toml(TOML), Profiles::from_yaml(YAML)] {
+        let profiles = profiles.unwrap();
+        assert_eq!(profiles.names().collect::<Vec<_>>(), ["warehouse"]);
+        let profile = profiles.get("warehouse").unwrap();
+        assert_eq!(profile.version, AdbcVersion::V110);
+        assert_eq!(profile.database[0].0, OptionDatabase::Uri);
+        assert!(!format!("{profile:?}").contains("secret"));
+
+        let mut session = profile.open().unwrap();
+        let value = session
+            .database
+            .get_option_string(OptionDatabase::Password)
+            .unwrap();
+        assert_eq!(value, "secret");
+        let value = session
+            .database
+            .get_option_string(OptionDatabase::Other("dummy.cost".into()))
+            .unwrap();
+        assert_eq!(value, "$42");
+
+        let value = session
+            .connection
+            .get_option_string(OptionConnection::AutoCommit)
+            .unwrap();
+        assert_eq!(value, "false");
+        let value = session
+            .connection
+            .get_option_int(OptionConnection::Other("dummy.retries".into()))
+            .unwrap();
+        assert_eq!(value, 3);
+
+        let statement = session.new_statement().unwrap();
+        let value = statement
+            .get_option_string(OptionStatement::IngestMode)
+            .unwrap();
+        assert_eq!(value, "adbc.ingest.mode.create_append");
+        let value = statement
+            .get_option_double(OptionStatement::Other("dummy.ratio".into()))
+            .unwrap();
+        assert_eq!(value, 0.5);
+    }
+}
+
+#[test]
+fn test_profile_errors() {
+    set_password();
+    let profiles = Profiles::from_toml(TOML).unwrap();
+    let error = profiles.get("missing").unwrap_err();
+    assert_eq!(error.status, Status::NotFound);
+
+    let document = TOML.replace("ADBC_TEST_PROFILE_PASSWORD", "ADBC_TEST_PROFILE_UNSET");
+    let error = Profiles::from_toml(&document).unwrap_err();
+    assert_eq!(error.status, Status::InvalidArguments);
+    assert!(error.message.contains("ADBC_TEST_PROFILE_UNSET"));
+
+    let document = TOML.replace("entrypoint", "entry_point");
+    let error = Profiles::from_toml(&document).unwrap_err();
+    assert_eq!(error.status, Status::InvalidArguments);
+
+    let document = format!("{TOML}\n[profiles.old]\ndriver = \"adbc_dummy\"\nversion = \"0.9\"\n");
+    let error = Profiles::from_toml(&document).unwrap_err();
+    assert_eq!(error.status, Status::InvalidArguments);
+}
+
+#[test]
+fn test_profile_discover() {
+    set_password();
+    let path = std::env::temp_dir().join(format!("adbc_profiles_{}.yaml", std::process::id()));
+    std::fs::write(&path, YAML).unwrap();
+    std::env::set_var(PROFILES_ENV, &path);
+
+    let session = profile::open("warehouse");
+    std::fs::remove_file(&path).unwrap();
+    let session = session.unwrap();
+    let value = session
+        .database
+        .get_option_string(OptionDatabase::Uri)
+        .unwrap();
+    assert_eq!(value, "dummy://warehouse");
+}
@@ -0,0 +1,116 @@
+/// This integration test checks the statements prepared by the prepared
+/// statement cache around the dummy driver.
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use adbc_core::error::{Error, Status};
+use adbc_core::layer::{Call, Layer, Layered, Method};
+use adbc_core::metrics::{self, InMemoryRecorder};
+use adbc_core::options::{OptionStatement, OptionValue};
+use adbc_core::statement_cache::{CacheStats, StatementCache};
+use adbc_core::{Connection, Database, Driver, Optionable};
+
+use adbc_dummy::DummyDriver;
+
+/// Counts prepared statements and fails executions while `stale` is set.
+#[derive(Default)]
+struct Recorder {
+    prepared: AtomicUsize,
+    stale: AtomicBool,
+}
+
+impl Layer for Recorder {
+    fn before(&self, call: &Call<'_>) -> adbc_core::error::Result<()> {
+        match call.method {
+            Method::StatementPrepare => {
+                self.prepared.fetch_add(1, Ordering::Relaxed);
+            }
+            Method::StatementExecuteQuery if self.stale.load(Ordering::Relaxed) => {
+                return Err(Error::with_message_and_status(
+                    "Cached plan must not change result type",
+                    Status::InvalidState,
+                ))
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+fn get_connection(recorder: &Arc<Recorder>) -> impl Connection {
+    let mut driver = Layered::new(DummyDriver {}, recorder.clone());
+    driver.new_database().unwrap().new_connection().unwrap()
+}
+
+#[test]
+fn test_statement_cache() {
+    let metrics_recorder = Arc::new(InMemoryRecorder::default());
+    metrics::set_recorder(metrics_recorder.clone());
+    let recorder = Arc::new(Recorder::default());
+    let mut cache = StatementCache::new(get_connection(&recorder), 2);
+
+    for query in ["SELECT 1", "SELECT 2", "SELECT 1", "SELECT 1"] {
+        let mut statement = cache.prepare(query).unwrap();
+        let rows: usize = statement
+            .execute()
+            .unwrap()
+            .map(|batch| batch.unwrap().num_rows())
+            .sum();
+        assert_eq!(rows, 3);
+    }
+    assert_eq!(recorder.prepared.load(Ordering::Relaxed), 2);
+    assert_eq!(cache.len(), 2);
+
+    // "SELECT 2" is the least recently used statement.
+    cache.prepare("SELECT 3").unwrap();
+    cache.prepare("SELECT 1").unwrap();
+    cache.prepare("SELECT 2").unwrap();
+    assert_eq!(recorder.prepared.load(Ordering::Relaxed), 4);
+    let stats = CacheStats {
+        hits: 3,
+        misses: 4,
+        evictions: 2,
+    };
+    assert_eq!(cache.stats(), stats);
+    assert_eq!(cache.stats().hit_rate(), 3.0 / 7.0);
+
+    // Options set on a cached statement are kept.
+    let mut statement = cache.prepare("SELECT 2").unwrap();
+    let key = OptionStatement::Other("dummy.batch_size".into());
+    statement
+        .statement()
+        .set_option(key.clone(), OptionValue::Int(10))
+        .unwrap();
+    drop(statement);
+    let mut statement = cache.prepare("SELECT 2").unwrap();
+    assert_eq!(statement.statement().get_option_int(key).unwrap(), 10);
+    drop(statement);
+
+    // Statements failing with InvalidState are prepared again.
+    recorder.stale.store(true, Ordering::Relaxed);
+    let error = cache.prepare("SELECT 2").unwrap().execute().err().unwrap();
+    assert_eq!(error.status, Status::InvalidState);
+    assert_eq!(cache.len(), 1);
+    recorder.stale.store(false, Ordering::Relaxed);
+    let mut statement = cache.prepare("SELECT 2").unwrap();
+    assert_eq!(statement.execute().unwrap().count(), 1);
+    drop(statement);
+    assert_eq!(recorder.prepared.load(Ordering::Relaxed), 5);
+
+    assert_eq!(
+        metrics_recorder.counter(metrics::STATEMENT_CACHE_HITS, &[]),
+        6
+    );
+    assert_eq!(
+        metrics_recorder.counter(metrics::STATEMENT_CACHE_MISSES, &[]),
+        5
+    );
+    assert_eq!(
+        metrics_recorder.counter(metrics::STATEMENT_CACHE_EVICTIONS, &[]),
+        3
+    );
+    metrics::clear_recorder();
+
+    cache.clear();
+    assert!(cache.is_empty());
+}
@@ -0,0 +1,195 @@
+/// This integration test checks the spans emitted by the driver manager and
+/// the driver exporter when the `tracing` feature is enabled.
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+use tracing_core::span::Current;
+
+use adbc_core::driver_manager::ManagedDriver;
+use adbc_core::error::Status;
+use adbc_core::ffi::FFI_AdbcDriverInitFunc;
+use adbc_core::options::AdbcVersion;
+use adbc_core::trace::{self, QueryText};
+use adbc_core::{Connection, Database, Driver, Statement};
+
+type Fields = HashMap<String, String>;
+
+/// A span captured by [Capture].
+#[derive(Debug)]
+struct CapturedSpan {
+    metadata: &'static Metadata<'static>,
+    fields: Fields,
+    refs: usize,
+}
+
+impl CapturedSpan {
+    fn get(&self, name: &str) -> Option<&str> {
+        self.fields.get(name).map(String::as_str)
+    }
+
+    fn closed(&self) -> bool {
+        self.refs == 0
+    }
+}
+
+struct FieldVisitor<'a>(&'a mut Fields);
+
+impl Visit for FieldVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.0.insert(field.name().into(), format!("{value:?}"));
+    }
+}
+
+/// Minimal single-threaded subscriber capturing spans.
+#[derive(Default, Clone)]
+struct Capture {
+    spans: Arc<Mutex<Vec<CapturedSpan>>>,
+    stack: Arc<Mutex<Vec<Id>>>,
+}
+
+impl Capture {
+    /// Spans of the given method, in creation order.
+    fn spans(&self, method: &str) -> Vec<usize> {
+        let spans = self.spans.lock().unwrap();
+        (0..spans.len())
+            .filter(|index| spans[*index].get("method") == Some(method))
+            .collect()
+    }
+
+    fn with<T>(&self, index: usize, f: impl FnOnce(&CapturedSpan) -> T) -> T {
+        f(&self.spans.lock().unwrap()[index])
+    }
+}
+
+impl Subscriber for Capture {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, attributes: &Attributes<'_>) -> Id {
+        let mut span = CapturedSpan {
+            metadata: attributes.metadata(),
+            fields: Fields::default(),
+            refs: 1,
+        };
+        attributes.record(&mut FieldVisitor(&mut span.fields));
+        let mut spans = self.spans.lock().unwrap();
+        spans.push(span);
+        Id::from_u64(spans.len() as u64)
+    }
+
+    fn record(&self, id: &Id, values: &Record<'_>) {
+        let mut spans = self.spans.lock().unwrap();
+        let span = &mut spans[id.into_u64() as usize - 1];
+        values.record(&mut FieldVisitor(&mut span.fields));
+    }
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, _event: &Event<'_>) {}
+
+    fn enter(&self, span: &Id) {
+        self.stack.lock().unwrap().push(span.clone());
+    }
+
+    fn exit(&self, _span: &Id) {
+        self.stack.lock().unwrap().pop();
+    }
+
+    fn current_span(&self) -> Current {
+        match self.stack.lock().unwrap().last() {
+            Some(id) => Current::new(
+                id.clone(),
+                self.with(id.into_u64() as usize - 1, |span| span.metadata),
+            ),
+            None => Current::none(),
+        }
+    }
+
+    fn clone_span(&self, id: &Id) -> Id {
+        self.spans.lock().unwrap()[id.into_u64() as usize - 1].refs += 1;
+        id.clone()
+    }
+
+    fn try_close(&self, id: Id) -> bool {
+        let mut spans = self.spans.lock().unwrap();
+        let span = &mut spans[id.into_u64() as usize - 1];
+        span.refs -= 1;
+        span.closed()
+    }
+}
+
+#[test]
+fn test_tracing_spans() {
+    let capture = Capture::default();
+    let _guard = tracing::subscriber::set_default(capture.clone());
+    trace::set_query_text(QueryText::Redacted);
+
+    let init: FFI_AdbcDriverInitFunc = adbc_dummy::DummyDriverInit;
+    let mut driver =
+        ManagedDriver::load_static_with_name("adbc_dummy", &init, AdbcVersion::V110).unwrap();
+    let mut database = driver.new_database().unwrap();
+    let mut connection = database.new_connection().unwrap();
+    let mut statement = connection.new_statement().unwrap();
+    statement
+        .set_sql_query("SELECT * FROM t WHERE name = 'secret'")
+        .unwrap();
+
+    // Spans are emitted by both the driver manager and the exported driver.
+    let init_spans = capture.spans("AdbcDatabaseInit");
+    assert_eq!(init_spans.len(), 2);
+    capture.with(init_spans[0], |span| {
+        assert_eq!(span.get("driver"), Some("adbc_dummy"));
+        assert_eq!(span.get("status"), Some("Ok"));
+        assert!(span.get("duration_us").is_some());
+    });
+    capture.with(init_spans[1], |span| {
+        assert_eq!(span.get("driver"), Some("adbc_dummy::DummyDriver"));
+        assert_eq!(span.get("status"), Some("Ok"));
+    });
+
+    let reader = statement.execute().unwrap();
+    let execute_span = capture.spans("AdbcStatementExecuteQuery")[0];
+    capture.with(execute_span, |span| {
+        assert_eq!(span.get("query"), Some("SELECT * FROM t WHERE name = ?"));
+        assert_eq!(span.get("rows"), None);
+        assert!(!span.closed());
+    });
+    assert_eq!(
+        reader.map(|batch| batch.unwrap().num_rows()).sum::<usize>(),
+        3
+    );
+    capture.with(execute_span, |span| {
+        assert_eq!(span.get("rows"), Some("3"));
+        assert_eq!(span.get("batches"), Some("1"));
+        assert_eq!(span.get("stream_status"), Some("Ok"));
+        assert!(span.closed());
+    });
+
+    let error = connection
+        .get_table_schema(None, None, "unknown")
+        .unwrap_err();
+    assert_eq!(error.status, Status::NotFound);
+    let schema_spans = capture.spans("AdbcConnectionGetTableSchema");
+    assert_eq!(schema_spans.len(), 2);
+    for index in schema_spans {
+        capture.with(index, |span| {
+            assert_eq!(span.get("status"), Some("NotFound"))
+        });
+    }
+
+    drop(connection.get_table_types().unwrap());
+    let types_span = capture.spans("AdbcConnectionGetTableTypes")[0];
+    capture.with(types_span, |span| {
+        assert_eq!(span.get("rows"), Some("0"));
+        assert_eq!(span.get("stream_status"), Some("Dropped"));
+    });
+}
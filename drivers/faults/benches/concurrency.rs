@@ -0,0 +1,70 @@
+//! Benchmarks queries run concurrently on separate connections of the same
+//! managed driver, each query spending a fixed latency in the driver.
+//!
+//! Since only the objects used by a call are locked, the time to run a query
+//! on each connection should stay close to the latency as connections are
+//! added, rather than grow with their number.
+use std::thread;
+use std::time::Duration;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use adbc_core::driver_manager::{ManagedDriver, ManagedStatement};
+use adbc_core::error::{Error, Status};
+use adbc_core::ffi::FFI_AdbcDriverInitFunc;
+use adbc_core::options::{AdbcVersion, OptionDatabase, OptionValue};
+use adbc_core::{Connection, Database, Driver, Statement};
+use adbc_faults::{FaultDriver, OPTION_RULE_PREFIX};
+
+use adbc_dummy::DummyDriver;
+
+const LATENCY: Duration = Duration::from_millis(10);
+
+adbc_core::export_driver!(LatencyDriverInit, FaultDriver<DummyDriver>);
+
+fn statements(connections: usize) -> Vec<ManagedStatement> {
+    let init: FFI_AdbcDriverInitFunc = LatencyDriverInit;
+    let mut driver = ManagedDriver::load_static(&init, AdbcVersion::V110).unwrap();
+    let rule = format!(
+        "fault=latency,ms={},method=StatementExecuteQuery",
+        LATENCY.as_millis()
+    );
+    let opts = [(
+        OptionDatabase::Other(format!("{OPTION_RULE_PREFIX}latency")),
+        OptionValue::from(rule),
+    )];
+    let mut database = driver.new_database_with_opts(opts).unwrap();
+    (0..connections)
+        .map(|_| {
+            let mut connection = database.new_connection().unwrap();
+            let mut statement = connection.new_statement().unwrap();
+            statement.set_sql_query("SELECT 1").unwrap();
+            statement
+        })
+        .collect()
+}
+
+fn concurrent_queries(c: &mut Criterion) {
+    let mut group = c.benchmark_group("concurrent_queries");
+    group.sample_size(10);
+    for connections in [1, 2, 4, 8] {
+        let mut statements = statements(connections);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(connections),
+            &connections,
+            |b, _| {
+                b.iter(|| {
+                    thread::scope(|scope| {
+                        for statement in statements.iter_mut() {
+                            scope.spawn(move || statement.execute().unwrap().count());
+                        }
+                    })
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, concurrent_queries);
+criterion_main!(benches);
@@ -0,0 +1,474 @@
+//! Driver injecting faults into another driver.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use arrow::array::{RecordBatch, RecordBatchReader};
+use arrow::datatypes::Schema;
+
+use adbc_core::capabilities::Capabilities;
+use adbc_core::error::Result;
+use adbc_core::layer::Method;
+use adbc_core::options::{
+    InfoCode, ObjectDepth, OptionConnection, OptionDatabase, OptionStatement, OptionValue,
+};
+use adbc_core::{Connection, Database, Driver, Optionable, PartitionedResult, Statement};
+
+use crate::fault::{CancelHandle, Fault, FaultReader, Faults, Interrupt};
+use crate::OPTION_RULE_PREFIX;
+
+/// Apply the fault selected for a call before forwarding it, returning the
+/// fault left to apply to its result.
+fn inject(
+    faults: &Faults,
+    interrupt: &Interrupt,
+    method: Method,
+    query: Option<&str>,
+) -> Result<Option<Fault>> {
+    match faults.select(method, query) {
+        Some(Fault::Error(error)) => Err(error),
+        Some(Fault::Latency(delay)) => interrupt.wait(Some(delay)).map(|_| None),
+        Some(Fault::Hang) => interrupt.wait(None).map(|_| None),
+        fault => Ok(fault),
+    }
+}
+
+/// A driver injecting the faults selected by its [Faults] into the driver it
+/// wraps.
+#[derive(Debug, Default, Clone)]
+pub struct FaultDriver<D> {
+    inner: D,
+    faults: Faults,
+}
+
+impl<D> FaultDriver<D> {
+    /// Inject the faults selected by `faults` into `inner`.
+    pub fn new(inner: D, faults: Faults) -> Self {
+        Self { inner, faults }
+    }
+
+    /// The rules applied by this driver and the objects it created.
+    pub fn faults(&self) -> &Faults {
+        &self.faults
+    }
+
+    /// The wrapped driver.
+    pub fn inner(&self) -> &D {
+        &self.inner
+    }
+}
+
+impl<D: Driver> Driver for FaultDriver<D> {
+    type DatabaseType = FaultDatabase<D::DatabaseType>;
+
+    fn new_database(&mut self) -> Result<Self::DatabaseType> {
+        self.new_database_with_opts(None)
+    }
+
+    fn new_database_with_opts(
+        &mut self,
+        opts: impl IntoIterator<Item = (OptionDatabase, OptionValue)>,
+    ) -> Result<Self::DatabaseType> {
+        let mut inner_opts = Vec::new();
+        for (key, value) in opts {
+            if key.as_ref().starts_with(OPTION_RULE_PREFIX) {
+                self.faults.set_option(key.as_ref(), value)?;
+            } else {
+                inner_opts.push((key, value));
+            }
+        }
+        let interrupt = Arc::new(Interrupt::default());
+        inject(&self.faults, &interrupt, Method::DatabaseInit, None)?;
+        let inner = self.inner.new_database_with_opts(inner_opts)?;
+        Ok(FaultDatabase {
+            inner,
+            faults: self.faults.clone(),
+            interrupt,
+        })
+    }
+}
+
+/// A database created by a [FaultDriver].
+///
+/// Besides the options of the wrapped database, it accepts the
+/// `adbc.faults.rule.<name>` options, which set the rules of the driver.
+#[derive(Debug)]
+pub struct FaultDatabase<D> {
+    inner: D,
+    faults: Faults,
+    /// Never cancelled, since databases cannot be.
+    interrupt: Arc<Interrupt>,
+}
+
+impl<D> FaultDatabase<D> {
+    /// The wrapped database.
+    pub fn inner(&self) -> &D {
+        &self.inner
+    }
+}
+
+impl<D: Database> Optionable for FaultDatabase<D> {
+    type Option = OptionDatabase;
+
+    fn set_option(&mut self, key: Self::Option, value: OptionValue) -> Result<()> {
+        if key.as_ref().starts_with(OPTION_RULE_PREFIX) {
+            return self.faults.set_option(key.as_ref(), value);
+        }
+        inject(
+            &self.faults,
+            &self.interrupt,
+            Method::DatabaseSetOption,
+            None,
+        )?;
+        self.inner.set_option(key, value)
+    }
+
+    fn get_option_string(&self, key: Self::Option) -> Result<String> {
+        if key.as_ref().starts_with(OPTION_RULE_PREFIX) {
+            return self.faults.get_option(key.as_ref());
+        }
+        inject(
+            &self.faults,
+            &self.interrupt,
+            Method::DatabaseGetOption,
+            None,
+        )?;
+        self.inner.get_option_string(key)
+    }
+
+    fn get_option_bytes(&self, key: Self::Option) -> Result<Vec<u8>> {
+        inject(
+            &self.faults,
+            &self.interrupt,
+            Method::DatabaseGetOption,
+            None,
+        )?;
+        self.inner.get_option_bytes(key)
+    }
+
+    fn get_option_int(&self, key: Self::Option) -> Result<i64> {
+        inject(
+            &self.faults,
+            &self.interrupt,
+            Method::DatabaseGetOption,
+            None,
+        )?;
+        self.inner.get_option_int(key)
+    }
+
+    fn get_option_double(&self, key: Self::Option) -> Result<f64> {
+        inject(
+            &self.faults,
+            &self.interrupt,
+            Method::DatabaseGetOption,
+            None,
+        )?;
+        self.inner.get_option_double(key)
+    }
+}
+
+impl<D: Database> Database for FaultDatabase<D> {
+    type ConnectionType = FaultConnection<D::ConnectionType>;
+
+    fn new_connection(&mut self) -> Result<Self::ConnectionType> {
+        self.new_connection_with_opts(None)
+    }
+
+    fn new_connection_with_opts(
+        &mut self,
+        opts: impl IntoIterator<Item = (OptionConnection, OptionValue)>,
+    ) -> Result<Self::ConnectionType> {
+        inject(&self.faults, &self.interrupt, Method::ConnectionInit, None)?;
+        let inner = self.inner.new_connection_with_opts(opts)?;
+        Ok(FaultConnection {
+            inner,
+            faults: self.faults.clone(),
+            interrupt: Arc::new(Interrupt::default()),
+        })
+    }
+}
+
+/// A connection created by a [FaultDriver].
+#[derive(Debug)]
+pub struct FaultConnection<C> {
+    inner: C,
+    faults: Faults,
+    interrupt: Arc<Interrupt>,
+}
+
+impl<C> FaultConnection<C> {
+    /// The wrapped connection.
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// A handle interrupting the injected delays and hangs of this connection
+    /// and its statements from another thread.
+    pub fn cancel_handle(&self) -> CancelHandle {
+        CancelHandle(self.interrupt.clone())
+    }
+
+    fn inject(&self, method: Method) -> Result<Option<Fault>> {
+        inject(&self.faults, &self.interrupt, method, None)
+    }
+}
+
+impl<C: Connection> Optionable for FaultConnection<C> {
+    type Option = OptionConnection;
+
+    fn set_option(&mut self, key: Self::Option, value: OptionValue) -> Result<()> {
+        self.inject(Method::ConnectionSetOption)?;
+        self.inner.set_option(key, value)
+    }
+
+    fn get_option_string(&self, key: Self::Option) -> Result<String> {
+        self.inject(Method::ConnectionGetOption)?;
+        self.inner.get_option_string(key)
+    }
+
+    fn get_option_bytes(&self, key: Self::Option) -> Result<Vec<u8>> {
+        self.inject(Method::ConnectionGetOption)?;
+        self.inner.get_option_bytes(key)
+    }
+
+    fn get_option_int(&self, key: Self::Option) -> Result<i64> {
+        self.inject(Method::ConnectionGetOption)?;
+        self.inner.get_option_int(key)
+    }
+
+    fn get_option_double(&self, key: Self::Option) -> Result<f64> {
+        self.inject(Method::ConnectionGetOption)?;
+        self.inner.get_option_double(key)
+    }
+}
+
+impl<C: Connection> Connection for FaultConnection<C> {
+    type StatementType = FaultStatement<C::StatementType>;
+
+    fn new_statement(&mut self) -> Result<Self::StatementType> {
+        self.inject(Method::StatementNew)?;
+        let inner = self.inner.new_statement()?;
+        Ok(FaultStatement {
+            inner,
+            faults: self.faults.clone(),
+            interrupt: self.interrupt.child(),
+            query: None,
+        })
+    }
+
+    fn cancel(&mut self) -> Result<()> {
+        self.interrupt.cancel();
+        self.inject(Method::ConnectionCancel)?;
+        self.inner.cancel()
+    }
+
+    fn get_info(&self, codes: Option<HashSet<InfoCode>>) -> Result<impl RecordBatchReader + Send> {
+        let fault = self.inject(Method::ConnectionGetInfo)?;
+        Ok(FaultReader::new(self.inner.get_info(codes)?, fault))
+    }
+
+    fn get_objects(
+        &self,
+        depth: ObjectDepth,
+        catalog: Option<&str>,
+        db_schema: Option<&str>,
+        table_name: Option<&str>,
+        table_type: Option<Vec<&str>>,
+        column_name: Option<&str>,
+    ) -> Result<impl RecordBatchReader + Send> {
+        let fault = self.inject(Method::ConnectionGetObjects)?;
+        let reader = self.inner.get_objects(
+            depth,
+            catalog,
+            db_schema,
+            table_name,
+            table_type,
+            column_name,
+        )?;
+        Ok(FaultReader::new(reader, fault))
+    }
+
+    fn get_table_schema(
+        &self,
+        catalog: Option<&str>,
+        db_schema: Option<&str>,
+        table_name: &str,
+    ) -> Result<Schema> {
+        self.inject(Method::ConnectionGetTableSchema)?;
+        self.inner.get_table_schema(catalog, db_schema, table_name)
+    }
+
+    fn get_table_types(&self) -> Result<impl RecordBatchReader + Send> {
+        let fault = self.inject(Method::ConnectionGetTableTypes)?;
+        Ok(FaultReader::new(self.inner.get_table_types()?, fault))
+    }
+
+    fn get_statistic_names(&self) -> Result<impl RecordBatchReader + Send> {
+        let fault = self.inject(Method::ConnectionGetStatisticNames)?;
+        Ok(FaultReader::new(self.inner.get_statistic_names()?, fault))
+    }
+
+    fn get_statistics(
+        &self,
+        catalog: Option<&str>,
+        db_schema: Option<&str>,
+        table_name: Option<&str>,
+        approximate: bool,
+    ) -> Result<impl RecordBatchReader + Send> {
+        let fault = self.inject(Method::ConnectionGetStatistics)?;
+        let reader = self
+            .inner
+            .get_statistics(catalog, db_schema, table_name, approximate)?;
+        Ok(FaultReader::new(reader, fault))
+    }
+
+    fn commit(&mut self) -> Result<()> {
+        self.inject(Method::ConnectionCommit)?;
+        self.inner.commit()
+    }
+
+    fn rollback(&mut self) -> Result<()> {
+        self.inject(Method::ConnectionRollback)?;
+        self.inner.rollback()
+    }
+
+    fn read_partition(&self, partition: impl AsRef<[u8]>) -> Result<impl RecordBatchReader + Send> {
+        let fault = self.inject(Method::ConnectionReadPartition)?;
+        Ok(FaultReader::new(
+            self.inner.read_partition(partition)?,
+            fault,
+        ))
+    }
+
+    fn capabilities(&self) -> Result<Capabilities> {
+        self.inner.capabilities()
+    }
+}
+
+/// A statement created by a [FaultDriver].
+#[derive(Debug)]
+pub struct FaultStatement<S> {
+    inner: S,
+    faults: Faults,
+    interrupt: Arc<Interrupt>,
+    /// The query last set on the statement, matched by [Rule::matching][crate::Rule::matching].
+    query: Option<String>,
+}
+
+impl<S> FaultStatement<S> {
+    /// The wrapped statement.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// A handle interrupting the injected delays and hangs of this statement
+    /// from another thread.
+    pub fn cancel_handle(&self) -> CancelHandle {
+        CancelHandle(self.interrupt.clone())
+    }
+
+    fn inject(&self, method: Method) -> Result<Option<Fault>> {
+        inject(&self.faults, &self.interrupt, method, self.query.as_deref())
+    }
+}
+
+impl<S: Statement> Optionable for FaultStatement<S> {
+    type Option = OptionStatement;
+
+    fn set_option(&mut self, key: Self::Option, value: OptionValue) -> Result<()> {
+        self.inject(Method::StatementSetOption)?;
+        self.inner.set_option(key, value)
+    }
+
+    fn get_option_string(&self, key: Self::Option) -> Result<String> {
+        self.inject(Method::StatementGetOption)?;
+        self.inner.get_option_string(key)
+    }
+
+    fn get_option_bytes(&self, key: Self::Option) -> Result<Vec<u8>> {
+        self.inject(Method::StatementGetOption)?;
+        self.inner.get_option_bytes(key)
+    }
+
+    fn get_option_int(&self, key: Self::Option) -> Result<i64> {
+        self.inject(Method::StatementGetOption)?;
+        self.inner.get_option_int(key)
+    }
+
+    fn get_option_double(&self, key: Self::Option) -> Result<f64> {
+        self.inject(Method::StatementGetOption)?;
+        self.inner.get_option_double(key)
+    }
+}
+
+impl<S: Statement> Statement for FaultStatement<S> {
+    fn bind(&mut self, batch: RecordBatch) -> Result<()> {
+        self.inject(Method::StatementBind)?;
+        self.inner.bind(batch)
+    }
+
+    fn bind_stream(&mut self, reader: Box<dyn RecordBatchReader + Send>) -> Result<()> {
+        self.inject(Method::StatementBindStream)?;
+        self.inner.bind_stream(reader)
+    }
+
+    fn execute(&mut self) -> Result<impl RecordBatchReader + Send> {
+        let fault = self.inject(Method::StatementExecuteQuery)?;
+        Ok(FaultReader::new(self.inner.execute()?, fault))
+    }
+
+    fn execute_update(&mut self) -> Result<Option<i64>> {
+        let fault = self.inject(Method::StatementExecuteUpdate)?;
+        let rows = self.inner.execute_update()?;
+        match fault {
+            Some(Fault::PartialUpdate(rows)) => Ok(Some(rows)),
+            _ => Ok(rows),
+        }
+    }
+
+    fn execute_schema(&mut self) -> Result<Schema> {
+        self.inject(Method::StatementExecuteSchema)?;
+        self.inner.execute_schema()
+    }
+
+    fn execute_partitions(&mut self) -> Result<PartitionedResult> {
+        self.inject(Method::StatementExecutePartitions)?;
+        self.inner.execute_partitions()
+    }
+
+    fn get_parameter_schema(&self) -> Result<Schema> {
+        self.inject(Method::StatementGetParameterSchema)?;
+        self.inner.get_parameter_schema()
+    }
+
+    fn prepare(&mut self) -> Result<()> {
+        self.inject(Method::StatementPrepare)?;
+        self.inner.prepare()
+    }
+
+    fn set_sql_query(&mut self, query: impl AsRef<str>) -> Result<()> {
+        let query = query.as_ref();
+        inject(
+            &self.faults,
+            &self.interrupt,
+            Method::StatementSetSqlQuery,
+            Some(query),
+        )?;
+        self.inner.set_sql_query(query)?;
+        self.query = Some(query.to_string());
+        Ok(())
+    }
+
+    fn set_substrait_plan(&mut self, plan: impl AsRef<[u8]>) -> Result<()> {
+        self.inject(Method::StatementSetSubstraitPlan)?;
+        self.inner.set_substrait_plan(plan)?;
+        self.query = None;
+        Ok(())
+    }
+
+    fn cancel(&mut self) -> Result<()> {
+        self.interrupt.cancel();
+        self.inject(Method::StatementCancel)?;
+        self.inner.cancel()
+    }
+}
@@ -0,0 +1,63 @@
+//! Driver loading the driver it delegates to from database options.
+
+use adbc_core::driver_manager::{ManagedDatabase, ManagedDriver};
+use adbc_core::error::{Error, Result, Status};
+use adbc_core::options::{AdbcVersion, OptionDatabase, OptionValue};
+use adbc_core::Driver;
+
+use crate::{OPTION_DRIVER, OPTION_ENTRYPOINT};
+
+/// A driver delegating to a driver loaded from a dynamic library when a
+/// database is created.
+///
+/// The library is given by the [OPTION_DRIVER] database option, and its
+/// entrypoint by the [OPTION_ENTRYPOINT] option (defaults to
+/// `AdbcDriverInit`). Other options are passed to the loaded driver.
+#[derive(Debug, Default, Clone)]
+pub struct DynamicDriver;
+
+fn string_option(key: &str, value: OptionValue) -> Result<String> {
+    match value {
+        OptionValue::String(value) => Ok(value),
+        _ => Err(Error::with_message_and_status(
+            format!("Option {key} must be a string"),
+            Status::InvalidArguments,
+        )),
+    }
+}
+
+impl Driver for DynamicDriver {
+    type DatabaseType = ManagedDatabase;
+
+    fn new_database(&mut self) -> Result<Self::DatabaseType> {
+        self.new_database_with_opts(None)
+    }
+
+    fn new_database_with_opts(
+        &mut self,
+        opts: impl IntoIterator<Item = (OptionDatabase, OptionValue)>,
+    ) -> Result<Self::DatabaseType> {
+        let mut filename = None;
+        let mut entrypoint = None;
+        let mut inner_opts = Vec::new();
+        for (key, value) in opts {
+            match key.as_ref() {
+                OPTION_DRIVER => filename = Some(string_option(OPTION_DRIVER, value)?),
+                OPTION_ENTRYPOINT => entrypoint = Some(string_option(OPTION_ENTRYPOINT, value)?),
+                _ => inner_opts.push((key, value)),
+            }
+        }
+        let filename = filename.ok_or_else(|| {
+            Error::with_message_and_status(
+                format!("Option {OPTION_DRIVER} is required"),
+                Status::InvalidState,
+            )
+        })?;
+        let mut driver = ManagedDriver::load_dynamic_from_filename(
+            filename,
+            entrypoint.as_deref().map(str::as_bytes),
+            AdbcVersion::V110,
+        )?;
+        driver.new_database_with_opts(inner_opts)
+    }
+}
@@ -0,0 +1,591 @@
+//! Faults and the rules selecting the calls they are injected into.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Condvar, Mutex, Weak};
+use std::time::Duration;
+
+use arrow::array::{RecordBatch, RecordBatchReader};
+use arrow::datatypes::SchemaRef;
+use arrow::error::ArrowError;
+
+use adbc_core::error::{Error, Result, Status};
+use adbc_core::layer::Method;
+use adbc_core::options::OptionValue;
+
+use crate::OPTION_RULE_PREFIX;
+
+/// A failure injected into a call.
+#[derive(Debug, Clone)]
+pub enum Fault {
+    /// Fail the call with the given error, without forwarding it.
+    Error(Error),
+    /// Delay the call by the given duration before forwarding it.
+    ///
+    /// The delay is interrupted by cancelling the object the call was made
+    /// on, which makes the call fail with [Status::Cancelled].
+    Latency(Duration),
+    /// Forward the call, failing the result set it returns with the given
+    /// error after the given number of batches (or at its end, if it has
+    /// fewer batches).
+    ///
+    /// Only applies to methods returning a result set.
+    StreamError { after: usize, error: Error },
+    /// Forward [Statement::execute_update][adbc_core::Statement::execute_update],
+    /// reporting the given number of affected rows instead of the actual one,
+    /// as if the update was only partially applied.
+    PartialUpdate(i64),
+    /// Block the call until the object it was made on (or its connection)
+    /// is cancelled, then fail it with [Status::Cancelled].
+    ///
+    /// Only applies to methods of connections and statements, except their
+    /// initialization and cancellation.
+    Hang,
+}
+
+impl Fault {
+    /// Fail calls with an error of the given status.
+    pub fn error(status: Status) -> Self {
+        Self::Error(injected_error(status))
+    }
+
+    /// Fail result sets with an error of the given status after `after` batches.
+    pub fn stream_error(after: usize, status: Status) -> Self {
+        Self::StreamError {
+            after,
+            error: injected_error(status),
+        }
+    }
+
+    /// Set the SQLSTATE of the injected error, for [Fault::Error] and
+    /// [Fault::StreamError].
+    pub fn with_sqlstate(mut self, sqlstate: &[u8; 5]) -> Self {
+        if let Self::Error(error) | Self::StreamError { error, .. } = &mut self {
+            error.sqlstate = sqlstate.map(|c| c as _);
+        }
+        self
+    }
+
+    fn applies_to(&self, method: Method) -> bool {
+        match self {
+            Self::Error(_) | Self::Latency(_) => true,
+            Self::StreamError { .. } => matches!(
+                method,
+                Method::ConnectionGetInfo
+                    | Method::ConnectionGetObjects
+                    | Method::ConnectionGetTableTypes
+                    | Method::ConnectionGetStatisticNames
+                    | Method::ConnectionGetStatistics
+                    | Method::ConnectionReadPartition
+                    | Method::StatementExecuteQuery
+            ),
+            Self::PartialUpdate(_) => method == Method::StatementExecuteUpdate,
+            Self::Hang => !matches!(
+                method,
+                Method::DatabaseInit
+                    | Method::DatabaseSetOption
+                    | Method::DatabaseGetOption
+                    | Method::ConnectionInit
+                    | Method::ConnectionCancel
+                    | Method::StatementCancel
+            ),
+        }
+    }
+}
+
+fn injected_error(status: Status) -> Error {
+    Error::with_message_and_status("Injected fault", status)
+}
+
+/// A fault along with the calls it is injected into.
+///
+/// By default, a rule injects its fault into every call of every method it
+/// applies to.
+///
+/// Rules can also be parsed from a specification made of comma-separated
+/// `key=value` fields, as given to the `adbc.faults.rule.<name>` database
+/// options:
+/// - `fault`: one of `error`, `latency`, `stream_error`, `partial_update` and
+///   `hang`;
+/// - `status`, `sqlstate` and `message`: the injected error, for `error` and
+///   `stream_error` (defaults to `IO`, no SQLSTATE and `Injected fault`);
+/// - `after`: the number of batches preceding the error, for `stream_error`
+///   (defaults to 0);
+/// - `ms`: the delay in milliseconds, for `latency`;
+/// - `rows`: the number of affected rows reported, for `partial_update`;
+/// - `method`, `query`, `skip` and `times`: see [Rule::on], [Rule::matching],
+///   [Rule::skip] and [Rule::times].
+///
+/// For instance `fault=error,status=IO,sqlstate=08006,method=StatementExecuteQuery,times=2`.
+#[derive(Debug, Clone)]
+pub struct Rule {
+    fault: Fault,
+    method: Option<Method>,
+    query: Option<String>,
+    skip: usize,
+    times: Option<usize>,
+}
+
+impl Rule {
+    /// Inject `fault`.
+    pub fn new(fault: Fault) -> Self {
+        Self {
+            fault,
+            method: None,
+            query: None,
+            skip: 0,
+            times: None,
+        }
+    }
+
+    /// Only inject the fault into calls of `method`.
+    pub fn on(mut self, method: Method) -> Self {
+        self.method = Some(method);
+        self
+    }
+
+    /// Only inject the fault into calls made on statements whose SQL query
+    /// contains `query`.
+    pub fn matching(mut self, query: impl Into<String>) -> Self {
+        self.query = Some(query.into());
+        self
+    }
+
+    /// Let the first `count` matching calls through.
+    pub fn skip(mut self, count: usize) -> Self {
+        self.skip = count;
+        self
+    }
+
+    /// Inject the fault at most `count` times.
+    pub fn times(mut self, count: usize) -> Self {
+        self.times = Some(count);
+        self
+    }
+
+    fn matches(&self, method: Method, query: Option<&str>) -> bool {
+        self.fault.applies_to(method)
+            && self.method.is_none_or(|m| m == method)
+            && self
+                .query
+                .as_deref()
+                .is_none_or(|pattern| query.is_some_and(|query| query.contains(pattern)))
+    }
+}
+
+const STATUSES: [Status; 15] = [
+    Status::Ok,
+    Status::Unknown,
+    Status::NotImplemented,
+    Status::NotFound,
+    Status::AlreadyExists,
+    Status::InvalidArguments,
+    Status::InvalidState,
+    Status::InvalidData,
+    Status::Integrity,
+    Status::Internal,
+    Status::IO,
+    Status::Cancelled,
+    Status::Timeout,
+    Status::Unauthenticated,
+    Status::Unauthorized,
+];
+
+const METHODS: [Method; 30] = [
+    Method::DatabaseInit,
+    Method::DatabaseSetOption,
+    Method::DatabaseGetOption,
+    Method::ConnectionInit,
+    Method::ConnectionSetOption,
+    Method::ConnectionGetOption,
+    Method::ConnectionCancel,
+    Method::ConnectionGetInfo,
+    Method::ConnectionGetObjects,
+    Method::ConnectionGetTableSchema,
+    Method::ConnectionGetTableTypes,
+    Method::ConnectionGetStatisticNames,
+    Method::ConnectionGetStatistics,
+    Method::ConnectionCommit,
+    Method::ConnectionRollback,
+    Method::ConnectionReadPartition,
+    Method::StatementNew,
+    Method::StatementSetOption,
+    Method::StatementGetOption,
+    Method::StatementBind,
+    Method::StatementBindStream,
+    Method::StatementExecuteQuery,
+    Method::StatementExecuteUpdate,
+    Method::StatementExecuteSchema,
+    Method::StatementExecutePartitions,
+    Method::StatementGetParameterSchema,
+    Method::StatementPrepare,
+    Method::StatementSetSqlQuery,
+    Method::StatementSetSubstraitPlan,
+    Method::StatementCancel,
+];
+
+fn invalid_spec(message: impl std::fmt::Display) -> Error {
+    Error::with_message_and_status(
+        format!("Invalid fault rule: {message}"),
+        Status::InvalidArguments,
+    )
+}
+
+/// Find the variant of `variants` whose name is `value`, ignoring case.
+fn parse_variant<T: std::fmt::Debug + Copy>(variants: &[T], kind: &str, value: &str) -> Result<T> {
+    variants
+        .iter()
+        .find(|variant| format!("{variant:?}").eq_ignore_ascii_case(value))
+        .copied()
+        .ok_or_else(|| invalid_spec(format!("unknown {kind} {value:?}")))
+}
+
+fn parse_number<T: FromStr>(key: &str, value: &str) -> Result<T> {
+    value
+        .parse()
+        .map_err(|_| invalid_spec(format!("{key} must be a number, got {value:?}")))
+}
+
+fn required<'a>(fields: &mut HashMap<&str, &'a str>, key: &str) -> Result<&'a str> {
+    fields
+        .remove(key)
+        .ok_or_else(|| invalid_spec(format!("missing field {key:?}")))
+}
+
+/// Parse the error injected by `error` and `stream_error` faults.
+fn parse_error(fields: &mut HashMap<&str, &str>) -> Result<Error> {
+    let status = match fields.remove("status") {
+        Some(status) => parse_variant(&STATUSES, "status", status)?,
+        None => Status::IO,
+    };
+    let mut error = injected_error(status);
+    if let Some(message) = fields.remove("message") {
+        error.message = message.into();
+    }
+    if let Some(sqlstate) = fields.remove("sqlstate") {
+        let sqlstate: [u8; 5] = sqlstate
+            .as_bytes()
+            .try_into()
+            .map_err(|_| invalid_spec(format!("invalid SQLSTATE {sqlstate:?}")))?;
+        error.sqlstate = sqlstate.map(|c| c as _);
+    }
+    Ok(error)
+}
+
+impl FromStr for Rule {
+    type Err = Error;
+
+    fn from_str(spec: &str) -> Result<Self> {
+        let mut fields = HashMap::new();
+        for field in spec.split(',').map(str::trim).filter(|f| !f.is_empty()) {
+            let (key, value) = field.split_once('=').unwrap_or((field, ""));
+            if fields.insert(key.trim(), value.trim()).is_some() {
+                return Err(invalid_spec(format!("duplicate field {key:?}")));
+            }
+        }
+
+        let fault = match required(&mut fields, "fault")? {
+            "error" => Fault::Error(parse_error(&mut fields)?),
+            "latency" => {
+                let ms = required(&mut fields, "ms")?;
+                Fault::Latency(Duration::from_millis(parse_number("ms", ms)?))
+            }
+            "stream_error" => {
+                let after = match fields.remove("after") {
+                    Some(after) => parse_number("after", after)?,
+                    None => 0,
+                };
+                let error = parse_error(&mut fields)?;
+                Fault::StreamError { after, error }
+            }
+            "partial_update" => {
+                let rows = required(&mut fields, "rows")?;
+                Fault::PartialUpdate(parse_number("rows", rows)?)
+            }
+            "hang" => Fault::Hang,
+            fault => return Err(invalid_spec(format!("unknown fault {fault:?}"))),
+        };
+
+        let mut rule = Rule::new(fault);
+        if let Some(method) = fields.remove("method") {
+            rule = rule.on(parse_variant(&METHODS, "method", method)?);
+        }
+        if let Some(query) = fields.remove("query") {
+            rule = rule.matching(query);
+        }
+        if let Some(skip) = fields.remove("skip") {
+            rule = rule.skip(parse_number("skip", skip)?);
+        }
+        if let Some(times) = fields.remove("times") {
+            rule = rule.times(parse_number("times", times)?);
+        }
+        if let Some(key) = fields.keys().next() {
+            return Err(invalid_spec(format!("unknown field {key:?}")));
+        }
+        Ok(rule)
+    }
+}
+
+#[derive(Debug)]
+struct Entry {
+    name: Option<String>,
+    spec: Option<String>,
+    rule: Rule,
+    /// Number of calls matched so far.
+    calls: usize,
+    /// Number of faults injected so far.
+    injected: usize,
+}
+
+#[derive(Debug, Default)]
+struct FaultsInner {
+    entries: Vec<Entry>,
+    injected: usize,
+}
+
+/// The set of rules applied by a [FaultDriver][crate::FaultDriver].
+///
+/// Rules are checked in the order they were added, and the first one
+/// matching a call selects the fault injected into it. Clones share the same
+/// rules, so they can be changed while the driver is in use.
+#[derive(Debug, Clone, Default)]
+pub struct Faults {
+    inner: Arc<Mutex<FaultsInner>>,
+}
+
+impl Faults {
+    /// An empty set of rules, injecting no fault.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `rule` after the existing rules.
+    pub fn inject(&self, rule: Rule) {
+        self.insert(None, None, rule);
+    }
+
+    /// Add `rule` under `name`, replacing the rule previously named so if any.
+    pub fn set(&self, name: impl Into<String>, rule: Rule) {
+        self.insert(Some(name.into()), None, rule);
+    }
+
+    /// Remove the rule named `name`, returning whether it existed.
+    pub fn remove(&self, name: &str) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        let len = inner.entries.len();
+        inner.entries.retain(|e| e.name.as_deref() != Some(name));
+        inner.entries.len() != len
+    }
+
+    /// Remove all rules.
+    pub fn clear(&self) {
+        self.inner.lock().unwrap().entries.clear();
+    }
+
+    /// Total number of faults injected so far.
+    pub fn injected(&self) -> usize {
+        self.inner.lock().unwrap().injected
+    }
+
+    fn insert(&self, name: Option<String>, spec: Option<String>, rule: Rule) {
+        let entry = Entry {
+            name,
+            spec,
+            rule,
+            calls: 0,
+            injected: 0,
+        };
+        let mut inner = self.inner.lock().unwrap();
+        let existing = entry.name.as_ref().and_then(|name| {
+            inner
+                .entries
+                .iter()
+                .position(|e| e.name.as_ref() == Some(name))
+        });
+        match existing {
+            Some(index) => inner.entries[index] = entry,
+            None => inner.entries.push(entry),
+        }
+    }
+
+    /// Set a rule from an `adbc.faults.rule.<name>` option, an empty value
+    /// removing it.
+    pub(crate) fn set_option(&self, key: &str, value: OptionValue) -> Result<()> {
+        let name = &key[OPTION_RULE_PREFIX.len()..];
+        let OptionValue::String(spec) = value else {
+            return Err(Error::with_message_and_status(
+                format!("Option {key} must be a string"),
+                Status::InvalidArguments,
+            ));
+        };
+        if spec.is_empty() {
+            self.remove(name);
+        } else {
+            let rule = spec.parse()?;
+            self.insert(Some(name.into()), Some(spec), rule);
+        }
+        Ok(())
+    }
+
+    /// The specification of the rule set with the `adbc.faults.rule.<name>` option.
+    pub(crate) fn get_option(&self, key: &str) -> Result<String> {
+        let name = &key[OPTION_RULE_PREFIX.len()..];
+        let inner = self.inner.lock().unwrap();
+        inner
+            .entries
+            .iter()
+            .find(|e| e.name.as_deref() == Some(name))
+            .and_then(|e| e.spec.clone())
+            .ok_or_else(|| {
+                Error::with_message_and_status(
+                    format!("Unrecognized database option: {key}"),
+                    Status::NotFound,
+                )
+            })
+    }
+
+    /// Select the fault to inject into a call, if any.
+    pub(crate) fn select(&self, method: Method, query: Option<&str>) -> Option<Fault> {
+        let mut inner = self.inner.lock().unwrap();
+        let fault = inner.entries.iter_mut().find_map(|entry| {
+            if !entry.rule.matches(method, query) {
+                return None;
+            }
+            entry.calls += 1;
+            let exhausted = entry
+                .rule
+                .times
+                .is_some_and(|times| entry.injected >= times);
+            if entry.calls <= entry.rule.skip || exhausted {
+                return None;
+            }
+            entry.injected += 1;
+            Some(entry.rule.fault.clone())
+        });
+        if fault.is_some() {
+            inner.injected += 1;
+        }
+        fault
+    }
+}
+
+/// Cancellation state of a connection or statement.
+#[derive(Debug, Default)]
+pub(crate) struct Interrupt {
+    /// Incremented by each cancellation.
+    generation: Mutex<u64>,
+    cond: Condvar,
+    /// Interrupts cancelled along with this one.
+    children: Mutex<Vec<Weak<Interrupt>>>,
+}
+
+impl Interrupt {
+    /// A new interrupt, cancelled whenever `self` is.
+    pub(crate) fn child(&self) -> Arc<Self> {
+        let child = Arc::new(Self::default());
+        let mut children = self.children.lock().unwrap();
+        children.retain(|c| c.strong_count() > 0);
+        children.push(Arc::downgrade(&child));
+        child
+    }
+
+    pub(crate) fn cancel(&self) {
+        *self.generation.lock().unwrap() += 1;
+        self.cond.notify_all();
+        for child in self.children.lock().unwrap().iter() {
+            if let Some(child) = child.upgrade() {
+                child.cancel();
+            }
+        }
+    }
+
+    /// Wait for `timeout`, or until cancelled if `None`, failing if cancelled.
+    pub(crate) fn wait(&self, timeout: Option<Duration>) -> Result<()> {
+        let generation = self.generation.lock().unwrap();
+        let start = *generation;
+        let cancelled = |g: &mut u64| *g == start;
+        match timeout {
+            Some(timeout) => {
+                let (_generation, result) = self
+                    .cond
+                    .wait_timeout_while(generation, timeout, cancelled)
+                    .unwrap();
+                if result.timed_out() {
+                    return Ok(());
+                }
+            }
+            None => drop(self.cond.wait_while(generation, cancelled).unwrap()),
+        }
+        Err(Error::with_message_and_status(
+            "Call was cancelled",
+            Status::Cancelled,
+        ))
+    }
+}
+
+/// A handle cancelling the calls made on a
+/// [FaultConnection][crate::FaultConnection] or
+/// [FaultStatement][crate::FaultStatement] from another thread.
+///
+/// Cancelling only interrupts injected delays and hangs; the wrapped object
+/// is cancelled by the `cancel` method of the connection or statement.
+#[derive(Debug, Clone)]
+pub struct CancelHandle(pub(crate) Arc<Interrupt>);
+
+impl CancelHandle {
+    /// Interrupt the injected delays and hangs in progress.
+    pub fn cancel(&self) {
+        self.0.cancel()
+    }
+}
+
+/// A result set failing after a number of batches, if a
+/// [Fault::StreamError] was injected into the call returning it.
+pub struct FaultReader<R> {
+    inner: R,
+    /// The number of batches left before the error, and the error.
+    fault: Option<(usize, Error)>,
+    failed: bool,
+}
+
+impl<R> FaultReader<R> {
+    pub(crate) fn new(inner: R, fault: Option<Fault>) -> Self {
+        let fault = match fault {
+            Some(Fault::StreamError { after, error }) => Some((after, error)),
+            _ => None,
+        };
+        Self {
+            inner,
+            fault,
+            failed: false,
+        }
+    }
+}
+
+impl<R: RecordBatchReader> Iterator for FaultReader<R> {
+    type Item = std::result::Result<RecordBatch, ArrowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let Some((remaining, _)) = &mut self.fault else {
+            return self.inner.next();
+        };
+        if *remaining > 0 {
+            *remaining -= 1;
+            if let Some(batch) = self.inner.next() {
+                return Some(batch);
+            }
+        }
+        let (_, error) = self.fault.take().unwrap();
+        self.failed = true;
+        Some(Err(ArrowError::ExternalError(Box::new(error))))
+    }
+}
+
+impl<R: RecordBatchReader> RecordBatchReader for FaultReader<R> {
+    fn schema(&self) -> SchemaRef {
+        self.inner.schema()
+    }
+}
@@ -0,0 +1,68 @@
+//! An ADBC driver injecting faults into other drivers.
+//!
+//! [FaultDriver] wraps any [Driver][adbc_core::Driver], including a
+//! [ManagedDriver][adbc_core::driver_manager::ManagedDriver], and injects
+//! failures into the calls made to it, to test how applications handle them:
+//! errors with a chosen status and SQLSTATE, latency, result sets failing
+//! after a number of batches, partial [execute_update][adbc_core::Statement::execute_update]
+//! results and calls hanging until they are cancelled. See [Fault].
+//!
+//! Which faults are injected into which calls is decided by the [Rule]s of a
+//! [Faults] set, either built with the Rust API or parsed from the
+//! `adbc.faults.rule.<name>` database options (see [OPTION_RULE_PREFIX]).
+//!
+//! Since methods of connections and statements take `&mut self`, hung calls
+//! are interrupted from other threads with a [CancelHandle] obtained
+//! beforehand, or through the C API when the driver is exported.
+//!
+//! The driver is exported as `AdbcFaultsInit`, wrapping the driver loaded
+//! from the library given by the [OPTION_DRIVER] database option.
+//!
+//! # Example
+//!
+//! ```rust
+//! use adbc_core::error::Status;
+//! use adbc_core::layer::Method;
+//! use adbc_core::{Connection, Database, Driver, Statement};
+//! use adbc_dummy::DummyDriver;
+//! use adbc_faults::{Fault, FaultDriver, Faults, Rule};
+//!
+//! let faults = Faults::new();
+//! let rule = Rule::new(Fault::error(Status::IO).with_sqlstate(b"08006"))
+//!     .on(Method::StatementExecuteQuery)
+//!     .times(1);
+//! faults.inject(rule);
+//!
+//! let mut driver = FaultDriver::new(DummyDriver::default(), faults);
+//! let mut statement = driver.new_database()?.new_connection()?.new_statement()?;
+//! statement.set_sql_query("SELECT 1")?;
+//! let error = statement.execute().err().unwrap();
+//! assert_eq!(error.status, Status::IO);
+//! assert!(statement.execute().is_ok());
+//! # Ok::<(), adbc_core::error::Error>(())
+//! ```
+
+// The driver exporter expects these in scope.
+use adbc_core::error::{Error, Status};
+
+mod driver;
+mod dynamic;
+mod fault;
+
+pub use driver::{FaultConnection, FaultDatabase, FaultDriver, FaultStatement};
+pub use dynamic::DynamicDriver;
+pub use fault::{CancelHandle, Fault, FaultReader, Faults, Rule};
+
+/// Prefix of the database options setting the rules of a [FaultDriver].
+///
+/// The option `adbc.faults.rule.<name>` sets the rule named `<name>` from the
+/// specification given as value (see [Rule]), an empty value removing it.
+pub const OPTION_RULE_PREFIX: &str = "adbc.faults.rule.";
+
+/// Database option setting the library of the driver loaded by [DynamicDriver].
+pub const OPTION_DRIVER: &str = "adbc.faults.driver";
+
+/// Database option setting the entrypoint of the driver loaded by [DynamicDriver].
+pub const OPTION_ENTRYPOINT: &str = "adbc.faults.entrypoint";
+
+adbc_core::export_driver!(AdbcFaultsInit, FaultDriver<DynamicDriver>);
@@ -0,0 +1,246 @@
+/// This integration test injects faults into the dummy driver, used directly,
+/// through the driver manager and through the exported fault driver.
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use arrow::array::RecordBatchReader;
+
+use adbc_core::driver_manager::ManagedDriver;
+use adbc_core::error::{Result, Status};
+use adbc_core::ffi::FFI_AdbcDriverInitFunc;
+use adbc_core::layer::Method;
+use adbc_core::options::{AdbcVersion, OptionDatabase, OptionValue};
+use adbc_core::{Connection, Database, Driver, Optionable, Statement};
+use adbc_faults::{
+    CancelHandle, Fault, FaultDriver, Faults, Rule, OPTION_DRIVER, OPTION_ENTRYPOINT,
+    OPTION_RULE_PREFIX,
+};
+
+use adbc_dummy::DummyDriver;
+
+fn rule_option(name: &str) -> OptionDatabase {
+    OptionDatabase::Other(format!("{OPTION_RULE_PREFIX}{name}"))
+}
+
+/// The number of rows of each batch of `reader`, or the error it returned.
+fn collect(reader: impl RecordBatchReader) -> Vec<std::result::Result<usize, String>> {
+    reader
+        .map(|batch| batch.map(|b| b.num_rows()).map_err(|e| e.to_string()))
+        .collect()
+}
+
+/// Run `f` while cancelling with `handle` until it returns.
+fn cancel_until_done<T>(handle: CancelHandle, f: impl FnOnce() -> T) -> T {
+    let done = Arc::new(AtomicBool::new(false));
+    let canceller = {
+        let done = done.clone();
+        thread::spawn(move || {
+            while !done.load(Ordering::SeqCst) {
+                thread::sleep(Duration::from_millis(10));
+                handle.cancel();
+            }
+        })
+    };
+    let result = f();
+    done.store(true, Ordering::SeqCst);
+    canceller.join().unwrap();
+    result
+}
+
+#[test]
+fn test_error() {
+    let faults = Faults::new();
+    let rule = Rule::new(Fault::error(Status::IO).with_sqlstate(b"08006"))
+        .on(Method::StatementExecuteQuery)
+        .matching("orders")
+        .skip(1)
+        .times(2);
+    faults.inject(rule);
+    let mut driver = FaultDriver::new(DummyDriver {}, faults.clone());
+    let mut connection = driver.new_database().unwrap().new_connection().unwrap();
+    let mut statement = connection.new_statement().unwrap();
+
+    statement.set_sql_query("SELECT * FROM users").unwrap();
+    assert!(statement.execute().is_ok());
+    statement.set_sql_query("SELECT * FROM orders").unwrap();
+    assert!(statement.execute().is_ok());
+    for _ in 0..2 {
+        let error = statement.execute().err().unwrap();
+        assert_eq!(error.status, Status::IO);
+        assert_eq!(error.sqlstate.map(|c| c as u8), *b"08006");
+    }
+    assert!(statement.execute().is_ok());
+    assert_eq!(faults.injected(), 2);
+}
+
+#[test]
+fn test_rule_options() {
+    let opts = [(
+        rule_option("commit"),
+        OptionValue::from(
+            "fault=error, status=Integrity, message=Conflict, method=ConnectionCommit",
+        ),
+    )];
+    let mut driver = FaultDriver::new(DummyDriver {}, Faults::new());
+    let mut database = driver.new_database_with_opts(opts).unwrap();
+    let mut connection = database.new_connection().unwrap();
+    let error = connection.commit().unwrap_err();
+    assert_eq!(error.status, Status::Integrity);
+    assert_eq!(error.message, "Conflict");
+
+    let spec = database.get_option_string(rule_option("commit")).unwrap();
+    assert!(spec.starts_with("fault=error"), "{spec}");
+    database
+        .set_option(rule_option("commit"), OptionValue::from(""))
+        .unwrap();
+    connection.commit().unwrap();
+    let error = database
+        .get_option_string(rule_option("commit"))
+        .unwrap_err();
+    assert_eq!(error.status, Status::NotFound);
+
+    for spec in [
+        "fault=explode",
+        "fault=error,status=Bogus",
+        "fault=error,sqlstate=0800",
+        "fault=latency",
+        "fault=hang,method=Nothing",
+        "fault=hang,color=red",
+    ] {
+        let error = database
+            .set_option(rule_option("invalid"), OptionValue::from(spec))
+            .unwrap_err();
+        assert_eq!(error.status, Status::InvalidArguments, "{spec}");
+    }
+}
+
+#[test]
+fn test_stream_error() {
+    let faults = Faults::new();
+    let mut driver = FaultDriver::new(DummyDriver {}, faults.clone());
+    let mut connection = driver.new_database().unwrap().new_connection().unwrap();
+    let mut statement = connection.new_statement().unwrap();
+    statement.set_sql_query("SELECT 1").unwrap();
+    let expected = collect(statement.execute().unwrap());
+    assert_eq!(expected.len(), 1);
+
+    faults.inject(Rule::new(Fault::stream_error(1, Status::IO)).times(1));
+    let batches = collect(statement.execute().unwrap());
+    assert_eq!(batches.len(), 2);
+    assert_eq!(batches[0], expected[0]);
+    assert!(batches[1].as_ref().unwrap_err().contains("Injected fault"));
+
+    // Faults not applying to a method leave it untouched.
+    faults.inject(Rule::new(Fault::stream_error(0, Status::IO)).on(Method::ConnectionCommit));
+    connection.commit().unwrap();
+    assert_eq!(faults.injected(), 1);
+}
+
+#[test]
+fn test_partial_update_and_latency() {
+    let faults = Faults::new();
+    faults.inject(Rule::new(Fault::PartialUpdate(7)).times(1));
+    faults
+        .inject(Rule::new(Fault::Latency(Duration::from_millis(50))).on(Method::StatementPrepare));
+    let mut driver = FaultDriver::new(DummyDriver {}, faults);
+    let mut connection = driver.new_database().unwrap().new_connection().unwrap();
+    let mut statement = connection.new_statement().unwrap();
+    statement.set_sql_query("UPDATE t SET a = 1").unwrap();
+    assert_eq!(statement.execute_update().unwrap(), Some(7));
+    assert_eq!(statement.execute_update().unwrap(), Some(0));
+
+    let start = Instant::now();
+    statement.prepare().unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(50));
+}
+
+#[test]
+fn test_hang() {
+    let faults = Faults::new();
+    faults.inject(
+        Rule::new(Fault::Hang)
+            .on(Method::StatementExecuteQuery)
+            .times(2),
+    );
+    let mut driver = FaultDriver::new(DummyDriver {}, faults);
+    let mut connection = driver.new_database().unwrap().new_connection().unwrap();
+    let mut statement = connection.new_statement().unwrap();
+    statement.set_sql_query("SELECT 1").unwrap();
+
+    let handle = statement.cancel_handle();
+    let error = cancel_until_done(handle, || statement.execute().err().unwrap());
+    assert_eq!(error.status, Status::Cancelled);
+
+    // Cancelling the connection interrupts its statements.
+    let handle = connection.cancel_handle();
+    let error = cancel_until_done(handle, || statement.execute().err().unwrap());
+    assert_eq!(error.status, Status::Cancelled);
+
+    assert!(statement.execute().is_ok());
+}
+
+#[test]
+fn test_managed_driver() -> Result<()> {
+    let init: FFI_AdbcDriverInitFunc = adbc_dummy::DummyDriverInit;
+    let inner = ManagedDriver::load_static(&init, AdbcVersion::V110)?;
+    let faults = Faults::new();
+    faults.inject(Rule::new(Fault::error(Status::Timeout)).on(Method::ConnectionGetTableTypes));
+    let mut driver = FaultDriver::new(inner, faults);
+    let connection = driver.new_database()?.new_connection()?;
+    let error = connection.get_table_types().err().unwrap();
+    assert_eq!(error.status, Status::Timeout);
+    assert!(connection.get_table_schema(None, None, "default").is_ok());
+    Ok(())
+}
+
+#[test]
+fn test_exported() -> Result<()> {
+    // The dummy driver library is built next to the test executable.
+    let library: PathBuf = std::env::current_exe()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .join(library_filename("adbc_dummy"));
+
+    let init: FFI_AdbcDriverInitFunc = adbc_faults::AdbcFaultsInit;
+    let mut driver = ManagedDriver::load_static(&init, AdbcVersion::V110)?;
+    let error = driver.new_database().err().unwrap();
+    assert_eq!(error.status, Status::InvalidState);
+
+    let opts = [
+        (
+            OptionDatabase::Other(OPTION_DRIVER.into()),
+            OptionValue::from(library.to_str().unwrap()),
+        ),
+        (
+            OptionDatabase::Other(OPTION_ENTRYPOINT.into()),
+            OptionValue::from("DummyDriverInit"),
+        ),
+        (
+            rule_option("read"),
+            OptionValue::from("fault=error,sqlstate=40001,method=StatementExecuteQuery,times=1"),
+        ),
+    ];
+    let mut database = driver.new_database_with_opts(opts)?;
+    let mut connection = database.new_connection()?;
+    let mut statement = connection.new_statement()?;
+    statement.set_sql_query("SELECT 1")?;
+    let error = statement.execute().err().unwrap();
+    assert_eq!(error.status, Status::IO);
+    assert_eq!(error.sqlstate.map(|c| c as u8), *b"40001");
+    assert!(statement.execute().is_ok());
+    Ok(())
+}
+
+fn library_filename(name: &str) -> String {
+    format!(
+        "{}{name}{}",
+        std::env::consts::DLL_PREFIX,
+        std::env::consts::DLL_SUFFIX
+    )
+}
@@ -0,0 +1,189 @@
+/// This integration test reads the partitions of the dummy driver concurrently,
+/// injecting faults to slow down or fail some of them.
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use arrow::array::RecordBatchReader;
+
+use adbc_core::error::Status;
+use adbc_core::layer::{Call, Layer, Layered, Method};
+use adbc_core::partitions::{ParallelOptions, ParallelReader};
+use adbc_core::{Connection, Database, Driver, PartitionedResult, Statement};
+use adbc_faults::{Fault, FaultDatabase, FaultDriver, Faults, Rule};
+
+use adbc_dummy::{DummyDatabase, DummyDriver};
+
+const LATENCY: Duration = Duration::from_millis(100);
+
+fn get_database(faults: &Faults) -> FaultDatabase<DummyDatabase> {
+    let mut driver = FaultDriver::new(DummyDriver {}, faults.clone());
+    driver.new_database().unwrap()
+}
+
+/// The partitions of the dummy driver, repeated to get `count` of them.
+fn get_result(database: &mut FaultDatabase<DummyDatabase>, count: usize) -> PartitionedResult {
+    let mut statement = database.new_connection().unwrap().new_statement().unwrap();
+    let mut result = statement.execute_partitions().unwrap();
+    result.partitions = result.partitions.into_iter().cycle().take(count).collect();
+    result
+}
+
+/// The number of rows of each batch of `reader`, or the error it returned.
+fn collect(reader: impl RecordBatchReader) -> Vec<std::result::Result<usize, String>> {
+    reader
+        .map(|batch| batch.map(|b| b.num_rows()).map_err(|e| e.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_parallel_read() {
+    let faults = Faults::new();
+    let mut database = get_database(&faults);
+    let rows = collect(
+        database
+            .new_connection()
+            .unwrap()
+            .read_partition(b"")
+            .unwrap(),
+    );
+
+    for ordered in [true, false] {
+        let result = get_result(&mut database, 10);
+        let schema = result.schema.clone();
+        let options = ParallelOptions::new()
+            .with_connections(3)
+            .with_buffer(1)
+            .with_ordered(ordered);
+        let reader = ParallelReader::new(&mut database, result, options).unwrap();
+        assert_eq!(*reader.schema(), schema);
+        let expected: Vec<_> = rows.iter().cycle().take(10 * rows.len()).cloned().collect();
+        assert_eq!(collect(reader), expected);
+    }
+
+    let result = get_result(&mut database, 0);
+    let reader = ParallelReader::new(&mut database, result, ParallelOptions::new()).unwrap();
+    assert!(collect(reader).is_empty());
+}
+
+#[test]
+fn test_parallel_concurrency() {
+    let faults = Faults::new();
+    faults.inject(Rule::new(Fault::Latency(LATENCY)).on(Method::ConnectionReadPartition));
+    let mut database = get_database(&faults);
+
+    let result = get_result(&mut database, 8);
+    let options = ParallelOptions::new().with_connections(4);
+    let start = Instant::now();
+    let reader = ParallelReader::new(&mut database, result, options).unwrap();
+    assert_eq!(collect(reader).len(), 8);
+    let elapsed = start.elapsed();
+    assert!(
+        elapsed >= 2 * LATENCY && elapsed < 8 * LATENCY,
+        "{elapsed:?}"
+    );
+}
+
+#[test]
+fn test_parallel_error() {
+    for ordered in [true, false] {
+        let faults = Faults::new();
+        let rule = Rule::new(Fault::error(Status::IO))
+            .on(Method::ConnectionReadPartition)
+            .skip(2)
+            .times(1);
+        faults.inject(rule);
+        let mut database = get_database(&faults);
+
+        let result = get_result(&mut database, 6);
+        let options = ParallelOptions::new()
+            .with_connections(2)
+            .with_ordered(ordered);
+        let reader = ParallelReader::new(&mut database, result, options.clone()).unwrap();
+        let batches = collect(reader);
+        let (error, batches) = batches.split_last().unwrap();
+        assert!(error.as_ref().unwrap_err().contains("Injected fault"));
+        assert!(batches.iter().all(Result::is_ok));
+        assert!(batches.len() < 6);
+
+        let rule =
+            Rule::new(Fault::stream_error(0, Status::IO)).on(Method::ConnectionReadPartition);
+        faults.clear();
+        faults.inject(rule);
+        let result = get_result(&mut database, 6);
+        let batches = collect(ParallelReader::new(&mut database, result, options).unwrap());
+        assert_eq!(batches.len(), 1);
+        assert!(batches[0].is_err());
+    }
+}
+
+#[test]
+fn test_parallel_abort() {
+    // A failure is returned without waiting for slower partitions.
+    let faults = Faults::new();
+    faults.inject(
+        Rule::new(Fault::Latency(10 * LATENCY))
+            .on(Method::ConnectionReadPartition)
+            .times(1),
+    );
+    faults.inject(
+        Rule::new(Fault::error(Status::IO))
+            .on(Method::ConnectionReadPartition)
+            .times(1),
+    );
+    let mut database = get_database(&faults);
+    let result = get_result(&mut database, 2);
+    let start = Instant::now();
+    let mut reader = ParallelReader::new(&mut database, result, ParallelOptions::new()).unwrap();
+    assert!(reader.next().unwrap().is_err());
+    assert!(start.elapsed() < 10 * LATENCY);
+    assert!(reader.next().is_none());
+    drop(reader);
+
+    // Dropping the reader waits for the partitions being read, but not for
+    // the remaining ones.
+    faults.clear();
+    faults.inject(
+        Rule::new(Fault::Latency(10 * LATENCY))
+            .on(Method::ConnectionReadPartition)
+            .skip(1),
+    );
+    let result = get_result(&mut database, 8);
+    let start = Instant::now();
+    let mut reader = ParallelReader::new(&mut database, result, ParallelOptions::new()).unwrap();
+    assert!(reader.next().unwrap().is_ok());
+    drop(reader);
+    let elapsed = start.elapsed();
+    assert!(
+        elapsed >= 10 * LATENCY && elapsed < 20 * LATENCY,
+        "{elapsed:?}"
+    );
+}
+
+/// Panics when reading partitions.
+struct Panicking;
+
+impl Layer for Panicking {
+    fn before(&self, call: &Call<'_>) -> adbc_core::error::Result<()> {
+        if call.method == Method::ConnectionReadPartition {
+            panic!("Injected panic");
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_parallel_panic() {
+    // A panicking thread fails the read instead of leaving it waiting.
+    let mut database = Layered::new(DummyDriver {}, Panicking)
+        .new_database()
+        .unwrap();
+    let mut statement = database.new_connection().unwrap().new_statement().unwrap();
+    let result = statement.execute_partitions().unwrap();
+    let reader = ParallelReader::new(&mut database, result, ParallelOptions::new()).unwrap();
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || sender.send(collect(reader)).unwrap());
+    let batches = receiver.recv_timeout(10 * LATENCY).unwrap();
+    let error = batches.last().unwrap().as_ref().unwrap_err();
+    assert!(error.contains("panicked"), "{error}");
+}
@@ -0,0 +1,230 @@
+/// This integration test checks that the retry driver recovers from faults
+/// injected into the dummy driver.
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use adbc_core::error::{Error, Status};
+use adbc_core::layer::{Call, Layer, Layered, Method};
+use adbc_core::options::{OptionConnection, OptionStatement, OptionValue};
+use adbc_core::retry::{RetryDriver, RetryPolicy};
+use adbc_core::{Connection, Database, Driver, Optionable, Statement};
+use adbc_faults::{Fault, FaultDriver, Faults, Rule};
+
+use adbc_dummy::DummyDriver;
+
+/// Counts the successful calls of each method.
+#[derive(Default)]
+struct Counter {
+    calls: Mutex<HashMap<Method, usize>>,
+}
+
+impl Layer for Counter {
+    fn after(&self, call: &Call<'_>, result: std::result::Result<(), &Error>, _: Duration) {
+        if result.is_ok() {
+            *self.calls.lock().unwrap().entry(call.method).or_default() += 1;
+        }
+    }
+}
+
+type TestDriver = RetryDriver<Layered<FaultDriver<DummyDriver>, Counter>>;
+
+fn driver(faults: &Faults) -> TestDriver {
+    let inner = Layered::new(
+        FaultDriver::new(DummyDriver {}, faults.clone()),
+        Counter::default(),
+    );
+    let policy =
+        RetryPolicy::new().with_backoff(Duration::from_millis(1), Duration::from_millis(10));
+    RetryDriver::new(inner, policy)
+}
+
+fn calls(driver: &TestDriver, method: Method) -> usize {
+    let calls = driver.inner().layer().calls.lock().unwrap();
+    calls.get(&method).copied().unwrap_or_default()
+}
+
+#[test]
+fn test_reconnect_and_retry_query() {
+    let faults = Faults::new();
+    let mut driver = driver(&faults);
+    let mut database = driver.new_database().unwrap();
+    let opts = [(OptionConnection::CurrentSchema, OptionValue::from("public"))];
+    let mut connection = database.new_connection_with_opts(opts).unwrap();
+    connection
+        .set_option(OptionConnection::CurrentCatalog, "main".into())
+        .unwrap();
+    let mut statement = connection.new_statement().unwrap();
+    statement
+        .set_option(
+            OptionStatement::Other("adbc.dummy.option".into()),
+            "value".into(),
+        )
+        .unwrap();
+    statement.set_sql_query("SELECT * FROM default").unwrap();
+
+    faults.inject(
+        Rule::new(Fault::error(Status::IO).with_sqlstate(b"08006"))
+            .on(Method::StatementExecuteQuery)
+            .times(2),
+    );
+    assert_eq!(statement.execute().unwrap().count(), 1);
+    assert_eq!(faults.injected(), 2);
+    assert_eq!(connection.reconnections(), 2);
+    assert_eq!(calls(&driver, Method::ConnectionInit), 3);
+    assert_eq!(calls(&driver, Method::StatementNew), 3);
+    assert_eq!(calls(&driver, Method::StatementSetOption), 3);
+
+    // Options are replayed on the new connection.
+    assert_eq!(
+        connection
+            .get_option_string(OptionConnection::CurrentSchema)
+            .unwrap(),
+        "public"
+    );
+    assert_eq!(
+        connection
+            .get_option_string(OptionConnection::CurrentCatalog)
+            .unwrap(),
+        "main"
+    );
+
+    // Running out of attempts returns the last error.
+    faults.inject(Rule::new(Fault::error(Status::IO)).on(Method::StatementExecuteQuery));
+    let error = statement.execute().err().unwrap();
+    assert_eq!(error.status, Status::IO);
+    assert_eq!(faults.injected(), 5);
+}
+
+#[test]
+fn test_retry_metadata() {
+    let faults = Faults::new();
+    let mut driver = driver(&faults);
+    let mut database = driver.new_database().unwrap();
+    let connection = database.new_connection().unwrap();
+
+    // Failures while reading result sets are retried too.
+    faults.inject(Rule::new(Fault::stream_error(0, Status::IO)).times(1));
+    assert_eq!(connection.get_table_types().unwrap().count(), 1);
+    assert_eq!(connection.reconnections(), 1);
+
+    // Serialization failures are retried on the same connection.
+    faults.inject(
+        Rule::new(Fault::error(Status::InvalidState).with_sqlstate(b"40001"))
+            .on(Method::ConnectionGetTableSchema)
+            .times(1),
+    );
+    connection.get_table_schema(None, None, "default").unwrap();
+    assert_eq!(connection.reconnections(), 1);
+
+    // Permanent errors are not retried.
+    let error = connection
+        .get_table_schema(None, None, "unknown")
+        .unwrap_err();
+    assert_eq!(error.status, Status::NotFound);
+    assert_eq!(faults.injected(), 2);
+}
+
+#[test]
+fn test_no_retry_update() {
+    let faults = Faults::new();
+    let mut driver = driver(&faults);
+    let mut database = driver.new_database().unwrap();
+    let mut connection = database.new_connection().unwrap();
+    let mut statement = connection.new_statement().unwrap();
+    statement.set_sql_query("UPDATE t SET a = 1").unwrap();
+
+    faults.inject(Rule::new(Fault::error(Status::IO)).times(1));
+    let error = statement.execute_update().unwrap_err();
+    assert_eq!(error.status, Status::IO);
+    assert_eq!(connection.reconnections(), 0);
+
+    // The broken connection is replaced by the next call.
+    assert_eq!(statement.execute_update().unwrap(), Some(0));
+    assert_eq!(connection.reconnections(), 1);
+
+    // Queries are not retried unless read-only.
+    statement
+        .set_sql_query("DELETE FROM t RETURNING a")
+        .unwrap();
+    faults.inject(
+        Rule::new(Fault::error(Status::IO))
+            .on(Method::StatementExecuteQuery)
+            .times(1),
+    );
+    assert!(statement.execute().is_err());
+    assert!(statement.execute().is_ok());
+}
+
+#[test]
+fn test_no_retry_in_transaction() {
+    let faults = Faults::new();
+    let mut driver = driver(&faults);
+    let mut database = driver.new_database().unwrap();
+    let mut connection = database.new_connection().unwrap();
+    connection
+        .set_option(OptionConnection::AutoCommit, "false".into())
+        .unwrap();
+    let mut statement = connection.new_statement().unwrap();
+    statement.set_sql_query("SELECT 1").unwrap();
+
+    faults.inject(Rule::new(Fault::error(Status::IO)).times(1));
+    let error = statement.execute().err().unwrap();
+    assert_eq!(error.status, Status::IO);
+    assert!(connection.get_table_types().is_ok());
+    assert_eq!(connection.reconnections(), 0);
+
+    // Enabling autocommit again replaces the broken connection.
+    connection
+        .set_option(OptionConnection::AutoCommit, "true".into())
+        .unwrap();
+    assert_eq!(connection.reconnections(), 1);
+    assert_eq!(
+        connection
+            .get_option_string(OptionConnection::AutoCommit)
+            .unwrap(),
+        "true"
+    );
+    assert!(statement.execute().is_ok());
+}
+
+#[test]
+fn test_no_retry_in_transaction_int() {
+    let faults = Faults::new();
+    let mut driver = driver(&faults);
+    let mut database = driver.new_database().unwrap();
+    let mut connection = database.new_connection().unwrap();
+    connection
+        .set_option(OptionConnection::AutoCommit, OptionValue::Int(0))
+        .unwrap();
+    let mut statement = connection.new_statement().unwrap();
+    statement.set_sql_query("SELECT 1").unwrap();
+
+    faults.inject(Rule::new(Fault::error(Status::IO)).times(1));
+    let error = statement.execute().err().unwrap();
+    assert_eq!(error.status, Status::IO);
+    assert_eq!(connection.reconnections(), 0);
+}
+
+#[test]
+fn test_stream_not_retried() {
+    let faults = Faults::new();
+    let mut driver = driver(&faults);
+    let mut database = driver.new_database().unwrap();
+    let mut connection = database.new_connection().unwrap();
+    let mut statement = connection.new_statement().unwrap();
+    statement
+        .set_sql_query("DELETE FROM t RETURNING a")
+        .unwrap();
+
+    // Result sets which are not retried are streamed, so failures surface
+    // while reading them.
+    faults.inject(
+        Rule::new(Fault::stream_error(0, Status::IO))
+            .on(Method::StatementExecuteQuery)
+            .times(1),
+    );
+    let mut reader = statement.execute().unwrap();
+    assert!(reader.next().unwrap().is_err());
+    assert_eq!(connection.reconnections(), 0);
+}
@@ -0,0 +1,679 @@
+//! Blocking implementation of the PostgreSQL frontend/backend protocol.
+
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+
+use adbc_core::error::{Error, Result, Status};
+use bytes::BytesMut;
+use fallible_iterator::FallibleIterator;
+use postgres_protocol::authentication::{
+    md5_hash,
+    sasl::{ChannelBinding, ScramSha256, SCRAM_SHA_256},
+};
+use postgres_protocol::message::backend::{ErrorFields, Message};
+use postgres_protocol::message::frontend;
+use postgres_protocol::Oid;
+
+use crate::config::{Config, Host};
+use crate::types::Column;
+
+const READ_CHUNK_SIZE: usize = 64 * 1024;
+
+enum Stream {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl Stream {
+    fn connect(config: &Config) -> Result<Self> {
+        match &config.host {
+            Host::Tcp(host) => {
+                let address = format!("{host}:{}", config.port);
+                let stream = match config.connect_timeout {
+                    Some(timeout) => {
+                        let addresses = std::net::ToSocketAddrs::to_socket_addrs(&address)
+                            .map_err(|err| io_error(err, &address))?;
+                        let mut last_error = None;
+                        let mut stream = None;
+                        for address in addresses {
+                            match TcpStream::connect_timeout(&address, timeout) {
+                                Ok(s) => {
+                                    stream = Some(s);
+                                    break;
+                                }
+                                Err(err) => last_error = Some(err),
+                            }
+                        }
+                        match (stream, last_error) {
+                            (Some(stream), _) => stream,
+                            (None, Some(err)) => return Err(io_error(err, &address)),
+                            (None, None) => {
+                                return Err(Error::with_message_and_status(
+                                    format!("Could not resolve host {host}"),
+                                    Status::IO,
+                                ))
+                            }
+                        }
+                    }
+                    None => TcpStream::connect(&address).map_err(|err| io_error(err, &address))?,
+                };
+                stream
+                    .set_nodelay(true)
+                    .map_err(|err| io_error(err, &address))?;
+                Ok(Stream::Tcp(stream))
+            }
+            #[cfg(unix)]
+            Host::Unix(directory) => {
+                let path = directory.join(format!(".s.PGSQL.{}", config.port));
+                let stream = UnixStream::connect(&path)
+                    .map_err(|err| io_error(err, &path.to_string_lossy()))?;
+                Ok(Stream::Unix(stream))
+            }
+            #[cfg(not(unix))]
+            Host::Unix(_) => Err(Error::with_message_and_status(
+                "Unix-domain sockets are not supported on this platform",
+                Status::NotImplemented,
+            )),
+        }
+    }
+}
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            Stream::Tcp(stream) => stream.read(buf),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Stream::Tcp(stream) => stream.write(buf),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Stream::Tcp(stream) => stream.flush(),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.flush(),
+        }
+    }
+}
+
+fn io_error(error: std::io::Error, target: &str) -> Error {
+    Error::with_message_and_status(format!("I/O error with {target}: {error}"), Status::IO)
+}
+
+fn protocol_error(message: impl AsRef<str>) -> Error {
+    Error::with_message_and_status(
+        format!("PostgreSQL protocol error: {}", message.as_ref()),
+        Status::Internal,
+    )
+}
+
+/// Map a SQLSTATE to the closest ADBC status.
+fn status_from_sqlstate(sqlstate: &str) -> Status {
+    match sqlstate {
+        "57014" => Status::Cancelled,
+        "42501" => Status::Unauthorized,
+        "42P01" | "42883" | "42704" | "3D000" | "3F000" => Status::NotFound,
+        "42P06" | "42P07" | "42710" | "42723" => Status::AlreadyExists,
+        "28000" | "28P01" => Status::Unauthenticated,
+        _ => match sqlstate.get(..2) {
+            Some("08") | Some("57") => Status::IO,
+            Some("0A") => Status::NotImplemented,
+            Some("22") => Status::InvalidData,
+            Some("23") => Status::Integrity,
+            Some("25") => Status::InvalidState,
+            Some("28") => Status::Unauthenticated,
+            Some("42") => Status::InvalidArguments,
+            Some("53") | Some("54") | Some("58") | Some("XX") => Status::Internal,
+            _ => Status::Unknown,
+        },
+    }
+}
+
+/// Convert the fields of an `ErrorResponse` into an ADBC error.
+fn server_error(mut fields: ErrorFields<'_>) -> Error {
+    let mut severity = String::new();
+    let mut sqlstate = String::new();
+    let mut message = String::new();
+    let mut details = Vec::new();
+    loop {
+        match fields.next() {
+            Ok(Some(field)) => {
+                let value = field.value_bytes();
+                match field.type_() {
+                    b'S' => severity = String::from_utf8_lossy(value).into_owned(),
+                    b'C' => sqlstate = String::from_utf8_lossy(value).into_owned(),
+                    b'M' => message = String::from_utf8_lossy(value).into_owned(),
+                    b'D' => details.push(("detail".to_string(), value.to_vec())),
+                    b'H' => details.push(("hint".to_string(), value.to_vec())),
+                    _ => {}
+                }
+            }
+            Ok(None) => break,
+            Err(err) => return protocol_error(err.to_string()),
+        }
+    }
+
+    let mut error = Error::with_message_and_status(
+        format!("{severity}: {message}"),
+        status_from_sqlstate(&sqlstate),
+    );
+    for (dst, src) in error.sqlstate.iter_mut().zip(sqlstate.bytes()) {
+        *dst = src as std::os::raw::c_char;
+    }
+    if !details.is_empty() {
+        error.details = Some(details);
+    }
+    error
+}
+
+/// Everything needed to ask the server to cancel the running query of a session.
+///
+/// The key is independent from the session itself so that cancellation never
+/// has to wait for the session lock held by the query being cancelled.
+#[derive(Clone)]
+pub(crate) struct CancelKey {
+    config: Config,
+    process_id: i32,
+    secret_key: i32,
+}
+
+impl CancelKey {
+    /// Send a `CancelRequest` over a fresh connection to the server.
+    pub(crate) fn cancel(&self) -> Result<()> {
+        let mut stream = Stream::connect(&self.config)?;
+        let mut buf = BytesMut::new();
+        frontend::cancel_request(self.process_id, self.secret_key, &mut buf);
+        stream
+            .write_all(&buf)
+            .map_err(|err| io_error(err, "cancel request"))?;
+        // The server closes the connection once it has processed the request.
+        let mut sink = [0; 1];
+        let _ = stream.read(&mut sink);
+        Ok(())
+    }
+}
+
+/// Result of a query sent with the simple query protocol, in text format.
+#[derive(Default)]
+pub(crate) struct SimpleResult {
+    pub(crate) rows: Vec<Vec<Option<String>>>,
+    pub(crate) tag: Option<String>,
+}
+
+/// An open session with a PostgreSQL server.
+pub(crate) struct Session {
+    stream: Stream,
+    read_buf: BytesMut,
+    write_buf: BytesMut,
+    parameters: HashMap<String, String>,
+    cancel_key: Option<CancelKey>,
+    /// Transaction status reported by the last `ReadyForQuery`.
+    transaction_status: u8,
+    /// Identifier of the result stream owning the in-flight response, if any.
+    active_stream: Option<u64>,
+    next_stream_id: u64,
+    next_statement_id: u64,
+    /// Prepared statements to close once the session is idle.
+    pending_close: Vec<String>,
+    /// Whether statements run outside of an explicit transaction.
+    pub(crate) autocommit: bool,
+}
+
+impl Session {
+    /// Open a new session and authenticate.
+    pub(crate) fn connect(config: &Config) -> Result<Self> {
+        let stream = Stream::connect(config)?;
+        let mut session = Session {
+            stream,
+            read_buf: BytesMut::with_capacity(READ_CHUNK_SIZE),
+            write_buf: BytesMut::new(),
+            parameters: HashMap::new(),
+            cancel_key: None,
+            transaction_status: b'I',
+            active_stream: None,
+            next_stream_id: 0,
+            next_statement_id: 0,
+            pending_close: Vec::new(),
+            autocommit: true,
+        };
+        session.startup(config)?;
+        Ok(session)
+    }
+
+    fn startup(&mut self, config: &Config) -> Result<()> {
+        let mut parameters = vec![
+            ("user", config.user.as_str()),
+            ("client_encoding", "UTF8"),
+            ("DateStyle", "ISO"),
+        ];
+        if let Some(database) = &config.database {
+            parameters.push(("database", database.as_str()));
+        }
+        for (key, value) in &config.parameters {
+            parameters.push((key.as_str(), value.as_str()));
+        }
+        frontend::startup_message(parameters, &mut self.write_buf)
+            .map_err(|err| protocol_error(err.to_string()))?;
+        self.flush()?;
+
+        let mut scram: Option<ScramSha256> = None;
+        loop {
+            match self.recv()? {
+                Message::AuthenticationOk => {}
+                Message::AuthenticationCleartextPassword => {
+                    let password = password(config)?;
+                    frontend::password_message(password.as_bytes(), &mut self.write_buf)
+                        .map_err(|err| protocol_error(err.to_string()))?;
+                    self.flush()?;
+                }
+                Message::AuthenticationMd5Password(body) => {
+                    let password = password(config)?;
+                    let hash = md5_hash(config.user.as_bytes(), password.as_bytes(), body.salt());
+                    frontend::password_message(hash.as_bytes(), &mut self.write_buf)
+                        .map_err(|err| protocol_error(err.to_string()))?;
+                    self.flush()?;
+                }
+                Message::AuthenticationSasl(body) => {
+                    let mut mechanisms = body.mechanisms();
+                    let mut supported = false;
+                    while let Some(mechanism) = mechanisms
+                        .next()
+                        .map_err(|err| protocol_error(err.to_string()))?
+                    {
+                        supported |= mechanism == SCRAM_SHA_256;
+                    }
+                    if !supported {
+                        return Err(Error::with_message_and_status(
+                            "Server requested an unsupported SASL mechanism",
+                            Status::NotImplemented,
+                        ));
+                    }
+                    let password = password(config)?;
+                    let state =
+                        ScramSha256::new(password.as_bytes(), ChannelBinding::unsupported());
+                    frontend::sasl_initial_response(
+                        SCRAM_SHA_256,
+                        state.message(),
+                        &mut self.write_buf,
+                    )
+                    .map_err(|err| protocol_error(err.to_string()))?;
+                    self.flush()?;
+                    scram = Some(state);
+                }
+                Message::AuthenticationSaslContinue(body) => {
+                    let state = scram
+                        .as_mut()
+                        .ok_or_else(|| protocol_error("unexpected SASL continuation"))?;
+                    state.update(body.data()).map_err(|err| {
+                        Error::with_message_and_status(err.to_string(), Status::Unauthenticated)
+                    })?;
+                    frontend::sasl_response(state.message(), &mut self.write_buf)
+                        .map_err(|err| protocol_error(err.to_string()))?;
+                    self.flush()?;
+                }
+                Message::AuthenticationSaslFinal(body) => {
+                    let state = scram
+                        .as_mut()
+                        .ok_or_else(|| protocol_error("unexpected SASL completion"))?;
+                    state.finish(body.data()).map_err(|err| {
+                        Error::with_message_and_status(err.to_string(), Status::Unauthenticated)
+                    })?;
+                }
+                Message::AuthenticationKerberosV5
+                | Message::AuthenticationScmCredential
+                | Message::AuthenticationGss
+                | Message::AuthenticationSspi
+                | Message::AuthenticationGssContinue(_) => {
+                    return Err(Error::with_message_and_status(
+                        "Server requested an unsupported authentication method",
+                        Status::NotImplemented,
+                    ))
+                }
+                Message::BackendKeyData(body) => {
+                    self.cancel_key = Some(CancelKey {
+                        config: config.clone(),
+                        process_id: body.process_id(),
+                        secret_key: body.secret_key(),
+                    });
+                }
+                Message::ReadyForQuery(body) => {
+                    self.transaction_status = body.status();
+                    return Ok(());
+                }
+                Message::ErrorResponse(body) => return Err(server_error(body.fields())),
+                _ => return Err(protocol_error("unexpected message during startup")),
+            }
+        }
+    }
+
+    pub(crate) fn cancel_key(&self) -> Option<CancelKey> {
+        self.cancel_key.clone()
+    }
+
+    pub(crate) fn parameter(&self, name: &str) -> Option<&str> {
+        self.parameters.get(name).map(String::as_str)
+    }
+
+    /// Whether the session is currently inside a transaction block.
+    pub(crate) fn in_transaction(&self) -> bool {
+        self.transaction_status != b'I'
+    }
+
+    pub(crate) fn buffer(&mut self) -> &mut BytesMut {
+        &mut self.write_buf
+    }
+
+    pub(crate) fn next_statement_name(&mut self) -> String {
+        self.next_statement_id += 1;
+        format!("adbc_{}", self.next_statement_id)
+    }
+
+    /// Send all buffered frontend messages.
+    pub(crate) fn flush(&mut self) -> Result<()> {
+        self.stream
+            .write_all(&self.write_buf)
+            .map_err(|err| io_error(err, "server"))?;
+        self.write_buf.clear();
+        Ok(())
+    }
+
+    /// Receive the next backend message, handling asynchronous messages.
+    pub(crate) fn recv(&mut self) -> Result<Message> {
+        loop {
+            let message = Message::parse(&mut self.read_buf)
+                .map_err(|err| protocol_error(err.to_string()))?;
+            match message {
+                Some(Message::ParameterStatus(body)) => {
+                    let name = body.name().map_err(|err| protocol_error(err.to_string()))?;
+                    let value = body
+                        .value()
+                        .map_err(|err| protocol_error(err.to_string()))?;
+                    self.parameters.insert(name.to_string(), value.to_string());
+                }
+                Some(Message::NoticeResponse(_)) | Some(Message::NotificationResponse(_)) => {}
+                Some(Message::ReadyForQuery(body)) => {
+                    self.transaction_status = body.status();
+                    return Ok(Message::ReadyForQuery(body));
+                }
+                Some(message) => return Ok(message),
+                None => {
+                    let length = self.read_buf.len();
+                    self.read_buf.resize(length + READ_CHUNK_SIZE, 0);
+                    let read = self.stream.read(&mut self.read_buf[length..]);
+                    match read {
+                        Ok(0) => {
+                            self.read_buf.truncate(length);
+                            return Err(Error::with_message_and_status(
+                                "Server closed the connection unexpectedly",
+                                Status::IO,
+                            ));
+                        }
+                        Ok(read) => self.read_buf.truncate(length + read),
+                        Err(err) => {
+                            self.read_buf.truncate(length);
+                            if err.kind() != std::io::ErrorKind::Interrupted {
+                                return Err(io_error(err, "server"));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Convert an `ErrorResponse` into an error once the server is ready again.
+    pub(crate) fn fail(&mut self, error: Error) -> Error {
+        // The original error is more useful than a failure to resynchronize.
+        let _ = self.wait_ready();
+        error
+    }
+
+    /// Discard messages until the server reports it is ready for a new query.
+    pub(crate) fn wait_ready(&mut self) -> Result<()> {
+        loop {
+            if let Message::ReadyForQuery(_) = self.recv()? {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Claim the session for a new result stream and return its identifier.
+    pub(crate) fn begin_stream(&mut self) -> u64 {
+        self.next_stream_id += 1;
+        self.active_stream = Some(self.next_stream_id);
+        self.next_stream_id
+    }
+
+    pub(crate) fn is_active_stream(&self, id: u64) -> bool {
+        self.active_stream == Some(id)
+    }
+
+    pub(crate) fn resume_stream(&mut self, id: u64) {
+        self.active_stream = Some(id);
+    }
+
+    pub(crate) fn end_stream(&mut self, id: u64) {
+        if self.active_stream == Some(id) {
+            self.active_stream = None;
+        }
+    }
+
+    /// Make the session available for a new command, draining any result
+    /// stream that was abandoned before being fully consumed.
+    pub(crate) fn ensure_idle(&mut self) -> Result<()> {
+        if self.active_stream.take().is_some() {
+            self.wait_ready()?;
+        }
+        if !self.pending_close.is_empty() {
+            for name in std::mem::take(&mut self.pending_close) {
+                frontend::close(b'S', &name, &mut self.write_buf).map_err(wire_error)?;
+            }
+            frontend::sync(&mut self.write_buf);
+            self.flush()?;
+            self.wait_ready()?;
+        }
+        Ok(())
+    }
+
+    /// Make the session available for a statement, opening a transaction
+    /// first when autocommit is disabled.
+    pub(crate) fn prepare_command(&mut self) -> Result<()> {
+        self.ensure_idle()?;
+        if !self.autocommit && !self.in_transaction() {
+            self.simple_query("BEGIN")?;
+        }
+        Ok(())
+    }
+
+    /// Close a prepared statement, deferring it while a result is streamed.
+    pub(crate) fn close_statement(&mut self, name: String) {
+        self.pending_close.push(name);
+        if self.active_stream.is_none() {
+            // Failing to close is harmless: the statement dies with the session.
+            let _ = self.ensure_idle();
+        }
+    }
+
+    /// Parse a statement and describe its parameters and result columns.
+    pub(crate) fn describe(
+        &mut self,
+        name: &str,
+        query: &str,
+        param_types: &[Oid],
+    ) -> Result<(Vec<Oid>, Vec<Column>)> {
+        frontend::parse(
+            name,
+            query,
+            param_types.iter().copied(),
+            &mut self.write_buf,
+        )
+        .map_err(wire_error)?;
+        frontend::describe(b'S', name, &mut self.write_buf).map_err(wire_error)?;
+        frontend::sync(&mut self.write_buf);
+        self.flush()?;
+
+        let mut params = Vec::new();
+        let mut columns = Vec::new();
+        loop {
+            match self.recv()? {
+                Message::ParameterDescription(body) => {
+                    params = body.parameters().collect().map_err(wire_error)?;
+                }
+                Message::RowDescription(body) => {
+                    columns = body
+                        .fields()
+                        .map(|field| Ok(Column::new(field.name(), field.type_oid())))
+                        .collect()
+                        .map_err(wire_error)?;
+                }
+                message @ Message::ErrorResponse(_) => {
+                    return Err(self.fail(message_error(&message)))
+                }
+                Message::ReadyForQuery(_) => return Ok((params, columns)),
+                _ => {}
+            }
+        }
+    }
+
+    /// Read the responses to pipelined commands up to the next `ReadyForQuery`,
+    /// summing the number of affected rows.
+    pub(crate) fn read_update_result(&mut self) -> Result<Option<i64>> {
+        let mut rows = None;
+        let mut error = None;
+        loop {
+            match self.recv()? {
+                Message::CommandComplete(body) => {
+                    let tag = body.tag().map_err(wire_error)?;
+                    if let Some(count) = rows_from_tag(tag) {
+                        rows = Some(rows.unwrap_or(0) + count);
+                    }
+                }
+                message @ Message::ErrorResponse(_) => {
+                    error.get_or_insert(message_error(&message));
+                }
+                Message::ReadyForQuery(_) => break,
+                _ => {}
+            }
+        }
+        match error {
+            Some(error) => Err(error),
+            None => Ok(rows),
+        }
+    }
+
+    /// Run one or more statements with the simple query protocol.
+    ///
+    /// Rows of the last statement returning data are collected in text format.
+    pub(crate) fn simple_query(&mut self, query: &str) -> Result<SimpleResult> {
+        self.ensure_idle()?;
+        frontend::query(query, &mut self.write_buf)
+            .map_err(|err| protocol_error(err.to_string()))?;
+        self.flush()?;
+
+        let mut result = SimpleResult::default();
+        let mut error = None;
+        loop {
+            match self.recv()? {
+                Message::RowDescription(_) => result.rows.clear(),
+                Message::DataRow(body) => {
+                    let mut row = Vec::new();
+                    let mut ranges = body.ranges();
+                    while let Some(range) = ranges
+                        .next()
+                        .map_err(|err| protocol_error(err.to_string()))?
+                    {
+                        row.push(match range {
+                            Some(range) => {
+                                Some(std::str::from_utf8(&body.buffer()[range])?.to_string())
+                            }
+                            None => None,
+                        });
+                    }
+                    result.rows.push(row);
+                }
+                Message::CommandComplete(body) => {
+                    let tag = body.tag().map_err(|err| protocol_error(err.to_string()))?;
+                    result.tag = Some(tag.to_string());
+                }
+                Message::ErrorResponse(body) => error = Some(server_error(body.fields())),
+                Message::CopyInResponse(_) => {
+                    frontend::copy_fail("COPY is not supported here", &mut self.write_buf)
+                        .map_err(|err| protocol_error(err.to_string()))?;
+                    self.flush()?;
+                }
+                Message::ReadyForQuery(_) => break,
+                _ => {}
+            }
+        }
+        match error {
+            Some(error) => Err(error),
+            None => Ok(result),
+        }
+    }
+
+    /// Close the session gracefully.
+    pub(crate) fn terminate(&mut self) {
+        frontend::terminate(&mut self.write_buf);
+        let _ = self.flush();
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        self.terminate();
+    }
+}
+
+fn password(config: &Config) -> Result<&str> {
+    config
+        .password
+        .as_ref()
+        .map(|password| password.expose().as_str())
+        .ok_or_else(|| {
+            Error::with_message_and_status(
+                "Server requested a password but none was provided",
+                Status::Unauthenticated,
+            )
+        })
+}
+
+/// Extract the number of affected rows from a command tag, e.g. `INSERT 0 3`.
+pub(crate) fn rows_from_tag(tag: &str) -> Option<i64> {
+    let mut words = tag.split_whitespace();
+    let command = words.next()?;
+    match command {
+        "INSERT" | "UPDATE" | "DELETE" | "SELECT" | "MOVE" | "FETCH" | "COPY" | "MERGE" => {
+            words.last()?.parse().ok()
+        }
+        _ => None,
+    }
+}
+
+pub(crate) fn message_error(message: &Message) -> Error {
+    match message {
+        Message::ErrorResponse(body) => server_error(body.fields()),
+        _ => protocol_error("unexpected message"),
+    }
+}
+
+pub(crate) fn unexpected(context: &str) -> Error {
+    protocol_error(format!("unexpected message {context}"))
+}
+
+pub(crate) fn wire_error(error: impl std::fmt::Display) -> Error {
+    protocol_error(error.to_string())
+}
@@ -0,0 +1,1197 @@
+//! A native ADBC driver for PostgreSQL.
+//!
+//! The driver speaks the PostgreSQL frontend/backend protocol directly, without
+//! relying on libpq:
+//! - Query results are requested in binary format and decoded straight into
+//!   Arrow builders. Types without a binary decoder are returned as strings.
+//! - Bulk ingestion (see [OptionStatement::TargetTable]) streams the bound data
+//!   with `COPY ... FROM STDIN (FORMAT binary)`, into a regular table of any
+//!   schema ([OptionStatement::TargetDbSchema]) or into a temporary table
+//!   ([OptionStatement::Temporary]).
+//! - Cancellation sends a protocol cancel request over a separate connection,
+//!   so it never waits on the connection running the query.
+//!
+//! The database is configured with a URI of the form
+//! `postgresql://[user[:password]@][host][:port][/dbname][?param=value&...]`.
+//! Supported parameters are `host` (an absolute path selects a Unix-domain
+//! socket directory), `port`, `user`, `password`, `dbname`, `connect_timeout`,
+//! `application_name`, `options` and `sslmode` (TLS is not supported, so only
+//! `disable`, `allow` and `prefer` are accepted). Parameters can also be set
+//! as database options, which take precedence over the URI.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use adbc_core::options::{OptionDatabase, OptionValue};
+//! use adbc_core::{Connection, Database, Driver, Statement};
+//! use adbc_postgresql::PostgresDriver;
+//!
+//! let mut driver = PostgresDriver::default();
+//! let opts = [(
+//!     OptionDatabase::Uri,
+//!     OptionValue::from("postgresql://postgres@localhost/postgres"),
+//! )];
+//! let mut database = driver.new_database_with_opts(opts).unwrap();
+//! let mut connection = database.new_connection().unwrap();
+//! let mut statement = connection.new_statement().unwrap();
+//! statement.set_sql_query("SELECT 42").unwrap();
+//! let batches: Vec<_> = statement.execute().unwrap().collect();
+//! ```
+
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+use arrow::array::{
+    new_empty_array, ArrayRef, Int64Array, RecordBatch, RecordBatchIterator, RecordBatchReader,
+    StringArray, UInt32Array, UnionArray,
+};
+use arrow::buffer::Buffer;
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::error::ArrowError;
+use bytes::{BufMut, BytesMut};
+use postgres_protocol::escape::{escape_identifier, escape_literal};
+use postgres_protocol::message::backend::Message;
+use postgres_protocol::message::frontend;
+
+use adbc_core::{
+    capabilities::Capabilities,
+    error::{Error, Result, Status},
+    ffi::constants,
+    options::{
+        InfoCode, IngestMode, IsolationLevel, ObjectDepth, OptionConnection, OptionDatabase,
+        OptionStatement, OptionValue,
+    },
+    schemas,
+    secret::Secret,
+    Connection, Database, Driver, Optionable, PartitionedResult, Statement,
+};
+
+mod client;
+mod config;
+mod reader;
+mod types;
+
+use client::{message_error, rows_from_tag, unexpected, wire_error, CancelKey, Session};
+use config::Config;
+use reader::{lock, param_formats, write_bind, Params};
+use types::{encode, encoding, Column, PgType};
+
+pub use reader::QueryReader;
+
+/// Statement option setting the maximum number of rows of each result batch.
+pub const OPTION_BATCH_SIZE: &str = "adbc.postgresql.batch_size";
+
+const DEFAULT_BATCH_SIZE: usize = 65_536;
+/// Number of parameter rows sent before waiting for the server's responses.
+const PIPELINE_DEPTH: usize = 1_024;
+/// Size of the `CopyData` messages sent during ingestion.
+const COPY_CHUNK_SIZE: usize = 1 << 20;
+
+const VALUE_ENABLED: &str = "true";
+const VALUE_DISABLED: &str = "false";
+
+type MemoryReader =
+    RecordBatchIterator<std::vec::IntoIter<std::result::Result<RecordBatch, ArrowError>>>;
+
+fn not_implemented(what: &str) -> Error {
+    Error::with_message_and_status(
+        format!("{what} is not supported by the PostgreSQL driver"),
+        Status::NotImplemented,
+    )
+}
+
+fn string_option(value: OptionValue, key: &str) -> Result<String> {
+    match value {
+        OptionValue::String(value) => Ok(value),
+        _ => Err(Error::with_message_and_status(
+            format!("Option {key} must be a string"),
+            Status::InvalidArguments,
+        )),
+    }
+}
+
+fn bool_option(value: OptionValue, key: &str) -> Result<bool> {
+    match string_option(value, key)?.as_str() {
+        VALUE_ENABLED => Ok(true),
+        VALUE_DISABLED => Ok(false),
+        value => Err(Error::with_message_and_status(
+            format!("Invalid value for option {key}: {value}"),
+            Status::InvalidArguments,
+        )),
+    }
+}
+
+fn unknown_option(kind: &str, key: &str) -> Error {
+    Error::with_message_and_status(
+        format!("Unrecognized {kind} option: {key}"),
+        Status::NotFound,
+    )
+}
+
+/// The PostgreSQL driver.
+#[derive(Default)]
+pub struct PostgresDriver {}
+
+impl Driver for PostgresDriver {
+    type DatabaseType = PostgresDatabase;
+
+    fn new_database(&mut self) -> Result<Self::DatabaseType> {
+        self.new_database_with_opts(None)
+    }
+
+    fn new_database_with_opts(
+        &mut self,
+        opts: impl IntoIterator<Item = (<Self::DatabaseType as Optionable>::Option, OptionValue)>,
+    ) -> Result<Self::DatabaseType> {
+        let mut database = Self::DatabaseType {
+            uri: None,
+            username: None,
+            password: None,
+            parameters: Vec::new(),
+        };
+        for (key, value) in opts {
+            database.set_option(key, value)?;
+        }
+        Ok(database)
+    }
+}
+
+/// A PostgreSQL database, i.e. the settings used to open connections.
+///
+/// The URI, which may hold credentials, the password and the parameters are
+/// zeroized on drop.
+pub struct PostgresDatabase {
+    uri: Option<Secret<String>>,
+    username: Option<String>,
+    password: Option<Secret<String>>,
+    /// URI parameters set as options, applied over those of the URI.
+    parameters: Vec<(String, Secret<String>)>,
+}
+
+impl PostgresDatabase {
+    fn config(&self) -> Result<Config> {
+        let uri = self.uri.as_ref().map(Secret::expose).ok_or_else(|| {
+            Error::with_message_and_status(
+                "Database option uri must be set to open a connection",
+                Status::InvalidState,
+            )
+        })?;
+        let mut config = Config::from_uri(uri)?;
+        if let Some(username) = &self.username {
+            config.user.clone_from(username);
+        }
+        if let Some(password) = &self.password {
+            config.password = Some(password.clone());
+        }
+        for (key, value) in &self.parameters {
+            config.set_parameter(key, value.expose().clone())?;
+        }
+        Ok(config)
+    }
+}
+
+impl Optionable for PostgresDatabase {
+    type Option = OptionDatabase;
+
+    fn set_option(&mut self, key: Self::Option, value: OptionValue) -> Result<()> {
+        let value = string_option(value, key.as_ref())?;
+        match key {
+            OptionDatabase::Uri => {
+                Config::from_uri(&value)?;
+                self.uri = Some(Secret::new(value));
+            }
+            OptionDatabase::Username => self.username = Some(value),
+            OptionDatabase::Password => self.password = Some(Secret::new(value)),
+            OptionDatabase::Other(key) => {
+                Config::new().set_parameter(&key, value.clone())?;
+                self.parameters.retain(|(name, _)| *name != key);
+                self.parameters.push((key, Secret::new(value)));
+            }
+            key => {
+                return Err(not_implemented(&format!(
+                    "Database option {}",
+                    key.as_ref()
+                )))
+            }
+        }
+        Ok(())
+    }
+
+    fn get_option_string(&self, key: Self::Option) -> Result<String> {
+        let value = match &key {
+            OptionDatabase::Uri => self.uri.as_ref().map(|uri| uri.expose().clone()),
+            OptionDatabase::Username => self.username.clone(),
+            OptionDatabase::Password => self.password.as_ref().map(|p| p.expose().clone()),
+            OptionDatabase::Other(key) => self
+                .parameters
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value.expose().clone()),
+            _ => None,
+        };
+        value.ok_or_else(|| unknown_option("database", key.as_ref()))
+    }
+
+    fn get_option_bytes(&self, key: Self::Option) -> Result<Vec<u8>> {
+        Err(unknown_option("database", key.as_ref()))
+    }
+
+    fn get_option_int(&self, key: Self::Option) -> Result<i64> {
+        Err(unknown_option("database", key.as_ref()))
+    }
+
+    fn get_option_double(&self, key: Self::Option) -> Result<f64> {
+        Err(unknown_option("database", key.as_ref()))
+    }
+}
+
+impl Database for PostgresDatabase {
+    type ConnectionType = PostgresConnection;
+
+    fn new_connection(&mut self) -> Result<Self::ConnectionType> {
+        self.new_connection_with_opts(None)
+    }
+
+    fn new_connection_with_opts(
+        &mut self,
+        opts: impl IntoIterator<Item = (<Self::ConnectionType as Optionable>::Option, OptionValue)>,
+    ) -> Result<Self::ConnectionType> {
+        let session = Session::connect(&self.config()?)?;
+        let mut connection = Self::ConnectionType {
+            cancel_key: session.cancel_key(),
+            session: Arc::new(Mutex::new(session)),
+            isolation_level: None,
+        };
+        for (key, value) in opts {
+            connection.set_option(key, value)?;
+        }
+        Ok(connection)
+    }
+}
+
+/// Map an ADBC isolation level to the value of `default_transaction_isolation`.
+///
+/// PostgreSQL's repeatable read is implemented with snapshot isolation, which
+/// is why both levels map to it. `None` stands for the server default.
+fn isolation_level_setting(level: IsolationLevel) -> Result<Option<&'static str>> {
+    match level {
+        IsolationLevel::Default => Ok(None),
+        IsolationLevel::ReadUncommitted => Ok(Some("read uncommitted")),
+        IsolationLevel::ReadCommitted => Ok(Some("read committed")),
+        IsolationLevel::RepeatableRead | IsolationLevel::Snapshot => Ok(Some("repeatable read")),
+        IsolationLevel::Serializable => Ok(Some("serializable")),
+        level => Err(not_implemented(&format!("{level:?} isolation level"))),
+    }
+}
+
+/// A connection to a PostgreSQL server.
+pub struct PostgresConnection {
+    session: Arc<Mutex<Session>>,
+    cancel_key: Option<CancelKey>,
+    /// Isolation level as set by the user, since several ADBC levels map to
+    /// the same PostgreSQL one.
+    isolation_level: Option<IsolationLevel>,
+}
+
+impl PostgresConnection {
+    fn query_value(&self, query: &str) -> Result<Option<String>> {
+        let result = lock(&self.session)?.simple_query(query)?;
+        Ok(result
+            .rows
+            .into_iter()
+            .next()
+            .and_then(|row| row.into_iter().next().flatten()))
+    }
+
+    fn end_transaction(&mut self, command: &str) -> Result<()> {
+        let mut session = lock(&self.session)?;
+        if session.autocommit {
+            return Err(Error::with_message_and_status(
+                format!(
+                    "Cannot {} while autocommit is enabled",
+                    command.to_lowercase()
+                ),
+                Status::InvalidState,
+            ));
+        }
+        session.ensure_idle()?;
+        if session.in_transaction() {
+            session.simple_query(command)?;
+        }
+        Ok(())
+    }
+}
+
+impl Optionable for PostgresConnection {
+    type Option = OptionConnection;
+
+    fn set_option(&mut self, key: Self::Option, value: OptionValue) -> Result<()> {
+        match key {
+            OptionConnection::AutoCommit => {
+                let enabled = bool_option(value, key.as_ref())?;
+                let mut session = lock(&self.session)?;
+                session.ensure_idle()?;
+                if enabled && !session.autocommit && session.in_transaction() {
+                    session.simple_query("COMMIT")?;
+                }
+                session.autocommit = enabled;
+                Ok(())
+            }
+            OptionConnection::IsolationLevel => {
+                let level = IsolationLevel::try_from(value)?;
+                let query = match isolation_level_setting(level)? {
+                    Some(setting) => format!("SET default_transaction_isolation TO '{setting}'"),
+                    None => "RESET default_transaction_isolation".to_string(),
+                };
+                lock(&self.session)?.simple_query(&query)?;
+                self.isolation_level = Some(level);
+                Ok(())
+            }
+            OptionConnection::ReadOnly => {
+                let setting = if bool_option(value, key.as_ref())? {
+                    "on"
+                } else {
+                    "off"
+                };
+                lock(&self.session)?
+                    .simple_query(&format!("SET default_transaction_read_only TO {setting}"))?;
+                Ok(())
+            }
+            OptionConnection::CurrentSchema => {
+                let schema = string_option(value, key.as_ref())?;
+                lock(&self.session)?.simple_query(&format!(
+                    "SET search_path TO {}",
+                    escape_identifier(&schema)
+                ))?;
+                Ok(())
+            }
+            key => Err(not_implemented(&format!(
+                "Connection option {}",
+                key.as_ref()
+            ))),
+        }
+    }
+
+    fn get_option_string(&self, key: Self::Option) -> Result<String> {
+        match key {
+            OptionConnection::AutoCommit => Ok(if lock(&self.session)?.autocommit {
+                VALUE_ENABLED.into()
+            } else {
+                VALUE_DISABLED.into()
+            }),
+            OptionConnection::IsolationLevel => {
+                if let Some(level) = self.isolation_level {
+                    return Ok(level.into());
+                }
+                let level = self.query_value("SHOW default_transaction_isolation")?;
+                Ok(match level.as_deref() {
+                    Some("read uncommitted") => IsolationLevel::ReadUncommitted,
+                    Some("repeatable read") => IsolationLevel::RepeatableRead,
+                    Some("serializable") => IsolationLevel::Serializable,
+                    _ => IsolationLevel::ReadCommitted,
+                }
+                .into())
+            }
+            OptionConnection::ReadOnly => {
+                let value = self.query_value("SHOW default_transaction_read_only")?;
+                Ok(if value.as_deref() == Some("on") {
+                    VALUE_ENABLED.into()
+                } else {
+                    VALUE_DISABLED.into()
+                })
+            }
+            OptionConnection::CurrentCatalog => self
+                .query_value("SELECT current_database()")?
+                .ok_or_else(|| unknown_option("connection", key.as_ref())),
+            OptionConnection::CurrentSchema => self
+                .query_value("SELECT current_schema()")?
+                .ok_or_else(|| unknown_option("connection", key.as_ref())),
+            key => Err(unknown_option("connection", key.as_ref())),
+        }
+    }
+
+    fn get_option_bytes(&self, key: Self::Option) -> Result<Vec<u8>> {
+        Err(unknown_option("connection", key.as_ref()))
+    }
+
+    fn get_option_int(&self, key: Self::Option) -> Result<i64> {
+        Err(unknown_option("connection", key.as_ref()))
+    }
+
+    fn get_option_double(&self, key: Self::Option) -> Result<f64> {
+        Err(unknown_option("connection", key.as_ref()))
+    }
+}
+
+impl Connection for PostgresConnection {
+    type StatementType = PostgresStatement;
+
+    fn new_statement(&mut self) -> Result<Self::StatementType> {
+        Ok(Self::StatementType {
+            session: self.session.clone(),
+            cancel_key: self.cancel_key.clone(),
+            query: None,
+            prepared: None,
+            bound: None,
+            target_table: None,
+            target_catalog: None,
+            target_db_schema: None,
+            temporary: false,
+            ingest_mode: IngestMode::Create,
+            batch_size: DEFAULT_BATCH_SIZE,
+        })
+    }
+
+    fn cancel(&mut self) -> Result<()> {
+        match &self.cancel_key {
+            Some(key) => key.cancel(),
+            None => Err(not_implemented("Cancellation without backend key")),
+        }
+    }
+
+    fn get_info(&self, codes: Option<HashSet<InfoCode>>) -> Result<impl RecordBatchReader + Send> {
+        let vendor_version = lock(&self.session)?
+            .parameter("server_version")
+            .unwrap_or_default()
+            .to_string();
+        let strings = [
+            (InfoCode::VendorName, "PostgreSQL".to_string()),
+            (InfoCode::VendorVersion, vendor_version),
+            (
+                InfoCode::DriverName,
+                "ADBC PostgreSQL Driver (Rust)".to_string(),
+            ),
+            (
+                InfoCode::DriverVersion,
+                env!("CARGO_PKG_VERSION").to_string(),
+            ),
+        ];
+        let ints = [(
+            InfoCode::DriverAdbcVersion,
+            i64::from(constants::ADBC_VERSION_1_1_0),
+        )];
+        let requested = |code: &InfoCode| codes.as_ref().is_none_or(|codes| codes.contains(code));
+
+        let mut names = Vec::new();
+        let mut type_ids = Vec::new();
+        let mut offsets = Vec::new();
+        let mut string_values = Vec::new();
+        let mut int_values = Vec::new();
+        for (code, value) in strings.into_iter().filter(|(code, _)| requested(code)) {
+            names.push(u32::from(&code));
+            type_ids.push(0_i8);
+            offsets.push(string_values.len() as i32);
+            string_values.push(value);
+        }
+        for (code, value) in ints.into_iter().filter(|(code, _)| requested(code)) {
+            names.push(u32::from(&code));
+            type_ids.push(2_i8);
+            offsets.push(int_values.len() as i32);
+            int_values.push(value);
+        }
+
+        let DataType::Union(fields, _) = schemas::GET_INFO_SCHEMA.field(1).data_type() else {
+            unreachable!("info_value is a union")
+        };
+        let children = fields
+            .iter()
+            .map(|(type_id, field)| {
+                let array: ArrayRef = match type_id {
+                    0 => Arc::new(StringArray::from(string_values.clone())),
+                    2 => Arc::new(Int64Array::from(int_values.clone())),
+                    _ => new_empty_array(field.data_type()),
+                };
+                (field.as_ref().clone(), array)
+            })
+            .collect();
+        let field_type_ids: Vec<i8> = fields.iter().map(|(type_id, _)| type_id).collect();
+        let values = UnionArray::try_new(
+            &field_type_ids,
+            Buffer::from_vec(type_ids),
+            Some(Buffer::from_vec(offsets)),
+            children,
+        )?;
+        let batch = RecordBatch::try_new(
+            schemas::GET_INFO_SCHEMA.clone(),
+            vec![Arc::new(UInt32Array::from(names)), Arc::new(values)],
+        )?;
+        Ok(RecordBatchIterator::new(
+            vec![Ok(batch)],
+            schemas::GET_INFO_SCHEMA.clone(),
+        ))
+    }
+
+    fn get_objects(
+        &self,
+        _depth: ObjectDepth,
+        _catalog: Option<&str>,
+        _db_schema: Option<&str>,
+        _table_name: Option<&str>,
+        _table_type: Option<Vec<&str>>,
+        _column_name: Option<&str>,
+    ) -> Result<impl RecordBatchReader + Send> {
+        Err::<MemoryReader, _>(not_implemented("GetObjects"))
+    }
+
+    fn get_table_schema(
+        &self,
+        catalog: Option<&str>,
+        db_schema: Option<&str>,
+        table_name: &str,
+    ) -> Result<Schema> {
+        let mut query = format!(
+            "SELECT a.attname, a.atttypid, a.attnotnull \
+             FROM pg_catalog.pg_attribute a \
+             JOIN pg_catalog.pg_class c ON c.oid = a.attrelid \
+             JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace \
+             WHERE c.relname = {} AND a.attnum > 0 AND NOT a.attisdropped",
+            escape_literal(table_name)
+        );
+        match db_schema {
+            Some(db_schema) => {
+                query.push_str(&format!(" AND n.nspname = {}", escape_literal(db_schema)))
+            }
+            None => query.push_str(" AND pg_catalog.pg_table_is_visible(c.oid)"),
+        }
+        if let Some(catalog) = catalog {
+            query.push_str(&format!(
+                " AND current_database() = {}",
+                escape_literal(catalog)
+            ));
+        }
+        query.push_str(" ORDER BY a.attnum");
+
+        let result = lock(&self.session)?.simple_query(&query)?;
+        if result.rows.is_empty() {
+            return Err(Error::with_message_and_status(
+                format!("Table not found: {table_name}"),
+                Status::NotFound,
+            ));
+        }
+        let fields = result
+            .rows
+            .into_iter()
+            .map(|row| {
+                let [Some(name), Some(type_oid), Some(not_null)] = &row[..] else {
+                    return Err(unexpected("in table schema query"));
+                };
+                let type_oid = type_oid.parse().map_err(wire_error)?;
+                let field = Column::new(name, type_oid).field();
+                Ok(field.with_nullable(not_null != "t"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Schema::new(fields))
+    }
+
+    fn get_table_types(&self) -> Result<impl RecordBatchReader + Send> {
+        let table_types = StringArray::from(vec![
+            "table",
+            "view",
+            "materialized_view",
+            "foreign_table",
+            "partitioned_table",
+        ]);
+        let batch = RecordBatch::try_new(
+            schemas::GET_TABLE_TYPES_SCHEMA.clone(),
+            vec![Arc::new(table_types)],
+        )?;
+        Ok(RecordBatchIterator::new(
+            vec![Ok(batch)],
+            schemas::GET_TABLE_TYPES_SCHEMA.clone(),
+        ))
+    }
+
+    fn get_statistic_names(&self) -> Result<impl RecordBatchReader + Send> {
+        Err::<MemoryReader, _>(not_implemented("GetStatisticNames"))
+    }
+
+    fn get_statistics(
+        &self,
+        _catalog: Option<&str>,
+        _db_schema: Option<&str>,
+        _table_name: Option<&str>,
+        _approximate: bool,
+    ) -> Result<impl RecordBatchReader + Send> {
+        Err::<MemoryReader, _>(not_implemented("GetStatistics"))
+    }
+
+    fn commit(&mut self) -> Result<()> {
+        self.end_transaction("COMMIT")
+    }
+
+    fn rollback(&mut self) -> Result<()> {
+        self.end_transaction("ROLLBACK")
+    }
+
+    fn read_partition(
+        &self,
+        _partition: impl AsRef<[u8]>,
+    ) -> Result<impl RecordBatchReader + Send> {
+        Err::<MemoryReader, _>(not_implemented("ReadPartition"))
+    }
+
+    fn capabilities(&self) -> Result<Capabilities> {
+        let mut capabilities = Capabilities::default();
+        capabilities.cancellation = true;
+        capabilities.transactions = true;
+        // Linearizable is the only level without a PostgreSQL equivalent, see
+        // isolation_level_setting.
+        capabilities.isolation_levels = vec![
+            IsolationLevel::Default,
+            IsolationLevel::ReadUncommitted,
+            IsolationLevel::ReadCommitted,
+            IsolationLevel::RepeatableRead,
+            IsolationLevel::Snapshot,
+            IsolationLevel::Serializable,
+        ];
+        capabilities.ingest_modes = vec![
+            IngestMode::Create,
+            IngestMode::Append,
+            IngestMode::Replace,
+            IngestMode::CreateAppend,
+        ];
+        capabilities.with_info(self)
+    }
+}
+
+/// A statement prepared on the server.
+struct Prepared {
+    name: String,
+    params: Vec<postgres_protocol::Oid>,
+    columns: Vec<Column>,
+}
+
+/// Data bound to a statement. Batches are kept so the statement can be
+/// executed again, while streams are consumed by the first execution.
+enum Bound {
+    Batch(RecordBatch),
+    Stream(Box<dyn RecordBatchReader + Send>),
+}
+
+/// A PostgreSQL statement.
+pub struct PostgresStatement {
+    session: Arc<Mutex<Session>>,
+    cancel_key: Option<CancelKey>,
+    query: Option<String>,
+    prepared: Option<Prepared>,
+    bound: Option<Bound>,
+    target_table: Option<String>,
+    target_catalog: Option<String>,
+    target_db_schema: Option<String>,
+    temporary: bool,
+    ingest_mode: IngestMode,
+    batch_size: usize,
+}
+
+impl PostgresStatement {
+    fn query(&self) -> Result<&str> {
+        self.query.as_deref().ok_or_else(|| {
+            Error::with_message_and_status(
+                "Statement has no query, call set_sql_query first",
+                Status::InvalidState,
+            )
+        })
+    }
+
+    fn take_params(&mut self) -> Option<Params> {
+        match self.bound.take()? {
+            Bound::Batch(batch) => {
+                self.bound = Some(Bound::Batch(batch.clone()));
+                Some(Params::from_batch(batch))
+            }
+            Bound::Stream(reader) => Some(Params::from_reader(reader)),
+        }
+    }
+
+    fn close_prepared(&mut self) {
+        if let Some(prepared) = self.prepared.take() {
+            if let Ok(mut session) = lock(&self.session) {
+                session.close_statement(prepared.name);
+            }
+        }
+    }
+
+    /// Pick the statement to execute with the given parameters, parsing the
+    /// query again if the prepared statement expects different types.
+    fn statement_for(
+        &self,
+        session: &mut Session,
+        params: Option<&Params>,
+    ) -> Result<(String, Vec<Column>)> {
+        let types = match params {
+            Some(params) => params
+                .schema()
+                .fields()
+                .iter()
+                .map(|field| Ok(encoding(field.data_type())?.oid))
+                .collect::<Result<Vec<_>>>()?,
+            None => Vec::new(),
+        };
+        if let Some(prepared) = &self.prepared {
+            let compatible = params.is_none()
+                || (types.len() == prepared.params.len()
+                    && types
+                        .iter()
+                        .zip(&prepared.params)
+                        .all(|(bound, expected)| *bound == 0 || bound == expected));
+            if compatible {
+                return Ok((prepared.name.clone(), prepared.columns.clone()));
+            }
+        }
+        let (_, columns) = session.describe("", self.query()?, &types)?;
+        Ok((String::new(), columns))
+    }
+
+    /// The escaped, qualified name of the target table.
+    ///
+    /// Temporary tables are qualified with `pg_temp` so that they are never
+    /// confused with a regular table of the same name.
+    fn target_table(&self) -> Result<String> {
+        let table = self.target_table.as_deref().ok_or_else(|| {
+            Error::with_message_and_status("Target table is not set", Status::InvalidState)
+        })?;
+        let table = escape_identifier(table);
+        match (self.temporary, &self.target_catalog, &self.target_db_schema) {
+            (true, None, None) => Ok(format!("pg_temp.{table}")),
+            (true, _, _) => Err(Error::with_message_and_status(
+                "Temporary tables cannot have a target catalog or db schema",
+                Status::InvalidState,
+            )),
+            (false, None, None) => Ok(table),
+            (false, None, Some(schema)) => Ok(format!("{}.{table}", escape_identifier(schema))),
+            (false, Some(catalog), Some(schema)) => Ok(format!(
+                "{}.{}.{table}",
+                escape_identifier(catalog),
+                escape_identifier(schema)
+            )),
+            (false, Some(_), None) => Err(not_implemented("Target catalog without a db schema")),
+        }
+    }
+
+    /// Bulk insert the bound data into the target table with binary `COPY`.
+    fn ingest(&mut self) -> Result<i64> {
+        let table = self.target_table()?;
+        let mut params = self.take_params().ok_or_else(|| {
+            Error::with_message_and_status(
+                "Data must be bound before ingestion",
+                Status::InvalidState,
+            )
+        })?;
+        let schema = params.schema();
+        let columns = schema
+            .fields()
+            .iter()
+            .map(|field| {
+                Ok(format!(
+                    "{} {}",
+                    escape_identifier(field.name()),
+                    encoding(field.data_type())?.sql_type
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?
+            .join(", ");
+        let create_table = if self.temporary {
+            "CREATE TEMPORARY TABLE"
+        } else {
+            "CREATE TABLE"
+        };
+        let create = match self.ingest_mode {
+            IngestMode::Create => Some(format!("{create_table} {table} ({columns})")),
+            IngestMode::Append => None,
+            IngestMode::Replace => Some(format!(
+                "DROP TABLE IF EXISTS {table}; {create_table} {table} ({columns})"
+            )),
+            IngestMode::CreateAppend => {
+                Some(format!("{create_table} IF NOT EXISTS {table} ({columns})"))
+            }
+            mode => return Err(not_implemented(&format!("{mode:?} ingest mode"))),
+        };
+        let column_names = schema
+            .fields()
+            .iter()
+            .map(|field| escape_identifier(field.name()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let copy = format!("COPY {table} ({column_names}) FROM STDIN (FORMAT binary)");
+
+        let mut session = lock(&self.session)?;
+        session.prepare_command()?;
+        // Without an enclosing transaction, make table creation and loading atomic.
+        let own_transaction = !session.in_transaction();
+        if own_transaction {
+            session.simple_query("BEGIN")?;
+        }
+        let result = (|| {
+            if let Some(create) = &create {
+                session.simple_query(create)?;
+            }
+            copy_in(&mut session, &copy, &mut params)
+        })();
+        if own_transaction {
+            session.ensure_idle()?;
+            let end = if result.is_ok() { "COMMIT" } else { "ROLLBACK" };
+            session.simple_query(end)?;
+        }
+        result
+    }
+}
+
+/// Stream bound data to the server with `COPY ... FROM STDIN (FORMAT binary)`.
+fn copy_in(session: &mut Session, query: &str, params: &mut Params) -> Result<i64> {
+    frontend::query(query, session.buffer()).map_err(wire_error)?;
+    session.flush()?;
+    match session.recv()? {
+        Message::CopyInResponse(_) => {}
+        message @ Message::ErrorResponse(_) => return Err(session.fail(message_error(&message))),
+        _ => return Err(session.fail(unexpected("before COPY"))),
+    }
+
+    let mut data = BytesMut::with_capacity(COPY_CHUNK_SIZE);
+    data.put_slice(b"PGCOPY\n\xff\r\n\0");
+    data.put_i32(0); // flags
+    data.put_i32(0); // header extension length
+    let mut encode_batches = || -> Result<()> {
+        while let Some(batch) = params.next_batch()? {
+            for row in 0..batch.num_rows() {
+                data.put_i16(batch.num_columns() as i16);
+                for column in batch.columns() {
+                    if column.is_null(row) {
+                        data.put_i32(-1);
+                        continue;
+                    }
+                    let start = data.len();
+                    data.put_i32(0);
+                    encode(column.as_ref(), row, &mut data)?;
+                    let length = (data.len() - start - 4) as i32;
+                    data[start..start + 4].copy_from_slice(&length.to_be_bytes());
+                }
+                if data.len() >= COPY_CHUNK_SIZE {
+                    let chunk = frontend::CopyData::new(data.split()).map_err(wire_error)?;
+                    chunk.write(session.buffer());
+                    session.flush()?;
+                }
+            }
+        }
+        data.put_i16(-1);
+        let chunk = frontend::CopyData::new(data.split()).map_err(wire_error)?;
+        chunk.write(session.buffer());
+        Ok(())
+    };
+
+    match encode_batches() {
+        Ok(()) => frontend::copy_done(session.buffer()),
+        Err(error) => {
+            frontend::copy_fail(&error.message, session.buffer()).map_err(wire_error)?;
+            session.flush()?;
+            let _ = session.read_update_result();
+            return Err(error);
+        }
+    }
+    session.flush()?;
+    Ok(session.read_update_result()?.unwrap_or(0))
+}
+
+impl Optionable for PostgresStatement {
+    type Option = OptionStatement;
+
+    fn set_option(&mut self, key: Self::Option, value: OptionValue) -> Result<()> {
+        match key {
+            OptionStatement::TargetTable => {
+                self.target_table = Some(string_option(value, key.as_ref())?);
+                Ok(())
+            }
+            OptionStatement::TargetCatalog => {
+                self.target_catalog = Some(string_option(value, key.as_ref())?);
+                Ok(())
+            }
+            OptionStatement::TargetDbSchema => {
+                self.target_db_schema = Some(string_option(value, key.as_ref())?);
+                Ok(())
+            }
+            OptionStatement::Temporary => {
+                self.temporary = bool_option(value, key.as_ref())?;
+                Ok(())
+            }
+            OptionStatement::IngestMode => {
+                self.ingest_mode = IngestMode::try_from(value)?;
+                Ok(())
+            }
+            OptionStatement::Other(ref name) if name == OPTION_BATCH_SIZE => {
+                let size = match value {
+                    OptionValue::Int(size) => usize::try_from(size).ok(),
+                    OptionValue::String(size) => size.parse().ok(),
+                    _ => None,
+                };
+                match size {
+                    Some(size) if size > 0 => {
+                        self.batch_size = size;
+                        Ok(())
+                    }
+                    _ => Err(Error::with_message_and_status(
+                        format!("Option {OPTION_BATCH_SIZE} must be a positive integer"),
+                        Status::InvalidArguments,
+                    )),
+                }
+            }
+            key => Err(not_implemented(&format!(
+                "Statement option {}",
+                key.as_ref()
+            ))),
+        }
+    }
+
+    fn get_option_string(&self, key: Self::Option) -> Result<String> {
+        match key {
+            OptionStatement::TargetTable => self
+                .target_table
+                .clone()
+                .ok_or_else(|| unknown_option("statement", key.as_ref())),
+            OptionStatement::TargetCatalog => self
+                .target_catalog
+                .clone()
+                .ok_or_else(|| unknown_option("statement", key.as_ref())),
+            OptionStatement::TargetDbSchema => self
+                .target_db_schema
+                .clone()
+                .ok_or_else(|| unknown_option("statement", key.as_ref())),
+            OptionStatement::Temporary => Ok(if self.temporary {
+                VALUE_ENABLED.into()
+            } else {
+                VALUE_DISABLED.into()
+            }),
+            OptionStatement::IngestMode => Ok(self.ingest_mode.into()),
+            OptionStatement::Other(ref name) if name == OPTION_BATCH_SIZE => {
+                Ok(self.batch_size.to_string())
+            }
+            key => Err(unknown_option("statement", key.as_ref())),
+        }
+    }
+
+    fn get_option_bytes(&self, key: Self::Option) -> Result<Vec<u8>> {
+        Err(unknown_option("statement", key.as_ref()))
+    }
+
+    fn get_option_int(&self, key: Self::Option) -> Result<i64> {
+        match key {
+            OptionStatement::Other(ref name) if name == OPTION_BATCH_SIZE => {
+                Ok(self.batch_size as i64)
+            }
+            key => Err(unknown_option("statement", key.as_ref())),
+        }
+    }
+
+    fn get_option_double(&self, key: Self::Option) -> Result<f64> {
+        Err(unknown_option("statement", key.as_ref()))
+    }
+}
+
+impl Statement for PostgresStatement {
+    fn bind(&mut self, batch: RecordBatch) -> Result<()> {
+        self.bound = Some(Bound::Batch(batch));
+        Ok(())
+    }
+
+    fn bind_stream(&mut self, reader: Box<dyn RecordBatchReader + Send>) -> Result<()> {
+        self.bound = Some(Bound::Stream(reader));
+        Ok(())
+    }
+
+    fn execute(&mut self) -> Result<impl RecordBatchReader + Send> {
+        if self.target_table.is_some() {
+            self.ingest()?;
+            return Ok(QueryReader::new(
+                self.session.clone(),
+                None,
+                &[],
+                self.batch_size,
+                String::new(),
+                Vec::new(),
+                None,
+            ));
+        }
+
+        let mut params = self.take_params();
+        let formats = match &params {
+            Some(params) => param_formats(&params.schema())?,
+            None => Vec::new(),
+        };
+
+        let mut session = lock(&self.session)?;
+        session.prepare_command()?;
+        let (statement, columns) = self.statement_for(&mut session, params.as_ref())?;
+        let result_formats: Vec<i16> = columns
+            .iter()
+            .map(|column| column.pg_type.format())
+            .collect();
+        let first = match params.as_mut() {
+            Some(params) => params.next_row()?,
+            None => None,
+        };
+        let stream_id = if params.is_some() && first.is_none() {
+            // Nothing to execute for an empty set of parameters.
+            None
+        } else {
+            write_bind(
+                session.buffer(),
+                &statement,
+                &formats,
+                first.as_ref().map(|(batch, row)| (batch, *row)),
+                &result_formats,
+            )?;
+            frontend::sync(session.buffer());
+            session.flush()?;
+            Some(session.begin_stream())
+        };
+        drop(session);
+
+        Ok(QueryReader::new(
+            self.session.clone(),
+            stream_id,
+            &columns,
+            self.batch_size,
+            statement,
+            formats,
+            params,
+        ))
+    }
+
+    fn execute_update(&mut self) -> Result<Option<i64>> {
+        if self.target_table.is_some() {
+            return self.ingest().map(Some);
+        }
+
+        let Some(mut params) = self.take_params() else {
+            let mut session = lock(&self.session)?;
+            session.prepare_command()?;
+            if let Some(prepared) = &self.prepared {
+                write_bind(session.buffer(), &prepared.name, &[], None, &[])?;
+                frontend::sync(session.buffer());
+                session.flush()?;
+                return session.read_update_result();
+            }
+            let result = session.simple_query(self.query()?)?;
+            return Ok(result.tag.as_deref().and_then(rows_from_tag));
+        };
+
+        let formats = param_formats(&params.schema())?;
+        let mut session = lock(&self.session)?;
+        session.prepare_command()?;
+        let (statement, _) = self.statement_for(&mut session, Some(&params))?;
+        let mut rows = None;
+        loop {
+            let mut pending = 0;
+            while pending < PIPELINE_DEPTH {
+                let row = match params.next_row() {
+                    Ok(Some(row)) => row,
+                    Ok(None) => break,
+                    Err(error) => {
+                        session.buffer().clear();
+                        return Err(error);
+                    }
+                };
+                if let Err(error) = write_bind(
+                    session.buffer(),
+                    &statement,
+                    &formats,
+                    Some((&row.0, row.1)),
+                    &[],
+                ) {
+                    session.buffer().clear();
+                    return Err(error);
+                }
+                pending += 1;
+            }
+            if pending == 0 {
+                return Ok(rows);
+            }
+            frontend::sync(session.buffer());
+            session.flush()?;
+            if let Some(count) = session.read_update_result()? {
+                rows = Some(rows.unwrap_or(0) + count);
+            }
+        }
+    }
+
+    fn execute_schema(&mut self) -> Result<Schema> {
+        let columns = match &self.prepared {
+            Some(prepared) => prepared.columns.clone(),
+            None => {
+                let query = self.query()?.to_string();
+                let mut session = lock(&self.session)?;
+                session.ensure_idle()?;
+                session.describe("", &query, &[])?.1
+            }
+        };
+        Ok(Schema::new(
+            columns.iter().map(Column::field).collect::<Vec<_>>(),
+        ))
+    }
+
+    fn execute_partitions(&mut self) -> Result<PartitionedResult> {
+        Err(not_implemented("ExecutePartitions"))
+    }
+
+    fn get_parameter_schema(&self) -> Result<Schema> {
+        let prepared = self.prepared.as_ref().ok_or_else(|| {
+            Error::with_message_and_status(
+                "Statement must be prepared to get its parameter schema",
+                Status::InvalidState,
+            )
+        })?;
+        let fields = prepared
+            .params
+            .iter()
+            .enumerate()
+            .map(|(index, oid)| {
+                Field::new(
+                    format!("${}", index + 1),
+                    PgType::from_oid(*oid).data_type(),
+                    true,
+                )
+            })
+            .collect::<Vec<_>>();
+        Ok(Schema::new(fields))
+    }
+
+    fn prepare(&mut self) -> Result<()> {
+        let query = self.query()?.to_string();
+        self.close_prepared();
+        let mut session = lock(&self.session)?;
+        session.ensure_idle()?;
+        let name = session.next_statement_name();
+        let (params, columns) = session.describe(&name, &query, &[])?;
+        drop(session);
+        self.prepared = Some(Prepared {
+            name,
+            params,
+            columns,
+        });
+        Ok(())
+    }
+
+    fn set_sql_query(&mut self, query: impl AsRef<str>) -> Result<()> {
+        self.close_prepared();
+        self.query = Some(query.as_ref().to_string());
+        self.target_table = None;
+        Ok(())
+    }
+
+    fn set_substrait_plan(&mut self, _plan: impl AsRef<[u8]>) -> Result<()> {
+        Err(not_implemented("Substrait"))
+    }
+
+    fn cancel(&mut self) -> Result<()> {
+        match &self.cancel_key {
+            Some(key) => key.cancel(),
+            None => Err(not_implemented("Cancellation without backend key")),
+        }
+    }
+}
+
+impl Drop for PostgresStatement {
+    fn drop(&mut self) {
+        self.close_prepared();
+    }
+}
+
+adbc_core::export_driver!(AdbcPostgresqlInit, PostgresDriver);
@@ -0,0 +1,285 @@
+//! Streaming of query results and bound parameters.
+
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use adbc_core::error::{Error, Result, Status};
+use arrow::array::{Array, RecordBatch, RecordBatchReader};
+use arrow::datatypes::{Schema, SchemaRef};
+use arrow::error::ArrowError;
+use bytes::BytesMut;
+use fallible_iterator::FallibleIterator;
+use postgres_protocol::message::backend::Message;
+use postgres_protocol::message::frontend::{self, BindError};
+use postgres_protocol::IsNull;
+
+use crate::client::{message_error, wire_error, Session};
+use crate::types::{encode, encoding, Column, Decoder};
+
+pub(crate) fn lock(session: &Mutex<Session>) -> Result<MutexGuard<'_, Session>> {
+    session.lock().map_err(|_| {
+        Error::with_message_and_status("PostgreSQL session lock is poisoned", Status::Internal)
+    })
+}
+
+/// Data bound to a statement, consumed one row at a time.
+pub(crate) struct Params {
+    schema: SchemaRef,
+    batch: Option<RecordBatch>,
+    row: usize,
+    rest: Option<Box<dyn RecordBatchReader + Send>>,
+}
+
+impl Params {
+    pub(crate) fn from_batch(batch: RecordBatch) -> Self {
+        Self {
+            schema: batch.schema(),
+            batch: Some(batch),
+            row: 0,
+            rest: None,
+        }
+    }
+
+    pub(crate) fn from_reader(reader: Box<dyn RecordBatchReader + Send>) -> Self {
+        Self {
+            schema: reader.schema(),
+            batch: None,
+            row: 0,
+            rest: Some(reader),
+        }
+    }
+
+    pub(crate) fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    /// The next non-empty batch, dropping any partially consumed one.
+    pub(crate) fn next_batch(&mut self) -> Result<Option<RecordBatch>> {
+        loop {
+            if let Some(batch) = self.batch.take() {
+                if self.row < batch.num_rows() {
+                    let batch = batch.slice(self.row, batch.num_rows() - self.row);
+                    self.row = 0;
+                    return Ok(Some(batch));
+                }
+            }
+            self.row = 0;
+            match self.rest.as_mut().and_then(|rest| rest.next()) {
+                Some(batch) => self.batch = Some(batch?),
+                None => return Ok(None),
+            }
+        }
+    }
+
+    /// The next row, as a batch and an index into it.
+    pub(crate) fn next_row(&mut self) -> Result<Option<(RecordBatch, usize)>> {
+        loop {
+            if let Some(batch) = &self.batch {
+                if self.row < batch.num_rows() {
+                    self.row += 1;
+                    return Ok(Some((batch.clone(), self.row - 1)));
+                }
+            }
+            self.batch = None;
+            self.row = 0;
+            match self.rest.as_mut().and_then(|rest| rest.next()) {
+                Some(batch) => self.batch = Some(batch?),
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+/// Parameter formats matching [write_bind], derived from the bound schema.
+pub(crate) fn param_formats(schema: &Schema) -> Result<Vec<i16>> {
+    schema
+        .fields()
+        .iter()
+        .map(|field| Ok(encoding(field.data_type())?.format))
+        .collect()
+}
+
+/// Queue `Bind` and `Execute` messages for one row of parameters.
+pub(crate) fn write_bind(
+    buf: &mut BytesMut,
+    statement: &str,
+    param_formats: &[i16],
+    row: Option<(&RecordBatch, usize)>,
+    result_formats: &[i16],
+) -> Result<()> {
+    let columns: Vec<&dyn Array> = match row {
+        Some((batch, _)) => batch.columns().iter().map(|c| c.as_ref()).collect(),
+        None => Vec::new(),
+    };
+    let index = row.map(|(_, index)| index).unwrap_or(0);
+    let length = buf.len();
+    frontend::bind(
+        "",
+        statement,
+        param_formats.iter().copied(),
+        columns,
+        |column, buf| {
+            if column.is_null(index) {
+                Ok(IsNull::Yes)
+            } else {
+                encode(column, index, buf)?;
+                Ok(IsNull::No)
+            }
+        },
+        result_formats.iter().copied(),
+        buf,
+    )
+    .map_err(|err| match err {
+        BindError::Conversion(err) => match err.downcast::<Error>() {
+            Ok(err) => *err,
+            Err(err) => wire_error(err),
+        },
+        BindError::Serialization(err) => wire_error(err),
+    })
+    .inspect_err(|_| buf.truncate(length))?;
+    frontend::execute("", 0, buf).map_err(wire_error)?;
+    Ok(())
+}
+
+/// A result set streamed from the server.
+pub struct QueryReader {
+    session: Arc<Mutex<Session>>,
+    /// Identifier of the result stream, `None` once it is exhausted.
+    stream_id: Option<u64>,
+    schema: SchemaRef,
+    decoders: Vec<Decoder>,
+    rows: usize,
+    batch_size: usize,
+    statement: String,
+    param_formats: Vec<i16>,
+    result_formats: Vec<i16>,
+    params: Option<Params>,
+}
+
+impl QueryReader {
+    /// A reader for a result stream started on the session.
+    pub(crate) fn new(
+        session: Arc<Mutex<Session>>,
+        stream_id: Option<u64>,
+        columns: &[Column],
+        batch_size: usize,
+        statement: String,
+        param_formats: Vec<i16>,
+        params: Option<Params>,
+    ) -> Self {
+        let schema = Arc::new(Schema::new(
+            columns.iter().map(Column::field).collect::<Vec<_>>(),
+        ));
+        Self {
+            session,
+            stream_id,
+            schema,
+            decoders: columns
+                .iter()
+                .map(|column| Decoder::new(column.pg_type))
+                .collect(),
+            rows: 0,
+            batch_size,
+            statement,
+            param_formats,
+            result_formats: columns
+                .iter()
+                .map(|column| column.pg_type.format())
+                .collect(),
+            params,
+        }
+    }
+
+    fn finish_batch(&mut self) -> Result<RecordBatch> {
+        let columns = self.decoders.iter_mut().map(Decoder::finish).collect();
+        self.rows = 0;
+        Ok(RecordBatch::try_new(self.schema.clone(), columns)?)
+    }
+
+    fn next_batch(&mut self) -> Result<Option<RecordBatch>> {
+        let Some(stream_id) = self.stream_id else {
+            return Ok(None);
+        };
+        let session = self.session.clone();
+        let mut session = lock(&session)?;
+        let batch = self.read_batch(&mut session, stream_id);
+        if batch.is_err() || !session.is_active_stream(stream_id) {
+            self.stream_id = None;
+        }
+        batch
+    }
+
+    fn read_batch(&mut self, session: &mut Session, stream_id: u64) -> Result<Option<RecordBatch>> {
+        if !session.is_active_stream(stream_id) {
+            // Another command was issued on the connection, which drained the stream.
+            return Err(Error::with_message_and_status(
+                "Result set was invalidated by another command on the same connection",
+                Status::InvalidState,
+            ));
+        }
+
+        loop {
+            match session.recv()? {
+                Message::DataRow(body) => {
+                    let mut ranges = body.ranges();
+                    let mut decoders = self.decoders.iter_mut();
+                    while let Some(range) = ranges.next().map_err(wire_error)? {
+                        let decoder = decoders.next().ok_or_else(|| {
+                            wire_error("data row has more columns than the row description")
+                        })?;
+                        decoder.append(range.map(|range| &body.buffer()[range]))?;
+                    }
+                    self.rows += 1;
+                    if self.rows >= self.batch_size {
+                        return self.finish_batch().map(Some);
+                    }
+                }
+                message @ Message::ErrorResponse(_) => {
+                    let error = session.fail(message_error(&message));
+                    session.end_stream(stream_id);
+                    return Err(error);
+                }
+                Message::ReadyForQuery(_) => {
+                    // The session is idle until the next set of parameters is sent.
+                    session.end_stream(stream_id);
+                    let next = match self.params.as_mut() {
+                        Some(params) => params.next_row()?,
+                        None => None,
+                    };
+                    match next {
+                        Some((batch, row)) => {
+                            write_bind(
+                                session.buffer(),
+                                &self.statement,
+                                &self.param_formats,
+                                Some((&batch, row)),
+                                &self.result_formats,
+                            )?;
+                            frontend::sync(session.buffer());
+                            session.flush()?;
+                            session.resume_stream(stream_id);
+                        }
+                        None if self.rows > 0 => return self.finish_batch().map(Some),
+                        None => return Ok(None),
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Iterator for QueryReader {
+    type Item = std::result::Result<RecordBatch, ArrowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_batch()
+            .map_err(|err| ArrowError::ExternalError(Box::new(err)))
+            .transpose()
+    }
+}
+
+impl RecordBatchReader for QueryReader {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
@@ -0,0 +1,573 @@
+//! Conversions between PostgreSQL binary values and Arrow arrays.
+
+use std::sync::Arc;
+
+use adbc_core::error::{Error, Result, Status};
+use arrow::array::{
+    Array, ArrayRef, AsArray, BinaryBuilder, BooleanBuilder, Date32Builder, FixedSizeBinaryBuilder,
+    Float32Builder, Float64Builder, Int16Builder, Int32Builder, Int64Builder,
+    IntervalMonthDayNanoBuilder, StringBuilder, Time64MicrosecondBuilder,
+    TimestampMicrosecondBuilder, UInt32Builder,
+};
+use arrow::datatypes::{
+    DataType, Date32Type, Date64Type, Field, Float32Type, Float64Type, Int16Type, Int32Type,
+    Int64Type, Int8Type, IntervalMonthDayNanoType, Time32MillisecondType, Time32SecondType,
+    Time64MicrosecondType, Time64NanosecondType, TimeUnit, TimestampMicrosecondType,
+    TimestampMillisecondType, TimestampNanosecondType, TimestampSecondType, UInt16Type, UInt32Type,
+    UInt8Type,
+};
+use bytes::{BufMut, BytesMut};
+use postgres_protocol::types as pg;
+use postgres_protocol::Oid;
+
+/// Days between the Unix epoch and the PostgreSQL epoch (2000-01-01).
+const PG_EPOCH_DAYS: i32 = 10_957;
+/// Microseconds between the Unix epoch and the PostgreSQL epoch (2000-01-01).
+const PG_EPOCH_MICROS: i64 = 946_684_800_000_000;
+
+pub(crate) const FORMAT_TEXT: i16 = 0;
+pub(crate) const FORMAT_BINARY: i16 = 1;
+
+mod oid {
+    pub const BOOL: u32 = 16;
+    pub const BYTEA: u32 = 17;
+    pub const NAME: u32 = 19;
+    pub const INT8: u32 = 20;
+    pub const INT2: u32 = 21;
+    pub const INT4: u32 = 23;
+    pub const TEXT: u32 = 25;
+    pub const OID: u32 = 26;
+    pub const JSON: u32 = 114;
+    pub const FLOAT4: u32 = 700;
+    pub const FLOAT8: u32 = 701;
+    pub const BPCHAR: u32 = 1042;
+    pub const VARCHAR: u32 = 1043;
+    pub const DATE: u32 = 1082;
+    pub const TIME: u32 = 1083;
+    pub const TIMESTAMP: u32 = 1114;
+    pub const TIMESTAMPTZ: u32 = 1184;
+    pub const INTERVAL: u32 = 1186;
+    pub const NUMERIC: u32 = 1700;
+    pub const UUID: u32 = 2950;
+    pub const JSONB: u32 = 3802;
+}
+
+/// The PostgreSQL types the driver knows how to decode from the binary format.
+///
+/// Any other type is requested in text format and exposed as a string.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum PgType {
+    Bool,
+    Int2,
+    Int4,
+    Int8,
+    Oid,
+    Float4,
+    Float8,
+    Text,
+    Jsonb,
+    Bytea,
+    Date,
+    Time,
+    Timestamp,
+    Timestamptz,
+    Interval,
+    Numeric,
+    Uuid,
+    Other,
+}
+
+impl PgType {
+    pub(crate) fn from_oid(type_oid: Oid) -> Self {
+        match type_oid {
+            oid::BOOL => Self::Bool,
+            oid::INT2 => Self::Int2,
+            oid::INT4 => Self::Int4,
+            oid::INT8 => Self::Int8,
+            oid::OID => Self::Oid,
+            oid::FLOAT4 => Self::Float4,
+            oid::FLOAT8 => Self::Float8,
+            oid::TEXT | oid::VARCHAR | oid::BPCHAR | oid::NAME | oid::JSON => Self::Text,
+            oid::JSONB => Self::Jsonb,
+            oid::BYTEA => Self::Bytea,
+            oid::DATE => Self::Date,
+            oid::TIME => Self::Time,
+            oid::TIMESTAMP => Self::Timestamp,
+            oid::TIMESTAMPTZ => Self::Timestamptz,
+            oid::INTERVAL => Self::Interval,
+            oid::NUMERIC => Self::Numeric,
+            oid::UUID => Self::Uuid,
+            _ => Self::Other,
+        }
+    }
+
+    /// The wire format requested for values of this type.
+    pub(crate) fn format(&self) -> i16 {
+        match self {
+            Self::Other => FORMAT_TEXT,
+            _ => FORMAT_BINARY,
+        }
+    }
+
+    pub(crate) fn data_type(&self) -> DataType {
+        match self {
+            Self::Bool => DataType::Boolean,
+            Self::Int2 => DataType::Int16,
+            Self::Int4 => DataType::Int32,
+            Self::Int8 => DataType::Int64,
+            Self::Oid => DataType::UInt32,
+            Self::Float4 => DataType::Float32,
+            Self::Float8 => DataType::Float64,
+            Self::Text | Self::Jsonb | Self::Numeric | Self::Other => DataType::Utf8,
+            Self::Bytea => DataType::Binary,
+            Self::Date => DataType::Date32,
+            Self::Time => DataType::Time64(TimeUnit::Microsecond),
+            Self::Timestamp => DataType::Timestamp(TimeUnit::Microsecond, None),
+            Self::Timestamptz => DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+            Self::Interval => DataType::Interval(arrow::datatypes::IntervalUnit::MonthDayNano),
+            Self::Uuid => DataType::FixedSizeBinary(16),
+        }
+    }
+}
+
+/// Description of a column of a result set.
+#[derive(Clone, Debug)]
+pub(crate) struct Column {
+    pub(crate) name: String,
+    pub(crate) pg_type: PgType,
+}
+
+impl Column {
+    pub(crate) fn new(name: impl Into<String>, type_oid: Oid) -> Self {
+        Self {
+            name: name.into(),
+            pg_type: PgType::from_oid(type_oid),
+        }
+    }
+
+    pub(crate) fn field(&self) -> Field {
+        Field::new(&self.name, self.pg_type.data_type(), true)
+    }
+}
+
+fn invalid_data(message: impl Into<String>) -> Error {
+    Error::with_message_and_status(message, Status::InvalidData)
+}
+
+fn decode_error(error: impl std::fmt::Display) -> Error {
+    invalid_data(format!("Failed to decode value: {error}"))
+}
+
+fn shift_epoch(micros: i64) -> Result<i64> {
+    micros
+        .checked_add(PG_EPOCH_MICROS)
+        .ok_or_else(|| invalid_data("Timestamp is out of range"))
+}
+
+/// Render a binary `numeric` value as a decimal string.
+fn numeric_to_string(mut buf: &[u8]) -> Result<String> {
+    let mut read_i16 = || -> Result<i16> {
+        let (head, tail) = buf
+            .split_first_chunk::<2>()
+            .ok_or_else(|| invalid_data("Truncated numeric value"))?;
+        buf = tail;
+        Ok(i16::from_be_bytes(*head))
+    };
+    let ndigits = read_i16()?;
+    let weight = read_i16()?;
+    let sign = read_i16()? as u16;
+    let dscale = read_i16()?;
+    let digits = (0..ndigits)
+        .map(|_| read_i16())
+        .collect::<Result<Vec<_>>>()?;
+
+    match sign {
+        0xC000 => return Ok("NaN".into()),
+        0xD000 => return Ok("Infinity".into()),
+        0xF000 => return Ok("-Infinity".into()),
+        _ => {}
+    }
+
+    let mut result = String::new();
+    if sign == 0x4000 {
+        result.push('-');
+    }
+    // Digits are in base 10000; `weight` is the power of the first one.
+    let digit = |position: i32| -> i16 {
+        usize::try_from(position)
+            .ok()
+            .and_then(|index| digits.get(index).copied())
+            .unwrap_or(0)
+    };
+    if weight < 0 {
+        result.push('0');
+    } else {
+        for position in 0..=weight as i32 {
+            let value = digit(position);
+            if position == 0 {
+                result.push_str(&value.to_string());
+            } else {
+                result.push_str(&format!("{value:04}"));
+            }
+        }
+    }
+    if dscale > 0 {
+        result.push('.');
+        let mut fraction = String::new();
+        let mut position = weight as i32 + 1;
+        while fraction.len() < dscale as usize {
+            fraction.push_str(&format!("{:04}", digit(position)));
+            position += 1;
+        }
+        fraction.truncate(dscale as usize);
+        result.push_str(&fraction);
+    }
+    Ok(result)
+}
+
+/// Accumulates the values of one result column into an Arrow array.
+pub(crate) enum Decoder {
+    Bool(BooleanBuilder),
+    Int2(Int16Builder),
+    Int4(Int32Builder),
+    Int8(Int64Builder),
+    Oid(UInt32Builder),
+    Float4(Float32Builder),
+    Float8(Float64Builder),
+    Text(StringBuilder),
+    Jsonb(StringBuilder),
+    Numeric(StringBuilder),
+    Bytea(BinaryBuilder),
+    Date(Date32Builder),
+    Time(Time64MicrosecondBuilder),
+    Timestamp(TimestampMicrosecondBuilder),
+    Interval(IntervalMonthDayNanoBuilder),
+    Uuid(FixedSizeBinaryBuilder),
+}
+
+impl Decoder {
+    pub(crate) fn new(pg_type: PgType) -> Self {
+        match pg_type {
+            PgType::Bool => Self::Bool(BooleanBuilder::new()),
+            PgType::Int2 => Self::Int2(Int16Builder::new()),
+            PgType::Int4 => Self::Int4(Int32Builder::new()),
+            PgType::Int8 => Self::Int8(Int64Builder::new()),
+            PgType::Oid => Self::Oid(UInt32Builder::new()),
+            PgType::Float4 => Self::Float4(Float32Builder::new()),
+            PgType::Float8 => Self::Float8(Float64Builder::new()),
+            PgType::Text | PgType::Other => Self::Text(StringBuilder::new()),
+            PgType::Jsonb => Self::Jsonb(StringBuilder::new()),
+            PgType::Numeric => Self::Numeric(StringBuilder::new()),
+            PgType::Bytea => Self::Bytea(BinaryBuilder::new()),
+            PgType::Date => Self::Date(Date32Builder::new()),
+            PgType::Time => Self::Time(Time64MicrosecondBuilder::new()),
+            PgType::Timestamp => Self::Timestamp(TimestampMicrosecondBuilder::new()),
+            PgType::Timestamptz => {
+                Self::Timestamp(TimestampMicrosecondBuilder::new().with_timezone("UTC"))
+            }
+            PgType::Interval => Self::Interval(IntervalMonthDayNanoBuilder::new()),
+            PgType::Uuid => Self::Uuid(FixedSizeBinaryBuilder::new(16)),
+        }
+    }
+
+    /// Append a raw value, `None` being SQL `NULL`.
+    pub(crate) fn append(&mut self, value: Option<&[u8]>) -> Result<()> {
+        let Some(value) = value else {
+            match self {
+                Self::Bool(builder) => builder.append_null(),
+                Self::Int2(builder) => builder.append_null(),
+                Self::Int4(builder) => builder.append_null(),
+                Self::Int8(builder) => builder.append_null(),
+                Self::Oid(builder) => builder.append_null(),
+                Self::Float4(builder) => builder.append_null(),
+                Self::Float8(builder) => builder.append_null(),
+                Self::Text(builder) | Self::Jsonb(builder) | Self::Numeric(builder) => {
+                    builder.append_null()
+                }
+                Self::Bytea(builder) => builder.append_null(),
+                Self::Date(builder) => builder.append_null(),
+                Self::Time(builder) => builder.append_null(),
+                Self::Timestamp(builder) => builder.append_null(),
+                Self::Interval(builder) => builder.append_null(),
+                Self::Uuid(builder) => builder.append_null(),
+            }
+            return Ok(());
+        };
+
+        match self {
+            Self::Bool(builder) => {
+                builder.append_value(pg::bool_from_sql(value).map_err(decode_error)?)
+            }
+            Self::Int2(builder) => {
+                builder.append_value(pg::int2_from_sql(value).map_err(decode_error)?)
+            }
+            Self::Int4(builder) => {
+                builder.append_value(pg::int4_from_sql(value).map_err(decode_error)?)
+            }
+            Self::Int8(builder) => {
+                builder.append_value(pg::int8_from_sql(value).map_err(decode_error)?)
+            }
+            Self::Oid(builder) => {
+                builder.append_value(pg::oid_from_sql(value).map_err(decode_error)?)
+            }
+            Self::Float4(builder) => {
+                builder.append_value(pg::float4_from_sql(value).map_err(decode_error)?)
+            }
+            Self::Float8(builder) => {
+                builder.append_value(pg::float8_from_sql(value).map_err(decode_error)?)
+            }
+            Self::Text(builder) => builder.append_value(std::str::from_utf8(value)?),
+            Self::Jsonb(builder) => match value.split_first() {
+                Some((1, json)) => builder.append_value(std::str::from_utf8(json)?),
+                _ => return Err(invalid_data("Unsupported jsonb version")),
+            },
+            Self::Numeric(builder) => builder.append_value(numeric_to_string(value)?),
+            Self::Bytea(builder) => builder.append_value(pg::bytea_from_sql(value)),
+            Self::Date(builder) => {
+                let days = pg::date_from_sql(value).map_err(decode_error)?;
+                let days = days
+                    .checked_add(PG_EPOCH_DAYS)
+                    .ok_or_else(|| invalid_data("Date is out of range"))?;
+                builder.append_value(days)
+            }
+            Self::Time(builder) => {
+                builder.append_value(pg::time_from_sql(value).map_err(decode_error)?)
+            }
+            Self::Timestamp(builder) => {
+                let micros = pg::timestamp_from_sql(value).map_err(decode_error)?;
+                builder.append_value(shift_epoch(micros)?)
+            }
+            Self::Interval(builder) => {
+                let [micros @ .., d0, d1, d2, d3, m0, m1, m2, m3] = value else {
+                    return Err(invalid_data("Truncated interval value"));
+                };
+                let micros = i64::from_be_bytes(
+                    micros
+                        .try_into()
+                        .map_err(|_| invalid_data("Truncated interval value"))?,
+                );
+                let days = i32::from_be_bytes([*d0, *d1, *d2, *d3]);
+                let months = i32::from_be_bytes([*m0, *m1, *m2, *m3]);
+                let nanos = micros
+                    .checked_mul(1_000)
+                    .ok_or_else(|| invalid_data("Interval is out of range"))?;
+                builder.append_value(IntervalMonthDayNanoType::make_value(months, days, nanos))
+            }
+            Self::Uuid(builder) => builder.append_value(value)?,
+        }
+        Ok(())
+    }
+
+    pub(crate) fn finish(&mut self) -> ArrayRef {
+        match self {
+            Self::Bool(builder) => Arc::new(builder.finish()),
+            Self::Int2(builder) => Arc::new(builder.finish()),
+            Self::Int4(builder) => Arc::new(builder.finish()),
+            Self::Int8(builder) => Arc::new(builder.finish()),
+            Self::Oid(builder) => Arc::new(builder.finish()),
+            Self::Float4(builder) => Arc::new(builder.finish()),
+            Self::Float8(builder) => Arc::new(builder.finish()),
+            Self::Text(builder) | Self::Jsonb(builder) | Self::Numeric(builder) => {
+                Arc::new(builder.finish())
+            }
+            Self::Bytea(builder) => Arc::new(builder.finish()),
+            Self::Date(builder) => Arc::new(builder.finish()),
+            Self::Time(builder) => Arc::new(builder.finish()),
+            Self::Timestamp(builder) => Arc::new(builder.finish()),
+            Self::Interval(builder) => Arc::new(builder.finish()),
+            Self::Uuid(builder) => Arc::new(builder.finish()),
+        }
+    }
+}
+
+/// How an Arrow type is sent to PostgreSQL.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Encoding {
+    /// Parameter type OID, 0 to let the server infer it.
+    pub(crate) oid: Oid,
+    pub(crate) format: i16,
+    /// Column type used when creating a table for ingestion.
+    pub(crate) sql_type: &'static str,
+}
+
+impl Encoding {
+    const fn binary(oid: Oid, sql_type: &'static str) -> Self {
+        Self {
+            oid,
+            format: FORMAT_BINARY,
+            sql_type,
+        }
+    }
+}
+
+pub(crate) fn encoding(data_type: &DataType) -> Result<Encoding> {
+    let encoding = match data_type {
+        DataType::Boolean => Encoding::binary(oid::BOOL, "BOOLEAN"),
+        DataType::Int8 | DataType::Int16 | DataType::UInt8 => {
+            Encoding::binary(oid::INT2, "SMALLINT")
+        }
+        DataType::Int32 | DataType::UInt16 => Encoding::binary(oid::INT4, "INTEGER"),
+        DataType::Int64 | DataType::UInt32 => Encoding::binary(oid::INT8, "BIGINT"),
+        DataType::Float32 => Encoding::binary(oid::FLOAT4, "REAL"),
+        DataType::Float64 => Encoding::binary(oid::FLOAT8, "DOUBLE PRECISION"),
+        // Strings are sent in text format with an unspecified type so that the
+        // server can coerce them to the type it expects.
+        DataType::Utf8 | DataType::LargeUtf8 => Encoding {
+            oid: 0,
+            format: FORMAT_TEXT,
+            sql_type: "TEXT",
+        },
+        DataType::Binary | DataType::LargeBinary => Encoding::binary(oid::BYTEA, "BYTEA"),
+        DataType::Date32 | DataType::Date64 => Encoding::binary(oid::DATE, "DATE"),
+        DataType::Time32(_) | DataType::Time64(_) => Encoding::binary(oid::TIME, "TIME"),
+        DataType::Timestamp(_, None) => Encoding::binary(oid::TIMESTAMP, "TIMESTAMP"),
+        DataType::Timestamp(_, Some(_)) => Encoding::binary(oid::TIMESTAMPTZ, "TIMESTAMPTZ"),
+        DataType::FixedSizeBinary(16) => Encoding::binary(oid::UUID, "UUID"),
+        data_type => {
+            return Err(Error::with_message_and_status(
+                format!("Arrow type {data_type} is not supported by the PostgreSQL driver"),
+                Status::NotImplemented,
+            ))
+        }
+    };
+    Ok(encoding)
+}
+
+/// Convert days since the Unix epoch into days since the PostgreSQL epoch.
+fn pg_date(days: i64) -> Result<i32> {
+    i32::try_from(days)
+        .ok()
+        .and_then(|days| days.checked_sub(PG_EPOCH_DAYS))
+        .ok_or_else(|| {
+            Error::with_message_and_status("Date is out of range", Status::InvalidArguments)
+        })
+}
+
+fn timestamp_micros(array: &dyn Array, unit: &TimeUnit, row: usize) -> Result<i64> {
+    let micros = match unit {
+        TimeUnit::Second => array
+            .as_primitive::<TimestampSecondType>()
+            .value(row)
+            .checked_mul(1_000_000),
+        TimeUnit::Millisecond => array
+            .as_primitive::<TimestampMillisecondType>()
+            .value(row)
+            .checked_mul(1_000),
+        TimeUnit::Microsecond => Some(array.as_primitive::<TimestampMicrosecondType>().value(row)),
+        TimeUnit::Nanosecond => Some(
+            array
+                .as_primitive::<TimestampNanosecondType>()
+                .value(row)
+                .div_euclid(1_000),
+        ),
+    };
+    micros
+        .and_then(|micros| micros.checked_sub(PG_EPOCH_MICROS))
+        .ok_or_else(|| invalid_data("Timestamp is out of range"))
+}
+
+/// Write the wire representation of a non-null value, using the format
+/// chosen by [encoding].
+pub(crate) fn encode(array: &dyn Array, row: usize, buf: &mut BytesMut) -> Result<()> {
+    match array.data_type() {
+        DataType::Boolean => pg::bool_to_sql(array.as_boolean().value(row), buf),
+        DataType::Int8 => pg::int2_to_sql(array.as_primitive::<Int8Type>().value(row).into(), buf),
+        DataType::Int16 => pg::int2_to_sql(array.as_primitive::<Int16Type>().value(row), buf),
+        DataType::UInt8 => {
+            pg::int2_to_sql(array.as_primitive::<UInt8Type>().value(row).into(), buf)
+        }
+        DataType::Int32 => pg::int4_to_sql(array.as_primitive::<Int32Type>().value(row), buf),
+        DataType::UInt16 => {
+            pg::int4_to_sql(array.as_primitive::<UInt16Type>().value(row).into(), buf)
+        }
+        DataType::Int64 => pg::int8_to_sql(array.as_primitive::<Int64Type>().value(row), buf),
+        DataType::UInt32 => {
+            pg::int8_to_sql(array.as_primitive::<UInt32Type>().value(row).into(), buf)
+        }
+        DataType::Float32 => pg::float4_to_sql(array.as_primitive::<Float32Type>().value(row), buf),
+        DataType::Float64 => pg::float8_to_sql(array.as_primitive::<Float64Type>().value(row), buf),
+        DataType::Utf8 => buf.put_slice(array.as_string::<i32>().value(row).as_bytes()),
+        DataType::LargeUtf8 => buf.put_slice(array.as_string::<i64>().value(row).as_bytes()),
+        DataType::Binary => pg::bytea_to_sql(array.as_binary::<i32>().value(row), buf),
+        DataType::LargeBinary => pg::bytea_to_sql(array.as_binary::<i64>().value(row), buf),
+        DataType::Date32 => {
+            let days = array.as_primitive::<Date32Type>().value(row);
+            pg::date_to_sql(pg_date(days.into())?, buf)
+        }
+        DataType::Date64 => {
+            let days = array
+                .as_primitive::<Date64Type>()
+                .value(row)
+                .div_euclid(86_400_000);
+            pg::date_to_sql(pg_date(days)?, buf)
+        }
+        DataType::Time32(TimeUnit::Second) => {
+            let seconds = array.as_primitive::<Time32SecondType>().value(row);
+            pg::time_to_sql(i64::from(seconds) * 1_000_000, buf)
+        }
+        DataType::Time32(_) => {
+            let millis = array.as_primitive::<Time32MillisecondType>().value(row);
+            pg::time_to_sql(i64::from(millis) * 1_000, buf)
+        }
+        DataType::Time64(TimeUnit::Microsecond) => pg::time_to_sql(
+            array.as_primitive::<Time64MicrosecondType>().value(row),
+            buf,
+        ),
+        DataType::Time64(_) => {
+            let nanos = array.as_primitive::<Time64NanosecondType>().value(row);
+            pg::time_to_sql(nanos / 1_000, buf)
+        }
+        DataType::Timestamp(unit, _) => {
+            pg::timestamp_to_sql(timestamp_micros(array, unit, row)?, buf)
+        }
+        DataType::FixedSizeBinary(16) => buf.put_slice(array.as_fixed_size_binary().value(row)),
+        data_type => {
+            return Err(Error::with_message_and_status(
+                format!("Arrow type {data_type} is not supported by the PostgreSQL driver"),
+                Status::NotImplemented,
+            ))
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numeric(ndigits: i16, weight: i16, sign: u16, dscale: i16, digits: &[i16]) -> Vec<u8> {
+        let mut buf = Vec::new();
+        for value in [ndigits, weight, sign as i16, dscale].iter().chain(digits) {
+            buf.extend_from_slice(&value.to_be_bytes());
+        }
+        buf
+    }
+
+    #[test]
+    fn test_numeric_to_string() {
+        // 12345.678
+        let value = numeric(3, 1, 0, 3, &[1, 2345, 6780]);
+        assert_eq!(numeric_to_string(&value).unwrap(), "12345.678");
+        // -0.0042
+        let value = numeric(1, -1, 0x4000, 4, &[42]);
+        assert_eq!(numeric_to_string(&value).unwrap(), "-0.0042");
+        // 1000000 (trailing zero digit groups are omitted)
+        let value = numeric(1, 1, 0, 0, &[100]);
+        assert_eq!(numeric_to_string(&value).unwrap(), "1000000");
+        // 0
+        let value = numeric(0, 0, 0, 2, &[]);
+        assert_eq!(numeric_to_string(&value).unwrap(), "0.00");
+        let value = numeric(0, 0, 0xC000, 0, &[]);
+        assert_eq!(numeric_to_string(&value).unwrap(), "NaN");
+    }
+
+    #[test]
+    fn test_pg_date() {
+        assert_eq!(pg_date(PG_EPOCH_DAYS.into()).unwrap(), 0);
+        assert_eq!(pg_date(0).unwrap(), -PG_EPOCH_DAYS);
+        let error = pg_date(i32::MIN.into()).unwrap_err();
+        assert_eq!(error.status, Status::InvalidArguments);
+        let error = pg_date(i64::from(i32::MAX) + 1).unwrap_err();
+        assert_eq!(error.status, Status::InvalidArguments);
+    }
+}
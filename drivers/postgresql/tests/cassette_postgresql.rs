@@ -0,0 +1,75 @@
+/// This integration test runs against a cassette recorded from a PostgreSQL
+/// server, so that it runs offline.
+///
+/// Set `ADBC_CASSETTE_RECORD` to record the cassette again from the test
+/// server instead of replaying it.
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use arrow::array::{AsArray, Int64Array, RecordBatch};
+use arrow::datatypes::{DataType, Field, Int32Type, Int64Type, Schema};
+
+use adbc_cassette::{Cassette, RecordingDriver, ReplayDriver};
+use adbc_core::options::{OptionDatabase, OptionValue};
+use adbc_core::{Connection, Database, Driver, Statement};
+use adbc_postgresql::PostgresDriver;
+
+mod common;
+
+fn cassette_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/cassettes/postgresql.arrow")
+}
+
+fn scenario(driver: &mut impl Driver, uri: &str) {
+    let opts = [(OptionDatabase::Uri, OptionValue::from(uri))];
+    let mut database = driver.new_database_with_opts(opts).unwrap();
+    let mut connection = database.new_connection().unwrap();
+    let mut statement = connection.new_statement().unwrap();
+
+    statement
+        .set_sql_query("SELECT 1::int4 AS a, 'one'::text AS b")
+        .unwrap();
+    let batches: Vec<RecordBatch> = statement.execute().unwrap().map(|b| b.unwrap()).collect();
+    assert_eq!(batches.len(), 1);
+    assert_eq!(batches[0].column(0).as_primitive::<Int32Type>().value(0), 1);
+    assert_eq!(batches[0].column(1).as_string::<i32>().value(0), "one");
+
+    let schema = Schema::new(vec![Field::new("v", DataType::Int64, false)]);
+    let parameters =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(Int64Array::from(vec![41]))]).unwrap();
+    statement.set_sql_query("SELECT $1::int8 + 1 AS v").unwrap();
+    statement.bind(parameters).unwrap();
+    let batches: Vec<RecordBatch> = statement.execute().unwrap().map(|b| b.unwrap()).collect();
+    assert_eq!(
+        batches[0].column(0).as_primitive::<Int64Type>().value(0),
+        42
+    );
+
+    statement
+        .set_sql_query("SELECT * FROM adbc_cassette_missing")
+        .unwrap();
+    let error = statement.execute().err().unwrap();
+    assert_eq!(error.sqlstate.map(|c| c as u8), *b"42P01");
+}
+
+#[test]
+fn test_cassette_replay() {
+    if std::env::var_os("ADBC_CASSETTE_RECORD").is_some() {
+        let uri = common::server_uri();
+        let cassette = Cassette::new();
+        scenario(
+            &mut RecordingDriver::new(PostgresDriver::default(), cassette.clone()),
+            &uri,
+        );
+        std::fs::create_dir_all(cassette_path().parent().unwrap()).unwrap();
+        cassette.save(cassette_path()).unwrap();
+    }
+
+    let cassette = Cassette::load(cassette_path()).unwrap();
+    // The URI is redacted in the cassette, so any URI matches.
+    scenario(
+        &mut ReplayDriver::new(cassette.clone()),
+        "postgresql://offline",
+    );
+    assert_eq!(cassette.remaining(), 0);
+}
@@ -0,0 +1,101 @@
+//! Test harness providing a PostgreSQL server.
+//!
+//! Tests use the server pointed to by `TEST_ADBC_POSTGRESQL_URI` when set.
+//! Otherwise a throwaway cluster is created with the `initdb` and `pg_ctl`
+//! binaries found through `pg_config` or the `PATH`, and stopped by a watchdog
+//! process once the test binary exits. When neither is available, tests fail.
+//!
+//! Tests needing a server are ignored by default, and run with
+//! `cargo test -p adbc_postgresql -- --ignored`.
+
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+static SERVER_URI: OnceLock<Result<String, String>> = OnceLock::new();
+
+fn bin_dir() -> Option<PathBuf> {
+    let output = Command::new("pg_config").arg("--bindir").output().ok();
+    if let Some(output) = output.filter(|output| output.status.success()) {
+        let dir = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+        if dir.join("initdb").exists() {
+            return Some(dir);
+        }
+    }
+    std::env::split_paths(&std::env::var_os("PATH")?).find(|dir| dir.join("initdb").exists())
+}
+
+fn run(command: &mut Command) -> Result<(), String> {
+    let output = command
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|err| format!("{command:?}: {err}"))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "{command:?}: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+fn start_cluster(bin_dir: &Path) -> Result<String, String> {
+    let data_dir =
+        std::env::temp_dir().join(format!("adbc_postgresql_test_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&data_dir);
+    let port = TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .map_err(|err| err.to_string())?
+        .port();
+
+    run(Command::new(bin_dir.join("initdb"))
+        .args(["--auth=trust", "--username=postgres", "--no-sync", "-D"])
+        .arg(&data_dir))?;
+    let options = format!(
+        "-p {port} -k {} -c listen_addresses=127.0.0.1 -c fsync=off",
+        data_dir.display()
+    );
+    run(Command::new(bin_dir.join("pg_ctl"))
+        .args(["-w", "-o", &options, "-l"])
+        .arg(data_dir.join("server.log"))
+        .arg("-D")
+        .arg(&data_dir)
+        .arg("start"))?;
+
+    // Stop the cluster once this process is gone.
+    let watchdog = format!(
+        "while kill -0 {pid} 2>/dev/null; do sleep 1; done; \
+         '{pg_ctl}' -D '{data}' -m immediate stop >/dev/null 2>&1; rm -rf '{data}'",
+        pid = std::process::id(),
+        pg_ctl = bin_dir.join("pg_ctl").display(),
+        data = data_dir.display(),
+    );
+    Command::new("sh")
+        .args(["-c", &watchdog])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|err| err.to_string())?;
+
+    Ok(format!("postgresql://postgres@127.0.0.1:{port}/postgres"))
+}
+
+/// URI of the test server.
+///
+/// Panics if no server is available.
+pub fn server_uri() -> String {
+    let uri = SERVER_URI.get_or_init(|| {
+        if let Ok(uri) = std::env::var("TEST_ADBC_POSTGRESQL_URI") {
+            return Ok(uri);
+        }
+        let bin_dir = bin_dir().ok_or("PostgreSQL binaries not found")?;
+        start_cluster(&bin_dir).map_err(|err| format!("Could not start PostgreSQL: {err}"))
+    });
+    match uri {
+        Ok(uri) => uri.clone(),
+        Err(err) => panic!("{err}; set TEST_ADBC_POSTGRESQL_URI to a PostgreSQL server"),
+    }
+}
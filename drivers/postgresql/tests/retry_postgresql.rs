@@ -0,0 +1,56 @@
+/// This integration test checks that the retry driver recovers from the
+/// PostgreSQL server terminating a connection.
+use std::time::Duration;
+
+use arrow::array::{AsArray, RecordBatch};
+use arrow::datatypes::Int32Type;
+
+use adbc_core::options::{OptionConnection, OptionDatabase, OptionValue};
+use adbc_core::retry::{RetryDriver, RetryPolicy};
+use adbc_core::{Connection, Database, Driver, Optionable, Statement};
+use adbc_postgresql::PostgresDriver;
+
+mod common;
+
+fn query(statement: &mut impl Statement, query: &str) -> RecordBatch {
+    statement.set_sql_query(query).unwrap();
+    let batches: Vec<RecordBatch> = statement.execute().unwrap().map(|b| b.unwrap()).collect();
+    batches.into_iter().next().unwrap()
+}
+
+#[test]
+#[ignore = "requires a PostgreSQL server"]
+fn test_reconnect_after_termination() {
+    let uri = common::server_uri();
+    let opts = [(OptionDatabase::Uri, OptionValue::from(uri.as_str()))];
+    let policy = RetryPolicy::new().with_backoff(Duration::from_millis(10), Duration::from_secs(1));
+    let mut driver = RetryDriver::new(PostgresDriver::default(), policy);
+    let mut database = driver.new_database_with_opts(opts.clone()).unwrap();
+    let mut connection = database.new_connection().unwrap();
+    connection
+        .set_option(OptionConnection::CurrentSchema, "information_schema".into())
+        .unwrap();
+    let mut statement = connection.new_statement().unwrap();
+    let batch = query(&mut statement, "SELECT pg_backend_pid()");
+    let pid = batch.column(0).as_primitive::<Int32Type>().value(0);
+
+    let mut admin = PostgresDriver::default()
+        .new_database_with_opts(opts)
+        .unwrap()
+        .new_connection()
+        .unwrap()
+        .new_statement()
+        .unwrap();
+    query(&mut admin, &format!("SELECT pg_terminate_backend({pid})"));
+
+    let batch = query(
+        &mut statement,
+        "SELECT pg_backend_pid(), current_schema()::text",
+    );
+    assert_ne!(batch.column(0).as_primitive::<Int32Type>().value(0), pid);
+    assert_eq!(
+        batch.column(1).as_string::<i32>().value(0),
+        "information_schema"
+    );
+    assert_eq!(connection.reconnections(), 1);
+}